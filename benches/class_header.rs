@@ -0,0 +1,65 @@
+//! Compares parsing only the declarations of classes with [`Class::parse_header`] against parsing
+//! them fully with [`Class::from_reader`].
+//!
+//! The classes are those compiled from `test_data` by the build script.
+//! Run with `cargo bench --bench class_header`.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use mokapot::jvm::Class;
+
+const ITERATIONS: u32 = 100;
+
+fn time<T>(mut f: impl FnMut() -> T) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        std::hint::black_box(f());
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn class_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).expect("The directory should be readable") {
+        let path = entry.expect("The entry should be readable").path();
+        if path.is_dir() {
+            class_files(&path, files);
+        } else if path.extension().is_some_and(|it| it == "class") {
+            files.push(path);
+        }
+    }
+}
+
+fn main() {
+    let dir = Path::new(env!("OUT_DIR"))
+        .join("mokapot")
+        .join("java_classes");
+    let mut files = Vec::new();
+    class_files(&dir, &mut files);
+    let classes: Vec<_> = files
+        .iter()
+        .map(|it| fs::read(it).expect("The class file should be readable"))
+        .collect();
+    println!("{}: {} classes", dir.display(), classes.len());
+
+    let full = time(|| {
+        classes
+            .iter()
+            .map(|bytes| Class::from_reader(bytes.as_slice()).unwrap())
+            .collect::<Vec<_>>()
+    });
+    println!("from_reader: {full:?}");
+    let header = time(|| {
+        classes
+            .iter()
+            .map(|bytes| Class::parse_header(bytes.as_slice()).unwrap())
+            .collect::<Vec<_>>()
+    });
+    println!(
+        "parse_header: {header:?}, speedup {:.2}x",
+        full.as_secs_f64() / header.as_secs_f64()
+    );
+}
@@ -0,0 +1,48 @@
+//! Compares parsing the classes in a JAR file sequentially and in parallel.
+//!
+//! The JAR file is taken from the `MOKAPOT_BENCH_JAR` environment variable, or defaults to
+//! `$JAVA_HOME/lib/jrt-fs.jar`. Use a JAR file with thousands of classes, e.g., `rt.jar` of a JDK 8,
+//! to see the speedup.
+//! Run with `cargo bench --features rayon --bench jar_parsing`.
+
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use mokapot::jvm::class_loader::class_paths::JarClassPath;
+
+const ITERATIONS: u32 = 5;
+
+fn time<T>(mut f: impl FnMut() -> T) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        std::hint::black_box(f());
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    let jar_path = std::env::var_os("MOKAPOT_BENCH_JAR")
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("JAVA_HOME").map(|it| PathBuf::from(it).join("lib").join("jrt-fs.jar"))
+        })
+        .expect("Either MOKAPOT_BENCH_JAR or JAVA_HOME should be set");
+    let jar_cp = JarClassPath::new(&jar_path);
+    let class_count = jar_cp
+        .classes()
+        .expect("The JAR file should be readable")
+        .len();
+    println!("{}: {class_count} classes", jar_path.display());
+
+    let sequential = time(|| jar_cp.classes().unwrap());
+    println!("sequential: {sequential:?}");
+    for num_threads in [2, 4, 8] {
+        let parallel = time(|| jar_cp.classes_par(Some(num_threads)).unwrap());
+        println!(
+            "parallel ({num_threads} threads): {parallel:?}, speedup {:.2}x",
+            sequential.as_secs_f64() / parallel.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! Access control of fields and methods.
+
+use crate::{
+    ir::ClassHierarchy,
+    jvm::{class, field, method, references::ClassRef, Class, Field, Method},
+    macros::see_jvm_spec,
+};
+
+/// A field or a method whose accessibility is checked by [`is_accessible_from`].
+#[derive(Debug, Clone, Copy, derive_more::From)]
+pub enum Member<'a> {
+    /// A field.
+    Field(&'a Field),
+    /// A method.
+    Method(&'a Method),
+}
+
+impl Member<'_> {
+    fn owner(&self) -> &ClassRef {
+        match self {
+            Self::Field(field) => &field.owner,
+            Self::Method(method) => &method.owner,
+        }
+    }
+
+    fn is_public(&self) -> bool {
+        match self {
+            Self::Field(field) => field.access_flags.contains(field::AccessFlags::PUBLIC),
+            Self::Method(method) => method.access_flags.contains(method::AccessFlags::PUBLIC),
+        }
+    }
+
+    fn is_protected(&self) -> bool {
+        match self {
+            Self::Field(field) => field.access_flags.contains(field::AccessFlags::PROTECTED),
+            Self::Method(method) => method.access_flags.contains(method::AccessFlags::PROTECTED),
+        }
+    }
+
+    fn is_private(&self) -> bool {
+        match self {
+            Self::Field(field) => field.access_flags.contains(field::AccessFlags::PRIVATE),
+            Self::Method(method) => method.access_flags.contains(method::AccessFlags::PRIVATE),
+        }
+    }
+}
+
+/// Checks if `member`, declared in `declaring_class`, is accessible from the code in
+/// `from_class` following the access control of the JVM.
+/// - The declaring class must be `public` or in the same package as `from_class`.
+/// - A `public` member is accessible from everywhere.
+/// - A `protected` member is accessible from the same package and from the subclasses of the
+///   declaring class, which are looked up in `hierarchy`.
+/// - A package-private member is accessible from the same package.
+/// - A `private` member is accessible from the declaring class and its nestmates, i.e., the
+///   classes with the same nest host. A class without a `NestHost` attribute is its own host.
+///
+/// Protected access has further restrictions not checked here since they depend on the
+/// instruction rather than the classes: an instance field or method accessed from a subclass
+/// in another package must be accessed through a reference to `from_class` or one of its
+/// subclasses, and a protected constructor can only be invoked from such a subclass through
+/// `super(...)`.
+/// Packages are compared by the binary names of the classes, so classes with the same package
+/// name loaded by different class loaders are considered in the same package. Module readability
+/// and exports are not considered.
+#[doc = see_jvm_spec!(5, 4, 4)]
+#[must_use]
+pub fn is_accessible_from<'a, M>(
+    member: M,
+    declaring_class: &Class,
+    from_class: &Class,
+    hierarchy: &ClassHierarchy,
+) -> bool
+where
+    M: Into<Member<'a>>,
+{
+    let member = member.into();
+    let from = from_class.as_ref();
+    let owner = member.owner();
+    let same_package = package_of(owner) == package_of(&from);
+    let class_accessible = declaring_class
+        .access_flags
+        .contains(class::AccessFlags::PUBLIC)
+        || same_package;
+    if !class_accessible {
+        return false;
+    }
+    if member.is_public() {
+        true
+    } else if member.is_protected() {
+        same_package || from == *owner || hierarchy.super_classes(&from).contains(owner)
+    } else if member.is_private() {
+        from == *owner || nest_host(from_class) == nest_host(declaring_class)
+    } else {
+        same_package
+    }
+}
+
+fn nest_host(class: &Class) -> ClassRef {
+    class.nest_host.clone().unwrap_or_else(|| class.as_ref())
+}
+
+fn package_of(class: &ClassRef) -> &str {
+    class
+        .binary_name
+        .rsplit_once('/')
+        .map_or("", |(package, _)| package)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn class(name: &str, super_class: &str, access_flags: class::AccessFlags) -> Class {
+        Class {
+            binary_name: name.to_owned(),
+            super_class: Some(ClassRef::new(super_class)),
+            access_flags,
+            ..Default::default()
+        }
+    }
+
+    fn field(owner: &str, access_flags: field::AccessFlags) -> Field {
+        Field {
+            access_flags,
+            name: "value".to_owned(),
+            owner: ClassRef::new(owner),
+            field_type: "I".parse().unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn access_levels() {
+        let public = class::AccessFlags::PUBLIC;
+        let base = class("a/Base", "java/lang/Object", public);
+        let neighbor = class("a/Neighbor", "java/lang/Object", public);
+        let derived = class("b/Derived", "a/Base", public);
+        let stranger = class("b/Stranger", "java/lang/Object", public);
+        let hierarchy = ClassHierarchy::from_classes([&base, &neighbor, &derived, &stranger]);
+        let accessible = |flags, from: &Class| {
+            is_accessible_from(&field("a/Base", flags), &base, from, &hierarchy)
+        };
+
+        let public = field::AccessFlags::PUBLIC;
+        assert!(accessible(public, &stranger));
+
+        let protected = field::AccessFlags::PROTECTED;
+        assert!(accessible(protected, &neighbor));
+        assert!(accessible(protected, &derived));
+        assert!(!accessible(protected, &stranger));
+
+        let package_private = field::AccessFlags::empty();
+        assert!(accessible(package_private, &neighbor));
+        assert!(!accessible(package_private, &derived));
+
+        let private = field::AccessFlags::PRIVATE;
+        assert!(accessible(private, &base));
+        assert!(!accessible(private, &neighbor));
+    }
+
+    #[test]
+    fn nestmates_and_class_access() {
+        let public = class::AccessFlags::PUBLIC;
+        let mut outer = class("a/Outer", "java/lang/Object", public);
+        outer.nest_members = vec![ClassRef::new("a/Outer$A"), ClassRef::new("a/Outer$B")];
+        let mut inner_a = class("a/Outer$A", "java/lang/Object", class::AccessFlags::empty());
+        inner_a.nest_host = Some(outer.as_ref());
+        let mut inner_b = class("a/Outer$B", "java/lang/Object", class::AccessFlags::empty());
+        inner_b.nest_host = Some(outer.as_ref());
+        let stranger = class("b/Stranger", "java/lang/Object", public);
+        let hierarchy = ClassHierarchy::from_classes([&outer, &inner_a, &inner_b, &stranger]);
+
+        let private = field("a/Outer$A", field::AccessFlags::PRIVATE);
+        assert!(is_accessible_from(&private, &inner_a, &inner_b, &hierarchy));
+        assert!(is_accessible_from(&private, &inner_a, &outer, &hierarchy));
+
+        let public = field("a/Outer$A", field::AccessFlags::PUBLIC);
+        assert!(is_accessible_from(&public, &inner_a, &outer, &hierarchy));
+        assert!(!is_accessible_from(
+            &public, &inner_a, &stranger, &hierarchy
+        ));
+    }
+}
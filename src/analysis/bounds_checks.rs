@@ -0,0 +1,281 @@
+//! Detection of array accesses whose implicit bounds checks are redundant.
+
+use std::{collections::BTreeSet, convert::Infallible};
+
+use crate::jvm::code::{Instruction, MethodBody, ProgramCounter, WideInstruction};
+
+use super::{
+    dataflow::{DataflowAnalysis, Lattice},
+    integer_ranges,
+    liveness::is_store,
+};
+
+/// Returns the program counters of the array loads and stores whose index is provably within the
+/// bounds of the array, so that the bounds check performed by the JVM can be eliminated.
+///
+/// An access `a[i]` is recognized as the instruction sequence `aload <a>; iload <i>; <t>aload`,
+/// or `aload <a>; iload <i>; <push>; <t>astore` where the value is pushed by a single constant or
+/// load instruction. Its index is within bounds if
+/// - `i < a.length` holds on all paths to the access, i.e., it is guarded by
+///   `iload <i>; aload <a>; arraylength; if_icmpge` (or `if_icmplt` when taken), or by
+///   `aload <a>; arraylength; iload <i>; if_icmple` (or `if_icmpgt` when taken), with neither
+///   local variable stored or incremented since, and
+/// - `i >= 0` according to [`integer_ranges`](super::integer_ranges).
+///
+/// Only sequences that cannot be entered in the middle, i.e., with no jump target or exception
+/// handler after their first instruction, are recognized.
+#[must_use]
+pub fn redundant_bounds_checks(body: &MethodBody) -> Vec<ProgramCounter> {
+    let entries: BTreeSet<_> = body
+        .instructions
+        .iter()
+        .flat_map(|(_, insn)| insn.jump_targets())
+        .chain(body.exception_table.iter().map(|it| it.handler_pc))
+        .collect();
+    let mut analysis = GuardAnalysis {
+        body,
+        entries: &entries,
+    };
+    let guards = analysis.analyze(body);
+    let ranges = integer_ranges(body);
+    body.instructions
+        .iter()
+        .filter_map(|(pc, _)| {
+            let (array, index) = analysis.accessed_element(*pc)?;
+            let guarded = guards
+                .get(pc)
+                .is_some_and(|it| it.0.contains(&(index, array)));
+            let non_negative = ranges
+                .frame_at(*pc)
+                .and_then(|frame| frame.local(index))
+                .is_some_and(|it| it.min() >= 0);
+            (guarded && non_negative).then_some(*pc)
+        })
+        .collect()
+}
+
+/// The pairs of local variables `(i, a)` such that `i < a.length`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Guards(BTreeSet<(u16, u16)>);
+
+impl Lattice for Guards {
+    /// The pairs guarded on all the paths.
+    fn join(&self, other: &Self) -> Self {
+        Self(self.0.intersection(&other.0).copied().collect())
+    }
+}
+
+struct GuardAnalysis<'a> {
+    body: &'a MethodBody,
+    /// The program counters at which the control flow may enter other than from the previous
+    /// instruction.
+    entries: &'a BTreeSet<ProgramCounter>,
+}
+
+impl GuardAnalysis<'_> {
+    /// Returns the `N` instructions ending with the one at `pc`, if they are always executed in
+    /// sequence.
+    fn sequence<const N: usize>(&self, pc: ProgramCounter) -> Option<[&Instruction; N]> {
+        let mut instructions = [self.body.instruction_at(pc)?; N];
+        let mut current = pc;
+        for slot in instructions.iter_mut().rev().skip(1) {
+            if self.entries.contains(&current) {
+                return None;
+            }
+            current = self.body.instructions.prev_pc_of(&current)?;
+            *slot = self.body.instruction_at(current)?;
+        }
+        Some(instructions)
+    }
+
+    /// Returns the local variables holding the array and the index accessed at `pc`.
+    fn accessed_element(&self, pc: ProgramCounter) -> Option<(u16, u16)> {
+        #[allow(clippy::enum_glob_use)]
+        use Instruction::*;
+        match self.body.instruction_at(pc)? {
+            IALoad | LALoad | FALoad | DALoad | AALoad | BALoad | CALoad | SALoad => {
+                let [array, index, _] = self.sequence(pc)?;
+                Some((aload_index(array)?, iload_index(index)?))
+            }
+            IAStore | LAStore | FAStore | DAStore | AAStore | BAStore | CAStore | SAStore => {
+                let [array, index, value, _] = self.sequence(pc)?;
+                let pushes_value = value.pushed_constant().is_some()
+                    || value.local_slots().is_some() && is_load(value);
+                pushes_value.then_some((aload_index(array)?, iload_index(index)?))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the local variables `(i, a)` such that `i < a.length` holds if the branch at `pc`
+    /// is `taken` or not.
+    fn guard(&self, pc: ProgramCounter, taken: bool) -> Option<(u16, u16)> {
+        match self.sequence(pc)? {
+            [index, array, Instruction::ArrayLength, branch] => {
+                let holds = match branch {
+                    Instruction::IfICmpGe(_) => !taken,
+                    Instruction::IfICmpLt(_) => taken,
+                    _ => return None,
+                };
+                holds.then_some((iload_index(index)?, aload_index(array)?))
+            }
+            [array, Instruction::ArrayLength, index, branch] => {
+                let holds = match branch {
+                    Instruction::IfICmpLe(_) => !taken,
+                    Instruction::IfICmpGt(_) => taken,
+                    _ => return None,
+                };
+                holds.then_some((iload_index(index)?, aload_index(array)?))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl DataflowAnalysis<Guards> for GuardAnalysis<'_> {
+    type Err = Infallible;
+
+    fn entry_state(&self) -> Guards {
+        Guards(BTreeSet::new())
+    }
+
+    fn transfer(
+        &mut self,
+        _pc: ProgramCounter,
+        instruction: &Instruction,
+        state: &Guards,
+    ) -> Result<Guards, Infallible> {
+        let writes_local = is_store(instruction)
+            || matches!(
+                instruction,
+                Instruction::IInc(..) | Instruction::Wide(WideInstruction::IInc(..))
+            );
+        Ok(match instruction.local_slots() {
+            Some((first, width)) if writes_local => {
+                let written = first..first.saturating_add(width);
+                Guards(
+                    state
+                        .0
+                        .iter()
+                        .filter(|(index, array)| {
+                            !written.contains(index) && !written.contains(array)
+                        })
+                        .copied()
+                        .collect(),
+                )
+            }
+            _ => state.clone(),
+        })
+    }
+
+    fn edge_state(
+        &self,
+        pc: ProgramCounter,
+        instruction: &Instruction,
+        target: ProgramCounter,
+        _before: &Guards,
+        after: &Guards,
+    ) -> Guards {
+        let (Instruction::IfICmpGe(jump_target)
+        | Instruction::IfICmpLt(jump_target)
+        | Instruction::IfICmpLe(jump_target)
+        | Instruction::IfICmpGt(jump_target)) = instruction
+        else {
+            return after.clone();
+        };
+        // Both edges lead to the same instruction, so nothing is known about the condition.
+        if self.body.instructions.next_pc_of(&pc) == Some(*jump_target) {
+            return after.clone();
+        }
+        let mut state = after.clone();
+        state.0.extend(self.guard(pc, target == *jump_target));
+        state
+    }
+}
+
+fn is_load(instruction: &Instruction) -> bool {
+    !is_store(instruction)
+        && !matches!(
+            instruction,
+            Instruction::IInc(..)
+                | Instruction::Ret(_)
+                | Instruction::Wide(WideInstruction::IInc(..) | WideInstruction::Ret(_))
+        )
+}
+
+fn aload_index(instruction: &Instruction) -> Option<u16> {
+    #[allow(clippy::enum_glob_use)]
+    use Instruction::*;
+    match instruction {
+        ALoad(_) | ALoad0 | ALoad1 | ALoad2 | ALoad3 | Wide(WideInstruction::ALoad(_)) => {
+            instruction.local_index()
+        }
+        _ => None,
+    }
+}
+
+fn iload_index(instruction: &Instruction) -> Option<u16> {
+    #[allow(clippy::enum_glob_use)]
+    use Instruction::*;
+    match instruction {
+        ILoad(_) | ILoad0 | ILoad1 | ILoad2 | ILoad3 | Wide(WideInstruction::ILoad(_)) => {
+            instruction.local_index()
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jvm::code::InstructionList;
+
+    /// The code of `for (int i = 0; i < a.length; i++) { a[i] = a[i]; }` with the loop condition
+    /// `cond` and the increment `step`.
+    fn counted_loop(cond: Instruction, step: Instruction) -> MethodBody {
+        let instructions = InstructionList::from([
+            (0.into(), Instruction::IConst0),
+            (1.into(), Instruction::IStore1),
+            (2.into(), Instruction::ILoad1),
+            (3.into(), Instruction::ALoad0),
+            (4.into(), Instruction::ArrayLength),
+            (5.into(), cond),
+            (8.into(), Instruction::ALoad0),
+            (9.into(), Instruction::ILoad1),
+            (10.into(), Instruction::ALoad0),
+            (11.into(), Instruction::ILoad1),
+            (12.into(), Instruction::IALoad),
+            (13.into(), Instruction::IAStore),
+            (14.into(), step),
+            (17.into(), Instruction::Goto(2.into())),
+            (20.into(), Instruction::Return),
+        ]);
+        MethodBody {
+            max_stack: 4,
+            max_locals: 2,
+            instructions,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn counted_loop_access() {
+        let body = counted_loop(Instruction::IfICmpGe(20.into()), Instruction::IInc(1, 1));
+        // The store is not recognized since its value is not pushed by a single instruction.
+        assert_eq!(
+            vec![ProgramCounter::from(12)],
+            redundant_bounds_checks(&body)
+        );
+    }
+
+    #[test]
+    fn unguarded_access() {
+        // i <= a.length
+        let body = counted_loop(Instruction::IfICmpGt(20.into()), Instruction::IInc(1, 1));
+        assert!(redundant_bounds_checks(&body).is_empty());
+
+        // The index may be negative.
+        let body = counted_loop(Instruction::IfICmpGe(20.into()), Instruction::IInc(1, -1));
+        assert!(redundant_bounds_checks(&body).is_empty());
+    }
+}
@@ -0,0 +1,485 @@
+//! A framework for data-flow analyses on JVM bytecode.
+//!
+//! An analysis supplies a [`Lattice`] of abstract states together with a transfer function for
+//! each instruction, and the framework propagates the states along the control flow of a
+//! [`MethodBody`] until a fixed point is reached. The propagation is driven by the same engine as
+//! the other analyses in this crate, i.e., [`Analyzer`], and the frames from which Moka IR is
+//! generated are computed by an analysis on this framework as well.
+//!
+//! Forward analyses, e.g., of the values held by the local variables, implement
+//! [`DataflowAnalysis`], and backward analyses, e.g., of the live local variables, implement
+//! [`BackwardDataflowAnalysis`].
+
+use std::{collections::BTreeMap, convert::Infallible};
+
+use crate::jvm::code::{ExceptionTableEntry, Instruction, MethodBody, ProgramCounter};
+
+use super::fixed_point::Analyzer;
+
+/// A join-semilattice of abstract states.
+pub trait Lattice: Clone + Ord {
+    /// Computes the least upper bound of `self` and `other`.
+    #[must_use]
+    fn join(&self, other: &Self) -> Self;
+
+    /// Computes a state that over-approximates both `self` (the state from the previous
+    /// iteration) and `next`.
+    /// Lattices with infinite ascending chains must override this to guarantee termination.
+    /// By default, it is the same as [`Lattice::join`].
+    #[must_use]
+    fn widen(&self, next: &Self) -> Self {
+        self.join(next)
+    }
+}
+
+/// A forward data-flow analysis over a lattice `L`.
+pub trait DataflowAnalysis<L: Lattice> {
+    /// The error that aborts the analysis, e.g., [`Infallible`] for analyses that cannot fail.
+    type Err;
+
+    /// Creates the state before the first instruction of the method.
+    fn entry_state(&self) -> L;
+
+    /// Computes the state after executing `instruction` at `pc` in the given state.
+    /// # Errors
+    /// Returns [`Self::Err`](DataflowAnalysis::Err) to abort the analysis, e.g., if the
+    /// instruction cannot be executed in the given state.
+    fn transfer(
+        &mut self,
+        pc: ProgramCounter,
+        instruction: &Instruction,
+        state: &L,
+    ) -> Result<L, Self::Err>;
+
+    /// Computes the state flowing from the instruction at `pc` to `target` given the states
+    /// before and after the instruction, e.g., to refine the state according to a branch
+    /// condition.
+    /// By default, the state after the instruction is passed unchanged.
+    fn edge_state(
+        &self,
+        pc: ProgramCounter,
+        instruction: &Instruction,
+        target: ProgramCounter,
+        before: &L,
+        after: &L,
+    ) -> L {
+        let _ = (pc, instruction, target, before);
+        after.clone()
+    }
+
+    /// Computes the state at the entry of an exception handler given the state before an
+    /// instruction covered by it.
+    /// By default, the state is passed unchanged.
+    fn exception_state(&self, handler: &ExceptionTableEntry, state: &L) -> L {
+        let _ = handler;
+        state.clone()
+    }
+
+    /// Computes the program counters the control may flow to after executing `instruction` at
+    /// `pc` in `body`, and the states flowing to them given the states before and after the
+    /// instruction.
+    /// By default, these are the jump targets and the next instruction if the instruction falls
+    /// through, with the states given by [`edge_state`](DataflowAnalysis::edge_state), and the
+    /// handlers covering the instruction, with the states given by
+    /// [`exception_state`](DataflowAnalysis::exception_state).
+    /// Analyses whose control flow depends on the state, e.g., on the return addresses of
+    /// subroutines, override this.
+    /// # Errors
+    /// Returns [`Self::Err`](DataflowAnalysis::Err) to abort the analysis, e.g., if the control
+    /// flows past the last instruction.
+    fn successors(
+        &mut self,
+        body: &MethodBody,
+        pc: ProgramCounter,
+        instruction: &Instruction,
+        before: &L,
+        after: &L,
+    ) -> Result<Vec<(ProgramCounter, L)>, Self::Err> {
+        let fall_through = instruction
+            .falls_through()
+            .then(|| body.instructions.next_pc_of(&pc))
+            .flatten();
+        let mut successors: Vec<_> = instruction
+            .jump_targets()
+            .into_iter()
+            .chain(fall_through)
+            .map(|target| {
+                let state = self.edge_state(pc, instruction, target, before, after);
+                (target, state)
+            })
+            .collect();
+        successors.extend(
+            body.handlers_at(pc)
+                .map(|handler| (handler.handler_pc, self.exception_state(handler, before))),
+        );
+        Ok(successors)
+    }
+
+    /// Merges the states where the control flow joins.
+    /// Returns [`None`] if there are no states to merge.
+    fn merge<I>(&self, states: I) -> Option<L>
+    where
+        I: IntoIterator<Item = L>,
+    {
+        states.into_iter().reduce(|lhs, rhs| lhs.join(&rhs))
+    }
+
+    /// Runs the analysis on the given method body and returns the state before each reachable
+    /// instruction.
+    /// # Errors
+    /// Returns the first error returned by [`transfer`](DataflowAnalysis::transfer) or
+    /// [`successors`](DataflowAnalysis::successors).
+    fn try_analyze(&mut self, body: &MethodBody) -> Result<BTreeMap<ProgramCounter, L>, Self::Err>
+    where
+        Self: Sized,
+    {
+        let mut driver = DataflowDriver {
+            analysis: self,
+            body,
+            _lattice: std::marker::PhantomData,
+        };
+        driver.analyze()
+    }
+
+    /// Runs an analysis that cannot fail on the given method body and returns the state before
+    /// each reachable instruction.
+    fn analyze(&mut self, body: &MethodBody) -> BTreeMap<ProgramCounter, L>
+    where
+        Self: Sized + DataflowAnalysis<L, Err = Infallible>,
+    {
+        match self.try_analyze(body) {
+            Ok(states) => states,
+            Err(infallible) => match infallible {},
+        }
+    }
+}
+
+struct DataflowDriver<'a, A, L> {
+    analysis: &'a mut A,
+    body: &'a MethodBody,
+    _lattice: std::marker::PhantomData<L>,
+}
+
+impl<A, L> Analyzer for DataflowDriver<'_, A, L>
+where
+    A: DataflowAnalysis<L>,
+    L: Lattice,
+{
+    type Location = ProgramCounter;
+    type Fact = L;
+    type Err = A::Err;
+    type AffectedLocations = Vec<(ProgramCounter, L)>;
+
+    fn entry_fact(&self) -> Result<Self::AffectedLocations, Self::Err> {
+        Ok(self
+            .body
+            .instructions
+            .entry_point()
+            .map(|(pc, _)| (*pc, self.analysis.entry_state()))
+            .into_iter()
+            .collect())
+    }
+
+    fn analyze_location(
+        &mut self,
+        location: &Self::Location,
+        fact: &Self::Fact,
+    ) -> Result<Self::AffectedLocations, Self::Err> {
+        let pc = *location;
+        let Some(instruction) = self.body.instruction_at(pc) else {
+            return Ok(Vec::new());
+        };
+        let out_state = self.analysis.transfer(pc, instruction, fact)?;
+        self.analysis
+            .successors(self.body, pc, instruction, fact, &out_state)
+    }
+
+    fn merge_facts(
+        &self,
+        current_fact: &Self::Fact,
+        incoming_fact: Self::Fact,
+    ) -> Result<Self::Fact, Self::Err> {
+        let merged = self
+            .analysis
+            .merge([current_fact.clone(), incoming_fact])
+            .expect("There are two states to merge");
+        Ok(current_fact.widen(&merged))
+    }
+}
+
+/// A backward data-flow analysis over a lattice `L`, which propagates the states against the
+/// control flow, from the instructions leaving the method to the entry point.
+pub trait BackwardDataflowAnalysis<L: Lattice> {
+    /// The error that aborts the analysis, e.g., [`Infallible`] for analyses that cannot fail.
+    type Err;
+
+    /// Creates the state after the instructions leaving the method, e.g., `return` and `athrow`.
+    /// Every instruction starts from this state, so that those from which the method is never
+    /// left, e.g., in an infinite loop, are analyzed as well. It should thus be the least
+    /// element of the lattice, e.g., the empty set.
+    fn exit_state(&self) -> L;
+
+    /// Computes the state before executing `instruction` at `pc` given the state after it.
+    /// # Errors
+    /// Returns [`Self::Err`](BackwardDataflowAnalysis::Err) to abort the analysis.
+    fn transfer(
+        &mut self,
+        pc: ProgramCounter,
+        instruction: &Instruction,
+        state: &L,
+    ) -> Result<L, Self::Err>;
+
+    /// Computes the state flowing back from the entry of an exception handler to an instruction
+    /// covered by it.
+    /// The state is joined with both the states after and before the covered instruction, since
+    /// it may throw before it takes effect.
+    /// By default, the state is passed unchanged.
+    fn exception_state(&self, handler: &ExceptionTableEntry, state: &L) -> L {
+        let _ = handler;
+        state.clone()
+    }
+
+    /// Computes the program counters the control may flow to after executing `instruction` at
+    /// `pc` in `body`, other than the exception handlers.
+    /// By default, these are the jump targets and the next instruction if the instruction falls
+    /// through. Analyses of code with subroutines override this to add the return addresses of
+    /// `ret`.
+    fn successors(
+        &self,
+        body: &MethodBody,
+        pc: ProgramCounter,
+        instruction: &Instruction,
+    ) -> Vec<ProgramCounter> {
+        let fall_through = instruction
+            .falls_through()
+            .then(|| body.instructions.next_pc_of(&pc))
+            .flatten();
+        let mut successors = instruction.jump_targets();
+        successors.extend(fall_through);
+        successors
+    }
+
+    /// Runs the analysis on the given method body and returns the states before and after each
+    /// instruction.
+    /// # Errors
+    /// Returns the first error returned by [`transfer`](BackwardDataflowAnalysis::transfer).
+    fn try_analyze(&mut self, body: &MethodBody) -> Result<BackwardStates<L>, Self::Err>
+    where
+        Self: Sized,
+    {
+        let mut predecessors: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for (pc, instruction) in &body.instructions {
+            for successor in self.successors(body, *pc, instruction) {
+                predecessors.entry(successor).or_default().push(*pc);
+            }
+        }
+        let mut driver = BackwardDriver {
+            analysis: self,
+            body,
+            predecessors,
+            states: BackwardStates {
+                before: BTreeMap::new(),
+                after: BTreeMap::new(),
+            },
+        };
+        driver.analyze()?;
+        Ok(driver.states)
+    }
+
+    /// Runs an analysis that cannot fail on the given method body and returns the states before
+    /// and after each instruction.
+    fn analyze(&mut self, body: &MethodBody) -> BackwardStates<L>
+    where
+        Self: Sized + BackwardDataflowAnalysis<L, Err = Infallible>,
+    {
+        match self.try_analyze(body) {
+            Ok(states) => states,
+            Err(infallible) => match infallible {},
+        }
+    }
+}
+
+/// The states computed by a [`BackwardDataflowAnalysis`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackwardStates<L> {
+    /// The state before each instruction.
+    pub before: BTreeMap<ProgramCounter, L>,
+    /// The state after each instruction, including the states flowing back from the exception
+    /// handlers covering it.
+    pub after: BTreeMap<ProgramCounter, L>,
+}
+
+/// The states flowing back to an instruction from its successors.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Exits<L> {
+    /// The state from the successors on the normal control flow.
+    normal: Option<L>,
+    /// The state from the exception handlers covering the instruction.
+    exceptional: Option<L>,
+}
+
+struct BackwardDriver<'a, A, L> {
+    analysis: &'a mut A,
+    body: &'a MethodBody,
+    predecessors: BTreeMap<ProgramCounter, Vec<ProgramCounter>>,
+    states: BackwardStates<L>,
+}
+
+impl<A, L> Analyzer for BackwardDriver<'_, A, L>
+where
+    A: BackwardDataflowAnalysis<L>,
+    L: Lattice,
+{
+    type Location = ProgramCounter;
+    type Fact = Exits<L>;
+    type Err = A::Err;
+    type AffectedLocations = Vec<(ProgramCounter, Exits<L>)>;
+
+    fn entry_fact(&self) -> Result<Self::AffectedLocations, Self::Err> {
+        Ok(self
+            .body
+            .instructions
+            .iter()
+            .map(|(pc, _)| {
+                let exits = Exits {
+                    normal: Some(self.analysis.exit_state()),
+                    exceptional: None,
+                };
+                (*pc, exits)
+            })
+            .collect())
+    }
+
+    fn analyze_location(
+        &mut self,
+        location: &Self::Location,
+        fact: &Self::Fact,
+    ) -> Result<Self::AffectedLocations, Self::Err> {
+        let pc = *location;
+        let Some(instruction) = self.body.instruction_at(pc) else {
+            return Ok(Vec::new());
+        };
+        let join = |lhs: Option<&L>, rhs: Option<&L>| match (lhs, rhs) {
+            (Some(lhs), Some(rhs)) => Some(lhs.join(rhs)),
+            (lhs, rhs) => lhs.or(rhs).cloned(),
+        };
+        let Some(after) = join(fact.normal.as_ref(), fact.exceptional.as_ref()) else {
+            return Ok(Vec::new());
+        };
+        let before = self.analysis.transfer(pc, instruction, &after)?;
+        let before = join(Some(&before), fact.exceptional.as_ref()).unwrap_or(before);
+
+        let mut affected: Vec<_> = self
+            .predecessors
+            .get(&pc)
+            .into_iter()
+            .flatten()
+            .map(|predecessor| {
+                let exits = Exits {
+                    normal: Some(before.clone()),
+                    exceptional: None,
+                };
+                (*predecessor, exits)
+            })
+            .collect();
+        for handler in self
+            .body
+            .exception_table
+            .iter()
+            .filter(|it| it.handler_pc == pc)
+        {
+            let state = self.analysis.exception_state(handler, &before);
+            affected.extend(
+                self.body
+                    .instructions
+                    .iter()
+                    .filter(|(covered, _)| handler.covers(**covered))
+                    .map(|(covered, _)| {
+                        let exits = Exits {
+                            normal: None,
+                            exceptional: Some(state.clone()),
+                        };
+                        (*covered, exits)
+                    }),
+            );
+        }
+        self.states.before.insert(pc, before);
+        self.states.after.insert(pc, after);
+        Ok(affected)
+    }
+
+    fn merge_facts(
+        &self,
+        current_fact: &Self::Fact,
+        incoming_fact: Self::Fact,
+    ) -> Result<Self::Fact, Self::Err> {
+        let merge = |current: &Option<L>, incoming: Option<L>| match (current, incoming) {
+            (Some(current), Some(incoming)) => Some(current.widen(&current.join(&incoming))),
+            (current, incoming) => incoming.or_else(|| current.clone()),
+        };
+        Ok(Exits {
+            normal: merge(&current_fact.normal, incoming_fact.normal),
+            exceptional: merge(&current_fact.exceptional, incoming_fact.exceptional),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::jvm::code::InstructionList;
+
+    /// Collects the constant values that may be on the top of the stack.
+    struct PushedConstants;
+
+    impl Lattice for BTreeSet<i32> {
+        fn join(&self, other: &Self) -> Self {
+            self.union(other).copied().collect()
+        }
+    }
+
+    impl DataflowAnalysis<BTreeSet<i32>> for PushedConstants {
+        type Err = Infallible;
+
+        fn entry_state(&self) -> BTreeSet<i32> {
+            BTreeSet::new()
+        }
+
+        fn transfer(
+            &mut self,
+            _pc: ProgramCounter,
+            instruction: &Instruction,
+            state: &BTreeSet<i32>,
+        ) -> Result<BTreeSet<i32>, Infallible> {
+            Ok(match instruction {
+                Instruction::IConst0 => BTreeSet::from([0]),
+                Instruction::IConst1 => BTreeSet::from([1]),
+                _ => state.clone(),
+            })
+        }
+    }
+
+    #[test]
+    fn merge_at_join_point() {
+        let instructions = InstructionList::from([
+            (0.into(), Instruction::ILoad(0)),
+            (1.into(), Instruction::IfEq(7.into())),
+            (4.into(), Instruction::IConst1),
+            (5.into(), Instruction::Goto(8.into())),
+            (7.into(), Instruction::IConst0),
+            (8.into(), Instruction::IReturn),
+        ]);
+        let body = MethodBody {
+            max_stack: 1,
+            max_locals: 1,
+            instructions,
+            ..Default::default()
+        };
+        let states = PushedConstants.analyze(&body);
+        assert_eq!(Some(&BTreeSet::from([0, 1])), states.get(&8.into()));
+        assert_eq!(Some(&BTreeSet::new()), states.get(&4.into()));
+        assert_eq!(6, states.len());
+    }
+}
@@ -0,0 +1,128 @@
+//! Writes to fields across classes.
+
+use crate::jvm::{
+    code::{Instruction, ProgramCounter},
+    references::{FieldRef, MethodRef},
+    Class,
+};
+
+/// An instruction writing to a field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldWrite {
+    /// The method containing the instruction.
+    pub method: MethodRef,
+    /// The program counter of the `putfield` or `putstatic` instruction.
+    pub pc: ProgramCounter,
+    /// Whether the write is in an initializer of the class declaring the field, i.e., a
+    /// constructor (`<init>`) for a `putfield` or the static initializer (`<clinit>`) for a
+    /// `putstatic`, where fields are expected to be written.
+    pub in_initializer: bool,
+}
+
+/// Finds the `putfield` and `putstatic` instructions in `classes` writing to `field`.
+/// The field references in the instructions are compared with `field` as they are, without
+/// resolving the field in the superclasses of its owner.
+///
+/// A field is effectively final if all the writes are [in initializers](FieldWrite::in_initializer).
+#[must_use]
+pub fn field_writers<'a, I>(classes: I, field: &FieldRef) -> Vec<FieldWrite>
+where
+    I: IntoIterator<Item = &'a Class>,
+{
+    let mut writes = Vec::new();
+    for class in classes {
+        for method in &class.methods {
+            let Some(body) = method.body.as_ref() else {
+                continue;
+            };
+            for (pc, instruction) in &body.instructions {
+                let in_initializer = match instruction {
+                    Instruction::PutField(it) if it == field => method.is_constructor(),
+                    Instruction::PutStatic(it) if it == field => {
+                        method.is_static_initializer_block()
+                    }
+                    _ => continue,
+                };
+                writes.push(FieldWrite {
+                    method: method.as_ref(),
+                    pc: *pc,
+                    in_initializer: in_initializer && method.owner == field.owner,
+                });
+            }
+        }
+    }
+    writes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jvm::{
+        code::{InstructionList, MethodBody},
+        method,
+        references::ClassRef,
+        Method,
+    };
+
+    fn method(name: &str, instructions: InstructionList<Instruction>) -> Method {
+        Method {
+            access_flags: method::AccessFlags::PUBLIC,
+            name: name.to_owned(),
+            descriptor: "()V".parse().unwrap(),
+            body: Some(MethodBody {
+                instructions,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn field(name: &str) -> FieldRef {
+        FieldRef {
+            owner: ClassRef::new("org/mokapot/Test"),
+            name: name.to_owned(),
+            field_type: "I".parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn writes_in_and_out_of_initializers() {
+        let class = Class {
+            binary_name: "org/mokapot/Test".to_owned(),
+            methods: vec![
+                method(
+                    "<init>",
+                    InstructionList::from([
+                        (0.into(), Instruction::ALoad0),
+                        (1.into(), Instruction::IConst0),
+                        (2.into(), Instruction::PutField(field("value"))),
+                        (5.into(), Instruction::Return),
+                    ]),
+                ),
+                method(
+                    "set",
+                    InstructionList::from([
+                        (0.into(), Instruction::ALoad0),
+                        (1.into(), Instruction::IConst1),
+                        (2.into(), Instruction::PutField(field("value"))),
+                        (5.into(), Instruction::IConst1),
+                        (6.into(), Instruction::PutStatic(field("count"))),
+                        (9.into(), Instruction::Return),
+                    ]),
+                ),
+            ],
+            ..Default::default()
+        };
+        let writes = field_writers([&class], &field("value"));
+        assert_eq!(2, writes.len());
+        assert_eq!("<init>", writes[0].method.name);
+        assert!(writes[0].in_initializer);
+        assert_eq!("set", writes[1].method.name);
+        assert_eq!(ProgramCounter::from(2), writes[1].pc);
+        assert!(!writes[1].in_initializer);
+
+        let writes = field_writers([&class], &field("count"));
+        assert_eq!(1, writes.len());
+        assert!(!writes[0].in_initializer);
+    }
+}
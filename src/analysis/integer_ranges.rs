@@ -0,0 +1,1123 @@
+//! Interval analysis of `int` values in local variables and on the operand stack.
+//!
+//! Each `int` value is approximated by an [`IntRange`]. The ranges are refined along the edges of
+//! the conditional branches comparing `int` values, so that, e.g., the loop counter is known to be
+//! within the loop bound in the loop body.
+//!
+//! The lattice of ranges has infinite ascending chains, e.g., the range of a loop counter grows by
+//! one in each iteration. To guarantee termination, the analysis widens the ranges at the loop
+//! headers, i.e., the targets of backward jumps and the exception handlers, through which every
+//! cycle in the control flow passes: a bound that grows between two visits of a loop header is
+//! moved to [`i32::MIN`] or [`i32::MAX`] at once, so each bound changes at most twice there.
+//! Elsewhere, the ranges are joined, so that the loop body still benefits from the refinement by
+//! the loop condition. There is no separate narrowing pass.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::Infallible,
+};
+
+use crate::jvm::{
+    code::{ExceptionTableEntry, Instruction, MethodBody, ProgramCounter, WideInstruction},
+    ConstantValue,
+};
+use crate::types::{field_type::FieldType, method_descriptor::ReturnType};
+
+use super::dataflow::{DataflowAnalysis, Lattice};
+
+/// A non-empty range of `int` values, with both bounds inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IntRange {
+    min: i32,
+    max: i32,
+}
+
+impl IntRange {
+    /// The range of all `int` values, i.e., nothing is known about the value.
+    pub const FULL: Self = Self {
+        min: i32::MIN,
+        max: i32::MAX,
+    };
+
+    /// Creates a range from `min` to `max`, both inclusive.
+    /// Returns `None` if `min` is greater than `max`.
+    #[must_use]
+    pub const fn new(min: i32, max: i32) -> Option<Self> {
+        if min <= max {
+            Some(Self { min, max })
+        } else {
+            None
+        }
+    }
+
+    /// Creates a range containing only `value`.
+    #[must_use]
+    pub const fn constant(value: i32) -> Self {
+        Self {
+            min: value,
+            max: value,
+        }
+    }
+
+    /// Returns the lower bound of the range.
+    #[must_use]
+    pub const fn min(self) -> i32 {
+        self.min
+    }
+
+    /// Returns the upper bound of the range.
+    #[must_use]
+    pub const fn max(self) -> i32 {
+        self.max
+    }
+
+    /// Checks if `value` is in the range.
+    #[must_use]
+    pub const fn contains(self, value: i32) -> bool {
+        self.min <= value && value <= self.max
+    }
+
+    /// Returns the value if the range contains exactly one.
+    #[must_use]
+    pub const fn as_constant(self) -> Option<i32> {
+        if self.min == self.max {
+            Some(self.min)
+        } else {
+            None
+        }
+    }
+
+    /// Computes the smallest range containing both `self` and `other`.
+    #[must_use]
+    pub fn join(self, other: Self) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    /// Computes the values in both `self` and `other`.
+    /// Returns `None` if there are none.
+    #[must_use]
+    pub fn intersect(self, other: Self) -> Option<Self> {
+        Self::new(self.min.max(other.min), self.max.min(other.max))
+    }
+
+    /// Widens `self`, the range from the previous iteration, to cover `next`, moving each bound
+    /// that grows to the extreme of `int`.
+    #[must_use]
+    pub fn widen(self, next: Self) -> Self {
+        Self {
+            min: if next.min < self.min {
+                i32::MIN
+            } else {
+                self.min
+            },
+            max: if next.max > self.max {
+                i32::MAX
+            } else {
+                self.max
+            },
+        }
+    }
+
+    /// Creates a range from bounds computed without overflow, or [`IntRange::FULL`] if the
+    /// computation in `int` may overflow and wrap around, or if the bounds do not form a
+    /// non-empty range.
+    fn from_i64(min: i64, max: i64) -> Self {
+        match (i32::try_from(min), i32::try_from(max)) {
+            (Ok(min), Ok(max)) if min <= max => Self { min, max },
+            _ => Self::FULL,
+        }
+    }
+
+    fn add(self, other: Self) -> Self {
+        Self::from_i64(
+            i64::from(self.min) + i64::from(other.min),
+            i64::from(self.max) + i64::from(other.max),
+        )
+    }
+
+    fn sub(self, other: Self) -> Self {
+        Self::from_i64(
+            i64::from(self.min) - i64::from(other.max),
+            i64::from(self.max) - i64::from(other.min),
+        )
+    }
+
+    fn neg(self) -> Self {
+        Self::from_i64(-i64::from(self.max), -i64::from(self.min))
+    }
+
+    /// Computes the range of `op` applied to the bounds, which is exact for operations monotone
+    /// in each operand.
+    fn corners(self, other: Self, op: impl Fn(i64, i64) -> i64) -> Self {
+        let corners = [
+            op(self.min.into(), other.min.into()),
+            op(self.min.into(), other.max.into()),
+            op(self.max.into(), other.min.into()),
+            op(self.max.into(), other.max.into()),
+        ];
+        let min = corners.iter().copied().min().unwrap_or(i64::MIN);
+        let max = corners.iter().copied().max().unwrap_or(i64::MAX);
+        Self::from_i64(min, max)
+    }
+
+    fn mul(self, other: Self) -> Self {
+        self.corners(other, |lhs, rhs| lhs * rhs)
+    }
+
+    fn div(self, divisor: Self) -> Self {
+        // Division is monotone in the divisor only if it does not change sign.
+        if divisor.contains(0) {
+            Self::FULL
+        } else {
+            self.corners(divisor, |lhs, rhs| lhs / rhs)
+        }
+    }
+
+    fn rem(self, divisor: Self) -> Self {
+        // The remainder by zero throws an exception, so there is no bound on the result.
+        if divisor == Self::constant(0) {
+            return Self::FULL;
+        }
+        // The magnitude of the remainder is less than that of the divisor, and the remainder has
+        // the sign of the dividend.
+        let bound = i64::from(divisor.min)
+            .abs()
+            .max(i64::from(divisor.max).abs())
+            - 1;
+        let min = if self.min >= 0 {
+            0
+        } else {
+            i64::from(self.min).max(-bound)
+        };
+        let max = if self.max <= 0 {
+            0
+        } else {
+            i64::from(self.max).min(bound)
+        };
+        Self::from_i64(min, max)
+    }
+
+    fn and(self, other: Self) -> Self {
+        // A bitwise and with a non-negative value is between zero and that value.
+        match (self.min >= 0, other.min >= 0) {
+            (true, true) => Self {
+                min: 0,
+                max: self.max.min(other.max),
+            },
+            (true, false) => Self {
+                min: 0,
+                max: self.max,
+            },
+            (false, true) => Self {
+                min: 0,
+                max: other.max,
+            },
+            (false, false) => Self::FULL,
+        }
+    }
+
+    fn shr(self, shift: Self) -> Self {
+        if let Some(shift) = shift.as_constant() {
+            let shift = shift & 0x1f;
+            Self {
+                min: self.min >> shift,
+                max: self.max >> shift,
+            }
+        } else {
+            Self {
+                min: self.min.min(0),
+                max: self.max.max(-1),
+            }
+        }
+    }
+
+    fn ushr(self, shift: Self) -> Self {
+        match shift.as_constant().map(|it| it & 0x1f) {
+            Some(0) => self,
+            Some(shift) if self.min >= 0 => Self {
+                min: self.min >> shift,
+                max: self.max >> shift,
+            },
+            Some(shift) => Self {
+                min: 0,
+                max: (u32::MAX >> shift).try_into().unwrap_or(i32::MAX),
+            },
+            None if self.min >= 0 => Self {
+                min: 0,
+                max: self.max,
+            },
+            None => Self::FULL,
+        }
+    }
+
+    /// Computes the range of the value converted to a narrower type with the given range.
+    fn narrow(self, target: Self) -> Self {
+        if target.min <= self.min && self.max <= target.max {
+            self
+        } else {
+            target
+        }
+    }
+}
+
+const BYTE: IntRange = IntRange {
+    min: i8::MIN as i32,
+    max: i8::MAX as i32,
+};
+const CHAR: IntRange = IntRange {
+    min: 0,
+    max: u16::MAX as i32,
+};
+const SHORT: IntRange = IntRange {
+    min: i16::MIN as i32,
+    max: i16::MAX as i32,
+};
+const COMPARISON: IntRange = IntRange { min: -1, max: 1 };
+const BOOLEAN: IntRange = IntRange { min: 0, max: 1 };
+const NON_NEGATIVE: IntRange = IntRange {
+    min: 0,
+    max: i32::MAX,
+};
+
+/// The ranges of the local variables and the operand stack before an instruction.
+/// Values of types other than `int`, including each slot of a `long` or a `double`, are
+/// approximated by [`IntRange::FULL`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RangeFrame {
+    locals: Vec<IntRange>,
+    /// The values on the operand stack, each with the local variable it was loaded from.
+    stack: Vec<(IntRange, Option<u16>)>,
+}
+
+impl RangeFrame {
+    /// Gets the range of the local variable at the given slot.
+    #[must_use]
+    pub fn local(&self, index: u16) -> Option<IntRange> {
+        self.locals.get(usize::from(index)).copied()
+    }
+
+    /// Gets the range of the value at the given depth of the operand stack, where `0` is the top
+    /// of the stack.
+    /// Values of type `long` and `double` take two slots.
+    #[must_use]
+    pub fn stack_value(&self, depth: usize) -> Option<IntRange> {
+        self.peek(depth).map(|(range, _)| range)
+    }
+
+    /// Returns the number of slots on the operand stack.
+    #[must_use]
+    pub fn stack_depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    fn peek(&self, depth: usize) -> Option<(IntRange, Option<u16>)> {
+        self.stack
+            .len()
+            .checked_sub(depth + 1)
+            .map(|idx| self.stack[idx])
+    }
+
+    fn push(&mut self, range: IntRange) {
+        self.stack.push((range, None));
+    }
+
+    fn push_n(&mut self, count: u16) {
+        for _ in 0..count {
+            self.push(IntRange::FULL);
+        }
+    }
+
+    fn pop(&mut self) -> IntRange {
+        self.stack.pop().map_or(IntRange::FULL, |(range, _)| range)
+    }
+
+    fn pop_n(&mut self, count: u16) {
+        for _ in 0..count {
+            self.pop();
+        }
+    }
+
+    fn load(&mut self, index: u16) {
+        let range = self.local(index).unwrap_or(IntRange::FULL);
+        self.stack.push((range, Some(index)));
+    }
+
+    fn store(&mut self, index: u16, range: IntRange) {
+        let idx = usize::from(index);
+        if self.locals.len() <= idx {
+            self.locals.resize(idx + 1, IntRange::FULL);
+        }
+        self.locals[idx] = range;
+        self.stack
+            .iter_mut()
+            .filter(|(_, origin)| *origin == Some(index))
+            .for_each(|(_, origin)| *origin = None);
+    }
+
+    fn store_n(&mut self, index: u16, width: u16) {
+        self.pop_n(width);
+        for offset in 0..width {
+            self.store(index + offset, IntRange::FULL);
+        }
+    }
+
+    fn store_int(&mut self, index: u16) {
+        let range = self.pop();
+        self.store(index, range);
+    }
+
+    fn increment(&mut self, index: u16, constant: i32) {
+        let range = self.local(index).unwrap_or(IntRange::FULL);
+        self.store(index, range.add(IntRange::constant(constant)));
+    }
+
+    fn binary(&mut self, op: impl FnOnce(IntRange, IntRange) -> IntRange) {
+        let rhs = self.pop();
+        let lhs = self.pop();
+        self.push(op(lhs, rhs));
+    }
+
+    fn unary(&mut self, op: impl FnOnce(IntRange) -> IntRange) {
+        let value = self.pop();
+        self.push(op(value));
+    }
+
+    /// Sets the range of the local variable the value came from, if any.
+    fn refine(&mut self, origin: Option<u16>, range: IntRange) {
+        if let Some(index) = origin {
+            if let Some(local) = self.locals.get_mut(usize::from(index)) {
+                *local = range;
+            }
+            for value in &mut self.stack {
+                if value.1 == Some(index) {
+                    value.0 = range;
+                }
+            }
+        }
+    }
+
+    fn widen(&self, next: &Self) -> Self {
+        let locals = self
+            .locals
+            .iter()
+            .zip(&next.locals)
+            .map(|(prev, next)| prev.widen(*next))
+            .collect();
+        let stack = self
+            .stack
+            .iter()
+            .zip(&next.stack)
+            .map(|((prev, prev_origin), (next, next_origin))| {
+                let origin = Some(prev_origin).filter(|it| it == &next_origin).copied();
+                (prev.widen(*next), origin.flatten())
+            })
+            .collect();
+        Self { locals, stack }
+    }
+}
+
+impl Lattice for RangeFrame {
+    fn join(&self, other: &Self) -> Self {
+        let locals = self
+            .locals
+            .iter()
+            .zip(&other.locals)
+            .map(|(lhs, rhs)| lhs.join(*rhs))
+            .collect();
+        let stack = self
+            .stack
+            .iter()
+            .zip(&other.stack)
+            .map(|((lhs, lhs_origin), (rhs, rhs_origin))| {
+                let origin = Some(lhs_origin).filter(|it| it == &rhs_origin).copied();
+                (lhs.join(*rhs), origin.flatten())
+            })
+            .collect();
+        Self { locals, stack }
+    }
+}
+
+/// The state before an instruction, which is unreachable if it is only reached through branches
+/// whose conditions cannot hold.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum RangeState {
+    Unreachable,
+    Reachable {
+        frame: RangeFrame,
+        /// Whether the instruction is a loop header, where the ranges are widened.
+        at_loop_header: bool,
+    },
+}
+
+impl RangeState {
+    fn map(&self, f: impl FnOnce(&RangeFrame) -> Option<RangeFrame>, at_loop_header: bool) -> Self {
+        match self {
+            Self::Reachable { frame, .. } => {
+                f(frame).map_or(Self::Unreachable, |frame| Self::Reachable {
+                    frame,
+                    at_loop_header,
+                })
+            }
+            Self::Unreachable => Self::Unreachable,
+        }
+    }
+}
+
+impl Lattice for RangeState {
+    fn join(&self, other: &Self) -> Self {
+        match (self, other) {
+            (
+                Self::Reachable {
+                    frame: lhs,
+                    at_loop_header,
+                },
+                Self::Reachable { frame: rhs, .. },
+            ) => Self::Reachable {
+                frame: lhs.join(rhs),
+                at_loop_header: *at_loop_header,
+            },
+            (Self::Unreachable, it) | (it, Self::Unreachable) => it.clone(),
+        }
+    }
+
+    fn widen(&self, next: &Self) -> Self {
+        match (self, next) {
+            (
+                Self::Reachable {
+                    frame: prev,
+                    at_loop_header: true,
+                },
+                Self::Reachable { frame: next, .. },
+            ) => Self::Reachable {
+                frame: prev.widen(next),
+                at_loop_header: true,
+            },
+            _ => self.join(next),
+        }
+    }
+}
+
+/// The result of [`integer_ranges`].
+#[derive(Debug, Clone)]
+pub struct RangeResult {
+    frames: BTreeMap<ProgramCounter, RangeFrame>,
+    impossible_branches: BTreeSet<(ProgramCounter, ProgramCounter)>,
+    out_of_bounds_candidates: BTreeSet<ProgramCounter>,
+}
+
+impl RangeResult {
+    /// Gets the ranges of the values before the instruction at `pc`.
+    /// Returns [`None`] if the instruction is unreachable, including when it is only reached
+    /// through [impossible branches](RangeResult::impossible_branches).
+    #[must_use]
+    pub fn frame_at(&self, pc: ProgramCounter) -> Option<&RangeFrame> {
+        self.frames.get(&pc)
+    }
+
+    /// Returns the edges, as pairs of the program counter of a conditional branch on `int` values
+    /// and its successor, that are never taken because the condition always or never holds.
+    #[must_use]
+    pub fn impossible_branches(&self) -> &BTreeSet<(ProgramCounter, ProgramCounter)> {
+        &self.impossible_branches
+    }
+
+    /// Returns the program counters of the array loads and stores whose index may be negative.
+    /// The lengths of the arrays are not tracked, so an index that is too large is not detected;
+    /// compare the [index range](RangeFrame::stack_value) with the length if it is known.
+    #[must_use]
+    pub fn out_of_bounds_candidates(&self) -> &BTreeSet<ProgramCounter> {
+        &self.out_of_bounds_candidates
+    }
+}
+
+/// Analyzes the ranges of the `int` values in the given method body.
+/// Nothing is assumed about the parameters, i.e., all local variables are [`IntRange::FULL`] at
+/// the entry of the method. To guarantee termination, a bound that grows between two visits of a
+/// loop header, i.e., the target of a backward jump or an exception handler, is widened to
+/// [`i32::MIN`] or [`i32::MAX`] at once.
+#[must_use]
+pub fn integer_ranges(body: &MethodBody) -> RangeResult {
+    let frames: BTreeMap<_, _> = RangeAnalysis::new(body)
+        .analyze(body)
+        .into_iter()
+        .filter_map(|(pc, state)| match state {
+            RangeState::Reachable { frame, .. } => Some((pc, frame)),
+            RangeState::Unreachable => None,
+        })
+        .collect();
+    let mut impossible_branches = BTreeSet::new();
+    let mut out_of_bounds_candidates = BTreeSet::new();
+    for (pc, frame) in &frames {
+        let Some(instruction) = body.instruction_at(*pc) else {
+            continue;
+        };
+        if let Some(depth) = array_index_depth(instruction) {
+            if frame.stack_value(depth).is_none_or(|it| it.min < 0) {
+                out_of_bounds_candidates.insert(*pc);
+            }
+        }
+        if let Some(branch) = Branch::of(instruction) {
+            let Some(fall_through) = body.instructions.next_pc_of(pc) else {
+                continue;
+            };
+            if fall_through == branch.target {
+                continue;
+            }
+            if branch.refine(frame, frame, branch.comparison).is_none() {
+                impossible_branches.insert((*pc, branch.target));
+            }
+            if branch
+                .refine(frame, frame, branch.comparison.negate())
+                .is_none()
+            {
+                impossible_branches.insert((*pc, fall_through));
+            }
+        }
+    }
+    RangeResult {
+        frames,
+        impossible_branches,
+        out_of_bounds_candidates,
+    }
+}
+
+/// Returns the depth of the index on the operand stack for array loads and stores.
+fn array_index_depth(instruction: &Instruction) -> Option<usize> {
+    #[allow(clippy::enum_glob_use)]
+    use Instruction::*;
+    match instruction {
+        IALoad | LALoad | FALoad | DALoad | AALoad | BALoad | CALoad | SALoad => Some(0),
+        IAStore | FAStore | AAStore | BAStore | CAStore | SAStore => Some(1),
+        LAStore | DAStore => Some(2),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Ge,
+    Gt,
+    Le,
+}
+
+impl Comparison {
+    const fn negate(self) -> Self {
+        match self {
+            Self::Eq => Self::Ne,
+            Self::Ne => Self::Eq,
+            Self::Lt => Self::Ge,
+            Self::Ge => Self::Lt,
+            Self::Gt => Self::Le,
+            Self::Le => Self::Gt,
+        }
+    }
+
+    /// Refines the ranges of the operands assuming that `lhs <op> rhs` holds.
+    /// Returns `None` if it cannot hold.
+    fn refine(self, lhs: IntRange, rhs: IntRange) -> Option<(IntRange, IntRange)> {
+        match self {
+            Self::Eq => {
+                let both = lhs.intersect(rhs)?;
+                Some((both, both))
+            }
+            Self::Ne => Some((exclude(lhs, rhs)?, exclude(rhs, lhs)?)),
+            Self::Lt => Some((
+                IntRange::new(lhs.min, lhs.max.min(rhs.max.checked_sub(1)?))?,
+                IntRange::new(rhs.min.max(lhs.min.checked_add(1)?), rhs.max)?,
+            )),
+            Self::Le => Some((
+                IntRange::new(lhs.min, lhs.max.min(rhs.max))?,
+                IntRange::new(rhs.min.max(lhs.min), rhs.max)?,
+            )),
+            Self::Gt => Self::Lt.refine(rhs, lhs).map(|(rhs, lhs)| (lhs, rhs)),
+            Self::Ge => Self::Le.refine(rhs, lhs).map(|(rhs, lhs)| (lhs, rhs)),
+        }
+    }
+}
+
+/// Removes the value of `other` from `range` if it is a constant at either end of `range`.
+fn exclude(range: IntRange, other: IntRange) -> Option<IntRange> {
+    match other.as_constant() {
+        Some(value) if value == range.min => IntRange::new(value.checked_add(1)?, range.max),
+        Some(value) if value == range.max => IntRange::new(range.min, value.checked_sub(1)?),
+        _ => Some(range),
+    }
+}
+
+/// A conditional branch on `int` values.
+struct Branch {
+    comparison: Comparison,
+    /// Whether the value on the top of the stack is compared with zero instead of the value
+    /// below it.
+    with_zero: bool,
+    target: ProgramCounter,
+}
+
+impl Branch {
+    fn of(instruction: &Instruction) -> Option<Self> {
+        #[allow(clippy::enum_glob_use)]
+        use Instruction::*;
+        let (comparison, with_zero, target) = match instruction {
+            IfEq(target) => (Comparison::Eq, true, target),
+            IfNe(target) => (Comparison::Ne, true, target),
+            IfLt(target) => (Comparison::Lt, true, target),
+            IfGe(target) => (Comparison::Ge, true, target),
+            IfGt(target) => (Comparison::Gt, true, target),
+            IfLe(target) => (Comparison::Le, true, target),
+            IfICmpEq(target) => (Comparison::Eq, false, target),
+            IfICmpNe(target) => (Comparison::Ne, false, target),
+            IfICmpLt(target) => (Comparison::Lt, false, target),
+            IfICmpGe(target) => (Comparison::Ge, false, target),
+            IfICmpGt(target) => (Comparison::Gt, false, target),
+            IfICmpLe(target) => (Comparison::Le, false, target),
+            _ => return None,
+        };
+        Some(Self {
+            comparison,
+            with_zero,
+            target: *target,
+        })
+    }
+
+    /// Refines `after`, the frame after the branch, assuming that `comparison` holds for the
+    /// operands in `before`.
+    /// Returns `None` if it cannot hold.
+    fn refine(
+        &self,
+        before: &RangeFrame,
+        after: &RangeFrame,
+        comparison: Comparison,
+    ) -> Option<RangeFrame> {
+        let unknown = (IntRange::FULL, None);
+        let (rhs, rhs_origin) = if self.with_zero {
+            (IntRange::constant(0), None)
+        } else {
+            before.peek(0).unwrap_or(unknown)
+        };
+        let lhs_depth = usize::from(!self.with_zero);
+        let (lhs, lhs_origin) = before.peek(lhs_depth).unwrap_or(unknown);
+        let (lhs, rhs) = comparison.refine(lhs, rhs)?;
+        let mut frame = after.clone();
+        frame.refine(lhs_origin, lhs);
+        frame.refine(rhs_origin, rhs);
+        Some(frame)
+    }
+}
+
+struct RangeAnalysis<'a> {
+    body: &'a MethodBody,
+    /// The targets of the backward jumps and the exception handlers, through which every cycle
+    /// in the control flow passes.
+    loop_headers: BTreeSet<ProgramCounter>,
+}
+
+impl<'a> RangeAnalysis<'a> {
+    fn new(body: &'a MethodBody) -> Self {
+        let backward_targets = body.instructions.iter().flat_map(|(pc, instruction)| {
+            instruction
+                .jump_targets()
+                .into_iter()
+                .filter(move |target| target <= pc)
+        });
+        let handlers = body.exception_table.iter().map(|it| it.handler_pc);
+        Self {
+            body,
+            loop_headers: backward_targets.chain(handlers).collect(),
+        }
+    }
+}
+
+impl DataflowAnalysis<RangeState> for RangeAnalysis<'_> {
+    type Err = Infallible;
+
+    fn entry_state(&self) -> RangeState {
+        RangeState::Reachable {
+            frame: RangeFrame {
+                locals: vec![IntRange::FULL; usize::from(self.body.max_locals)],
+                stack: Vec::new(),
+            },
+            at_loop_header: false,
+        }
+    }
+
+    fn transfer(
+        &mut self,
+        _pc: ProgramCounter,
+        instruction: &Instruction,
+        state: &RangeState,
+    ) -> Result<RangeState, Infallible> {
+        Ok(state.map(|frame| Some(transfer(instruction, frame)), false))
+    }
+
+    fn edge_state(
+        &self,
+        pc: ProgramCounter,
+        instruction: &Instruction,
+        target: ProgramCounter,
+        before: &RangeState,
+        after: &RangeState,
+    ) -> RangeState {
+        let at_loop_header = self.loop_headers.contains(&target);
+        let RangeState::Reachable { frame: before, .. } = before else {
+            return RangeState::Unreachable;
+        };
+        let branch = Branch::of(instruction)
+            // Both edges lead to the same instruction, so nothing is known about the condition.
+            .filter(|it| self.body.instructions.next_pc_of(&pc) != Some(it.target));
+        let Some(branch) = branch else {
+            return after.map(|frame| Some(frame.clone()), at_loop_header);
+        };
+        let comparison = if target == branch.target {
+            branch.comparison
+        } else {
+            branch.comparison.negate()
+        };
+        after.map(
+            |frame| branch.refine(before, frame, comparison),
+            at_loop_header,
+        )
+    }
+
+    fn exception_state(&self, handler: &ExceptionTableEntry, state: &RangeState) -> RangeState {
+        let handler_frame = |frame: &RangeFrame| {
+            Some(RangeFrame {
+                locals: frame.locals.clone(),
+                stack: vec![(IntRange::FULL, None)],
+            })
+        };
+        state.map(
+            handler_frame,
+            self.loop_headers.contains(&handler.handler_pc),
+        )
+    }
+}
+
+#[allow(clippy::too_many_lines)]
+fn transfer(instruction: &Instruction, state: &RangeFrame) -> RangeFrame {
+    #[allow(clippy::enum_glob_use)]
+    use Instruction::*;
+    let mut frame = state.clone();
+    match instruction {
+        Nop | Goto(_) | GotoW(_) | Ret(_) | Return | Breakpoint | ImpDep1 | ImpDep2
+        | CheckCast(_) => {}
+        IConstM1 | IConst0 | IConst1 | IConst2 | IConst3 | IConst4 | IConst5 | BiPush(_)
+        | SiPush(_) => match instruction.pushed_constant() {
+            Some(ConstantValue::Integer(value)) => frame.push(IntRange::constant(value)),
+            _ => frame.push(IntRange::FULL),
+        },
+        AConstNull | FConst0 | FConst1 | FConst2 | Jsr(_) | JsrW(_) | New(_) | FLoad(_)
+        | FLoad0 | FLoad1 | FLoad2 | FLoad3 | ALoad(_) | ALoad0 | ALoad1 | ALoad2 | ALoad3 => {
+            frame.push_n(1);
+        }
+        Ldc(value) | LdcW(value) | Ldc2W(value) => match value {
+            ConstantValue::Integer(value) => frame.push(IntRange::constant(*value)),
+            ConstantValue::Long(_) | ConstantValue::Double(_) => frame.push_n(2),
+            ConstantValue::Dynamic(_, _, field_type) => frame.push_n(field_type.slot_width()),
+            _ => frame.push_n(1),
+        },
+        ILoad(idx) => frame.load(u16::from(*idx)),
+        ILoad0 => frame.load(0),
+        ILoad1 => frame.load(1),
+        ILoad2 => frame.load(2),
+        ILoad3 => frame.load(3),
+        LConst0 | LConst1 | DConst0 | DConst1 | LLoad(_) | DLoad(_) | LLoad0 | LLoad1 | LLoad2
+        | LLoad3 | DLoad0 | DLoad1 | DLoad2 | DLoad3 => frame.push_n(2),
+        BALoad | CALoad | SALoad => {
+            frame.pop_n(2);
+            frame.push(match instruction {
+                BALoad => BYTE,
+                CALoad => CHAR,
+                _ => SHORT,
+            });
+        }
+        IStore(idx) => frame.store_int(u16::from(*idx)),
+        IStore0 => frame.store_int(0),
+        IStore1 => frame.store_int(1),
+        IStore2 => frame.store_int(2),
+        IStore3 => frame.store_int(3),
+        FStore(idx) | AStore(idx) => frame.store_n(u16::from(*idx), 1),
+        FStore0 | AStore0 => frame.store_n(0, 1),
+        FStore1 | AStore1 => frame.store_n(1, 1),
+        FStore2 | AStore2 => frame.store_n(2, 1),
+        FStore3 | AStore3 => frame.store_n(3, 1),
+        LStore(idx) | DStore(idx) => frame.store_n(u16::from(*idx), 2),
+        LStore0 | DStore0 => frame.store_n(0, 2),
+        LStore1 | DStore1 => frame.store_n(1, 2),
+        LStore2 | DStore2 => frame.store_n(2, 2),
+        LStore3 | DStore3 => frame.store_n(3, 2),
+        IAStore | FAStore | AAStore | BAStore | CAStore | SAStore => frame.pop_n(3),
+        LAStore | DAStore => frame.pop_n(4),
+        Dup => duplicate(&mut frame, 1, 0),
+        DupX1 => duplicate(&mut frame, 1, 1),
+        DupX2 => duplicate(&mut frame, 1, 2),
+        Dup2 => duplicate(&mut frame, 2, 0),
+        Dup2X1 => duplicate(&mut frame, 2, 1),
+        Dup2X2 => duplicate(&mut frame, 2, 2),
+        Swap => {
+            let len = frame.stack.len();
+            if len >= 2 {
+                frame.stack.swap(len - 1, len - 2);
+            }
+        }
+        IAdd => frame.binary(IntRange::add),
+        ISub => frame.binary(IntRange::sub),
+        IMul => frame.binary(IntRange::mul),
+        IDiv => frame.binary(IntRange::div),
+        IRem => frame.binary(IntRange::rem),
+        IAnd => frame.binary(IntRange::and),
+        IShr => frame.binary(IntRange::shr),
+        IUShr => frame.binary(IntRange::ushr),
+        IALoad | FALoad | AALoad | IShl | IOr | IXor | FAdd | FSub | FMul | FDiv | FRem | L2I
+        | L2F | D2I | D2F => {
+            frame.pop_n(2);
+            frame.push_n(1);
+        }
+        FCmpL | FCmpG => {
+            frame.pop_n(2);
+            frame.push(COMPARISON);
+        }
+        LAdd | DAdd | LSub | DSub | LMul | DMul | LDiv | DDiv | LRem | DRem | LAnd | LOr | LXor => {
+            frame.pop_n(4);
+            frame.push_n(2);
+        }
+        LShl | LShr | LUShr => {
+            frame.pop_n(3);
+            frame.push_n(2);
+        }
+        INeg => frame.unary(IntRange::neg),
+        I2B => frame.unary(|it| it.narrow(BYTE)),
+        I2C => frame.unary(|it| it.narrow(CHAR)),
+        I2S => frame.unary(|it| it.narrow(SHORT)),
+        NewArray(_) | ANewArray(_) | FNeg | I2F | F2I => {
+            frame.pop_n(1);
+            frame.push_n(1);
+        }
+        InstanceOf(_) => {
+            frame.pop_n(1);
+            frame.push(BOOLEAN);
+        }
+        ArrayLength => {
+            frame.pop_n(1);
+            frame.push(NON_NEGATIVE);
+        }
+        LALoad | DALoad | LNeg | DNeg | L2D | D2L => {
+            frame.pop_n(2);
+            frame.push_n(2);
+        }
+        I2L | I2D | F2L | F2D => {
+            frame.pop_n(1);
+            frame.push_n(2);
+        }
+        LCmp | DCmpL | DCmpG => {
+            frame.pop_n(4);
+            frame.push(COMPARISON);
+        }
+        IInc(idx, constant) => frame.increment(u16::from(*idx), *constant),
+        Pop
+        | IfEq(_)
+        | IfNe(_)
+        | IfLt(_)
+        | IfGe(_)
+        | IfGt(_)
+        | IfLe(_)
+        | IfNull(_)
+        | IfNonNull(_)
+        | TableSwitch { .. }
+        | LookupSwitch { .. }
+        | IReturn
+        | FReturn
+        | AReturn
+        | AThrow
+        | MonitorEnter
+        | MonitorExit => frame.pop_n(1),
+        Pop2 | IfICmpEq(_) | IfICmpNe(_) | IfICmpLt(_) | IfICmpGe(_) | IfICmpGt(_)
+        | IfICmpLe(_) | IfACmpEq(_) | IfACmpNe(_) | LReturn | DReturn => frame.pop_n(2),
+        GetStatic(field) => frame.push_n(field.field_type.slot_width()),
+        PutStatic(field) => frame.pop_n(field.field_type.slot_width()),
+        GetField(field) => {
+            frame.pop_n(1);
+            frame.push_n(field.field_type.slot_width());
+        }
+        PutField(field) => frame.pop_n(field.field_type.slot_width() + 1),
+        InvokeVirtual(method) | InvokeSpecial(method) | InvokeInterface(method, _) => {
+            frame.pop_n(1);
+            invoke(
+                &mut frame,
+                &method.descriptor.parameters_types,
+                &method.descriptor.return_type,
+            );
+        }
+        InvokeStatic(method) => {
+            invoke(
+                &mut frame,
+                &method.descriptor.parameters_types,
+                &method.descriptor.return_type,
+            );
+        }
+        InvokeDynamic { descriptor, .. } => {
+            invoke(
+                &mut frame,
+                &descriptor.parameters_types,
+                &descriptor.return_type,
+            );
+        }
+        MultiANewArray(_, dimensions) => {
+            frame.pop_n(u16::from(*dimensions));
+            frame.push_n(1);
+        }
+        Wide(wide) => match wide {
+            WideInstruction::ILoad(idx) => frame.load(*idx),
+            WideInstruction::FLoad(_) | WideInstruction::ALoad(_) => frame.push_n(1),
+            WideInstruction::LLoad(_) | WideInstruction::DLoad(_) => frame.push_n(2),
+            WideInstruction::IStore(idx) => frame.store_int(*idx),
+            WideInstruction::FStore(idx) | WideInstruction::AStore(idx) => frame.store_n(*idx, 1),
+            WideInstruction::LStore(idx) | WideInstruction::DStore(idx) => frame.store_n(*idx, 2),
+            WideInstruction::IInc(idx, constant) => frame.increment(*idx, *constant),
+            WideInstruction::Ret(_) => {}
+        },
+    }
+    frame
+}
+
+/// Pops the arguments of an invocation, but not the receiver, and pushes its result.
+fn invoke(frame: &mut RangeFrame, parameters_types: &[FieldType], return_type: &ReturnType) {
+    for parameter_type in parameters_types {
+        frame.pop_n(parameter_type.slot_width());
+    }
+    if let ReturnType::Some(return_type) = return_type {
+        frame.push_n(return_type.slot_width());
+    }
+}
+
+/// Duplicates the top `count` slots and inserts them below the `skip` slots beneath them.
+fn duplicate(frame: &mut RangeFrame, count: usize, skip: usize) {
+    let len = frame.stack.len();
+    let Some(insert_at) = len.checked_sub(count + skip) else {
+        return;
+    };
+    let copied: Vec<_> = frame.stack[len - count..].to_vec();
+    frame.stack.splice(insert_at..insert_at, copied);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jvm::code::InstructionList;
+
+    fn body_of(instructions: InstructionList<Instruction>) -> MethodBody {
+        MethodBody {
+            max_stack: 3,
+            max_locals: 2,
+            instructions,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn loop_counter() {
+        // for (int i = 0; i < 10; i++) { a[i]; }
+        let body = body_of(InstructionList::from([
+            (0.into(), Instruction::IConst0),
+            (1.into(), Instruction::IStore1),
+            (2.into(), Instruction::ILoad1),
+            (3.into(), Instruction::BiPush(10)),
+            (5.into(), Instruction::IfICmpGe(18.into())),
+            (8.into(), Instruction::ALoad0),
+            (9.into(), Instruction::ILoad1),
+            (10.into(), Instruction::IALoad),
+            (11.into(), Instruction::Pop),
+            (12.into(), Instruction::IInc(1, 1)),
+            (15.into(), Instruction::Goto(2.into())),
+            (18.into(), Instruction::Return),
+        ]));
+        let result = integer_ranges(&body);
+        let local = |pc: u16| result.frame_at(pc.into()).unwrap().local(1);
+        // The counter is widened at the loop header and refined by the loop condition.
+        assert_eq!(IntRange::new(0, i32::MAX), local(2));
+        assert_eq!(IntRange::new(0, 9), local(8));
+        assert_eq!(IntRange::new(1, 10), local(15));
+        assert_eq!(IntRange::new(10, i32::MAX), local(18));
+        assert_eq!(
+            IntRange::new(0, 9),
+            result.frame_at(10.into()).unwrap().stack_value(0)
+        );
+        assert!(result.out_of_bounds_candidates().is_empty());
+        assert!(result.impossible_branches().is_empty());
+    }
+
+    #[test]
+    fn impossible_branch() {
+        let body = body_of(InstructionList::from([
+            (0.into(), Instruction::ALoad0),
+            (1.into(), Instruction::ArrayLength),
+            (2.into(), Instruction::IStore1),
+            (3.into(), Instruction::ILoad1),
+            (4.into(), Instruction::IfGe(9.into())),
+            (7.into(), Instruction::IConstM1),
+            (8.into(), Instruction::IReturn),
+            (9.into(), Instruction::ILoad1),
+            (10.into(), Instruction::IReturn),
+        ]));
+        let result = integer_ranges(&body);
+        // The length of an array is never negative.
+        assert_eq!(
+            &BTreeSet::from([(4.into(), 7.into())]),
+            result.impossible_branches()
+        );
+        assert!(result.frame_at(7.into()).is_none());
+        assert_eq!(
+            IntRange::new(0, i32::MAX),
+            result.frame_at(9.into()).unwrap().local(1)
+        );
+    }
+
+    #[test]
+    fn negative_index() {
+        // return a[i & 3] + a[i];
+        let body = body_of(InstructionList::from([
+            (0.into(), Instruction::ALoad0),
+            (1.into(), Instruction::ILoad1),
+            (2.into(), Instruction::IConst3),
+            (3.into(), Instruction::IAnd),
+            (4.into(), Instruction::IALoad),
+            (5.into(), Instruction::ALoad0),
+            (6.into(), Instruction::ILoad1),
+            (7.into(), Instruction::IALoad),
+            (8.into(), Instruction::IAdd),
+            (9.into(), Instruction::IReturn),
+        ]));
+        let result = integer_ranges(&body);
+        assert_eq!(
+            IntRange::new(0, 3),
+            result.frame_at(4.into()).unwrap().stack_value(0)
+        );
+        assert_eq!(
+            &BTreeSet::from([7.into()]),
+            result.out_of_bounds_candidates()
+        );
+    }
+
+    #[test]
+    fn arithmetic() {
+        let range = |min, max| IntRange::new(min, max).unwrap();
+        assert_eq!(range(3, 12), range(1, 4).add(range(2, 8)));
+        assert_eq!(IntRange::FULL, range(1, i32::MAX).add(range(0, 1)));
+        assert_eq!(range(-8, 8), range(-2, 1).mul(range(-4, 4)));
+        assert_eq!(range(-5, 2), range(-10, 5).div(range(2, 2)));
+        assert_eq!(IntRange::FULL, range(-10, 5).div(range(-1, 1)));
+        assert_eq!(range(0, 6), range(0, 100).rem(range(-7, 3)));
+        assert_eq!(IntRange::FULL, range(5, 5).rem(IntRange::constant(0)));
+        assert_eq!(range(-4, 4), range(-5, 5).rem(range(0, 5)));
+        assert_eq!(IntRange::FULL, IntRange::from_i64(1, 0));
+        assert_eq!(range(0, 0xff), IntRange::FULL.and(IntRange::constant(0xff)));
+        assert_eq!(range(0, 1), IntRange::FULL.ushr(IntRange::constant(31)));
+        assert_eq!(BYTE, range(0, 1000).narrow(BYTE));
+        assert_eq!(range(0, i32::MAX), range(0, 5).widen(range(0, 6)));
+    }
+}
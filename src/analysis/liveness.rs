@@ -0,0 +1,404 @@
+//! Liveness analysis of local variables.
+
+use std::{cmp::Ordering, collections::BTreeMap, convert::Infallible};
+
+use crate::jvm::code::{Instruction, MethodBody, ProgramCounter, WideInstruction};
+
+use super::dataflow::{BackwardDataflowAnalysis, BackwardStates, Lattice};
+
+/// A set of local variable slots.
+#[derive(Debug, Clone, Default)]
+pub struct LocalSlots {
+    words: Vec<u64>,
+}
+
+impl PartialEq for LocalSlots {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for LocalSlots {}
+
+impl PartialOrd for LocalSlots {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for LocalSlots {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // The sets may have different capacities, so the empty words at the end are ignored.
+        self.significant_words().cmp(other.significant_words())
+    }
+}
+
+impl Lattice for LocalSlots {
+    fn join(&self, other: &Self) -> Self {
+        let mut joined = self.clone();
+        joined.union_with(other);
+        joined
+    }
+}
+
+impl LocalSlots {
+    fn with_capacity(slots: u16) -> Self {
+        Self {
+            words: vec![0; usize::from(slots).div_ceil(64)],
+        }
+    }
+
+    /// Checks if the set contains the given slot.
+    #[must_use]
+    pub fn contains(&self, slot: u16) -> bool {
+        let (word, bit) = Self::position(slot);
+        self.words.get(word).is_some_and(|it| it & (1 << bit) != 0)
+    }
+
+    /// Returns the number of slots in the set.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|it| usize::try_from(it.count_ones()).unwrap_or_default())
+            .sum()
+    }
+
+    /// Checks if the set is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|it| *it == 0)
+    }
+
+    /// Returns an iterator over the slots in the set in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = u16> + '_ {
+        (0..=u16::MAX)
+            .take(self.words.len() * 64)
+            .filter(|it| self.contains(*it))
+    }
+
+    fn insert(&mut self, slot: u16) {
+        let (word, bit) = Self::position(slot);
+        if self.words.len() <= word {
+            self.words.resize(word + 1, 0);
+        }
+        self.words[word] |= 1 << bit;
+    }
+
+    fn remove(&mut self, slot: u16) {
+        let (word, bit) = Self::position(slot);
+        if let Some(it) = self.words.get_mut(word) {
+            *it &= !(1 << bit);
+        }
+    }
+
+    fn union_with(&mut self, other: &Self) {
+        if self.words.len() < other.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (lhs, rhs) in self.words.iter_mut().zip(&other.words) {
+            *lhs |= rhs;
+        }
+    }
+
+    fn significant_words(&self) -> &[u64] {
+        let len = self
+            .words
+            .iter()
+            .rposition(|it| *it != 0)
+            .map_or(0, |it| it + 1);
+        &self.words[..len]
+    }
+
+    const fn position(slot: u16) -> (usize, u16) {
+        ((slot / 64) as usize, slot % 64)
+    }
+}
+
+/// The live local variables before and after each instruction of a method.
+#[derive(Debug, Clone)]
+pub struct Liveness {
+    live_in: BTreeMap<ProgramCounter, LocalSlots>,
+    live_out: BTreeMap<ProgramCounter, LocalSlots>,
+}
+
+impl Liveness {
+    /// Returns the local variable slots that are live before the instruction at `pc`.
+    #[must_use]
+    pub fn live_in(&self, pc: ProgramCounter) -> Option<&LocalSlots> {
+        self.live_in.get(&pc)
+    }
+
+    /// Returns the local variable slots that are live after the instruction at `pc`.
+    #[must_use]
+    pub fn live_out(&self, pc: ProgramCounter) -> Option<&LocalSlots> {
+        self.live_out.get(&pc)
+    }
+}
+
+/// Computes the live local variables at each instruction of `body`, i.e., the slots whose
+/// current values may be read on some path before they are overwritten.
+///
+/// A value is considered live at an instruction if it may be read by an exception handler
+/// covering the instruction, since the instruction may throw before it takes effect.
+/// A `ret` instruction is assumed to return to any instruction following a `jsr`.
+#[must_use]
+pub fn liveness(body: &MethodBody) -> Liveness {
+    let return_addresses = body
+        .instructions
+        .iter()
+        .filter(|(_, insn)| matches!(insn, Instruction::Jsr(_) | Instruction::JsrW(_)))
+        .filter_map(|(pc, _)| body.instructions.next_pc_of(pc))
+        .collect();
+    let mut analysis = LivenessAnalysis {
+        slots: body.max_locals,
+        return_addresses,
+    };
+    let BackwardStates { before, after } = analysis.analyze(body);
+    Liveness {
+        live_in: before,
+        live_out: after,
+    }
+}
+
+/// The backward analysis computing the live local variables.
+struct LivenessAnalysis {
+    /// The number of local variable slots, which the sets are created with.
+    slots: u16,
+    /// The instructions following a `jsr`, to which a `ret` may return.
+    return_addresses: Vec<ProgramCounter>,
+}
+
+impl BackwardDataflowAnalysis<LocalSlots> for LivenessAnalysis {
+    type Err = Infallible;
+
+    fn exit_state(&self) -> LocalSlots {
+        LocalSlots::with_capacity(self.slots)
+    }
+
+    fn transfer(
+        &mut self,
+        _pc: ProgramCounter,
+        instruction: &Instruction,
+        state: &LocalSlots,
+    ) -> Result<LocalSlots, Self::Err> {
+        let mut live = state.clone();
+        if let Some((index, width)) = instruction.local_slots() {
+            let slots = index..index.saturating_add(width);
+            if is_store(instruction) {
+                slots.for_each(|it| live.remove(it));
+            } else {
+                // Loads, `iinc`, and `ret` read the local variables.
+                slots.for_each(|it| live.insert(it));
+            }
+        }
+        Ok(live)
+    }
+
+    fn successors(
+        &self,
+        body: &MethodBody,
+        pc: ProgramCounter,
+        instruction: &Instruction,
+    ) -> Vec<ProgramCounter> {
+        let fall_through = instruction
+            .falls_through()
+            .then(|| body.instructions.next_pc_of(&pc))
+            .flatten();
+        let mut successors = instruction.jump_targets();
+        successors.extend(fall_through);
+        if matches!(
+            instruction,
+            Instruction::Ret(_) | Instruction::Wide(WideInstruction::Ret(_))
+        ) {
+            successors.extend(&self.return_addresses);
+        }
+        successors
+    }
+}
+
+/// Checks if the instruction overwrites the local variables it accesses.
+pub(crate) fn is_store(instruction: &Instruction) -> bool {
+    #[allow(clippy::enum_glob_use)]
+    use Instruction::*;
+    matches!(
+        instruction,
+        IStore(_)
+            | LStore(_)
+            | FStore(_)
+            | DStore(_)
+            | AStore(_)
+            | IStore0
+            | IStore1
+            | IStore2
+            | IStore3
+            | LStore0
+            | LStore1
+            | LStore2
+            | LStore3
+            | FStore0
+            | FStore1
+            | FStore2
+            | FStore3
+            | DStore0
+            | DStore1
+            | DStore2
+            | DStore3
+            | AStore0
+            | AStore1
+            | AStore2
+            | AStore3
+            | Wide(
+                WideInstruction::IStore(_)
+                    | WideInstruction::LStore(_)
+                    | WideInstruction::FStore(_)
+                    | WideInstruction::DStore(_)
+                    | WideInstruction::AStore(_)
+            )
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jvm::code::{ExceptionTableEntry, InstructionList};
+
+    fn slots(liveness: Option<&LocalSlots>) -> Vec<u16> {
+        liveness.unwrap().iter().collect()
+    }
+
+    #[test]
+    fn straight_line() {
+        // static int f(int a, int b) { int c = a + 1; return c; }
+        let body = MethodBody {
+            max_locals: 3,
+            instructions: InstructionList::from([
+                (0.into(), Instruction::ILoad0),
+                (1.into(), Instruction::IConst1),
+                (2.into(), Instruction::IAdd),
+                (3.into(), Instruction::IStore2),
+                (4.into(), Instruction::ILoad2),
+                (5.into(), Instruction::IReturn),
+            ]),
+            ..Default::default()
+        };
+        let liveness = liveness(&body);
+        assert_eq!(vec![0], slots(liveness.live_in(0.into())));
+        assert!(liveness.live_in(1.into()).unwrap().is_empty());
+        assert_eq!(vec![2], slots(liveness.live_out(3.into())));
+        assert!(liveness.live_in(3.into()).unwrap().is_empty());
+        assert!(liveness.live_out(5.into()).unwrap().is_empty());
+        assert!(!liveness.live_in(0.into()).unwrap().contains(1));
+    }
+
+    #[test]
+    fn loop_and_wide_values() {
+        // for (long i = 0; i < n; i++) {}
+        let body = MethodBody {
+            max_locals: 3,
+            instructions: InstructionList::from([
+                (0.into(), Instruction::LConst0),
+                (1.into(), Instruction::LStore1),
+                (2.into(), Instruction::LLoad1),
+                (3.into(), Instruction::ILoad0),
+                (4.into(), Instruction::I2L),
+                (5.into(), Instruction::LCmp),
+                (6.into(), Instruction::IfGe(16.into())),
+                (9.into(), Instruction::LLoad1),
+                (10.into(), Instruction::LConst1),
+                (11.into(), Instruction::LAdd),
+                (12.into(), Instruction::LStore1),
+                (13.into(), Instruction::Goto(2.into())),
+                (16.into(), Instruction::Return),
+            ]),
+            ..Default::default()
+        };
+        let liveness = liveness(&body);
+        assert_eq!(vec![0], slots(liveness.live_in(0.into())));
+        assert_eq!(vec![0, 1, 2], slots(liveness.live_in(2.into())));
+        assert_eq!(vec![0, 1, 2], slots(liveness.live_out(13.into())));
+        assert_eq!(vec![0], slots(liveness.live_in(12.into())));
+        assert!(liveness.live_in(16.into()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn exception_handler() {
+        let body = MethodBody {
+            max_locals: 2,
+            instructions: InstructionList::from([
+                (0.into(), Instruction::IConst0),
+                (1.into(), Instruction::IStore1),
+                (2.into(), Instruction::Return),
+                (3.into(), Instruction::Pop),
+                (4.into(), Instruction::ILoad1),
+                (5.into(), Instruction::IReturn),
+            ]),
+            exception_table: vec![ExceptionTableEntry {
+                covered_pc: 0.into()..2.into(),
+                handler_pc: 3.into(),
+                catch_type: None,
+            }],
+            ..Default::default()
+        };
+        let liveness = liveness(&body);
+        assert_eq!(vec![1], slots(liveness.live_in(0.into())));
+        assert_eq!(vec![1], slots(liveness.live_in(1.into())));
+        assert!(liveness.live_out(1.into()).unwrap().contains(1));
+        assert!(liveness.live_in(2.into()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn handler_reading_overwritten_slot() {
+        let body = MethodBody {
+            max_locals: 2,
+            instructions: InstructionList::from([
+                (0.into(), Instruction::IConst0),
+                (1.into(), Instruction::IStore1),
+                (2.into(), Instruction::ILoad1),
+                (3.into(), Instruction::IReturn),
+                (4.into(), Instruction::ILoad1),
+                (5.into(), Instruction::IReturn),
+            ]),
+            exception_table: vec![ExceptionTableEntry {
+                covered_pc: 1.into()..2.into(),
+                handler_pc: 4.into(),
+                catch_type: None,
+            }],
+            ..Default::default()
+        };
+        let liveness = liveness(&body);
+        // The store may throw before it overwrites the value read by the handler.
+        assert_eq!(vec![1], slots(liveness.live_in(1.into())));
+        assert_eq!(vec![1], slots(liveness.live_in(0.into())));
+    }
+
+    #[test]
+    fn infinite_loop() {
+        let body = MethodBody {
+            max_locals: 1,
+            instructions: InstructionList::from([
+                (0.into(), Instruction::IInc(0, 1)),
+                (3.into(), Instruction::Goto(0.into())),
+            ]),
+            ..Default::default()
+        };
+        let liveness = liveness(&body);
+        assert_eq!(vec![0], slots(liveness.live_in(0.into())));
+        assert_eq!(vec![0], slots(liveness.live_out(3.into())));
+    }
+
+    #[test]
+    fn slots_equality() {
+        let mut small = LocalSlots::with_capacity(1);
+        let mut large = LocalSlots::with_capacity(200);
+        assert_eq!(small, large);
+        small.insert(3);
+        large.insert(3);
+        assert_eq!(small, large);
+        large.insert(130);
+        assert_ne!(small, large);
+        assert_ne!(large, small);
+        large.remove(130);
+        assert_eq!(large, small);
+    }
+}
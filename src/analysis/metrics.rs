@@ -0,0 +1,142 @@
+//! Instruction-level metrics of methods and classes.
+
+use std::collections::BTreeMap;
+
+use crate::jvm::{
+    code::{Instruction, MethodBody},
+    Class,
+};
+
+/// Metrics of a method body.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MethodMetrics {
+    /// The number of instructions.
+    pub instruction_count: usize,
+    /// The number of conditional branches and switches.
+    pub branch_count: usize,
+    /// The cyclomatic complexity, i.e., `E - N + 2` over the control flow graph.
+    pub cyclomatic_complexity: usize,
+    /// The number of occurrences of each instruction, keyed by its name.
+    pub opcode_histogram: BTreeMap<&'static str, usize>,
+}
+
+/// Metrics of a class, aggregated over the methods with a body.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClassMetrics {
+    /// The number of methods with a body.
+    pub method_count: usize,
+    /// The sum of the metrics of the methods.
+    pub total: MethodMetrics,
+    /// The highest cyclomatic complexity among the methods.
+    pub max_cyclomatic_complexity: usize,
+}
+
+/// Computes the metrics of the given method body.
+/// The control flow graph used for the cyclomatic complexity has one node per instruction and a
+/// virtual exit node that all returning and throwing instructions flow to.
+/// Each case of a switch is counted as a separate edge, even if several cases share a target.
+/// Exceptional control flow is not considered.
+#[must_use]
+pub fn method_metrics(body: &MethodBody) -> MethodMetrics {
+    let mut metrics = MethodMetrics::default();
+    let mut edges = 0;
+    for (pc, instruction) in &body.instructions {
+        metrics.instruction_count += 1;
+        *metrics
+            .opcode_histogram
+            .entry(instruction.name())
+            .or_default() += 1;
+        let jump_targets = instruction.jump_targets();
+        if is_branch(instruction) {
+            metrics.branch_count += 1;
+        }
+        let falls_through =
+            instruction.falls_through() && body.instructions.next_pc_of(pc).is_some();
+        let out_edges = jump_targets.len() + usize::from(falls_through);
+        // Instructions without successors flow to the virtual exit node.
+        edges += out_edges.max(1);
+    }
+    let nodes = metrics.instruction_count + 1;
+    metrics.cyclomatic_complexity = (edges + 2).saturating_sub(nodes);
+    metrics
+}
+
+fn is_branch(instruction: &Instruction) -> bool {
+    use Instruction::{Goto, GotoW, Jsr, JsrW};
+    !instruction.jump_targets().is_empty()
+        && !matches!(instruction, Goto(_) | GotoW(_) | Jsr(_) | JsrW(_))
+}
+
+impl MethodMetrics {
+    fn accumulate(&mut self, other: Self) {
+        self.instruction_count += other.instruction_count;
+        self.branch_count += other.branch_count;
+        self.cyclomatic_complexity += other.cyclomatic_complexity;
+        for (name, count) in other.opcode_histogram {
+            *self.opcode_histogram.entry(name).or_default() += count;
+        }
+    }
+}
+
+impl Class {
+    /// Computes the metrics of the class by aggregating [`method_metrics`] over its methods.
+    #[must_use]
+    pub fn metrics(&self) -> ClassMetrics {
+        let mut class_metrics = ClassMetrics::default();
+        for body in self.methods.iter().filter_map(|it| it.body.as_ref()) {
+            let metrics = method_metrics(body);
+            class_metrics.method_count += 1;
+            class_metrics.max_cyclomatic_complexity = class_metrics
+                .max_cyclomatic_complexity
+                .max(metrics.cyclomatic_complexity);
+            class_metrics.total.accumulate(metrics);
+        }
+        class_metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jvm::code::{Instruction::*, InstructionList};
+
+    #[test]
+    fn straight_line() {
+        let body = MethodBody {
+            instructions: InstructionList::from([(0.into(), IConst0), (1.into(), IReturn)]),
+            ..Default::default()
+        };
+        let metrics = method_metrics(&body);
+        assert_eq!(2, metrics.instruction_count);
+        assert_eq!(0, metrics.branch_count);
+        assert_eq!(1, metrics.cyclomatic_complexity);
+        assert_eq!(Some(&1), metrics.opcode_histogram.get("ireturn"));
+    }
+
+    #[test]
+    fn branches_and_switches() {
+        let body = MethodBody {
+            instructions: InstructionList::from([
+                (0.into(), ILoad0),
+                (1.into(), IfEq(6.into())),
+                (4.into(), IConst1),
+                (5.into(), IReturn),
+                (6.into(), ILoad0),
+                (
+                    7.into(),
+                    LookupSwitch {
+                        default: 32.into(),
+                        match_targets: BTreeMap::from([(1, 33.into()), (2, 33.into())]),
+                    },
+                ),
+                (32.into(), Return),
+                (33.into(), Return),
+            ]),
+            ..Default::default()
+        };
+        let metrics = method_metrics(&body);
+        assert_eq!(2, metrics.branch_count);
+        // One decision for `ifeq` and two for the switch cases.
+        assert_eq!(4, metrics.cyclomatic_complexity);
+    }
+}
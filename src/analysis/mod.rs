@@ -7,7 +7,31 @@ use crate::{
     jvm::{class_loader::ClassPath, references::ClassRef, Class},
 };
 
+mod access;
+mod bounds_checks;
+pub mod dataflow;
+mod field_writers;
 pub mod fixed_point;
+mod integer_ranges;
+mod liveness;
+pub mod metrics;
+mod monitors;
+mod nullness;
+mod reachability;
+mod string_switch;
+
+pub use access::{is_accessible_from, Member};
+pub use bounds_checks::redundant_bounds_checks;
+pub use field_writers::{field_writers, FieldWrite};
+pub use integer_ranges::{integer_ranges, IntRange, RangeFrame, RangeResult};
+pub use liveness::{liveness, Liveness, LocalSlots};
+pub use monitors::{
+    is_monitor_exit_handler, monitor_exit_handlers, monitor_regions, MonitorRegion,
+    UnbalancedMonitor,
+};
+pub use nullness::{nullness, Nullness, NullnessFrame, NullnessResult};
+pub use reachability::unreachable_instructions;
+pub use string_switch::{detect_string_switch, StringSwitch};
 
 /// A context for class resolution during analysis.
 #[derive(Debug)]
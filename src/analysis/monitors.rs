@@ -0,0 +1,310 @@
+//! Analysis of the monitors used by `synchronized` blocks.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+};
+
+use crate::jvm::code::{Instruction, MethodBody, ProgramCounter, WideInstruction};
+
+/// A region of code in which a monitor is held.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonitorRegion {
+    /// The program counter of the `monitorenter` instruction.
+    pub enter: ProgramCounter,
+    /// The program counters of the `monitorexit` instructions releasing the monitor.
+    /// There are usually two of them, one on the normal path and one in the exception handler.
+    pub exits: BTreeSet<ProgramCounter>,
+    /// The instructions from the `monitorenter` to the last `monitorexit`. As in
+    /// [`ExceptionTableEntry::covered_pc`](crate::jvm::code::ExceptionTableEntry::covered_pc), the
+    /// end is exclusive, i.e., it is the program counter right after the last `monitorexit`.
+    pub covered_pc: Range<ProgramCounter>,
+}
+
+/// An error indicating that the monitors in a method are not balanced.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum UnbalancedMonitor {
+    /// A `monitorexit` is executed while no monitor is held.
+    #[error("monitorexit at {0} without a matching monitorenter")]
+    ExitWithoutEnter(ProgramCounter),
+    /// An instruction is reached with different monitors held on different paths.
+    #[error("different monitors are held on the paths reaching {0}")]
+    InconsistentMonitors(ProgramCounter),
+    /// The method returns or throws out of the method while holding a monitor.
+    #[error("monitor entered at {enter} is still held when leaving the method at {pc}")]
+    HeldOnExit {
+        /// The program counter of the `monitorenter` instruction.
+        enter: ProgramCounter,
+        /// The program counter of the instruction leaving the method.
+        pc: ProgramCounter,
+    },
+}
+
+/// Pairs each `monitorenter` in `body` with the `monitorexit` instructions releasing the monitor.
+/// The monitors are tracked along all the paths through the method, including those through the
+/// exception handlers, assuming that they are entered and exited in a nested manner.
+/// # Errors
+/// See [`UnbalancedMonitor`]. Balanced monitors are expected in the code generated by compilers,
+/// so an error indicates that the bytecode is corrupted or hand-crafted.
+pub fn monitor_regions(body: &MethodBody) -> Result<Vec<MonitorRegion>, UnbalancedMonitor> {
+    // The `monitorenter` instructions of the monitors held before executing each instruction.
+    let mut held: BTreeMap<ProgramCounter, Vec<ProgramCounter>> = BTreeMap::new();
+    let mut exits: BTreeMap<ProgramCounter, BTreeSet<ProgramCounter>> = BTreeMap::new();
+    let mut worklist: Vec<_> = body
+        .instructions
+        .entry_point()
+        .map(|(pc, _)| (*pc, Vec::new()))
+        .into_iter()
+        .collect();
+    while let Some((pc, monitors)) = worklist.pop() {
+        let Some(instruction) = body.instruction_at(pc) else {
+            continue;
+        };
+        match held.get(&pc) {
+            Some(existing) if *existing == monitors => continue,
+            Some(_) => return Err(UnbalancedMonitor::InconsistentMonitors(pc)),
+            None => {
+                held.insert(pc, monitors.clone());
+            }
+        }
+        // An exception may be thrown before the instruction takes effect.
+        let handlers: Vec<_> = body.handlers_at(pc).map(|it| it.handler_pc).collect();
+        worklist.extend(handlers.iter().map(|it| (*it, monitors.clone())));
+
+        let mut monitors = monitors;
+        match instruction {
+            Instruction::MonitorEnter => monitors.push(pc),
+            Instruction::MonitorExit => {
+                let enter = monitors
+                    .pop()
+                    .ok_or(UnbalancedMonitor::ExitWithoutEnter(pc))?;
+                exits.entry(enter).or_default().insert(pc);
+            }
+            _ => {}
+        }
+        let leaves_method = match instruction {
+            Instruction::IReturn
+            | Instruction::LReturn
+            | Instruction::FReturn
+            | Instruction::DReturn
+            | Instruction::AReturn
+            | Instruction::Return => true,
+            Instruction::AThrow => handlers.is_empty(),
+            _ => false,
+        };
+        if let (true, Some(enter)) = (leaves_method, monitors.last()) {
+            return Err(UnbalancedMonitor::HeldOnExit { enter: *enter, pc });
+        }
+        let fall_through = instruction
+            .falls_through()
+            .then(|| body.instructions.next_pc_of(&pc))
+            .flatten();
+        worklist.extend(
+            instruction
+                .jump_targets()
+                .into_iter()
+                .chain(fall_through)
+                .map(|it| (it, monitors.clone())),
+        );
+    }
+    let regions = body
+        .instructions
+        .iter()
+        .filter(|(pc, instruction)| {
+            matches!(instruction, Instruction::MonitorEnter) && held.contains_key(pc)
+        })
+        .map(|(pc, _)| {
+            let exits = exits.remove(pc).unwrap_or_default();
+            let last_exit = exits.last().copied().unwrap_or(*pc);
+            // Both `monitorenter` and `monitorexit` take one byte.
+            let end = ProgramCounter::from(u16::from(last_exit) + 1);
+            MonitorRegion {
+                enter: *pc,
+                exits,
+                covered_pc: *pc..end,
+            }
+        })
+        .collect();
+    Ok(regions)
+}
+
+/// Checks if the exception handler at `handler_pc` releases a monitor on behalf of a
+/// `synchronized` block.
+/// Compilers guard the body of a `synchronized` block with a handler catching any exception,
+/// which exits the monitor and rethrows the exception. Such a handler is detected by its shape:
+/// it catches any exception (i.e., the catch type is `null`), and its instructions consist only of
+/// reference loads and stores followed by `monitorexit` and ending in `athrow`.
+#[must_use]
+pub fn is_monitor_exit_handler(body: &MethodBody, handler_pc: ProgramCounter) -> bool {
+    let catches_any = body
+        .exception_table
+        .iter()
+        .any(|it| it.handler_pc == handler_pc && it.catch_type.is_none());
+    if !catches_any {
+        return false;
+    }
+    let mut exits_monitor = false;
+    let mut pc = Some(handler_pc);
+    while let Some(instruction) = pc.and_then(|it| body.instruction_at(it)) {
+        #[allow(clippy::enum_glob_use)]
+        use Instruction::*;
+        match instruction {
+            MonitorExit => exits_monitor = true,
+            AThrow => return exits_monitor,
+            ALoad(_)
+            | ALoad0
+            | ALoad1
+            | ALoad2
+            | ALoad3
+            | AStore(_)
+            | AStore0
+            | AStore1
+            | AStore2
+            | AStore3
+            | Wide(WideInstruction::ALoad(_) | WideInstruction::AStore(_)) => {}
+            _ => return false,
+        }
+        pc = pc.and_then(|it| body.instructions.next_pc_of(&it));
+    }
+    false
+}
+
+/// Returns the program counters of the exception handlers that release a monitor on behalf of a
+/// `synchronized` block.
+/// See [`is_monitor_exit_handler`] for how such handlers are detected.
+#[must_use]
+pub fn monitor_exit_handlers(body: &MethodBody) -> BTreeSet<ProgramCounter> {
+    body.exception_table
+        .iter()
+        .map(|it| it.handler_pc)
+        .filter(|it| is_monitor_exit_handler(body, *it))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jvm::{
+        code::{ExceptionTableEntry, Instruction::*, InstructionList},
+        references::ClassRef,
+    };
+
+    /// The code generated by `javac` for `synchronized (lock) { run(); }`.
+    fn synchronized_block() -> MethodBody {
+        MethodBody {
+            instructions: InstructionList::from([
+                (0.into(), ALoad1),
+                (1.into(), Dup),
+                (2.into(), AStore2),
+                (3.into(), MonitorEnter),
+                (4.into(), Nop),
+                (5.into(), ALoad2),
+                (6.into(), MonitorExit),
+                (7.into(), Goto(15.into())),
+                (10.into(), AStore3),
+                (11.into(), ALoad2),
+                (12.into(), MonitorExit),
+                (13.into(), ALoad3),
+                (14.into(), AThrow),
+                (15.into(), Return),
+            ]),
+            exception_table: vec![
+                ExceptionTableEntry {
+                    covered_pc: 4.into()..7.into(),
+                    handler_pc: 10.into(),
+                    catch_type: None,
+                },
+                ExceptionTableEntry {
+                    covered_pc: 10.into()..13.into(),
+                    handler_pc: 10.into(),
+                    catch_type: None,
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn monitor_exit_handler() {
+        let body = synchronized_block();
+        assert!(is_monitor_exit_handler(&body, 10.into()));
+        assert!(!is_monitor_exit_handler(&body, 11.into()));
+        assert_eq!(BTreeSet::from([10.into()]), monitor_exit_handlers(&body));
+    }
+
+    #[test]
+    fn synchronized_block_region() {
+        let body = synchronized_block();
+        assert_eq!(
+            vec![MonitorRegion {
+                enter: 3.into(),
+                exits: BTreeSet::from([6.into(), 12.into()]),
+                covered_pc: 3.into()..13.into(),
+            }],
+            monitor_regions(&body).unwrap()
+        );
+    }
+
+    #[test]
+    fn unbalanced_monitors() {
+        let mut body = synchronized_block();
+        body.instructions = InstructionList::from([(0.into(), ALoad1), (1.into(), MonitorExit)]);
+        body.exception_table.clear();
+        assert_eq!(
+            Err(UnbalancedMonitor::ExitWithoutEnter(1.into())),
+            monitor_regions(&body)
+        );
+
+        body.instructions = InstructionList::from([
+            (0.into(), ALoad1),
+            (1.into(), MonitorEnter),
+            (2.into(), Return),
+        ]);
+        assert_eq!(
+            Err(UnbalancedMonitor::HeldOnExit {
+                enter: 1.into(),
+                pc: 2.into()
+            }),
+            monitor_regions(&body)
+        );
+
+        body.instructions = InstructionList::from([
+            (0.into(), ILoad0),
+            (1.into(), IfEq(6.into())),
+            (4.into(), ALoad1),
+            (5.into(), MonitorEnter),
+            (6.into(), ALoad1),
+            (7.into(), MonitorExit),
+            (8.into(), Return),
+        ]);
+        assert_eq!(
+            Err(UnbalancedMonitor::InconsistentMonitors(6.into())),
+            monitor_regions(&body)
+        );
+    }
+
+    #[test]
+    fn typed_handler() {
+        let mut body = synchronized_block();
+        for entry in &mut body.exception_table {
+            entry.catch_type = Some(ClassRef::new("java/lang/Exception"));
+        }
+        assert!(monitor_exit_handlers(&body).is_empty());
+    }
+
+    #[test]
+    fn finally_handler() {
+        let mut body = synchronized_block();
+        // A `finally` block that rethrows without exiting the monitor.
+        body.instructions = InstructionList::from([
+            (4.into(), Nop),
+            (7.into(), Goto(15.into())),
+            (10.into(), AStore3),
+            (11.into(), Nop),
+            (12.into(), ALoad3),
+            (13.into(), AThrow),
+            (15.into(), Return),
+        ]);
+        assert!(!is_monitor_exit_handler(&body, 10.into()));
+    }
+}
@@ -0,0 +1,617 @@
+//! Nullability analysis of local variables and operand stack values.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::Infallible,
+};
+
+use crate::{
+    jvm::{
+        code::{ExceptionTableEntry, Instruction, MethodBody, ProgramCounter, WideInstruction},
+        method, ConstantValue, Method,
+    },
+    types::{field_type::FieldType, method_descriptor::ReturnType},
+};
+
+use super::dataflow::{DataflowAnalysis, Lattice};
+
+/// Whether a value may be `null`.
+/// Values of primitive types and return addresses are always [`Nullness::NonNull`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Nullness {
+    /// The value is definitely `null`.
+    Null,
+    /// The value is definitely not `null`.
+    NonNull,
+    /// The value may or may not be `null`.
+    MaybeNull,
+}
+
+impl Nullness {
+    /// Computes the nullness of a value that is either `self` or `other`.
+    #[must_use]
+    pub fn join(self, other: Self) -> Self {
+        if self == other {
+            self
+        } else {
+            Self::MaybeNull
+        }
+    }
+
+    /// Checks if the value may be `null`.
+    #[must_use]
+    pub const fn may_be_null(self) -> bool {
+        !matches!(self, Self::NonNull)
+    }
+}
+
+/// The nullness of the local variables and the operand stack before an instruction.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NullnessFrame {
+    locals: Vec<Nullness>,
+    /// The values on the operand stack, each with the local variable it was loaded from.
+    stack: Vec<(Nullness, Option<u16>)>,
+}
+
+impl NullnessFrame {
+    /// Gets the nullness of the local variable at the given slot.
+    #[must_use]
+    pub fn local(&self, index: u16) -> Option<Nullness> {
+        self.locals.get(usize::from(index)).copied()
+    }
+
+    /// Gets the nullness of the value at the given depth of the operand stack, where `0` is the
+    /// top of the stack.
+    /// Values of type `long` and `double` take two slots.
+    #[must_use]
+    pub fn stack_value(&self, depth: usize) -> Option<Nullness> {
+        self.stack
+            .len()
+            .checked_sub(depth + 1)
+            .map(|idx| self.stack[idx].0)
+    }
+
+    /// Returns the number of slots on the operand stack.
+    #[must_use]
+    pub fn stack_depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    fn push(&mut self, nullness: Nullness) {
+        self.stack.push((nullness, None));
+    }
+
+    fn push_n(&mut self, count: u16) {
+        for _ in 0..count {
+            self.push(Nullness::NonNull);
+        }
+    }
+
+    fn pop(&mut self) -> (Nullness, Option<u16>) {
+        self.stack.pop().unwrap_or((Nullness::MaybeNull, None))
+    }
+
+    fn pop_n(&mut self, count: u16) {
+        for _ in 0..count {
+            self.pop();
+        }
+    }
+
+    fn load(&mut self, index: u16) {
+        let nullness = self.local(index).unwrap_or(Nullness::MaybeNull);
+        self.stack.push((nullness, Some(index)));
+    }
+
+    fn store(&mut self, index: u16, nullness: Nullness) {
+        let idx = usize::from(index);
+        if self.locals.len() <= idx {
+            self.locals.resize(idx + 1, Nullness::MaybeNull);
+        }
+        self.locals[idx] = nullness;
+        self.stack
+            .iter_mut()
+            .filter(|(_, origin)| *origin == Some(index))
+            .for_each(|(_, origin)| *origin = None);
+    }
+
+    /// Sets the nullness of the local variable the value came from, if any.
+    fn refine(&mut self, origin: Option<u16>, nullness: Nullness) {
+        if let Some(index) = origin {
+            if let Some(local) = self.locals.get_mut(usize::from(index)) {
+                *local = nullness;
+            }
+            for value in &mut self.stack {
+                if value.1 == Some(index) {
+                    value.0 = nullness;
+                }
+            }
+        }
+    }
+
+    /// Pops a value that is dereferenced by the instruction.
+    /// If the instruction completes normally, the value must have been non-null.
+    fn pop_dereferenced(&mut self) {
+        let (_, origin) = self.pop();
+        self.refine(origin, Nullness::NonNull);
+    }
+
+    fn push_typed(&mut self, field_type: &FieldType) {
+        match field_type {
+            FieldType::Base(_) => self.push_n(field_type.slot_width()),
+            FieldType::Object(_) | FieldType::Array(_) => self.push(Nullness::MaybeNull),
+        }
+    }
+}
+
+impl Lattice for NullnessFrame {
+    fn join(&self, other: &Self) -> Self {
+        let locals = self
+            .locals
+            .iter()
+            .zip(&other.locals)
+            .map(|(lhs, rhs)| lhs.join(*rhs))
+            .collect();
+        let stack = self
+            .stack
+            .iter()
+            .zip(&other.stack)
+            .map(|((lhs, lhs_origin), (rhs, rhs_origin))| {
+                let origin = Some(lhs_origin).filter(|it| it == &rhs_origin).copied();
+                (lhs.join(*rhs), origin.flatten())
+            })
+            .collect();
+        Self { locals, stack }
+    }
+}
+
+/// The result of [`nullness`].
+#[derive(Debug, Clone)]
+pub struct NullnessResult {
+    frames: BTreeMap<ProgramCounter, NullnessFrame>,
+    possibly_null_receivers: BTreeSet<ProgramCounter>,
+}
+
+impl NullnessResult {
+    /// Gets the nullness of the values before the instruction at `pc`.
+    /// Returns [`None`] if the instruction is unreachable.
+    #[must_use]
+    pub fn frame_at(&self, pc: ProgramCounter) -> Option<&NullnessFrame> {
+        self.frames.get(&pc)
+    }
+
+    /// Returns the program counters of the field accesses and method invocations whose receiver
+    /// may be `null`.
+    #[must_use]
+    pub fn possibly_null_receivers(&self) -> &BTreeSet<ProgramCounter> {
+        &self.possibly_null_receivers
+    }
+}
+
+/// Analyzes the nullness of the values in the given method.
+/// The parameters are assumed to be possibly `null` and `this` to be non-null.
+/// Methods without a body yield an empty result.
+#[must_use]
+pub fn nullness(method: &Method) -> NullnessResult {
+    let Some(body) = method.body.as_ref() else {
+        return NullnessResult {
+            frames: BTreeMap::new(),
+            possibly_null_receivers: BTreeSet::new(),
+        };
+    };
+    let frames = NullnessAnalysis { method, body }.analyze(body);
+    let possibly_null_receivers = frames
+        .iter()
+        .filter(|(pc, frame)| {
+            body.instruction_at(**pc)
+                .and_then(receiver_depth)
+                .and_then(|depth| frame.stack_value(depth))
+                .is_some_and(Nullness::may_be_null)
+        })
+        .map(|(pc, _)| *pc)
+        .collect();
+    NullnessResult {
+        frames,
+        possibly_null_receivers,
+    }
+}
+
+/// Returns the depth of the receiver on the operand stack for field accesses and instance method
+/// invocations.
+fn receiver_depth(instruction: &Instruction) -> Option<usize> {
+    use Instruction::{GetField, InvokeInterface, InvokeSpecial, InvokeVirtual, PutField};
+    let slots = match instruction {
+        GetField(_) => 0,
+        PutField(field) => field.field_type.slot_width(),
+        InvokeVirtual(method) | InvokeSpecial(method) | InvokeInterface(method, _) => method
+            .descriptor
+            .parameters_types
+            .iter()
+            .map(FieldType::slot_width)
+            .sum(),
+        _ => return None,
+    };
+    Some(usize::from(slots))
+}
+
+struct NullnessAnalysis<'a> {
+    method: &'a Method,
+    body: &'a MethodBody,
+}
+
+impl DataflowAnalysis<NullnessFrame> for NullnessAnalysis<'_> {
+    type Err = Infallible;
+
+    fn entry_state(&self) -> NullnessFrame {
+        let mut locals = Vec::with_capacity(usize::from(self.body.max_locals));
+        if !self
+            .method
+            .access_flags
+            .contains(method::AccessFlags::STATIC)
+        {
+            locals.push(Nullness::NonNull);
+        }
+        for param in &self.method.descriptor.parameters_types {
+            match param {
+                FieldType::Base(_) => {
+                    locals.extend((0..param.slot_width()).map(|_| Nullness::NonNull));
+                }
+                FieldType::Object(_) | FieldType::Array(_) => locals.push(Nullness::MaybeNull),
+            }
+        }
+        let max_locals = usize::from(self.body.max_locals).max(locals.len());
+        locals.resize(max_locals, Nullness::MaybeNull);
+        NullnessFrame {
+            locals,
+            stack: Vec::new(),
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn transfer(
+        &mut self,
+        _pc: ProgramCounter,
+        instruction: &Instruction,
+        state: &NullnessFrame,
+    ) -> Result<NullnessFrame, Infallible> {
+        #[allow(clippy::enum_glob_use)]
+        use Instruction::*;
+        let mut frame = state.clone();
+        match instruction {
+            Nop | IInc(..) | Goto(_) | GotoW(_) | Ret(_) | Return | Breakpoint | ImpDep1
+            | ImpDep2 | CheckCast(_) => {}
+            AConstNull => frame.push(Nullness::Null),
+            IConstM1 | IConst0 | IConst1 | IConst2 | IConst3 | IConst4 | IConst5 | FConst0
+            | FConst1 | FConst2 | BiPush(_) | SiPush(_) | Jsr(_) | JsrW(_) | New(_) => {
+                frame.push(Nullness::NonNull);
+            }
+            Ldc(value) | LdcW(value) | Ldc2W(value) => match value {
+                ConstantValue::Null => frame.push(Nullness::Null),
+                ConstantValue::Long(_) | ConstantValue::Double(_) => frame.push_n(2),
+                ConstantValue::Dynamic(_, _, field_type) => frame.push_typed(field_type),
+                _ => frame.push(Nullness::NonNull),
+            },
+            ILoad(_) | FLoad(_) | ILoad0 | ILoad1 | ILoad2 | ILoad3 | FLoad0 | FLoad1 | FLoad2
+            | FLoad3 => frame.push(Nullness::NonNull),
+            LConst0 | LConst1 | DConst0 | DConst1 | LLoad(_) | DLoad(_) | LLoad0 | LLoad1
+            | LLoad2 | LLoad3 | DLoad0 | DLoad1 | DLoad2 | DLoad3 => frame.push_n(2),
+            ALoad(idx) => frame.load(u16::from(*idx)),
+            ALoad0 => frame.load(0),
+            ALoad1 => frame.load(1),
+            ALoad2 => frame.load(2),
+            ALoad3 => frame.load(3),
+            IALoad | FALoad | BALoad | CALoad | SALoad => {
+                frame.pop();
+                frame.pop_dereferenced();
+                frame.push(Nullness::NonNull);
+            }
+            LALoad | DALoad => {
+                frame.pop();
+                frame.pop_dereferenced();
+                frame.push_n(2);
+            }
+            AALoad => {
+                frame.pop();
+                frame.pop_dereferenced();
+                frame.push(Nullness::MaybeNull);
+            }
+            IStore(idx) | FStore(idx) => store_primitive(&mut frame, u16::from(*idx), 1),
+            LStore(idx) | DStore(idx) => store_primitive(&mut frame, u16::from(*idx), 2),
+            IStore0 | FStore0 => store_primitive(&mut frame, 0, 1),
+            IStore1 | FStore1 => store_primitive(&mut frame, 1, 1),
+            IStore2 | FStore2 => store_primitive(&mut frame, 2, 1),
+            IStore3 | FStore3 => store_primitive(&mut frame, 3, 1),
+            LStore0 | DStore0 => store_primitive(&mut frame, 0, 2),
+            LStore1 | DStore1 => store_primitive(&mut frame, 1, 2),
+            LStore2 | DStore2 => store_primitive(&mut frame, 2, 2),
+            LStore3 | DStore3 => store_primitive(&mut frame, 3, 2),
+            AStore(idx) => store_reference(&mut frame, u16::from(*idx)),
+            AStore0 => store_reference(&mut frame, 0),
+            AStore1 => store_reference(&mut frame, 1),
+            AStore2 => store_reference(&mut frame, 2),
+            AStore3 => store_reference(&mut frame, 3),
+            IAStore | FAStore | AAStore | BAStore | CAStore | SAStore => {
+                frame.pop_n(2);
+                frame.pop_dereferenced();
+            }
+            LAStore | DAStore => {
+                frame.pop_n(3);
+                frame.pop_dereferenced();
+            }
+            Dup => duplicate(&mut frame, 1, 0),
+            DupX1 => duplicate(&mut frame, 1, 1),
+            DupX2 => duplicate(&mut frame, 1, 2),
+            Dup2 => duplicate(&mut frame, 2, 0),
+            Dup2X1 => duplicate(&mut frame, 2, 1),
+            Dup2X2 => duplicate(&mut frame, 2, 2),
+            Swap => {
+                let len = frame.stack.len();
+                if len >= 2 {
+                    frame.stack.swap(len - 1, len - 2);
+                }
+            }
+            IAdd | FAdd | ISub | FSub | IMul | FMul | IDiv | FDiv | IRem | FRem | IShl | IShr
+            | IUShr | IAnd | IOr | IXor | FCmpL | FCmpG | L2I | L2F | D2I | D2F => {
+                frame.pop_n(2);
+                frame.push(Nullness::NonNull);
+            }
+            LAdd | DAdd | LSub | DSub | LMul | DMul | LDiv | DDiv | LRem | DRem | LAnd | LOr
+            | LXor => {
+                frame.pop_n(4);
+                frame.push_n(2);
+            }
+            LShl | LShr | LUShr => {
+                frame.pop_n(3);
+                frame.push_n(2);
+            }
+            NewArray(_) | ANewArray(_) | InstanceOf(_) | INeg | FNeg | I2F | F2I | I2B | I2C
+            | I2S => {
+                frame.pop_n(1);
+                frame.push(Nullness::NonNull);
+            }
+            LNeg | DNeg | L2D | D2L => {
+                frame.pop_n(2);
+                frame.push_n(2);
+            }
+            I2L | I2D | F2L | F2D => {
+                frame.pop_n(1);
+                frame.push_n(2);
+            }
+            LCmp | DCmpL | DCmpG => {
+                frame.pop_n(4);
+                frame.push(Nullness::NonNull);
+            }
+            Pop
+            | IfEq(_)
+            | IfNe(_)
+            | IfLt(_)
+            | IfGe(_)
+            | IfGt(_)
+            | IfLe(_)
+            | IfNull(_)
+            | IfNonNull(_)
+            | TableSwitch { .. }
+            | LookupSwitch { .. }
+            | IReturn
+            | FReturn
+            | AReturn => frame.pop_n(1),
+            Pop2 | IfICmpEq(_) | IfICmpNe(_) | IfICmpLt(_) | IfICmpGe(_) | IfICmpGt(_)
+            | IfICmpLe(_) | IfACmpEq(_) | IfACmpNe(_) | LReturn | DReturn => frame.pop_n(2),
+            GetStatic(field) => frame.push_typed(&field.field_type),
+            PutStatic(field) => frame.pop_n(field.field_type.slot_width()),
+            GetField(field) => {
+                frame.pop_dereferenced();
+                frame.push_typed(&field.field_type);
+            }
+            PutField(field) => {
+                frame.pop_n(field.field_type.slot_width());
+                frame.pop_dereferenced();
+            }
+            InvokeVirtual(method) | InvokeSpecial(method) | InvokeInterface(method, _) => {
+                let descriptor = &method.descriptor;
+                descriptor
+                    .parameters_types
+                    .iter()
+                    .for_each(|it| frame.pop_n(it.slot_width()));
+                frame.pop_dereferenced();
+                if let ReturnType::Some(return_type) = &descriptor.return_type {
+                    frame.push_typed(return_type);
+                }
+            }
+            InvokeStatic(method) => {
+                let descriptor = &method.descriptor;
+                descriptor
+                    .parameters_types
+                    .iter()
+                    .for_each(|it| frame.pop_n(it.slot_width()));
+                if let ReturnType::Some(return_type) = &descriptor.return_type {
+                    frame.push_typed(return_type);
+                }
+            }
+            InvokeDynamic { descriptor, .. } => {
+                descriptor
+                    .parameters_types
+                    .iter()
+                    .for_each(|it| frame.pop_n(it.slot_width()));
+                if let ReturnType::Some(return_type) = &descriptor.return_type {
+                    frame.push_typed(return_type);
+                }
+            }
+            MultiANewArray(_, dimensions) => {
+                frame.pop_n(u16::from(*dimensions));
+                frame.push(Nullness::NonNull);
+            }
+            ArrayLength => {
+                frame.pop_dereferenced();
+                frame.push(Nullness::NonNull);
+            }
+            AThrow | MonitorEnter | MonitorExit => frame.pop_dereferenced(),
+            Wide(wide) => match wide {
+                WideInstruction::ILoad(_) | WideInstruction::FLoad(_) => {
+                    frame.push(Nullness::NonNull);
+                }
+                WideInstruction::LLoad(_) | WideInstruction::DLoad(_) => frame.push_n(2),
+                WideInstruction::ALoad(idx) => frame.load(*idx),
+                WideInstruction::IStore(idx) | WideInstruction::FStore(idx) => {
+                    store_primitive(&mut frame, *idx, 1);
+                }
+                WideInstruction::LStore(idx) | WideInstruction::DStore(idx) => {
+                    store_primitive(&mut frame, *idx, 2);
+                }
+                WideInstruction::AStore(idx) => store_reference(&mut frame, *idx),
+                WideInstruction::IInc(..) | WideInstruction::Ret(_) => {}
+            },
+        }
+        Ok(frame)
+    }
+
+    fn edge_state(
+        &self,
+        _pc: ProgramCounter,
+        instruction: &Instruction,
+        target: ProgramCounter,
+        before: &NullnessFrame,
+        after: &NullnessFrame,
+    ) -> NullnessFrame {
+        let (null_target, non_null_target) = match instruction {
+            Instruction::IfNull(target) => (Some(*target), None),
+            Instruction::IfNonNull(target) => (None, Some(*target)),
+            _ => return after.clone(),
+        };
+        let is_null = if null_target.is_some() {
+            null_target == Some(target)
+        } else {
+            non_null_target != Some(target)
+        };
+        let mut frame = after.clone();
+        if let Some(&(_, origin)) = before.stack.last() {
+            let refined = if is_null {
+                Nullness::Null
+            } else {
+                Nullness::NonNull
+            };
+            frame.refine(origin, refined);
+        }
+        frame
+    }
+
+    fn exception_state(
+        &self,
+        _handler: &ExceptionTableEntry,
+        state: &NullnessFrame,
+    ) -> NullnessFrame {
+        NullnessFrame {
+            locals: state.locals.clone(),
+            stack: vec![(Nullness::NonNull, None)],
+        }
+    }
+}
+
+fn store_primitive(frame: &mut NullnessFrame, index: u16, width: u16) {
+    frame.pop_n(width);
+    for offset in 0..width {
+        frame.store(index + offset, Nullness::NonNull);
+    }
+}
+
+fn store_reference(frame: &mut NullnessFrame, index: u16) {
+    let (nullness, _) = frame.pop();
+    frame.store(index, nullness);
+}
+
+/// Duplicates the top `count` slots and inserts them below the `skip` slots beneath them.
+fn duplicate(frame: &mut NullnessFrame, count: usize, skip: usize) {
+    let len = frame.stack.len();
+    let Some(insert_at) = len.checked_sub(count + skip) else {
+        return;
+    };
+    let copied: Vec<_> = frame.stack[len - count..].to_vec();
+    frame.stack.splice(insert_at..insert_at, copied);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jvm::{
+        code::InstructionList,
+        references::{ClassRef, FieldRef},
+    };
+
+    fn method_of(instructions: InstructionList<Instruction>) -> Method {
+        Method {
+            access_flags: method::AccessFlags::PUBLIC | method::AccessFlags::STATIC,
+            name: "test".to_owned(),
+            descriptor: "(LTest;)I".parse().unwrap(),
+            owner: ClassRef::new("Test"),
+            body: Some(MethodBody {
+                max_stack: 2,
+                max_locals: 2,
+                instructions,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn field() -> FieldRef {
+        FieldRef {
+            owner: ClassRef::new("Test"),
+            name: "x".to_owned(),
+            field_type: "I".parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn unchecked_parameter() {
+        let method = method_of(InstructionList::from([
+            (0.into(), Instruction::ALoad0),
+            (1.into(), Instruction::GetField(field())),
+            (4.into(), Instruction::ALoad0),
+            (5.into(), Instruction::GetField(field())),
+            (8.into(), Instruction::IAdd),
+            (9.into(), Instruction::IReturn),
+        ]));
+        let result = nullness(&method);
+        // The second access is safe because the first one would have thrown.
+        assert_eq!(
+            &BTreeSet::from([1.into()]),
+            result.possibly_null_receivers()
+        );
+    }
+
+    #[test]
+    fn null_check_refines_branches() {
+        let method = method_of(InstructionList::from([
+            (0.into(), Instruction::ALoad0),
+            (1.into(), Instruction::IfNull(9.into())),
+            (4.into(), Instruction::ALoad0),
+            (5.into(), Instruction::GetField(field())),
+            (8.into(), Instruction::IReturn),
+            (9.into(), Instruction::AConstNull),
+            (10.into(), Instruction::AStore1),
+            (11.into(), Instruction::IConst0),
+            (12.into(), Instruction::IReturn),
+        ]));
+        let result = nullness(&method);
+        assert!(result.possibly_null_receivers().is_empty());
+        let frame = result.frame_at(4.into()).unwrap();
+        assert_eq!(Some(Nullness::NonNull), frame.local(0));
+        let frame = result.frame_at(11.into()).unwrap();
+        assert_eq!(Some(Nullness::Null), frame.local(0));
+        assert_eq!(Some(Nullness::Null), frame.local(1));
+    }
+
+    #[test]
+    fn allocation_is_non_null() {
+        let method = method_of(InstructionList::from([
+            (0.into(), Instruction::New(ClassRef::new("Test"))),
+            (3.into(), Instruction::Dup),
+            (4.into(), Instruction::AStore1),
+            (5.into(), Instruction::GetField(field())),
+            (8.into(), Instruction::IReturn),
+        ]));
+        let result = nullness(&method);
+        assert!(result.possibly_null_receivers().is_empty());
+        let frame = result.frame_at(5.into()).unwrap();
+        assert_eq!(Some(Nullness::NonNull), frame.local(1));
+    }
+}
@@ -0,0 +1,103 @@
+//! Reachability of instructions within a method.
+
+use std::collections::BTreeSet;
+
+use crate::jvm::code::{MethodBody, ProgramCounter};
+
+/// Returns the program counters of the instructions that cannot be reached from the entry of the
+/// method.
+/// An exception handler is considered reachable if any instruction it covers is reachable.
+#[must_use]
+pub fn unreachable_instructions(body: &MethodBody) -> BTreeSet<ProgramCounter> {
+    let mut reachable = BTreeSet::new();
+    let mut worklist: Vec<_> = body
+        .instructions
+        .entry_point()
+        .map(|(pc, _)| *pc)
+        .into_iter()
+        .collect();
+    while let Some(pc) = worklist.pop() {
+        let Some(instruction) = body.instruction_at(pc) else {
+            continue;
+        };
+        if !reachable.insert(pc) {
+            continue;
+        }
+        let fall_through = instruction
+            .falls_through()
+            .then(|| body.instructions.next_pc_of(&pc))
+            .flatten();
+        let handlers = body.handlers_at(pc).map(|it| it.handler_pc);
+        worklist.extend(
+            instruction
+                .jump_targets()
+                .into_iter()
+                .chain(fall_through)
+                .chain(handlers)
+                .filter(|it| !reachable.contains(it)),
+        );
+    }
+    body.instructions
+        .iter()
+        .map(|(pc, _)| *pc)
+        .filter(|pc| !reachable.contains(pc))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jvm::{
+        code::{ExceptionTableEntry, Instruction::*, InstructionList},
+        references::ClassRef,
+    };
+
+    #[test]
+    fn code_after_goto_and_return() {
+        let body = MethodBody {
+            instructions: InstructionList::from([
+                (0.into(), Goto(4.into())),
+                (3.into(), Nop),
+                (4.into(), IConst0),
+                (5.into(), IReturn),
+                (6.into(), IConst1),
+                (7.into(), IReturn),
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(
+            BTreeSet::from([3.into(), 6.into(), 7.into()]),
+            unreachable_instructions(&body)
+        );
+    }
+
+    #[test]
+    fn exception_handlers() {
+        let body = MethodBody {
+            instructions: InstructionList::from([
+                (0.into(), Nop),
+                (1.into(), Return),
+                (2.into(), AThrow),
+                (3.into(), Nop),
+                (4.into(), AThrow),
+            ]),
+            exception_table: vec![
+                ExceptionTableEntry {
+                    covered_pc: 0.into()..1.into(),
+                    handler_pc: 2.into(),
+                    catch_type: Some(ClassRef::new("java/lang/Exception")),
+                },
+                ExceptionTableEntry {
+                    covered_pc: 3.into()..4.into(),
+                    handler_pc: 4.into(),
+                    catch_type: None,
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            BTreeSet::from([3.into(), 4.into()]),
+            unreachable_instructions(&body)
+        );
+    }
+}
@@ -0,0 +1,328 @@
+//! Recognition of `switch` statements on strings desugared by javac.
+
+use std::collections::BTreeMap;
+
+use crate::jvm::{
+    code::{Instruction, MethodBody, ProgramCounter, WideInstruction},
+    references::MethodRef,
+    ConstantValue, JavaString,
+};
+
+/// A `switch` on a string reconstructed by [`detect_string_switch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StringSwitch {
+    /// The case labels and the program counters of their code, in the order of the labels in the
+    /// source code. Labels sharing the same code have the same target.
+    pub cases: Vec<(String, ProgramCounter)>,
+    /// The program counter of the code of the `default` label, or of the code after the `switch`
+    /// if there is no `default` label.
+    pub default: ProgramCounter,
+    /// The local variable holding a copy of the string being switched on.
+    pub string_local: u16,
+    /// The local variable holding the index of the matched label, or `-1` if none matches.
+    pub index_local: u16,
+    /// The program counter of the `tableswitch` or `lookupswitch` on the index, where the
+    /// desugared code ends.
+    pub index_switch_pc: ProgramCounter,
+}
+
+/// Recognizes a `switch` on a string compiled by javac starting at `start_pc`, and reconstructs
+/// the mapping from the case labels to their code.
+///
+/// javac compiles such a `switch` into two, the first on the hash code of the string and the
+/// second on the index of the matched label. The code from `start_pc` must be in the following
+/// shape, where the instructions before `start_pc` copy the string to `<s>` and initialize
+/// `<i>` to `-1`.
+/// ```text
+/// start_pc: aload <s>
+///           invokevirtual java/lang/String.hashCode:()I
+///           lookupswitch | tableswitch { <hash>: <check>, ..., default: <join> }
+/// <check>:  aload <s>
+///           ldc | ldc_w "<label>"
+///           invokevirtual java/lang/String.equals:(Ljava/lang/Object;)Z
+///           ifeq <next check with the same hash> | <join>
+///           <push the index of the label>
+///           istore <i>
+///           goto <join>                  // omitted if <join> follows
+///           ...
+/// <join>:   iload <i>
+///           tableswitch | lookupswitch { <index>: <case>, ..., default: <default> }
+/// ```
+/// Each label must have the hash code it is dispatched on.
+/// Returns `None` if the code is in any other shape, e.g., produced by another compiler or an
+/// obfuscator.
+#[must_use]
+pub fn detect_string_switch(body: &MethodBody, start_pc: ProgramCounter) -> Option<StringSwitch> {
+    let string_local = body.instruction_at(start_pc).and_then(aload_index)?;
+    let hash_code_pc = body.instructions.next_pc_of(&start_pc)?;
+    match body.instruction_at(hash_code_pc)? {
+        Instruction::InvokeVirtual(method) if is_string_method(method, "hashCode", "()I") => {}
+        _ => return None,
+    }
+    let hash_switch_pc = body.instructions.next_pc_of(&hash_code_pc)?;
+    let (hash_targets, join) = switch_targets(body.instruction_at(hash_switch_pc)?)?;
+
+    let mut labels = BTreeMap::new();
+    let mut index_local = None;
+    for (hash, mut check_pc) in hash_targets {
+        while check_pc != join {
+            let check = equals_check(body, check_pc, string_local, join)?;
+            if java_hash_code(&check.label) != hash
+                || *index_local.get_or_insert(check.index_local) != check.index_local
+                || labels.insert(check.index, check.label).is_some()
+                || check.next_check <= check_pc
+            {
+                return None;
+            }
+            check_pc = check.next_check;
+        }
+    }
+    let index_local = index_local?;
+    if body.instruction_at(join).and_then(iload_index)? != index_local {
+        return None;
+    }
+    let index_switch_pc = body.instructions.next_pc_of(&join)?;
+    let (index_targets, default) = switch_targets(body.instruction_at(index_switch_pc)?)?;
+    let index_targets: BTreeMap<_, _> = index_targets.into_iter().collect();
+    let cases = labels
+        .into_iter()
+        .map(|(index, label)| {
+            let target = index_targets.get(&index).copied().unwrap_or(default);
+            (label, target)
+        })
+        .collect();
+    Some(StringSwitch {
+        cases,
+        default,
+        string_local,
+        index_local,
+        index_switch_pc,
+    })
+}
+
+/// A comparison of the string with a case label, setting the index if they are equal.
+struct EqualsCheck {
+    label: String,
+    index: i32,
+    index_local: u16,
+    next_check: ProgramCounter,
+}
+
+fn equals_check(
+    body: &MethodBody,
+    pc: ProgramCounter,
+    string_local: u16,
+    join: ProgramCounter,
+) -> Option<EqualsCheck> {
+    // javac lays out the checks before the join.
+    if pc >= join {
+        return None;
+    }
+    let mut instructions = body.instructions_in_range(pc..join).map(|(_, insn)| insn);
+    if aload_index(instructions.next()?)? != string_local {
+        return None;
+    }
+    let label = match instructions.next()? {
+        Instruction::Ldc(ConstantValue::String(JavaString::Utf8(label)))
+        | Instruction::LdcW(ConstantValue::String(JavaString::Utf8(label))) => label.clone(),
+        _ => return None,
+    };
+    match instructions.next()? {
+        Instruction::InvokeVirtual(method)
+            if is_string_method(method, "equals", "(Ljava/lang/Object;)Z") => {}
+        _ => return None,
+    }
+    let Instruction::IfEq(next_check) = instructions.next()? else {
+        return None;
+    };
+    let Some(ConstantValue::Integer(index)) = instructions.next()?.pushed_constant() else {
+        return None;
+    };
+    let index_local = istore_index(instructions.next()?)?;
+    match instructions.next() {
+        // The last check falls through to the join.
+        None => {}
+        Some(Instruction::Goto(target)) if *target == join => {}
+        Some(_) => return None,
+    }
+    Some(EqualsCheck {
+        label,
+        index,
+        index_local,
+        next_check: *next_check,
+    })
+}
+
+/// Returns the keys and the targets of a `tableswitch` or `lookupswitch`, and its default target.
+fn switch_targets(
+    instruction: &Instruction,
+) -> Option<(Vec<(i32, ProgramCounter)>, ProgramCounter)> {
+    match instruction {
+        Instruction::TableSwitch {
+            range,
+            jump_targets,
+            default,
+        } => Some((
+            range.clone().zip(jump_targets.iter().copied()).collect(),
+            *default,
+        )),
+        Instruction::LookupSwitch {
+            default,
+            match_targets,
+        } => Some((
+            match_targets.iter().map(|(k, v)| (*k, *v)).collect(),
+            *default,
+        )),
+        _ => None,
+    }
+}
+
+fn is_string_method(method: &MethodRef, name: &str, descriptor: &str) -> bool {
+    method.owner.binary_name == "java/lang/String"
+        && method.name == name
+        && method.descriptor.to_string() == descriptor
+}
+
+/// Computes `String.hashCode()` as specified by the Java SE API, i.e., over UTF-16 code units.
+fn java_hash_code(string: &str) -> i32 {
+    string.encode_utf16().fold(0i32, |hash, unit| {
+        hash.wrapping_mul(31).wrapping_add(i32::from(unit))
+    })
+}
+
+fn aload_index(instruction: &Instruction) -> Option<u16> {
+    #[allow(clippy::enum_glob_use)]
+    use Instruction::*;
+    match instruction {
+        ALoad(_) | ALoad0 | ALoad1 | ALoad2 | ALoad3 | Wide(WideInstruction::ALoad(_)) => {
+            instruction.local_index()
+        }
+        _ => None,
+    }
+}
+
+fn iload_index(instruction: &Instruction) -> Option<u16> {
+    #[allow(clippy::enum_glob_use)]
+    use Instruction::*;
+    match instruction {
+        ILoad(_) | ILoad0 | ILoad1 | ILoad2 | ILoad3 | Wide(WideInstruction::ILoad(_)) => {
+            instruction.local_index()
+        }
+        _ => None,
+    }
+}
+
+fn istore_index(instruction: &Instruction) -> Option<u16> {
+    #[allow(clippy::enum_glob_use)]
+    use Instruction::*;
+    match instruction {
+        IStore(_) | IStore0 | IStore1 | IStore2 | IStore3 | Wide(WideInstruction::IStore(_)) => {
+            instruction.local_index()
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jvm::{
+        code::{Instruction::*, InstructionList},
+        references::ClassRef,
+    };
+
+    fn string_method(name: &str, descriptor: &str) -> Instruction {
+        InvokeVirtual(MethodRef {
+            owner: ClassRef::new("java/lang/String"),
+            name: name.to_owned(),
+            descriptor: descriptor.parse().unwrap(),
+        })
+    }
+
+    fn ldc(label: &str) -> Instruction {
+        Ldc(ConstantValue::String(JavaString::Utf8(label.to_owned())))
+    }
+
+    /// The code of `switch (s) { case "Aa": return 1; case "BB": return 2; default: return 0; }`
+    /// compiled by javac, where `"Aa"` and `"BB"` have the same hash code, with `before_join`
+    /// inserted at the end of the last check.
+    fn colliding_labels(before_join: Option<Instruction>) -> MethodBody {
+        let equals = || string_method("equals", "(Ljava/lang/Object;)Z");
+        let mut instructions = BTreeMap::from([
+            (0.into(), ALoad0),
+            (1.into(), AStore1),
+            (2.into(), IConstM1),
+            (3.into(), IStore2),
+            (4.into(), ALoad1),
+            (5.into(), string_method("hashCode", "()I")),
+            (
+                8.into(),
+                LookupSwitch {
+                    default: 46.into(),
+                    match_targets: [(2112, 20.into())].into(),
+                },
+            ),
+            (20.into(), ALoad1),
+            (21.into(), ldc("BB")),
+            (23.into(), equals()),
+            (26.into(), IfEq(34.into())),
+            (29.into(), IConst1),
+            (30.into(), IStore2),
+            (31.into(), Goto(46.into())),
+            (34.into(), ALoad1),
+            (35.into(), ldc("Aa")),
+            (37.into(), equals()),
+            (40.into(), IfEq(46.into())),
+            (43.into(), IConst0),
+            (44.into(), IStore2),
+            (46.into(), ILoad2),
+            (
+                47.into(),
+                TableSwitch {
+                    range: 0..=1,
+                    jump_targets: vec![72.into(), 74.into()],
+                    default: 76.into(),
+                },
+            ),
+            (72.into(), IConst1),
+            (73.into(), IReturn),
+            (74.into(), IConst2),
+            (75.into(), IReturn),
+            (76.into(), IConst0),
+            (77.into(), IReturn),
+        ]);
+        instructions.extend(before_join.map(|it| (45.into(), it)));
+        MethodBody {
+            instructions: InstructionList::from(instructions),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn javac_string_switch() {
+        let body = colliding_labels(None);
+        assert_eq!(
+            Some(StringSwitch {
+                cases: vec![("Aa".to_owned(), 72.into()), ("BB".to_owned(), 74.into())],
+                default: 76.into(),
+                string_local: 1,
+                index_local: 2,
+                index_switch_pc: 47.into(),
+            }),
+            detect_string_switch(&body, 4.into())
+        );
+        assert_eq!(None, detect_string_switch(&body, 0.into()));
+    }
+
+    #[test]
+    fn unexpected_shape() {
+        // An instruction between the `istore` and the join.
+        let body = colliding_labels(Some(Nop));
+        assert_eq!(None, detect_string_switch(&body, 4.into()));
+
+        let mut body = colliding_labels(None);
+        body.replace_instruction(21.into(), ldc("CC")).unwrap();
+        // The hash code of "CC" is not 2112.
+        assert_eq!(None, detect_string_switch(&body, 4.into()));
+    }
+}
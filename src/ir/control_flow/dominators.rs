@@ -0,0 +1,242 @@
+//! Dominance relation in a control flow graph.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{ir::ControlFlowGraph, jvm::code::ProgramCounter};
+
+/// The dominator tree of a [`ControlFlowGraph`].
+/// A node `a` dominates a node `b` if every path from the entry point to `b` goes through `a`.
+/// Only the nodes reachable from the entry point are in the tree.
+#[derive(Debug, Clone)]
+pub struct DominatorTree {
+    entry: ProgramCounter,
+    immediate_dominators: BTreeMap<ProgramCounter, ProgramCounter>,
+    frontiers: BTreeMap<ProgramCounter, BTreeSet<ProgramCounter>>,
+}
+
+impl DominatorTree {
+    /// Returns the entry point of the control flow graph, which is the root of the tree.
+    #[must_use]
+    pub const fn entry_point(&self) -> ProgramCounter {
+        self.entry
+    }
+
+    /// Returns the immediate dominator of `node`.
+    /// Returns `None` for the entry point and for the nodes that are unreachable.
+    #[must_use]
+    pub fn idom(&self, node: ProgramCounter) -> Option<ProgramCounter> {
+        if node == self.entry {
+            None
+        } else {
+            self.immediate_dominators.get(&node).copied()
+        }
+    }
+
+    /// Checks if `dominator` dominates `node`. Every reachable node dominates itself.
+    #[must_use]
+    pub fn dominates(&self, dominator: ProgramCounter, node: ProgramCounter) -> bool {
+        if !self.immediate_dominators.contains_key(&node) {
+            return false;
+        }
+        let mut current = node;
+        loop {
+            if current == dominator {
+                return true;
+            }
+            match self.idom(current) {
+                Some(idom) => current = idom,
+                None => return false,
+            }
+        }
+    }
+
+    /// Returns the dominance frontier of `node`, i.e., the nodes where the dominance of `node`
+    /// ends: each of them has a predecessor dominated by `node` but is not strictly dominated by
+    /// `node` itself.
+    /// Returns `None` if `node` is unreachable.
+    #[must_use]
+    pub fn dominance_frontier(&self, node: ProgramCounter) -> Option<&BTreeSet<ProgramCounter>> {
+        self.frontiers.get(&node)
+    }
+}
+
+impl<N, E> ControlFlowGraph<N, E> {
+    /// Computes the dominator tree with the algorithm by Cooper, Harvey, and Kennedy.
+    /// See [A Simple, Fast Dominance Algorithm](https://www.cs.tufts.edu/comp/150FP/archive/keith-cooper/dom14.pdf).
+    #[must_use]
+    pub fn dominators(&self) -> DominatorTree {
+        let entry = self.entry_point();
+        let postorder = self.postorder(entry);
+        let postorder_number: BTreeMap<_, _> = postorder
+            .iter()
+            .enumerate()
+            .map(|(number, node)| (*node, number))
+            .collect();
+        let mut predecessors: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for (src, dst, _) in self.edges() {
+            if postorder_number.contains_key(&src) {
+                predecessors.entry(dst).or_default().push(src);
+            }
+        }
+
+        let mut idoms = BTreeMap::from([(entry, entry)]);
+        let intersect = |idoms: &BTreeMap<ProgramCounter, ProgramCounter>, mut lhs, mut rhs| {
+            while lhs != rhs {
+                while postorder_number[&lhs] < postorder_number[&rhs] {
+                    lhs = idoms[&lhs];
+                }
+                while postorder_number[&rhs] < postorder_number[&lhs] {
+                    rhs = idoms[&rhs];
+                }
+            }
+            lhs
+        };
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for node in postorder.iter().rev().filter(|it| **it != entry) {
+                // In reverse postorder, the parent of a node in the DFS tree is processed first.
+                let Some(new_idom) = predecessors
+                    .get(node)
+                    .into_iter()
+                    .flatten()
+                    .filter(|it| idoms.contains_key(*it))
+                    .copied()
+                    .reduce(|lhs, rhs| intersect(&idoms, lhs, rhs))
+                else {
+                    continue;
+                };
+                if idoms.insert(*node, new_idom) != Some(new_idom) {
+                    changed = true;
+                }
+            }
+        }
+
+        let mut frontiers: BTreeMap<_, _> = postorder
+            .iter()
+            .map(|node| (*node, BTreeSet::new()))
+            .collect();
+        for (node, preds) in &predecessors {
+            if preds.len() < 2 {
+                continue;
+            }
+            let Some(idom) = idoms.get(node) else {
+                continue;
+            };
+            for pred in preds {
+                let mut runner = *pred;
+                while runner != *idom {
+                    frontiers.entry(runner).or_default().insert(*node);
+                    if runner == entry {
+                        break;
+                    }
+                    runner = idoms[&runner];
+                }
+            }
+        }
+
+        DominatorTree {
+            entry,
+            immediate_dominators: idoms,
+            frontiers,
+        }
+    }
+
+    /// Returns the nodes reachable from `entry` in postorder.
+    fn postorder(&self, entry: ProgramCounter) -> Vec<ProgramCounter> {
+        let mut postorder = Vec::new();
+        if !self.inner.contains_key(&entry) {
+            return postorder;
+        }
+        let mut visited = BTreeSet::from([entry]);
+        let successors = |node: ProgramCounter| {
+            self.inner
+                .get(&node)
+                .map(|(_, edges)| edges.keys().copied().collect::<Vec<_>>())
+                .unwrap_or_default()
+        };
+        let mut stack = vec![(entry, successors(entry).into_iter())];
+        while let Some((node, successors_left)) = stack.last_mut() {
+            if let Some(next) = successors_left.next() {
+                if visited.insert(next) {
+                    stack.push((next, successors(next).into_iter()));
+                }
+            } else {
+                postorder.push(*node);
+                stack.pop();
+            }
+        }
+        postorder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// ```text
+    /// 0 -> 1 -> 2 -> 4 -> 5 -> 6
+    ///      |         ^    |
+    ///      +--> 3 ---+    |
+    ///      ^              |
+    ///      +--------------+
+    /// ```
+    /// Node `7`, which jumps to `6`, is unreachable.
+    fn diamond_in_loop() -> ControlFlowGraph<(), ()> {
+        ControlFlowGraph::from_edges([
+            (0.into(), 1.into(), ()),
+            (1.into(), 2.into(), ()),
+            (1.into(), 3.into(), ()),
+            (2.into(), 4.into(), ()),
+            (3.into(), 4.into(), ()),
+            (4.into(), 5.into(), ()),
+            (5.into(), 1.into(), ()),
+            (5.into(), 6.into(), ()),
+            (7.into(), 6.into(), ()),
+        ])
+    }
+
+    #[test]
+    fn immediate_dominators() {
+        let dominators = diamond_in_loop().dominators();
+        assert_eq!(None, dominators.idom(0.into()));
+        assert_eq!(Some(0.into()), dominators.idom(1.into()));
+        assert_eq!(Some(1.into()), dominators.idom(2.into()));
+        assert_eq!(Some(1.into()), dominators.idom(3.into()));
+        assert_eq!(Some(1.into()), dominators.idom(4.into()));
+        assert_eq!(Some(4.into()), dominators.idom(5.into()));
+        assert_eq!(Some(5.into()), dominators.idom(6.into()));
+        assert_eq!(None, dominators.idom(7.into()));
+    }
+
+    #[test]
+    fn dominates() {
+        let dominators = diamond_in_loop().dominators();
+        assert!(dominators.dominates(0.into(), 6.into()));
+        assert!(dominators.dominates(1.into(), 5.into()));
+        assert!(dominators.dominates(4.into(), 4.into()));
+        assert!(!dominators.dominates(2.into(), 4.into()));
+        assert!(!dominators.dominates(5.into(), 1.into()));
+        assert!(!dominators.dominates(0.into(), 7.into()));
+    }
+
+    #[test]
+    fn dominance_frontier() {
+        let dominators = diamond_in_loop().dominators();
+        let frontier = |node: u16| {
+            dominators
+                .dominance_frontier(node.into())
+                .unwrap()
+                .iter()
+                .copied()
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(Vec::<ProgramCounter>::new(), frontier(0));
+        assert_eq!(vec![ProgramCounter::from(1)], frontier(1));
+        assert_eq!(vec![ProgramCounter::from(4)], frontier(2));
+        assert_eq!(vec![ProgramCounter::from(4)], frontier(3));
+        assert_eq!(vec![ProgramCounter::from(1)], frontier(4));
+        assert_eq!(vec![ProgramCounter::from(1)], frontier(5));
+        assert_eq!(None, dominators.dominance_frontier(7.into()));
+    }
+}
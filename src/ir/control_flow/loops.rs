@@ -0,0 +1,245 @@
+//! Natural loops in a control flow graph.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    ir::{control_flow::dominators::DominatorTree, ControlFlowGraph},
+    jvm::code::ProgramCounter,
+};
+
+/// A natural loop, i.e., a set of nodes with a single entry (the header) that can reach the
+/// header through a back edge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Loop {
+    /// The only node through which the loop can be entered.
+    pub header: ProgramCounter,
+    /// The sources of the back edges to the header.
+    pub latches: BTreeSet<ProgramCounter>,
+    /// The nodes in the loop, including the header.
+    pub body: BTreeSet<ProgramCounter>,
+    /// The header of the innermost loop enclosing this loop, or `None` if this is an outermost
+    /// loop.
+    pub parent: Option<ProgramCounter>,
+}
+
+impl Loop {
+    /// Checks if the loop contains `node`.
+    #[must_use]
+    pub fn contains(&self, node: ProgramCounter) -> bool {
+        self.body.contains(&node)
+    }
+}
+
+/// An error indicating that the control flow graph is irreducible, i.e., it has a cycle with more
+/// than one entry.
+/// Such cycles are not generated by Java compilers but may be found in obfuscated code.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "irreducible control flow: the edge from {from} to {to} enters a cycle not through its header"
+)]
+pub struct IrreducibleControlFlow {
+    /// The source of the edge closing the cycle.
+    pub from: ProgramCounter,
+    /// The target of the edge, which does not dominate its source.
+    pub to: ProgramCounter,
+}
+
+impl<N, E> ControlFlowGraph<N, E> {
+    /// Finds the natural loops in the control flow graph.
+    /// The loops are identified by the back edges, i.e., the edges whose targets dominate their
+    /// sources. The back edges to the same header form a single loop.
+    /// The loops are ordered by their headers, and the nesting is given by [`Loop::parent`].
+    /// Nodes unreachable from the entry point are not in any loop.
+    /// # Errors
+    /// Returns [`IrreducibleControlFlow`] if there is a cycle that is not a natural loop.
+    pub fn natural_loops(&self) -> Result<Vec<Loop>, IrreducibleControlFlow> {
+        let dominators = self.dominators();
+        if let Some((from, to)) = self
+            .retreating_edges()
+            .into_iter()
+            .find(|(from, to)| !dominators.dominates(*to, *from))
+        {
+            return Err(IrreducibleControlFlow { from, to });
+        }
+
+        let mut predecessors: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        let mut latches: BTreeMap<_, BTreeSet<_>> = BTreeMap::new();
+        for (src, dst, _) in self.edges() {
+            predecessors.entry(dst).or_default().push(src);
+            if dominators.dominates(dst, src) {
+                latches.entry(dst).or_default().insert(src);
+            }
+        }
+        let mut loops: Vec<_> = latches
+            .into_iter()
+            .map(|(header, latches)| {
+                let body = loop_body(header, &latches, &predecessors, &dominators);
+                Loop {
+                    header,
+                    latches,
+                    body,
+                    parent: None,
+                }
+            })
+            .collect();
+        // The loops are either nested or disjoint, so the innermost enclosing loop is the smallest
+        // one containing the header.
+        let parents: Vec<_> = loops
+            .iter()
+            .map(|inner| {
+                loops
+                    .iter()
+                    .filter(|outer| outer.header != inner.header && outer.contains(inner.header))
+                    .min_by_key(|outer| outer.body.len())
+                    .map(|outer| outer.header)
+            })
+            .collect();
+        for (it, parent) in loops.iter_mut().zip(parents) {
+            it.parent = parent;
+        }
+        Ok(loops)
+    }
+
+    /// Returns the edges from the nodes to their ancestors in a depth-first search from the entry
+    /// point.
+    fn retreating_edges(&self) -> Vec<(ProgramCounter, ProgramCounter)> {
+        let mut retreating_edges = Vec::new();
+        let entry = self.entry_point();
+        let Some((_, edges)) = self.inner.get(&entry) else {
+            return retreating_edges;
+        };
+        let mut visited = BTreeSet::from([entry]);
+        let mut on_stack = BTreeSet::from([entry]);
+        let mut stack = vec![(entry, edges.keys())];
+        while let Some((node, successors)) = stack.last_mut() {
+            let node = *node;
+            if let Some(next) = successors.next().copied() {
+                if on_stack.contains(&next) {
+                    retreating_edges.push((node, next));
+                } else if visited.insert(next) {
+                    if let Some((_, edges)) = self.inner.get(&next) {
+                        on_stack.insert(next);
+                        stack.push((next, edges.keys()));
+                    }
+                }
+            } else {
+                on_stack.remove(&node);
+                stack.pop();
+            }
+        }
+        retreating_edges
+    }
+}
+
+/// Collects the nodes that reach one of the `latches` without going through `header`.
+fn loop_body(
+    header: ProgramCounter,
+    latches: &BTreeSet<ProgramCounter>,
+    predecessors: &BTreeMap<ProgramCounter, Vec<ProgramCounter>>,
+    dominators: &DominatorTree,
+) -> BTreeSet<ProgramCounter> {
+    let mut body = BTreeSet::from([header]);
+    let mut worklist: Vec<_> = latches.iter().copied().collect();
+    while let Some(node) = worklist.pop() {
+        // Unreachable predecessors are not dominated by the header.
+        if dominators.dominates(header, node) && body.insert(node) {
+            worklist.extend(predecessors.get(&node).into_iter().flatten());
+        }
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// ```text
+    /// 0 -> 1 -> 2 -> 3 -> 4 -> 5
+    ///      ^    ^    |    |
+    ///      |    +----+    |
+    ///      +--------------+
+    /// ```
+    fn nested_loops() -> ControlFlowGraph<(), ()> {
+        ControlFlowGraph::from_edges([
+            (0.into(), 1.into(), ()),
+            (1.into(), 2.into(), ()),
+            (2.into(), 3.into(), ()),
+            (3.into(), 2.into(), ()),
+            (3.into(), 4.into(), ()),
+            (4.into(), 1.into(), ()),
+            (4.into(), 5.into(), ()),
+        ])
+    }
+
+    fn pcs<const N: usize>(pcs: [u16; N]) -> BTreeSet<ProgramCounter> {
+        pcs.into_iter().map(ProgramCounter::from).collect()
+    }
+
+    #[test]
+    fn nested() {
+        let loops = nested_loops().natural_loops().unwrap();
+        assert_eq!(
+            vec![
+                Loop {
+                    header: 1.into(),
+                    latches: pcs([4]),
+                    body: pcs([1, 2, 3, 4]),
+                    parent: None,
+                },
+                Loop {
+                    header: 2.into(),
+                    latches: pcs([3]),
+                    body: pcs([2, 3]),
+                    parent: Some(1.into()),
+                },
+            ],
+            loops
+        );
+    }
+
+    #[test]
+    fn multiple_latches() {
+        // 0 -> 1 -> 2 -> 1, 1 -> 3 -> 1, 3 -> 4
+        let cfg = ControlFlowGraph::from_edges([
+            (0.into(), 1.into(), ()),
+            (1.into(), 2.into(), ()),
+            (2.into(), 1.into(), ()),
+            (1.into(), 3.into(), ()),
+            (3.into(), 1.into(), ()),
+            (3.into(), 4.into(), ()),
+        ]);
+        let loops = cfg.natural_loops().unwrap();
+        assert_eq!(1, loops.len());
+        assert_eq!(pcs([2, 3]), loops[0].latches);
+        assert_eq!(pcs([1, 2, 3]), loops[0].body);
+    }
+
+    #[test]
+    fn acyclic() {
+        let cfg = ControlFlowGraph::from_edges([
+            (0.into(), 1.into(), ()),
+            (0.into(), 2.into(), ()),
+            (1.into(), 3.into(), ()),
+            (2.into(), 3.into(), ()),
+        ]);
+        assert_eq!(Ok(vec![]), cfg.natural_loops());
+    }
+
+    #[test]
+    fn irreducible() {
+        // The cycle between 1 and 2 can be entered at both nodes.
+        let cfg = ControlFlowGraph::from_edges([
+            (0.into(), 1.into(), ()),
+            (0.into(), 2.into(), ()),
+            (1.into(), 2.into(), ()),
+            (2.into(), 1.into(), ()),
+        ]);
+        assert_eq!(
+            Err(IrreducibleControlFlow {
+                from: 2.into(),
+                to: 1.into(),
+            }),
+            cfg.natural_loops()
+        );
+    }
+}
@@ -1,5 +1,7 @@
 //! Control flow analysis
 
+pub mod dominators;
+pub mod loops;
 pub mod path_condition;
 
 use crate::{
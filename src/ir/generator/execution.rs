@@ -508,11 +508,11 @@ impl MokaIRGenerator<'_> {
                     expr: Expression::New(class.clone()),
                 }
             }
-            ANewArray(class_ref) => {
+            ANewArray(element_type) => {
                 let count = frame.pop_value::<SINGLE_SLOT>()?;
                 frame.push_value::<SINGLE_SLOT>(def.as_argument())?;
                 let array_op = ArrayOperation::New {
-                    element_type: FieldType::Object(class_ref.clone()),
+                    element_type: element_type.clone(),
                     length: count,
                 };
                 IR::Definition {
@@ -23,7 +23,7 @@ pub(super) struct JvmStackFrame {
     pub possible_ret_addresses: BTreeSet<ProgramCounter>,
 }
 
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, thiserror::Error)]
 pub enum ExecutionError {
     #[error("Trying to pop an empty stack")]
     StackUnderflow,
@@ -222,6 +222,10 @@ impl JvmStackFrame {
         Ok(())
     }
 
+    pub(super) fn stack_depth(&self) -> usize {
+        self.operand_stack.len()
+    }
+
     pub(super) fn same_frame(&self) -> Self {
         self.clone()
     }
@@ -544,7 +548,7 @@ mod test {
                 true,
                 &"()V".parse().expect("Invalid method desc"),
                 0,
-                (values.len() + (values.len() + 1) / 2).try_into().unwrap(),
+                (values.len() + values.len().div_ceil(2)).try_into().unwrap(),
             ).unwrap();
             for (i, value) in values.iter().enumerate() {
                 if i % 2 == 0 {
@@ -10,14 +10,14 @@ use std::{
 use crate::{
     ir::control_flow::path_condition::{PathCondition, Predicate},
     jvm::{
-        code::{ExceptionTableEntry, InstructionList, MethodBody, ProgramCounter},
+        code::{ExceptionTableEntry, Instruction, InstructionList, MethodBody, ProgramCounter},
         method,
         references::ClassRef,
         ConstantValue, Method,
     },
 };
 
-use crate::analysis::fixed_point::Analyzer;
+use crate::analysis::dataflow::{DataflowAnalysis, Lattice};
 
 use self::jvm_frame::{Entry, JvmStackFrame};
 
@@ -42,55 +42,117 @@ pub enum MokaIRBrewingError {
     /// An error that occurs when the method contains malformed control flow.
     #[error("The method contains malformed control flow")]
     MalformedControlFlow,
+    /// An error that occurs when executing the instruction at a program counter, e.g., the
+    /// operand stack underflows or a value has an unexpected type.
+    #[error("Error when executing the instruction at {pc}: {source}")]
+    InstructionError {
+        /// The program counter of the instruction.
+        pc: ProgramCounter,
+        /// The error when executing the instruction.
+        source: ExecutionError,
+    },
+    /// An error that occurs when the control flows to a program counter where there is no
+    /// instruction, e.g., a jump into the middle of an instruction.
+    #[error("There is no instruction at {0}, to which the control flows")]
+    MissingInstruction(ProgramCounter),
+    /// An error that occurs when the control flows past the last instruction.
+    #[error("The control flows past the last instruction at {0}")]
+    FallsOffCode(ProgramCounter),
+    /// An error that occurs when the frames do not converge within the given number of
+    /// iterations, i.e., instructions analyzed.
+    #[error("The analysis does not converge within {0} iterations")]
+    IterationLimitExceeded(usize),
+}
+
+impl MokaIRBrewingError {
+    /// Attaches the program counter of the instruction being executed to an execution error.
+    fn at(self, pc: ProgramCounter) -> Self {
+        match self {
+            Self::ExecutionError(source) => Self::InstructionError { pc, source },
+            other => other,
+        }
+    }
 }
 
 struct MokaIRGenerator<'m> {
     ir_instructions: BTreeMap<ProgramCounter, MokaInstruction>,
-    method: &'m Method,
     body: &'m MethodBody,
+    entry_frame: JvmStackFrame,
     control_flow_edges: BTreeMap<(ProgramCounter, ProgramCounter), ControlTransfer>,
+    max_iterations: usize,
+    iterations: usize,
+}
+
+/// The frame before an instruction, or the error of merging the frames flowing into it, which
+/// is reported when the instruction is executed.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum FrameState {
+    Frame(JvmStackFrame),
+    MergeError(ExecutionError),
 }
 
-impl Analyzer for MokaIRGenerator<'_> {
-    type Location = ProgramCounter;
-    type Fact = JvmStackFrame;
+impl Lattice for FrameState {
+    fn join(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::Frame(lhs), Self::Frame(rhs)) => lhs
+                .merge(rhs.clone())
+                .map_or_else(Self::MergeError, Self::Frame),
+            (Self::MergeError(err), _) | (_, Self::MergeError(err)) => Self::MergeError(*err),
+        }
+    }
+
+    /// The frames have a finite height, so the merged frame is taken as is.
+    fn widen(&self, next: &Self) -> Self {
+        next.clone()
+    }
+}
+
+impl DataflowAnalysis<FrameState> for MokaIRGenerator<'_> {
     type Err = MokaIRBrewingError;
-    type AffectedLocations = Vec<(Self::Location, Self::Fact)>;
 
-    fn entry_fact(&self) -> Result<Self::AffectedLocations, Self::Err> {
-        let first_pc = self
-            .body
-            .instructions
-            .entry_point()
-            .ok_or(MokaIRBrewingError::MalformedControlFlow)?
-            .0
-            .to_owned();
-        JvmStackFrame::new(
-            self.method
-                .access_flags
-                .contains(method::AccessFlags::STATIC),
-            &self.method.descriptor,
-            self.body.max_locals,
-            self.body.max_stack,
-        )
-        .map(|frame| vec![(first_pc, frame)])
-        .map_err(Into::into)
+    fn entry_state(&self) -> FrameState {
+        FrameState::Frame(self.entry_frame.clone())
     }
 
-    fn analyze_location(
+    fn transfer(
         &mut self,
-        location: &Self::Location,
-        fact: &Self::Fact,
-    ) -> Result<Self::AffectedLocations, Self::Err> {
+        pc: ProgramCounter,
+        instruction: &Instruction,
+        state: &FrameState,
+    ) -> Result<FrameState, Self::Err> {
+        if self.iterations == self.max_iterations {
+            return Err(MokaIRBrewingError::IterationLimitExceeded(
+                self.max_iterations,
+            ));
+        }
+        self.iterations += 1;
+        let frame = match state {
+            FrameState::Frame(frame) => frame,
+            FrameState::MergeError(err) => return Err(MokaIRBrewingError::MergeError(*err)),
+        };
+        let mut frame = frame.same_frame();
+        let ir_instruction = self
+            .run_instruction(instruction, pc, &mut frame)
+            .map_err(|err| err.at(pc))?;
+        self.ir_instructions.insert(pc, ir_instruction);
+        Ok(FrameState::Frame(frame))
+    }
+
+    fn successors(
+        &mut self,
+        body: &MethodBody,
+        location: ProgramCounter,
+        _instruction: &Instruction,
+        _before: &FrameState,
+        after: &FrameState,
+    ) -> Result<Vec<(ProgramCounter, FrameState)>, Self::Err> {
         use ControlTransfer::{Conditional, Unconditional};
-        let location = location.to_owned();
-        let mut frame = fact.same_frame();
-        let insn = self
-            .body
-            .instruction_at(location)
-            .ok_or(MokaIRBrewingError::MalformedControlFlow)?;
-        let ir_instruction = self.run_instruction(insn, location, &mut frame)?;
-        let edges_and_frames = match &ir_instruction {
+        let FrameState::Frame(frame) = after else {
+            return Ok(Vec::new());
+        };
+        let mut frame = frame.same_frame();
+        let ir_instruction = &self.ir_instructions[&location];
+        let edges_and_frames = match ir_instruction {
             MokaInstruction::Nop => {
                 let next_pc = self.next_pc_of(location)?;
                 let edge = (location, next_pc, Unconditional);
@@ -100,7 +162,7 @@ impl Analyzer for MokaIRGenerator<'_> {
             MokaInstruction::Definition {
                 expr: Expression::Throw(_),
                 ..
-            } => Self::exception_edges(&self.body.exception_table, location, &frame),
+            } => Self::exception_edges(&body.exception_table, location, &frame),
             MokaInstruction::Definition {
                 expr:
                     Expression::Subroutine {
@@ -115,7 +177,7 @@ impl Analyzer for MokaIRGenerator<'_> {
             }
             MokaInstruction::Definition { .. } => {
                 let next_pc = self.next_pc_of(location)?;
-                Self::exception_edges(&self.body.exception_table, location, &frame)
+                Self::exception_edges(&body.exception_table, location, &frame)
                     .into_iter()
                     .chain(once(((location, next_pc, Unconditional), frame)))
                     .collect()
@@ -165,25 +227,20 @@ impl Analyzer for MokaIRGenerator<'_> {
                 })
                 .collect(),
         };
-        self.ir_instructions.insert(location, ir_instruction);
 
-        let (affected_locations, edges) = edges_and_frames
+        let (successors, edges): (Vec<_>, BTreeSet<_>) = edges_and_frames
             .into_iter()
-            .map(|(edge, frame)| ((edge.1, frame), edge))
+            .map(|(edge, frame)| ((edge.1, FrameState::Frame(frame)), edge))
             .unzip();
+        if let Some((target, _)) = successors
+            .iter()
+            .find(|(target, _)| body.instruction_at(*target).is_none())
+        {
+            return Err(MokaIRBrewingError::MissingInstruction(*target));
+        }
         self.control_flow_edges
-            .extend(BTreeSet::into_iter(edges).map(|(src, tgt, ctr)| ((src, tgt), ctr)));
-        Ok(affected_locations)
-    }
-
-    fn merge_facts(
-        &self,
-        current_fact: &Self::Fact,
-        incoming_fact: Self::Fact,
-    ) -> Result<Self::Fact, Self::Err> {
-        current_fact
-            .merge(incoming_fact)
-            .map_err(MokaIRBrewingError::MergeError)
+            .extend(edges.into_iter().map(|(src, tgt, ctr)| ((src, tgt), ctr)));
+        Ok(successors)
     }
 }
 
@@ -192,19 +249,30 @@ impl<'m> MokaIRGenerator<'m> {
         self.body
             .instructions
             .next_pc_of(&pc)
-            .ok_or(MokaIRBrewingError::MalformedControlFlow)
+            .ok_or(MokaIRBrewingError::FallsOffCode(pc))
     }
 
-    fn for_method(method: &'m Method) -> Result<Self, <Self as Analyzer>::Err> {
+    fn for_method(method: &'m Method, max_iterations: usize) -> Result<Self, MokaIRBrewingError> {
         let body = method
             .body
             .as_ref()
             .ok_or(MokaIRBrewingError::NoMethodBody)?;
+        if body.instructions.is_empty() {
+            return Err(MokaIRBrewingError::MalformedControlFlow);
+        }
+        let entry_frame = JvmStackFrame::new(
+            method.access_flags.contains(method::AccessFlags::STATIC),
+            &method.descriptor,
+            body.max_locals,
+            body.max_stack,
+        )?;
         Ok(Self {
             ir_instructions: BTreeMap::default(),
-            method,
             body,
+            entry_frame,
             control_flow_edges: BTreeMap::default(),
+            max_iterations,
+            iterations: 0,
         })
     }
 
@@ -244,15 +312,34 @@ impl<'m> MokaIRGenerator<'m> {
 
 /// An extension trait for [`Method`] that generates Moka IR.
 pub trait MokaIRMethodExt {
+    /// The default maximum number of iterations of [`brew`](Self::brew), which is far more than
+    /// any method produced by a compiler needs.
+    const DEFAULT_MAX_ITERATIONS: usize = 1 << 22;
+
     /// Generates Moka IR for the method.
+    /// The frames at each instruction are always computed from the instructions and the
+    /// exception table, and the [`stack_map_table`](crate::jvm::code::MethodBody::stack_map_table)
+    /// is ignored, so methods rewritten without valid frames, or compiled for versions without
+    /// them, are supported.
     /// # Errors
     /// See [`MokaIRBrewingError`] for more information.
-    fn brew(&self) -> Result<MokaIRMethod, MokaIRBrewingError>;
+    fn brew(&self) -> Result<MokaIRMethod, MokaIRBrewingError> {
+        self.brew_with_limit(Self::DEFAULT_MAX_ITERATIONS)
+    }
+
+    /// Generates Moka IR for the method like [`brew`](Self::brew), but gives up after analyzing
+    /// `max_iterations` instructions, counting each instruction every time its frame changes.
+    /// This bounds the time spent on adversarial methods, e.g., in untrusted classes.
+    /// # Errors
+    /// - [`MokaIRBrewingError::IterationLimitExceeded`] if the frames do not converge in time.
+    /// - See [`MokaIRBrewingError`] for the other errors.
+    fn brew_with_limit(&self, max_iterations: usize) -> Result<MokaIRMethod, MokaIRBrewingError>;
 }
 
 impl MokaIRMethodExt for Method {
-    fn brew(&self) -> Result<MokaIRMethod, MokaIRBrewingError> {
-        let (instructions, control_flow_graph) = MokaIRGenerator::for_method(self)?.generate()?;
+    fn brew_with_limit(&self, max_iterations: usize) -> Result<MokaIRMethod, MokaIRBrewingError> {
+        let (instructions, control_flow_graph) =
+            MokaIRGenerator::for_method(self, max_iterations)?.generate()?;
         Ok(MokaIRMethod {
             access_flags: self.access_flags,
             name: self.name.clone(),
@@ -265,6 +352,21 @@ impl MokaIRMethodExt for Method {
     }
 }
 
+/// Computes the depth (in slots) of the operand stack before each reachable instruction.
+pub(crate) fn operand_stack_depths(
+    method: &Method,
+) -> Result<BTreeMap<ProgramCounter, usize>, MokaIRBrewingError> {
+    let mut generator = MokaIRGenerator::for_method(method, Method::DEFAULT_MAX_ITERATIONS)?;
+    let frames = generator.try_analyze(generator.body)?;
+    Ok(frames
+        .into_iter()
+        .filter_map(|(pc, state)| match state {
+            FrameState::Frame(frame) => Some((pc, frame.stack_depth())),
+            FrameState::MergeError(_) => None,
+        })
+        .collect())
+}
+
 impl MokaIRGenerator<'_> {
     fn generate(
         mut self,
@@ -275,7 +377,7 @@ impl MokaIRGenerator<'_> {
         ),
         MokaIRBrewingError,
     > {
-        self.analyze()?;
+        self.try_analyze(self.body)?;
         let cfg = ControlFlowGraph::from_edges(
             self.control_flow_edges
                 .into_iter()
@@ -284,3 +386,100 @@ impl MokaIRGenerator<'_> {
         Ok((InstructionList::from(self.ir_instructions), cfg))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn static_method(body: MethodBody) -> Method {
+        Method {
+            body: Some(body),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn stack_underflow() {
+        let method = static_method(MethodBody {
+            max_stack: 1,
+            instructions: InstructionList::from([
+                (0.into(), Instruction::Nop),
+                (1.into(), Instruction::Pop),
+                (2.into(), Instruction::Return),
+            ]),
+            ..Default::default()
+        });
+        assert!(matches!(
+            method.brew(),
+            Err(MokaIRBrewingError::InstructionError {
+                pc,
+                source: ExecutionError::StackUnderflow
+            }) if pc == 1.into()
+        ));
+    }
+
+    #[test]
+    fn value_mismatch() {
+        let method = static_method(MethodBody {
+            max_stack: 2,
+            max_locals: 2,
+            instructions: InstructionList::from([
+                (0.into(), Instruction::IConst0),
+                (1.into(), Instruction::IConst1),
+                (2.into(), Instruction::LStore0),
+                (3.into(), Instruction::Return),
+            ]),
+            ..Default::default()
+        });
+        assert!(matches!(
+            method.brew(),
+            Err(MokaIRBrewingError::InstructionError {
+                pc,
+                source: ExecutionError::ValueMismatch
+            }) if pc == 2.into()
+        ));
+    }
+
+    #[test]
+    fn malformed_control_flow() {
+        let jump_into_instruction = static_method(MethodBody {
+            instructions: InstructionList::from([
+                (0.into(), Instruction::Goto(1.into())),
+                (3.into(), Instruction::Return),
+            ]),
+            ..Default::default()
+        });
+        assert!(matches!(
+            jump_into_instruction.brew(),
+            Err(MokaIRBrewingError::MissingInstruction(pc)) if pc == 1.into()
+        ));
+        let falls_off_code = static_method(MethodBody {
+            instructions: InstructionList::from([(0.into(), Instruction::Nop)]),
+            ..Default::default()
+        });
+        assert!(matches!(
+            falls_off_code.brew(),
+            Err(MokaIRBrewingError::FallsOffCode(pc)) if pc == 0.into()
+        ));
+    }
+
+    #[test]
+    fn iteration_limit() {
+        let method = static_method(MethodBody {
+            max_stack: 1,
+            max_locals: 1,
+            instructions: InstructionList::from([
+                (0.into(), Instruction::IConst0),
+                (1.into(), Instruction::IStore0),
+                (2.into(), Instruction::IInc(0, 1)),
+                (5.into(), Instruction::Goto(2.into())),
+            ]),
+            ..Default::default()
+        });
+        assert!(method.brew().is_ok());
+        assert!(matches!(
+            method.brew_with_limit(3),
+            Err(MokaIRBrewingError::IterationLimitExceeded(3))
+        ));
+    }
+}
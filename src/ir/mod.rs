@@ -13,6 +13,7 @@ pub mod type_hierarchy;
 
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
+pub(crate) use generator::operand_stack_depths;
 pub use generator::{MokaIRBrewingError, MokaIRMethodExt};
 pub use moka_instruction::*;
 
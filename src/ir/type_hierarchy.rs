@@ -3,7 +3,14 @@ use std::collections::{HashMap, HashSet};
 
 use petgraph::visit::{depth_first_search, Control, DfsEvent, Reversed};
 
-use crate::jvm::{references::ClassRef, Class};
+use crate::{
+    jvm::{
+        method,
+        references::{ClassRef, MethodRef},
+        Class, Method,
+    },
+    macros::see_jvm_spec,
+};
 
 use super::{ClassHierarchy, InterfaceImplHierarchy};
 
@@ -59,6 +66,101 @@ impl ClassHierarchy {
         subclasses.remove(class);
         subclasses.into_iter().cloned().collect()
     }
+
+    /// Checks if `sub_method` overrides `super_method`.
+    /// The following rules are applied:
+    /// - The class declaring `sub_method` must be a subclass of the one declaring `super_method`.
+    /// - Both methods must have the same name and descriptor.
+    /// - Neither method may be static, a constructor, or a class initializer.
+    /// - `super_method` must not be private.
+    /// - If `super_method` is package-private, both classes must be in the same package.
+    ///
+    /// Covariant return types are not matched directly since their descriptors differ. Instead,
+    /// the compiler generates a bridge method with the descriptor of the overridden method in the
+    /// subclass, and the bridge method is the one reported as overriding.
+    /// Overriding a package-private method through an intermediate method in another package
+    /// and implementing interface methods are not considered.
+    #[doc = see_jvm_spec!(5, 4, 5)]
+    #[must_use]
+    pub fn overrides(&self, sub_method: &Method, super_method: &Method) -> bool {
+        let is_instance_method = |method: &Method| {
+            !method.access_flags.contains(method::AccessFlags::STATIC)
+                && !method.is_constructor()
+                && !method.is_static_initializer_block()
+        };
+        let accessible = if super_method
+            .access_flags
+            .contains(method::AccessFlags::PRIVATE)
+        {
+            false
+        } else if super_method
+            .access_flags
+            .intersects(method::AccessFlags::PUBLIC | method::AccessFlags::PROTECTED)
+        {
+            true
+        } else {
+            package_of(&sub_method.owner) == package_of(&super_method.owner)
+        };
+        is_instance_method(sub_method)
+            && is_instance_method(super_method)
+            && accessible
+            && sub_method.name == super_method.name
+            && sub_method.descriptor == super_method.descriptor
+            && self
+                .super_classes(&sub_method.owner)
+                .contains(&super_method.owner)
+    }
+
+    /// Resolves `reference` to the method it refers to by looking it up in the class named by the
+    /// reference and then in its super classes, from the nearest to the farthest.
+    /// `find_class` is used to get the declared methods of each class on the way.
+    ///
+    /// Returns `None` if no class declares a method with the same name and descriptor, or if
+    /// `find_class` does not find a class before the method is found.
+    /// Methods declared in super interfaces and signature polymorphic methods are not resolved.
+    #[doc = see_jvm_spec!(5, 4, 3, 3)]
+    pub fn resolve_method<'c, F>(
+        &self,
+        reference: &MethodRef,
+        mut find_class: F,
+    ) -> Option<&'c Method>
+    where
+        F: FnMut(&ClassRef) -> Option<&'c Class>,
+    {
+        let mut current = &reference.owner;
+        loop {
+            let class = find_class(current)?;
+            if let Some(method) = class.get_method(&reference.name, &reference.descriptor) {
+                return Some(method);
+            }
+            current = self.super_classes.get(current)?;
+        }
+    }
+
+    /// Resolves the method invoked by an `invokestatic` instruction with `reference`.
+    /// Since static methods are inherited, the method may be declared in a super class of the
+    /// class named by the reference, e.g., `Child.foo()` may invoke `Parent.foo()`.
+    /// Static methods of interfaces are not inherited, and are only found in the interface
+    /// itself.
+    ///
+    /// Returns `None` if the method cannot be resolved by
+    /// [`resolve_method`](ClassHierarchy::resolve_method), or if the resolved method is not
+    /// static, in which case the invocation fails with an `IncompatibleClassChangeError`.
+    #[doc = see_jvm_spec!(6, 5, "invokestatic")]
+    pub fn resolve_static<'c, F>(&self, reference: &MethodRef, find_class: F) -> Option<&'c Method>
+    where
+        F: FnMut(&ClassRef) -> Option<&'c Class>,
+    {
+        self.resolve_method(reference, find_class)
+            .filter(|it| it.access_flags.contains(method::AccessFlags::STATIC))
+    }
+}
+
+fn package_of(class: &ClassRef) -> &str {
+    class
+        .binary_name
+        .rsplit_once('/')
+        .map_or("", |(package, _)| package)
 }
 
 impl InterfaceImplHierarchy {
@@ -36,6 +36,9 @@ pub enum ElementValue {
 }
 
 /// Information about the target of a [`TypeAnnotation`](super::TypeAnnotation).
+/// Several kinds of targets share the same information, e.g., [`TargetInfo::Empty`] is used for
+/// field types, return types, and receiver types. The kind of the target is therefore kept
+/// separately in [`TypeAnnotation::target_kind`](super::TypeAnnotation::target_kind).
 #[doc = see_jvm_spec!(4, 7, 20, 1)]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TargetInfo {
@@ -95,6 +98,102 @@ pub enum TargetInfo {
     },
 }
 
+/// The kind of the target of a [`TypeAnnotation`](super::TypeAnnotation), i.e., the
+/// `target_type` item of the `type_annotation` structure.
+/// Unlike [`TargetInfo`], which only holds the information needed to locate the target, this
+/// distinguishes all the kinds of targets, e.g., the return type and the receiver type of a
+/// method, which share [`TargetInfo::Empty`].
+#[doc = see_jvm_spec!(4, 7, 20, 1)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum TargetKind {
+    /// A type parameter declaration of a generic class or interface.
+    ClassTypeParameter = 0x00,
+    /// A type parameter declaration of a generic method or constructor.
+    MethodTypeParameter = 0x01,
+    /// A type in the `extends` or `implements` clause of a class or interface declaration.
+    SuperType = 0x10,
+    /// A bound of a type parameter declaration of a generic class or interface.
+    ClassTypeParameterBound = 0x11,
+    /// A bound of a type parameter declaration of a generic method or constructor.
+    MethodTypeParameterBound = 0x12,
+    /// The type in a field or record component declaration.
+    Field = 0x13,
+    /// The return type of a method, or the type of a newly constructed object.
+    MethodReturn = 0x14,
+    /// The receiver type of a method or constructor.
+    MethodReceiver = 0x15,
+    /// The type in a formal parameter declaration of a method, constructor, or lambda expression.
+    FormalParameter = 0x16,
+    /// A type in the `throws` clause of a method or constructor declaration.
+    ThrowsClause = 0x17,
+    /// The type in a local variable declaration.
+    LocalVariable = 0x40,
+    /// The type in a resource variable declaration of a `try`-with-resources statement.
+    ResourceVariable = 0x41,
+    /// The type in an exception parameter declaration.
+    ExceptionParameter = 0x42,
+    /// The type in an `instanceof` expression.
+    InstanceOf = 0x43,
+    /// The type in a `new` expression.
+    New = 0x44,
+    /// The type before the `::` in a constructor reference expression (`::new`).
+    ConstructorReference = 0x45,
+    /// The type before the `::` in a method reference expression.
+    MethodReference = 0x46,
+    /// The type in a cast expression.
+    Cast = 0x47,
+    /// A type argument for a generic constructor in a `new` expression or an explicit
+    /// constructor invocation statement.
+    ConstructorInvocationTypeArgument = 0x48,
+    /// A type argument for a generic method in a method invocation expression.
+    MethodInvocationTypeArgument = 0x49,
+    /// A type argument for a generic constructor in a constructor reference expression.
+    ConstructorReferenceTypeArgument = 0x4A,
+    /// A type argument for a generic method in a method reference expression.
+    MethodReferenceTypeArgument = 0x4B,
+}
+
+impl TargetKind {
+    /// Returns the kind with the given `target_type` value, or `None` if it is not defined by
+    /// the JVM specification.
+    #[must_use]
+    pub const fn from_target_type(target_type: u8) -> Option<Self> {
+        let kind = match target_type {
+            0x00 => Self::ClassTypeParameter,
+            0x01 => Self::MethodTypeParameter,
+            0x10 => Self::SuperType,
+            0x11 => Self::ClassTypeParameterBound,
+            0x12 => Self::MethodTypeParameterBound,
+            0x13 => Self::Field,
+            0x14 => Self::MethodReturn,
+            0x15 => Self::MethodReceiver,
+            0x16 => Self::FormalParameter,
+            0x17 => Self::ThrowsClause,
+            0x40 => Self::LocalVariable,
+            0x41 => Self::ResourceVariable,
+            0x42 => Self::ExceptionParameter,
+            0x43 => Self::InstanceOf,
+            0x44 => Self::New,
+            0x45 => Self::ConstructorReference,
+            0x46 => Self::MethodReference,
+            0x47 => Self::Cast,
+            0x48 => Self::ConstructorInvocationTypeArgument,
+            0x49 => Self::MethodInvocationTypeArgument,
+            0x4A => Self::ConstructorReferenceTypeArgument,
+            0x4B => Self::MethodReferenceTypeArgument,
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    /// Returns the `target_type` value of the kind.
+    #[must_use]
+    pub const fn target_type(self) -> u8 {
+        self as u8
+    }
+}
+
 /// Identifies a part of a type that is annotated.
 #[doc = see_jvm_spec!(4, 7, 20, 2)]
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -108,3 +207,30 @@ pub enum TypePathElement {
     /// Annotation is on a type argument of a parameterized type.
     TypeArgument(u8),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn target_kind() {
+        let kinds = [
+            (0x14, TargetKind::MethodReturn),
+            (0x15, TargetKind::MethodReceiver),
+            (0x16, TargetKind::FormalParameter),
+            (0x17, TargetKind::ThrowsClause),
+            (0x40, TargetKind::LocalVariable),
+            (0x41, TargetKind::ResourceVariable),
+            (0x42, TargetKind::ExceptionParameter),
+            (0x43, TargetKind::InstanceOf),
+            (0x44, TargetKind::New),
+            (0x47, TargetKind::Cast),
+        ];
+        for (target_type, kind) in kinds {
+            assert_eq!(Some(kind), TargetKind::from_target_type(target_type));
+            assert_eq!(target_type, kind.target_type());
+        }
+        assert_eq!(None, TargetKind::from_target_type(0x02));
+        assert_eq!(None, TargetKind::from_target_type(0x4C));
+    }
+}
@@ -49,10 +49,48 @@ impl ConstantPool {
             _ => Err(BadConstantPoolIndex(index)),
         }
     }
+
+    /// Gets the tag of the constant pool entry at the given index.
+    /// Returns `None` if `index` does not point to a valid entry.
+    #[must_use]
+    pub fn tag(&self, index: u16) -> Option<ConstantPoolTag> {
+        self.get_entry(index).ok().map(Entry::tag)
+    }
+
+    /// Returns the number of entries in the constant pool.
+    /// Unlike `constant_pool_count` in the class file, this does not count the unusable slot
+    /// at index `0` nor the slots following [`Entry::Long`] and [`Entry::Double`].
+    #[must_use]
+    pub fn entry_count(&self) -> usize {
+        self.entries().count()
+    }
+
+    /// Returns the number of slots in the constant pool, i.e., `constant_pool_count - 1` in the
+    /// class file.
+    /// Each [`Entry::Long`] and [`Entry::Double`] takes two slots, so this may be greater than
+    /// [`ConstantPool::entry_count`]. A class file can hold at most `65534` slots.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.inner.len().saturating_sub(1)
+    }
+
+    /// Checks if the constant pool has no entries.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over the entries in the constant pool, skipping the padding slots.
+    pub(crate) fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.inner.iter().filter_map(|slot| match slot {
+            Slot::Entry(entry) => Some(entry),
+            Slot::Padding => None,
+        })
+    }
 }
 
 /// An error when getting an entry from the constant pool with an invalid index.
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, Clone, thiserror::Error)]
 #[error("Bad constant pool index: {0}")]
 pub struct BadConstantPoolIndex(pub u16);
 
@@ -180,7 +218,62 @@ pub enum Entry {
     } = 20,
 }
 
+/// The tag of an [`Entry`] in the [`ConstantPool`].
+#[doc = see_jvm_spec!(4, 4)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+#[allow(missing_docs)]
+pub enum ConstantPoolTag {
+    Utf8 = 1,
+    Integer = 3,
+    Float = 4,
+    Long = 5,
+    Double = 6,
+    Class = 7,
+    String = 8,
+    FieldRef = 9,
+    MethodRef = 10,
+    InterfaceMethodRef = 11,
+    NameAndType = 12,
+    MethodHandle = 15,
+    MethodType = 16,
+    Dynamic = 17,
+    InvokeDynamic = 18,
+    Module = 19,
+    Package = 20,
+}
+
+impl From<ConstantPoolTag> for u8 {
+    fn from(tag: ConstantPoolTag) -> Self {
+        tag as u8
+    }
+}
+
 impl Entry {
+    /// Gets the tag of this constant pool entry.
+    #[must_use]
+    pub const fn tag(&self) -> ConstantPoolTag {
+        match self {
+            Self::Utf8(_) => ConstantPoolTag::Utf8,
+            Self::Integer(_) => ConstantPoolTag::Integer,
+            Self::Float(_) => ConstantPoolTag::Float,
+            Self::Long(_) => ConstantPoolTag::Long,
+            Self::Double(_) => ConstantPoolTag::Double,
+            Self::Class { .. } => ConstantPoolTag::Class,
+            Self::String { .. } => ConstantPoolTag::String,
+            Self::FieldRef { .. } => ConstantPoolTag::FieldRef,
+            Self::MethodRef { .. } => ConstantPoolTag::MethodRef,
+            Self::InterfaceMethodRef { .. } => ConstantPoolTag::InterfaceMethodRef,
+            Self::NameAndType { .. } => ConstantPoolTag::NameAndType,
+            Self::MethodHandle { .. } => ConstantPoolTag::MethodHandle,
+            Self::MethodType { .. } => ConstantPoolTag::MethodType,
+            Self::Dynamic { .. } => ConstantPoolTag::Dynamic,
+            Self::InvokeDynamic { .. } => ConstantPoolTag::InvokeDynamic,
+            Self::Module { .. } => ConstantPoolTag::Module,
+            Self::Package { .. } => ConstantPoolTag::Package,
+        }
+    }
+
     /// Gets the kind of this constant pool entry.
     #[must_use]
     pub const fn constant_kind<'a>(&self) -> &'a str {
@@ -252,5 +345,26 @@ mod tests {
             assert!(kind.starts_with("CONSTANT_"));
         }
 
+        #[test]
+        fn tag(entry in arb_constant_pool_info()) {
+            let expected_tag = entry[0];
+            let mut reader = entry.as_slice();
+            let entry = Entry::parse(&mut reader).unwrap();
+            assert_eq!(expected_tag, u8::from(entry.tag()));
+        }
+
+        #[test]
+        fn entry_count((count, bytes) in arb_constant_pool_bytes()) {
+            let constant_pool = ConstantPool::from_reader(&mut bytes.as_slice(), count).unwrap();
+            let wide_entries = constant_pool
+                .entries()
+                .filter(|it| matches!(it.tag(), ConstantPoolTag::Long | ConstantPoolTag::Double))
+                .count();
+            assert_eq!(usize::from(count) - 1, constant_pool.entry_count() + wide_entries);
+            assert_eq!(None, constant_pool.tag(0));
+            assert_eq!(None, constant_pool.tag(count));
+            assert!(constant_pool.tag(1).is_some());
+        }
+
     }
 }
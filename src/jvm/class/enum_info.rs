@@ -0,0 +1,160 @@
+//! Recognition of the members generated by javac for enum classes.
+
+use crate::{
+    jvm::{class, field, method, references::ClassRef, Class, Field, Method},
+    types::{
+        field_type::FieldType,
+        method_descriptor::{MethodDescriptor, ReturnType},
+    },
+};
+
+/// The structure of an enum class, returned by [`Class::enum_info`].
+#[derive(Debug, Clone)]
+pub struct EnumInfo<'a> {
+    /// The enum constants, i.e., the fields with `ACC_ENUM`, in declaration order.
+    pub constants: Vec<&'a Field>,
+    /// The `public static E[] values()` method.
+    pub values: Option<&'a Method>,
+    /// The `public static E valueOf(String)` method.
+    pub value_of: Option<&'a Method>,
+    /// The `private static final synthetic E[] $VALUES` field backing [`values`](Self::values).
+    pub values_field: Option<&'a Field>,
+}
+
+impl Class {
+    /// Recognizes the members generated by javac for an enum class, i.e., the enum constants, the
+    /// implicitly declared `values()` and `valueOf(String)` methods, and the synthetic `$VALUES`
+    /// field holding the constants.
+    /// The members are matched by their names, types, and flags, so any of them may be missing
+    /// from the result if the class was produced by another compiler or transformed afterwards.
+    ///
+    /// Returns `None` if the class is not an enum class, i.e., it does not have `ACC_ENUM`.
+    #[must_use]
+    pub fn enum_info(&self) -> Option<EnumInfo<'_>> {
+        if !self.access_flags.contains(class::AccessFlags::ENUM) {
+            return None;
+        }
+        let this_type = FieldType::Object(self.as_ref());
+        let array_type = this_type.clone().into_array_type();
+        let is_static_method =
+            |method: &&Method| method.access_flags.contains(method::AccessFlags::STATIC);
+        let values = self.methods.iter().filter(is_static_method).find(|method| {
+            method.name == "values"
+                && method.descriptor.parameters_types.is_empty()
+                && method.descriptor.return_type == ReturnType::Some(array_type.clone())
+        });
+        let value_of_descriptor = MethodDescriptor {
+            parameters_types: vec![FieldType::Object(ClassRef::new("java/lang/String"))],
+            return_type: ReturnType::Some(this_type.clone()),
+        };
+        let value_of =
+            self.methods.iter().filter(is_static_method).find(|method| {
+                method.name == "valueOf" && method.descriptor == value_of_descriptor
+            });
+        let values_field = self.fields.iter().find(|field| {
+            field.name == "$VALUES"
+                && field.field_type == array_type
+                && field
+                    .access_flags
+                    .contains(field::AccessFlags::STATIC | field::AccessFlags::SYNTHETIC)
+        });
+        let constants = self
+            .fields
+            .iter()
+            .filter(|field| {
+                field.access_flags.contains(field::AccessFlags::ENUM)
+                    && field.field_type == this_type
+            })
+            .collect();
+        Some(EnumInfo {
+            constants,
+            values,
+            value_of,
+            values_field,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str, field_type: &str, access_flags: field::AccessFlags) -> Field {
+        Field {
+            access_flags,
+            name: name.to_owned(),
+            owner: ClassRef::new("org/mokapot/Color"),
+            field_type: field_type.parse().unwrap(),
+            ..Default::default()
+        }
+    }
+
+    fn method(name: &str, descriptor: &str) -> Method {
+        Method {
+            access_flags: method::AccessFlags::PUBLIC | method::AccessFlags::STATIC,
+            name: name.to_owned(),
+            descriptor: descriptor.parse().unwrap(),
+            owner: ClassRef::new("org/mokapot/Color"),
+            body: None,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn javac_enum_members() {
+        let constant = field::AccessFlags::PUBLIC
+            | field::AccessFlags::STATIC
+            | field::AccessFlags::FINAL
+            | field::AccessFlags::ENUM;
+        let values = field::AccessFlags::PRIVATE
+            | field::AccessFlags::STATIC
+            | field::AccessFlags::FINAL
+            | field::AccessFlags::SYNTHETIC;
+        let class = Class {
+            binary_name: "org/mokapot/Color".to_owned(),
+            access_flags: class::AccessFlags::PUBLIC
+                | class::AccessFlags::FINAL
+                | class::AccessFlags::SUPER
+                | class::AccessFlags::ENUM,
+            fields: vec![
+                field("RED", "Lorg/mokapot/Color;", constant),
+                field("GREEN", "Lorg/mokapot/Color;", constant),
+                field("rgb", "I", field::AccessFlags::PRIVATE),
+                field("$VALUES", "[Lorg/mokapot/Color;", values),
+            ],
+            methods: vec![
+                method("values", "()[Lorg/mokapot/Color;"),
+                method("valueOf", "(Ljava/lang/String;)Lorg/mokapot/Color;"),
+                method("valueOf", "(I)Lorg/mokapot/Color;"),
+            ],
+            ..Default::default()
+        };
+        let info = class.enum_info().unwrap();
+        assert_eq!(
+            vec!["RED", "GREEN"],
+            info.constants
+                .iter()
+                .map(|it| it.name.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some("values"), info.values.map(|it| it.name.as_str()));
+        assert_eq!(
+            Some("(Ljava/lang/String;)Lorg/mokapot/Color;".to_owned()),
+            info.value_of.map(|it| it.descriptor.to_string())
+        );
+        assert_eq!(
+            Some("$VALUES"),
+            info.values_field.map(|it| it.name.as_str())
+        );
+    }
+
+    #[test]
+    fn not_an_enum() {
+        let class = Class {
+            binary_name: "org/mokapot/Color".to_owned(),
+            methods: vec![method("values", "()[Lorg/mokapot/Color;")],
+            ..Default::default()
+        };
+        assert!(class.enum_info().is_none());
+    }
+}
@@ -1,8 +1,15 @@
 //! JVM classes and interfaces
 
 pub mod constant_pool;
+mod enum_info;
+pub mod string_concat;
+mod string_constants;
+mod structural_hash;
+pub mod summary;
 
-use std::borrow::Borrow;
+pub use enum_info::EnumInfo;
+
+use std::{borrow::Borrow, collections::HashSet};
 
 use bitflags::bitflags;
 
@@ -13,9 +20,10 @@ use crate::{
 
 use super::{
     annotation::ElementValue,
+    code::Instruction,
     field,
     parsing::Error,
-    references::{ClassRef, FieldRef, MethodRef},
+    references::{ClassRef, FieldRef, MethodRef, PackageRef},
     Annotation, Class, ConstantValue, Field, Method,
 };
 
@@ -45,6 +53,52 @@ impl Class {
             .find(|f| f.name == name && &f.field_type == field_type.borrow())
     }
 
+    /// Gets the position of a field in [`Class::fields`], which follows the declaration order in
+    /// the class file.
+    #[must_use]
+    pub fn field_index<T>(&self, name: &str, field_type: T) -> Option<usize>
+    where
+        T: Borrow<FieldType>,
+    {
+        self.fields
+            .iter()
+            .position(|f| f.name == name && &f.field_type == field_type.borrow())
+    }
+
+    /// Gets the position of a method in [`Class::methods`], which follows the declaration order
+    /// in the class file.
+    #[must_use]
+    pub fn method_index<D>(&self, name: &str, descriptor: D) -> Option<usize>
+    where
+        D: Borrow<MethodDescriptor>,
+    {
+        self.methods
+            .iter()
+            .position(|m| m.name == name && &m.descriptor == descriptor.borrow())
+    }
+
+    /// Gets the methods of the class whose bodies invoke `target` with `invokevirtual`,
+    /// `invokespecial`, `invokestatic`, or `invokeinterface`.
+    /// The references are compared as they are, including the owners and the descriptors, so
+    /// overloads are distinguished and the target is not resolved in the class hierarchy.
+    #[must_use]
+    pub fn methods_invoking(&self, target: &MethodRef) -> Vec<&Method> {
+        self.methods
+            .iter()
+            .filter(|method| {
+                method.body.iter().any(|body| {
+                    body.instructions.iter().any(|(_, insn)| match insn {
+                        Instruction::InvokeVirtual(it)
+                        | Instruction::InvokeSpecial(it)
+                        | Instruction::InvokeStatic(it)
+                        | Instruction::InvokeInterface(it, _) => it == target,
+                        _ => false,
+                    })
+                })
+            })
+            .collect()
+    }
+
     /// Creates a [`ClassRef`] referring to the class.
     #[must_use]
     pub fn as_ref(&self) -> ClassRef {
@@ -53,6 +107,41 @@ impl Class {
         }
     }
 
+    /// Returns the name of the source file from which the class was compiled, if recorded in the
+    /// `SourceFile` attribute.
+    #[must_use]
+    pub fn source_file(&self) -> Option<&str> {
+        self.source_file.as_deref()
+    }
+
+    /// Returns the path of the source file relative to the source root, e.g.,
+    /// `com/example/Foo.java` for `com/example/Foo$Inner` compiled from `Foo.java`.
+    /// Returns `None` if the class has no `SourceFile` attribute.
+    #[must_use]
+    pub fn source_path(&self) -> Option<String> {
+        let source_file = self.source_file()?;
+        let path = match self.binary_name.rsplit_once('/') {
+            Some((package, _)) => format!("{package}/{source_file}"),
+            None => source_file.to_owned(),
+        };
+        Some(path)
+    }
+
+    /// Returns the main class of the module, if recorded in the `ModuleMainClass` attribute.
+    /// Only a `module-info` class has a main class.
+    #[must_use]
+    pub fn module_main_class(&self) -> Option<&ClassRef> {
+        self.module_main_class.as_ref()
+    }
+
+    /// Returns the packages of the module recorded in the `ModulePackages` attribute, or an
+    /// empty slice if the attribute is absent.
+    /// Only a `module-info` class has packages.
+    #[must_use]
+    pub fn module_packages(&self) -> &[PackageRef] {
+        &self.module_packages
+    }
+
     /// Checks if the class is an interface.
     #[must_use]
     pub const fn is_interface(&self) -> bool {
@@ -64,6 +153,130 @@ impl Class {
     pub const fn is_abstract(&self) -> bool {
         self.access_flags.contains(AccessFlags::ABSTRACT)
     }
+
+    /// Checks if the class is `target` or a subtype of it, i.e., if `target` is reachable by
+    /// following the superclasses and interfaces.
+    /// The supertypes are loaded lazily with `resolve`, which is called at most once for each
+    /// class. The supertypes of a class that `resolve` fails to load are not searched, so the
+    /// result may be a false negative if the hierarchy is incomplete.
+    pub fn is_instance_of<F>(&self, target: &str, mut resolve: F) -> bool
+    where
+        F: FnMut(&str) -> Option<Class>,
+    {
+        if self.binary_name == target {
+            return true;
+        }
+        let mut visited = HashSet::from([self.binary_name.clone()]);
+        let mut pending: Vec<ClassRef> = self
+            .super_class
+            .iter()
+            .chain(&self.interfaces)
+            .cloned()
+            .collect();
+        while let Some(class_ref) = pending.pop() {
+            if class_ref.binary_name == target {
+                return true;
+            }
+            if !visited.insert(class_ref.binary_name.clone()) {
+                continue;
+            }
+            if let Some(class) = resolve(&class_ref.binary_name) {
+                pending.extend(class.super_class.into_iter().chain(class.interfaces));
+            }
+        }
+        false
+    }
+
+    /// Checks that the class, its fields, and its methods do not use any feature introduced after
+    /// the declared version of the class.
+    /// # Errors
+    /// Returns [`VersionError`] with the first feature that is too new for the class version.
+    pub fn verify_version_features(&self) -> Result<(), VersionError> {
+        let check = |feature: VersionedFeature| {
+            if self.version.major() < feature.since() {
+                Err(VersionError {
+                    feature,
+                    version: self.version,
+                })
+            } else {
+                Ok(())
+            }
+        };
+        let class_features = [
+            (self.signature.is_some(), VersionedFeature::Signature),
+            (
+                self.enclosing_method.is_some(),
+                VersionedFeature::EnclosingMethod,
+            ),
+            (
+                !self.runtime_visible_annotations.is_empty()
+                    || !self.runtime_invisible_annotations.is_empty(),
+                VersionedFeature::Annotations,
+            ),
+            (
+                !self.bootstrap_methods.is_empty(),
+                VersionedFeature::BootstrapMethods,
+            ),
+            (
+                !self.runtime_visible_type_annotations.is_empty()
+                    || !self.runtime_invisible_type_annotations.is_empty(),
+                VersionedFeature::TypeAnnotations,
+            ),
+            (
+                self.module.is_some()
+                    || !self.module_packages.is_empty()
+                    || self.module_main_class.is_some(),
+                VersionedFeature::Module,
+            ),
+            (
+                self.nest_host.is_some() || !self.nest_members.is_empty(),
+                VersionedFeature::NestMates,
+            ),
+            (self.record.is_some(), VersionedFeature::Record),
+            (
+                !self.permitted_subclasses.is_empty(),
+                VersionedFeature::PermittedSubclasses,
+            ),
+        ];
+        class_features
+            .into_iter()
+            .filter(|(used, _)| *used)
+            .try_for_each(|(_, feature)| check(feature))?;
+        self.bootstrap_methods
+            .iter()
+            .flat_map(|it| &it.arguments)
+            .filter_map(VersionedFeature::of_constant)
+            .try_for_each(check)?;
+
+        self.fields
+            .iter()
+            .flat_map(VersionedFeature::of_field)
+            .try_for_each(check)?;
+
+        for method in &self.methods {
+            VersionedFeature::of_method(method).try_for_each(check)?;
+            let Some(body) = method.body.as_ref() else {
+                continue;
+            };
+            if body.stack_map_table.is_some() {
+                check(VersionedFeature::StackMapTable)?;
+            }
+            for (_, insn) in &body.instructions {
+                match insn {
+                    Instruction::InvokeDynamic { .. } => check(VersionedFeature::InvokeDynamic)?,
+                    Instruction::Ldc(value)
+                    | Instruction::LdcW(value)
+                    | Instruction::Ldc2W(value) => {
+                        if let Some(feature) = VersionedFeature::of_constant(value) {
+                            check(feature)?;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Annotation {
@@ -294,12 +507,152 @@ pub struct EnclosingMethod {
     pub method_name_and_desc: Option<(String, MethodDescriptor)>,
 }
 
+/// A feature of the class file format that is only available since a certain class version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+#[non_exhaustive]
+pub enum VersionedFeature {
+    /// The `Signature` attribute.
+    #[display("Signature attribute")]
+    Signature,
+    /// The `EnclosingMethod` attribute.
+    #[display("EnclosingMethod attribute")]
+    EnclosingMethod,
+    /// The `Runtime(In)VisibleAnnotations` attributes.
+    #[display("annotations")]
+    Annotations,
+    /// Loading a class constant with `ldc`.
+    #[display("class constant")]
+    ClassConstant,
+    /// The `StackMapTable` attribute.
+    #[display("StackMapTable attribute")]
+    StackMapTable,
+    /// The `invokedynamic` instruction.
+    #[display("invokedynamic")]
+    InvokeDynamic,
+    /// The `BootstrapMethods` attribute.
+    #[display("BootstrapMethods attribute")]
+    BootstrapMethods,
+    /// Method handle and method type constants.
+    #[display("method handle constant")]
+    MethodHandleConstant,
+    /// The `Runtime(In)VisibleTypeAnnotations` attributes.
+    #[display("type annotations")]
+    TypeAnnotations,
+    /// The `MethodParameters` attribute.
+    #[display("MethodParameters attribute")]
+    MethodParameters,
+    /// The `Module`, `ModulePackages`, and `ModuleMainClass` attributes.
+    #[display("Module attribute")]
+    Module,
+    /// The `NestHost` and `NestMembers` attributes.
+    #[display("NestHost attribute")]
+    NestMates,
+    /// Dynamically-computed constants.
+    #[display("dynamic constant")]
+    DynamicConstant,
+    /// The `Record` attribute.
+    #[display("Record attribute")]
+    Record,
+    /// The `PermittedSubclasses` attribute.
+    #[display("PermittedSubclasses attribute")]
+    PermittedSubclasses,
+}
+
+impl VersionedFeature {
+    /// Returns the major version of the first class file version supporting the feature.
+    #[must_use]
+    pub const fn since(self) -> u16 {
+        match self {
+            Self::Signature | Self::EnclosingMethod | Self::Annotations | Self::ClassConstant => 49,
+            Self::StackMapTable => 50,
+            Self::InvokeDynamic | Self::BootstrapMethods | Self::MethodHandleConstant => 51,
+            Self::TypeAnnotations | Self::MethodParameters => 52,
+            Self::Module => 53,
+            Self::NestMates | Self::DynamicConstant => 55,
+            Self::Record => 60,
+            Self::PermittedSubclasses => 61,
+        }
+    }
+
+    fn of_constant(value: &ConstantValue) -> Option<Self> {
+        match value {
+            ConstantValue::Class(_) => Some(Self::ClassConstant),
+            ConstantValue::Handle(_) | ConstantValue::MethodType(_) => {
+                Some(Self::MethodHandleConstant)
+            }
+            ConstantValue::Dynamic(..) => Some(Self::DynamicConstant),
+            _ => None,
+        }
+    }
+
+    fn of_field(field: &Field) -> impl Iterator<Item = Self> {
+        [
+            (field.signature.is_some(), Self::Signature),
+            (
+                !field.runtime_visible_annotations.is_empty()
+                    || !field.runtime_invisible_annotations.is_empty(),
+                Self::Annotations,
+            ),
+            (
+                !field.runtime_visible_type_annotations.is_empty()
+                    || !field.runtime_invisible_type_annotations.is_empty(),
+                Self::TypeAnnotations,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(used, feature)| used.then_some(feature))
+    }
+
+    fn of_method(method: &Method) -> impl Iterator<Item = Self> {
+        let body_type_annotations = method.body.as_ref().is_some_and(|body| {
+            !body.runtime_visible_type_annotations.is_empty()
+                || !body.runtime_invisible_type_annotations.is_empty()
+        });
+        [
+            (method.signature.is_some(), Self::Signature),
+            (
+                !method.runtime_visible_annotations.is_empty()
+                    || !method.runtime_invisible_annotations.is_empty()
+                    || !method.runtime_visible_parameter_annotations.is_empty()
+                    || !method.runtime_invisible_parameter_annotations.is_empty()
+                    || method.annotation_default.is_some(),
+                Self::Annotations,
+            ),
+            (
+                !method.runtime_visible_type_annotations.is_empty()
+                    || !method.runtime_invisible_type_annotations.is_empty()
+                    || body_type_annotations,
+                Self::TypeAnnotations,
+            ),
+            (!method.parameters.is_empty(), Self::MethodParameters),
+        ]
+        .into_iter()
+        .filter_map(|(used, feature)| used.then_some(feature))
+    }
+}
+
+/// An error indicating that a class uses a feature that is too new for its version.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(
+    "{feature} requires class version {} or above, but the class version is {}",
+    feature.since(),
+    version.major()
+)]
+pub struct VersionError {
+    /// The feature that is not supported.
+    pub feature: VersionedFeature,
+    /// The version of the class.
+    pub version: Version,
+}
+
 /// The information of a bootstrap method.
 #[derive(Debug, Clone)]
 pub struct BootstrapMethod {
     /// The method handle of the bootstrap method.
     pub method: MethodHandle,
-    /// The argument that are passed to the bootstrap method.
+    /// The static arguments that are passed to the bootstrap method, resolved from the constant
+    /// pool, e.g., the [`MethodType`](ConstantValue::MethodType) and
+    /// [`Handle`](ConstantValue::Handle) arguments of `LambdaMetafactory.metafactory`.
     pub arguments: Vec<ConstantValue>,
 }
 
@@ -327,6 +680,30 @@ pub enum MethodHandle {
     RefInvokeInterface(MethodRef),
 }
 
+impl std::fmt::Display for MethodHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let field = |it: &FieldRef| {
+            let owner = it.owner.dotted_name();
+            format!("{owner}.{}:{}", it.name, it.field_type.qualified_name())
+        };
+        let method = |it: &MethodRef| {
+            let owner = it.owner.dotted_name();
+            format!("{owner}.{}:{}", it.name, it.descriptor.qualified_name())
+        };
+        match self {
+            Self::RefGetField(it) => write!(f, "REF_getField {}", field(it)),
+            Self::RefGetStatic(it) => write!(f, "REF_getStatic {}", field(it)),
+            Self::RefPutField(it) => write!(f, "REF_putField {}", field(it)),
+            Self::RefPutStatic(it) => write!(f, "REF_putStatic {}", field(it)),
+            Self::RefInvokeVirtual(it) => write!(f, "REF_invokeVirtual {}", method(it)),
+            Self::RefInvokeStatic(it) => write!(f, "REF_invokeStatic {}", method(it)),
+            Self::RefInvokeSpecial(it) => write!(f, "REF_invokeSpecial {}", method(it)),
+            Self::RefNewInvokeSpecial(it) => write!(f, "REF_newInvokeSpecial {}", method(it)),
+            Self::RefInvokeInterface(it) => write!(f, "REF_invokeInterface {}", method(it)),
+        }
+    }
+}
+
 /// The record components of a [`Class`] that represents a `record`.
 #[derive(Debug, Clone)]
 pub struct RecordComponent {
@@ -346,6 +723,11 @@ pub struct RecordComponent {
     pub runtime_invisible_type_annotations: Vec<super::TypeAnnotation>,
     /// Unrecognized JVM attributes.
     pub free_attributes: Vec<(String, Vec<u8>)>,
+    /// The names and the original bytes of all the attributes, including the recognized ones, in
+    /// the order in which they appear in the class file. The bytes refer to the constant pool of
+    /// the class file.
+    /// Only populated when parsing with [`ParseOptions::retain_raw_attributes`](crate::jvm::parsing::ParseOptions::retain_raw_attributes).
+    pub raw_attributes: Vec<(String, Vec<u8>)>,
 }
 
 bitflags! {
@@ -413,6 +795,123 @@ mod tests {
     use proptest::prelude::*;
 
     use super::*;
+    use crate::jvm::{
+        annotation::{TargetInfo, TargetKind},
+        code::{InstructionList, MethodBody},
+        method, TypeAnnotation,
+    };
+
+    #[test]
+    fn source_path() {
+        let mut class = Class {
+            binary_name: "com/example/Foo$Inner".to_owned(),
+            ..Default::default()
+        };
+        assert_eq!(None, class.source_path());
+        class.source_file = Some("Foo.java".to_owned());
+        assert_eq!(Some("Foo.java"), class.source_file());
+        assert_eq!(Some("com/example/Foo.java".to_owned()), class.source_path());
+        class.binary_name = "Foo".to_owned();
+        assert_eq!(Some("Foo.java".to_owned()), class.source_path());
+    }
+
+    #[test]
+    fn is_instance_of() {
+        let class = |name: &str, super_class: &str, interfaces: &[&str]| Class {
+            binary_name: name.to_owned(),
+            super_class: Some(ClassRef::new(super_class)),
+            interfaces: interfaces.iter().copied().map(ClassRef::new).collect(),
+            ..Default::default()
+        };
+        let hierarchy = [
+            class("java/io/IOException", "java/lang/Exception", &[]),
+            class("java/lang/Exception", "java/lang/Throwable", &[]),
+            class(
+                "java/lang/Throwable",
+                "java/lang/Object",
+                &["java/io/Serializable"],
+            ),
+        ];
+        let resolve = |name: &str| hierarchy.iter().find(|it| it.binary_name == name).cloned();
+        let exception = class("org/mokapot/MyException", "java/io/IOException", &[]);
+        assert!(exception.is_instance_of("java/lang/Throwable", resolve));
+        assert!(exception.is_instance_of("java/io/Serializable", resolve));
+        assert!(exception.is_instance_of("org/mokapot/MyException", |_| None));
+        assert!(!exception.is_instance_of("java/lang/Throwable", |_| None));
+
+        let mut resolved = Vec::new();
+        let is_runnable = exception.is_instance_of("java/lang/Runnable", |name| {
+            resolved.push(name.to_owned());
+            resolve(name)
+        });
+        assert!(!is_runnable);
+        resolved.sort();
+        assert_eq!(
+            vec![
+                "java/io/IOException",
+                "java/io/Serializable",
+                "java/lang/Exception",
+                "java/lang/Object",
+                "java/lang/Throwable",
+            ],
+            resolved
+        );
+    }
+
+    #[test]
+    fn methods_invoking() {
+        let println = |descriptor: &str| MethodRef {
+            owner: ClassRef::new("java/io/PrintStream"),
+            name: "println".to_owned(),
+            descriptor: descriptor.parse().unwrap(),
+        };
+        let method = |name: &str, instructions| Method {
+            access_flags: method::AccessFlags::STATIC,
+            name: name.to_owned(),
+            descriptor: "()V".parse().unwrap(),
+            body: Some(MethodBody {
+                instructions,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let class = Class {
+            methods: vec![
+                method(
+                    "printInt",
+                    InstructionList::from([
+                        (0.into(), Instruction::InvokeVirtual(println("(I)V"))),
+                        (3.into(), Instruction::Return),
+                    ]),
+                ),
+                method(
+                    "printString",
+                    InstructionList::from([
+                        (
+                            0.into(),
+                            Instruction::InvokeVirtual(println("(Ljava/lang/String;)V")),
+                        ),
+                        (3.into(), Instruction::Return),
+                    ]),
+                ),
+                method(
+                    "nothing",
+                    InstructionList::from([(0.into(), Instruction::Return)]),
+                ),
+            ],
+            ..Default::default()
+        };
+        let names = |target| {
+            class
+                .methods_invoking(&target)
+                .into_iter()
+                .map(|it| it.name.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["printInt"], names(println("(I)V")));
+        assert_eq!(vec!["printString"], names(println("(Ljava/lang/String;)V")));
+        assert!(names(println("()V")).is_empty());
+    }
 
     proptest! {
 
@@ -521,4 +1020,135 @@ mod tests {
         };
         assert!(!class.is_interface());
     }
+
+    #[test]
+    fn version_features() {
+        let class = Class {
+            version: Version::Jdk11,
+            record: Some(vec![]),
+            ..Default::default()
+        };
+        let err = class.verify_version_features().unwrap_err();
+        assert_eq!(err.feature, VersionedFeature::Record);
+
+        let class = Class {
+            version: Version::Jdk16(false),
+            record: Some(vec![]),
+            ..Default::default()
+        };
+        assert!(class.verify_version_features().is_ok());
+
+        let class = Class {
+            version: Version::Jdk16(false),
+            permitted_subclasses: vec![ClassRef::new("Sub")],
+            ..Default::default()
+        };
+        let err = class.verify_version_features().unwrap_err();
+        assert_eq!(err.feature, VersionedFeature::PermittedSubclasses);
+    }
+
+    #[test]
+    fn version_features_fields() {
+        let field = Field {
+            access_flags: field::AccessFlags::PRIVATE,
+            name: "values".to_owned(),
+            owner: ClassRef::new(crate::test_support::TEST_CLASS),
+            field_type: "Ljava/util/List;".parse().unwrap(),
+            constant_value: None,
+            is_synthetic: false,
+            is_deperecated: false,
+            signature: Some("Ljava/util/List<Ljava/lang/String;>;".to_owned()),
+            runtime_visible_annotations: Vec::new(),
+            runtime_invisible_annotations: Vec::new(),
+            runtime_visible_type_annotations: Vec::new(),
+            runtime_invisible_type_annotations: Vec::new(),
+            free_attributes: Vec::new(),
+            raw_attributes: Vec::new(),
+        };
+        let class = Class {
+            version: Version::Jdk1_4,
+            fields: vec![field.clone()],
+            ..Default::default()
+        };
+        let err = class.verify_version_features().unwrap_err();
+        assert_eq!(err.feature, VersionedFeature::Signature);
+
+        let annotated = Field {
+            signature: None,
+            runtime_invisible_type_annotations: vec![TypeAnnotation {
+                annotation_type: "LNonNull;".parse().unwrap(),
+                target_info: TargetInfo::Empty,
+                target_kind: TargetKind::Field,
+                target_path: Vec::new(),
+                element_value_pairs: Vec::new(),
+            }],
+            ..field
+        };
+        let class = Class {
+            version: Version::Jdk7,
+            fields: vec![annotated],
+            ..Default::default()
+        };
+        let err = class.verify_version_features().unwrap_err();
+        assert_eq!(err.feature, VersionedFeature::TypeAnnotations);
+    }
+
+    #[test]
+    fn version_features_methods() {
+        let mut method = crate::test_support::build_method(&[Instruction::Return]);
+        method.runtime_visible_annotations.push(Annotation {
+            annotation_type: "Ljava/lang/Deprecated;".parse().unwrap(),
+            element_value_pairs: Vec::new(),
+        });
+        let class = Class {
+            version: Version::Jdk1_4,
+            methods: vec![method.clone()],
+            ..Default::default()
+        };
+        let err = class.verify_version_features().unwrap_err();
+        assert_eq!(err.feature, VersionedFeature::Annotations);
+
+        method.runtime_visible_annotations.clear();
+        method
+            .body
+            .as_mut()
+            .unwrap()
+            .runtime_visible_type_annotations
+            .push(TypeAnnotation {
+                annotation_type: "LNonNull;".parse().unwrap(),
+                target_info: TargetInfo::Offset(0),
+                target_kind: TargetKind::New,
+                target_path: Vec::new(),
+                element_value_pairs: Vec::new(),
+            });
+        let class = Class {
+            version: Version::Jdk7,
+            methods: vec![method],
+            ..Default::default()
+        };
+        let err = class.verify_version_features().unwrap_err();
+        assert_eq!(err.feature, VersionedFeature::TypeAnnotations);
+    }
+
+    #[test]
+    fn version_features_bootstrap_arguments() {
+        let class = Class {
+            version: Version::Jdk8,
+            bootstrap_methods: vec![BootstrapMethod {
+                method: MethodHandle::RefInvokeStatic(MethodRef {
+                    owner: ClassRef::new("Bootstrap"),
+                    name: "bsm".to_owned(),
+                    descriptor: "()V".parse().unwrap(),
+                }),
+                arguments: vec![ConstantValue::Dynamic(
+                    0,
+                    "x".to_owned(),
+                    FieldType::Base(crate::types::field_type::PrimitiveType::Int),
+                )],
+            }],
+            ..Default::default()
+        };
+        let err = class.verify_version_features().unwrap_err();
+        assert_eq!(err.feature, VersionedFeature::DynamicConstant);
+    }
 }
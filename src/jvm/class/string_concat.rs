@@ -0,0 +1,158 @@
+//! Decoding string concatenations compiled to `invokedynamic`.
+
+use crate::jvm::{code::Instruction, Class, ConstantValue, JavaString};
+
+use super::{BootstrapMethod, MethodHandle};
+
+/// The binary name of `java.lang.invoke.StringConcatFactory`.
+const STRING_CONCAT_FACTORY: &str = "java/lang/invoke/StringConcatFactory";
+
+/// The tag in a recipe denoting an argument passed to the call site.
+const ARGUMENT_TAG: char = '\u{1}';
+
+/// The tag in a recipe denoting a constant passed to the bootstrap method.
+const CONSTANT_TAG: char = '\u{2}';
+
+/// A segment of a string concatenation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConcatSegment {
+    /// A literal string embedded in the recipe.
+    Literal(String),
+    /// An argument of the call site, identified by its index in the descriptor.
+    Argument(usize),
+    /// A constant passed to the bootstrap method.
+    Constant(ConstantValue),
+}
+
+impl BootstrapMethod {
+    /// Decodes the segments of a string concatenation if this bootstrap method is
+    /// `StringConcatFactory::makeConcat` or `StringConcatFactory::makeConcatWithConstants`.
+    /// - `argument_count` is the number of parameters in the descriptor of the call site.
+    ///
+    /// Returns [`None`] if this is not a string concatenation bootstrap method, or if the recipe
+    /// does not match the arguments.
+    #[must_use]
+    pub fn string_concat_segments(&self, argument_count: usize) -> Option<Vec<ConcatSegment>> {
+        let MethodHandle::RefInvokeStatic(method) = &self.method else {
+            return None;
+        };
+        if method.owner.binary_name != STRING_CONCAT_FACTORY {
+            return None;
+        }
+        match method.name.as_str() {
+            "makeConcat" => Some((0..argument_count).map(ConcatSegment::Argument).collect()),
+            "makeConcatWithConstants" => {
+                let (ConstantValue::String(JavaString::Utf8(recipe)), constants) =
+                    self.arguments.split_first()?
+                else {
+                    return None;
+                };
+                decode_recipe(recipe, constants, argument_count)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn decode_recipe(
+    recipe: &str,
+    constants: &[ConstantValue],
+    argument_count: usize,
+) -> Option<Vec<ConcatSegment>> {
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut arguments = 0..argument_count;
+    let mut constants = constants.iter();
+    for c in recipe.chars() {
+        let segment = match c {
+            ARGUMENT_TAG => ConcatSegment::Argument(arguments.next()?),
+            CONSTANT_TAG => ConcatSegment::Constant(constants.next()?.clone()),
+            _ => {
+                literal.push(c);
+                continue;
+            }
+        };
+        if !literal.is_empty() {
+            segments.push(ConcatSegment::Literal(std::mem::take(&mut literal)));
+        }
+        segments.push(segment);
+    }
+    if !literal.is_empty() {
+        segments.push(ConcatSegment::Literal(literal));
+    }
+    // All arguments must be consumed by the recipe.
+    arguments.next().is_none().then_some(segments)
+}
+
+impl Class {
+    /// Decodes the segments of the string concatenation performed by the given
+    /// [`Instruction::InvokeDynamic`].
+    /// See [`BootstrapMethod::string_concat_segments`] for more information.
+    #[must_use]
+    pub fn string_concat_segments(&self, instruction: &Instruction) -> Option<Vec<ConcatSegment>> {
+        let Instruction::InvokeDynamic {
+            bootstrap_method_index,
+            descriptor,
+            ..
+        } = instruction
+        else {
+            return None;
+        };
+        self.bootstrap_methods
+            .get(usize::from(*bootstrap_method_index))?
+            .string_concat_segments(descriptor.parameters_types.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jvm::references::{ClassRef, MethodRef};
+
+    fn bootstrap_method(name: &str, arguments: Vec<ConstantValue>) -> BootstrapMethod {
+        BootstrapMethod {
+            method: MethodHandle::RefInvokeStatic(MethodRef {
+                owner: ClassRef::new(STRING_CONCAT_FACTORY),
+                name: name.to_owned(),
+                descriptor: "()V".parse().unwrap(),
+            }),
+            arguments,
+        }
+    }
+
+    #[test]
+    fn recipe_with_constants() {
+        let bsm = bootstrap_method(
+            "makeConcatWithConstants",
+            vec![
+                ConstantValue::String(JavaString::Utf8("x = \u{1}, \u{2}!\u{1}".to_owned())),
+                ConstantValue::Integer(42),
+            ],
+        );
+        let segments = bsm.string_concat_segments(2).unwrap();
+        assert_eq!(
+            vec![
+                ConcatSegment::Literal("x = ".to_owned()),
+                ConcatSegment::Argument(0),
+                ConcatSegment::Literal(", ".to_owned()),
+                ConcatSegment::Constant(ConstantValue::Integer(42)),
+                ConcatSegment::Literal("!".to_owned()),
+                ConcatSegment::Argument(1),
+            ],
+            segments
+        );
+        assert!(bsm.string_concat_segments(3).is_none());
+        assert!(bsm.string_concat_segments(1).is_none());
+    }
+
+    #[test]
+    fn make_concat() {
+        let bsm = bootstrap_method("makeConcat", vec![]);
+        assert_eq!(
+            Some(vec![ConcatSegment::Argument(0), ConcatSegment::Argument(1)]),
+            bsm.string_concat_segments(2)
+        );
+        let bsm = bootstrap_method("metafactory", vec![]);
+        assert!(bsm.string_concat_segments(0).is_none());
+    }
+}
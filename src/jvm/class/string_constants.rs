@@ -0,0 +1,163 @@
+//! Extraction of the string constants referenced by a class.
+
+use std::collections::BTreeSet;
+
+use crate::jvm::{
+    annotation::ElementValue, code::Instruction, Annotation, Class, ConstantValue, JavaString,
+    TypeAnnotation,
+};
+
+impl Class {
+    /// Returns the string constants in the class, in the order they appear.
+    /// This includes the strings loaded by `ldc` instructions, the constant values of fields, the
+    /// static arguments of bootstrap methods, and the string elements of annotations.
+    /// If `dedup` is `true`, only the first occurrence of each string is returned.
+    pub fn string_constants(&self, dedup: bool) -> impl Iterator<Item = &JavaString> {
+        let mut strings = Strings::default();
+        strings.annotations(&self.runtime_visible_annotations);
+        strings.annotations(&self.runtime_invisible_annotations);
+        strings.type_annotations(&self.runtime_visible_type_annotations);
+        strings.type_annotations(&self.runtime_invisible_type_annotations);
+        for field in &self.fields {
+            if let Some(value) = field.constant_value.as_ref() {
+                strings.constant(value);
+            }
+            strings.annotations(&field.runtime_visible_annotations);
+            strings.annotations(&field.runtime_invisible_annotations);
+            strings.type_annotations(&field.runtime_visible_type_annotations);
+            strings.type_annotations(&field.runtime_invisible_type_annotations);
+        }
+        for method in &self.methods {
+            strings.annotations(&method.runtime_visible_annotations);
+            strings.annotations(&method.runtime_invisible_annotations);
+            strings.type_annotations(&method.runtime_visible_type_annotations);
+            strings.type_annotations(&method.runtime_invisible_type_annotations);
+            for annotations in method
+                .runtime_visible_parameter_annotations
+                .iter()
+                .chain(&method.runtime_invisible_parameter_annotations)
+            {
+                strings.annotations(annotations);
+            }
+            if let Some(value) = method.annotation_default.as_ref() {
+                strings.element_value(value);
+            }
+            let Some(body) = method.body.as_ref() else {
+                continue;
+            };
+            for (_, instruction) in &body.instructions {
+                if let Instruction::Ldc(value) | Instruction::LdcW(value) = instruction {
+                    strings.constant(value);
+                }
+            }
+            strings.type_annotations(&body.runtime_visible_type_annotations);
+            strings.type_annotations(&body.runtime_invisible_type_annotations);
+        }
+        for component in self.record.iter().flatten() {
+            strings.annotations(&component.runtime_visible_annotations);
+            strings.annotations(&component.runtime_invisible_annotations);
+            strings.type_annotations(&component.runtime_visible_type_annotations);
+            strings.type_annotations(&component.runtime_invisible_type_annotations);
+        }
+        for bootstrap_method in &self.bootstrap_methods {
+            for argument in &bootstrap_method.arguments {
+                strings.constant(argument);
+            }
+        }
+
+        let mut strings = strings.0;
+        if dedup {
+            let mut seen = BTreeSet::new();
+            strings.retain(|it| seen.insert(*it));
+        }
+        strings.into_iter()
+    }
+}
+
+#[derive(Default)]
+struct Strings<'c>(Vec<&'c JavaString>);
+
+impl<'c> Strings<'c> {
+    fn constant(&mut self, value: &'c ConstantValue) {
+        if let ConstantValue::String(string) = value {
+            self.0.push(string);
+        }
+    }
+
+    fn element_value(&mut self, value: &'c ElementValue) {
+        match value {
+            ElementValue::String(value) => self.constant(value),
+            ElementValue::AnnotationInterface(annotation) => {
+                self.annotations(std::slice::from_ref(annotation));
+            }
+            ElementValue::Array(values) => values.iter().for_each(|it| self.element_value(it)),
+            ElementValue::Primitive(..)
+            | ElementValue::EnumConstant { .. }
+            | ElementValue::Class { .. } => {}
+        }
+    }
+
+    fn annotations(&mut self, annotations: &'c [Annotation]) {
+        for (_, value) in annotations.iter().flat_map(|it| &it.element_value_pairs) {
+            self.element_value(value);
+        }
+    }
+
+    fn type_annotations(&mut self, annotations: &'c [TypeAnnotation]) {
+        for (_, value) in annotations.iter().flat_map(|it| &it.element_value_pairs) {
+            self.element_value(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        jvm::{
+            class::{BootstrapMethod, MethodHandle},
+            references::{ClassRef, MethodRef},
+        },
+        types::field_type::FieldType,
+    };
+
+    fn string(value: &str) -> ConstantValue {
+        ConstantValue::String(JavaString::Utf8(value.to_owned()))
+    }
+
+    #[test]
+    fn string_constants() {
+        let annotation = Annotation {
+            annotation_type: FieldType::Object(ClassRef::new("org/mokapot/Anno")),
+            element_value_pairs: vec![(
+                "value".to_owned(),
+                ElementValue::Array(vec![
+                    ElementValue::String(string("license")),
+                    ElementValue::String(string("key")),
+                ]),
+            )],
+        };
+        let class = Class {
+            runtime_visible_annotations: vec![annotation],
+            bootstrap_methods: vec![BootstrapMethod {
+                method: MethodHandle::RefInvokeStatic(MethodRef {
+                    owner: ClassRef::new("java/lang/invoke/StringConcatFactory"),
+                    name: "makeConcatWithConstants".to_owned(),
+                    descriptor: "()Ljava/lang/invoke/CallSite;".parse().unwrap(),
+                }),
+                arguments: vec![string("key"), ConstantValue::Integer(1)],
+            }],
+            ..Default::default()
+        };
+        let all: Vec<_> = class.string_constants(false).collect();
+        assert_eq!(3, all.len());
+        let unique: Vec<_> = class.string_constants(true).collect();
+        assert_eq!(
+            vec![
+                &JavaString::Utf8("license".to_owned()),
+                &JavaString::Utf8("key".to_owned())
+            ],
+            unique
+        );
+    }
+}
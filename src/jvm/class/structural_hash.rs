@@ -0,0 +1,582 @@
+//! A fingerprint of the structure of a class.
+
+use std::collections::BTreeMap;
+
+use crate::{
+    jvm::{
+        class::BootstrapMethod,
+        code::{Instruction, MethodBody, ProgramCounter, WideInstruction},
+        references::{ClassRef, FieldRef, MethodRef},
+        Class, ConstantValue, Field, JavaString, Method, MethodHandle,
+    },
+    macros::see_jvm_spec,
+    types::{
+        field_type::FieldType,
+        method_descriptor::{MethodDescriptor, ReturnType},
+    },
+};
+
+impl Class {
+    /// Computes a hash of the declarations and the code in the class.
+    /// The hash covers the name, access flags, and supertypes of the class, the signatures and
+    /// constant values of the fields, and the signatures, thrown exceptions, instructions, and
+    /// exception tables of the methods. The bootstrap methods are covered through the
+    /// `invokedynamic` instructions and the dynamic constants using them.
+    ///
+    /// The hash does not depend on the layout of the constant pool, the order in which the
+    /// members and the bootstrap methods are declared, or the debug information (e.g., line
+    /// numbers, local variable names, and the source file). Jump targets are hashed by the
+    /// position of the instructions they refer to rather than their program counters, so
+    /// instructions whose encoding depends on the constant pool (e.g., `ldc` and `ldc_w`) do not
+    /// affect it.
+    ///
+    /// The value is the FNV-1a hash of a fixed encoding of the structure of the class, where
+    /// strings are written as their UTF-8 bytes and numbers as little-endian integers, so it
+    /// remains the same across runs, platforms, and Rust versions.
+    #[must_use]
+    pub fn structural_hash(&self) -> u64 {
+        let mut hasher = StructuralHasher {
+            hash: 0xcbf2_9ce4_8422_2325,
+            bootstrap_methods: &self.bootstrap_methods,
+            resolving: Vec::new(),
+        };
+        hasher.write_str(&self.binary_name);
+        hasher.write_u64(u64::from(self.access_flags.bits()));
+        hasher.write_class(self.super_class.as_ref());
+        hasher.write_len(self.interfaces.len());
+        for interface in &self.interfaces {
+            hasher.write_str(&interface.binary_name);
+        }
+        let mut fields: Vec<_> = self.fields.iter().collect();
+        fields.sort_by(|lhs, rhs| (&lhs.name, &lhs.field_type).cmp(&(&rhs.name, &rhs.field_type)));
+        hasher.write_len(fields.len());
+        for field in fields {
+            hasher.write_field(field);
+        }
+        let mut methods: Vec<_> = self.methods.iter().collect();
+        methods.sort_by(|lhs, rhs| (&lhs.name, &lhs.descriptor).cmp(&(&rhs.name, &rhs.descriptor)));
+        hasher.write_len(methods.len());
+        for method in methods {
+            hasher.write_method(method);
+        }
+        hasher.hash
+    }
+}
+
+/// Feeds the structure of the elements of a class to the 64-bit FNV-1a hash function.
+struct StructuralHasher<'a> {
+    hash: u64,
+    /// The bootstrap methods of the class, which are resolved from the indices referring to them.
+    bootstrap_methods: &'a [BootstrapMethod],
+    /// The indices of the bootstrap methods being written, to break cycles between the dynamic
+    /// constants passed to them.
+    resolving: Vec<u16>,
+}
+
+impl StructuralHasher<'_> {
+    fn write_field(&mut self, field: &Field) {
+        self.write_str(&field.name);
+        self.write_field_type(&field.field_type);
+        self.write_u64(u64::from(field.access_flags.bits()));
+        match field.constant_value.as_ref() {
+            None => self.write_u8(0),
+            Some(value) => {
+                self.write_u8(1);
+                self.write_constant(value);
+            }
+        }
+    }
+
+    fn write_method(&mut self, method: &Method) {
+        self.write_str(&method.name);
+        self.write_method_descriptor(&method.descriptor);
+        self.write_u64(u64::from(method.access_flags.bits()));
+        self.write_len(method.exceptions.len());
+        for exception in &method.exceptions {
+            self.write_str(&exception.binary_name);
+        }
+        self.write_u64(u64::from(method.body.is_some()));
+        if let Some(body) = method.body.as_ref() {
+            self.write_body(body);
+        }
+    }
+
+    fn write_body(&mut self, body: &MethodBody) {
+        // The positions of the instructions, with the end of the code after the last one.
+        let positions: BTreeMap<_, _> = body
+            .instructions
+            .iter()
+            .map(|(pc, _)| *pc)
+            .chain(
+                body.code_size()
+                    .ok()
+                    .and_then(|it| u16::try_from(it).ok())
+                    .map(ProgramCounter::from),
+            )
+            .enumerate()
+            .map(|(index, pc)| (pc, index))
+            .collect();
+        let position = |pc: ProgramCounter| {
+            positions
+                .get(&pc)
+                .and_then(|it| u64::try_from(*it).ok())
+                .unwrap_or(u64::MAX)
+        };
+        self.write_len(body.instructions.len());
+        for (_, instruction) in &body.instructions {
+            self.write_instruction(instruction, position);
+        }
+        self.write_len(body.exception_table.len());
+        for entry in &body.exception_table {
+            self.write_u64(position(entry.covered_pc.start));
+            self.write_u64(position(entry.covered_pc.end));
+            self.write_u64(position(entry.handler_pc));
+            self.write_class(entry.catch_type.as_ref());
+        }
+    }
+
+    fn write_instruction(
+        &mut self,
+        instruction: &Instruction,
+        position: impl Fn(ProgramCounter) -> u64,
+    ) {
+        #[allow(clippy::enum_glob_use)]
+        use Instruction::*;
+        let instruction = match instruction {
+            LdcW(value) => &Ldc(value.clone()),
+            GotoW(target) => &Goto(*target),
+            JsrW(target) => &Jsr(*target),
+            other => other,
+        };
+        self.write_u8(instruction.opcode());
+        match instruction {
+            BiPush(value) | ILoad(value) | LLoad(value) | FLoad(value) | DLoad(value)
+            | ALoad(value) | IStore(value) | LStore(value) | FStore(value) | DStore(value)
+            | AStore(value) | Ret(value) => self.write_u8(*value),
+            SiPush(value) => self.write_u64(u64::from(*value)),
+            Ldc(value) | Ldc2W(value) => self.write_constant(value),
+            IInc(index, value) => {
+                self.write_u8(*index);
+                self.write_i32(*value);
+            }
+            IfEq(target) | IfNe(target) | IfLt(target) | IfGe(target) | IfGt(target)
+            | IfLe(target) | IfICmpEq(target) | IfICmpNe(target) | IfICmpLt(target)
+            | IfICmpGe(target) | IfICmpGt(target) | IfICmpLe(target) | IfACmpEq(target)
+            | IfACmpNe(target) | Goto(target) | Jsr(target) | IfNull(target)
+            | IfNonNull(target) => self.write_u64(position(*target)),
+            TableSwitch {
+                range,
+                jump_targets,
+                default,
+            } => {
+                self.write_i32(*range.start());
+                self.write_i32(*range.end());
+                self.write_len(jump_targets.len());
+                for target in jump_targets {
+                    self.write_u64(position(*target));
+                }
+                self.write_u64(position(*default));
+            }
+            LookupSwitch {
+                default,
+                match_targets,
+            } => {
+                self.write_u64(position(*default));
+                self.write_len(match_targets.len());
+                for (key, target) in match_targets {
+                    self.write_i32(*key);
+                    self.write_u64(position(*target));
+                }
+            }
+            GetStatic(field) | PutStatic(field) | GetField(field) | PutField(field) => {
+                self.write_field_ref(field);
+            }
+            InvokeVirtual(method) | InvokeSpecial(method) | InvokeStatic(method) => {
+                self.write_method_ref(method);
+            }
+            InvokeInterface(method, count) => {
+                self.write_method_ref(method);
+                self.write_u8(*count);
+            }
+            InvokeDynamic {
+                bootstrap_method_index,
+                name,
+                descriptor,
+            } => {
+                self.write_bootstrap_method(*bootstrap_method_index);
+                self.write_str(name);
+                self.write_method_descriptor(descriptor);
+            }
+            New(class) => self.write_str(&class.binary_name),
+            NewArray(element) => self.write_field_type(&FieldType::Base(*element)),
+            ANewArray(field_type) | CheckCast(field_type) | InstanceOf(field_type) => {
+                self.write_field_type(field_type);
+            }
+            MultiANewArray(field_type, dimensions) => {
+                self.write_field_type(field_type);
+                self.write_u8(*dimensions);
+            }
+            Wide(wide) => self.write_wide(wide),
+            _ => {}
+        }
+    }
+
+    fn write_wide(&mut self, wide: &WideInstruction) {
+        let (tag, index) = match wide {
+            WideInstruction::ILoad(index) => (0, index),
+            WideInstruction::LLoad(index) => (1, index),
+            WideInstruction::FLoad(index) => (2, index),
+            WideInstruction::DLoad(index) => (3, index),
+            WideInstruction::ALoad(index) => (4, index),
+            WideInstruction::IStore(index) => (5, index),
+            WideInstruction::LStore(index) => (6, index),
+            WideInstruction::FStore(index) => (7, index),
+            WideInstruction::DStore(index) => (8, index),
+            WideInstruction::AStore(index) => (9, index),
+            WideInstruction::IInc(index, _) => (10, index),
+            WideInstruction::Ret(index) => (11, index),
+        };
+        self.write_u8(tag);
+        self.write_u64(u64::from(*index));
+        if let WideInstruction::IInc(_, value) = wide {
+            self.write_i32(*value);
+        }
+    }
+
+    /// Writes the constant tagged as in the constant pool.
+    #[doc = see_jvm_spec!(4, 4)]
+    fn write_constant(&mut self, value: &ConstantValue) {
+        match value {
+            ConstantValue::Null => self.write_u8(0),
+            ConstantValue::Integer(value) => {
+                self.write_u8(3);
+                self.write_i32(*value);
+            }
+            ConstantValue::Float(value) => {
+                self.write_u8(4);
+                self.write_u64(u64::from(value.to_bits()));
+            }
+            ConstantValue::Long(value) => {
+                self.write_u8(5);
+                self.write(&value.to_le_bytes());
+            }
+            ConstantValue::Double(value) => {
+                self.write_u8(6);
+                self.write_u64(value.to_bits());
+            }
+            ConstantValue::Class(class) => {
+                self.write_u8(7);
+                self.write_str(&class.binary_name);
+            }
+            ConstantValue::String(JavaString::Utf8(string)) => {
+                self.write_u8(8);
+                self.write_str(string);
+            }
+            ConstantValue::String(JavaString::InvalidUtf8(bytes)) => {
+                // The bytes are never those of a valid string, so the two cannot be confused.
+                self.write_u8(8);
+                self.write_len(bytes.len());
+                self.write(bytes);
+            }
+            ConstantValue::Handle(handle) => {
+                self.write_u8(15);
+                self.write_method_handle(handle);
+            }
+            ConstantValue::MethodType(descriptor) => {
+                self.write_u8(16);
+                self.write_method_descriptor(descriptor);
+            }
+            ConstantValue::Dynamic(bootstrap_method_index, name, field_type) => {
+                self.write_u8(17);
+                self.write_bootstrap_method(*bootstrap_method_index);
+                self.write_str(name);
+                self.write_field_type(field_type);
+            }
+        }
+    }
+
+    /// Writes the bootstrap method at `index` in the bootstrap method table rather than the
+    /// index, which depends on the order of the table.
+    fn write_bootstrap_method(&mut self, index: u16) {
+        let bootstrap_method = self.bootstrap_methods.get(usize::from(index));
+        let Some(bootstrap_method) = bootstrap_method.filter(|_| !self.resolving.contains(&index))
+        else {
+            // A missing or recursive bootstrap method, which is illegal, is written by its index.
+            self.write_u8(0);
+            self.write_u64(u64::from(index));
+            return;
+        };
+        self.resolving.push(index);
+        self.write_u8(1);
+        self.write_method_handle(&bootstrap_method.method);
+        self.write_len(bootstrap_method.arguments.len());
+        for argument in &bootstrap_method.arguments {
+            self.write_constant(argument);
+        }
+        self.resolving.pop();
+    }
+
+    /// Writes the method handle tagged with its reference kind.
+    #[doc = see_jvm_spec!(4, 4, 8)]
+    fn write_method_handle(&mut self, handle: &MethodHandle) {
+        match handle {
+            MethodHandle::RefGetField(field) => {
+                self.write_u8(1);
+                self.write_field_ref(field);
+            }
+            MethodHandle::RefGetStatic(field) => {
+                self.write_u8(2);
+                self.write_field_ref(field);
+            }
+            MethodHandle::RefPutField(field) => {
+                self.write_u8(3);
+                self.write_field_ref(field);
+            }
+            MethodHandle::RefPutStatic(field) => {
+                self.write_u8(4);
+                self.write_field_ref(field);
+            }
+            MethodHandle::RefInvokeVirtual(method) => {
+                self.write_u8(5);
+                self.write_method_ref(method);
+            }
+            MethodHandle::RefInvokeStatic(method) => {
+                self.write_u8(6);
+                self.write_method_ref(method);
+            }
+            MethodHandle::RefInvokeSpecial(method) => {
+                self.write_u8(7);
+                self.write_method_ref(method);
+            }
+            MethodHandle::RefNewInvokeSpecial(method) => {
+                self.write_u8(8);
+                self.write_method_ref(method);
+            }
+            MethodHandle::RefInvokeInterface(method) => {
+                self.write_u8(9);
+                self.write_method_ref(method);
+            }
+        }
+    }
+
+    fn write_field_ref(&mut self, field: &FieldRef) {
+        self.write_str(&field.owner.binary_name);
+        self.write_str(&field.name);
+        self.write_field_type(&field.field_type);
+    }
+
+    fn write_method_ref(&mut self, method: &MethodRef) {
+        self.write_str(&method.owner.binary_name);
+        self.write_str(&method.name);
+        self.write_method_descriptor(&method.descriptor);
+    }
+
+    fn write_method_descriptor(&mut self, descriptor: &MethodDescriptor) {
+        self.write_len(descriptor.parameters_types.len());
+        for parameter in &descriptor.parameters_types {
+            self.write_field_type(parameter);
+        }
+        match &descriptor.return_type {
+            ReturnType::Void => self.write_u8(0),
+            ReturnType::Some(return_type) => {
+                self.write_u8(1);
+                self.write_field_type(return_type);
+            }
+        }
+    }
+
+    fn write_field_type(&mut self, field_type: &FieldType) {
+        match field_type {
+            FieldType::Base(primitive) => {
+                self.write_u8(0);
+                self.write_u64(u64::from(primitive.descriptor()));
+            }
+            FieldType::Object(class) => {
+                self.write_u8(1);
+                self.write_str(&class.binary_name);
+            }
+            FieldType::Array(element) => {
+                self.write_u8(2);
+                self.write_field_type(element);
+            }
+        }
+    }
+
+    /// Feeds `bytes` to the FNV-1a hash function.
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.hash ^= u64::from(*byte);
+            self.hash = self.hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u8(&mut self, value: u8) {
+        self.write(&[value]);
+    }
+
+    /// Writes `value` as 8 little-endian bytes.
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    /// Writes `value` as 4 little-endian bytes.
+    fn write_i32(&mut self, value: i32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_len(&mut self, len: usize) {
+        self.write_u64(u64::try_from(len).unwrap_or(u64::MAX));
+    }
+
+    /// Writes the length of `value` in bytes followed by its UTF-8 bytes, so that adjacent
+    /// strings cannot run into each other.
+    fn write_str(&mut self, value: &str) {
+        self.write_len(value.len());
+        self.write(value.as_bytes());
+    }
+
+    /// Writes a `0` byte for `None`, or a `1` byte followed by the class name.
+    fn write_class(&mut self, class: Option<&ClassRef>) {
+        match class {
+            None => self.write_u8(0),
+            Some(class) => {
+                self.write_u8(1);
+                self.write_str(&class.binary_name);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jvm::code::{InstructionList, LineNumberTableEntry};
+    use crate::jvm::method;
+
+    fn method(body: MethodBody) -> Method {
+        Method {
+            access_flags: method::AccessFlags::PUBLIC | method::AccessFlags::STATIC,
+            name: "run".to_owned(),
+            descriptor: "()V".parse().unwrap(),
+            body: Some(body),
+            ..Default::default()
+        }
+    }
+
+    fn class(instructions: InstructionList<Instruction>) -> Class {
+        Class {
+            binary_name: "org/mokapot/Test".to_owned(),
+            methods: vec![method(MethodBody {
+                instructions,
+                ..Default::default()
+            })],
+            ..Default::default()
+        }
+    }
+
+    fn hello() -> ConstantValue {
+        ConstantValue::String(JavaString::Utf8("hello".to_owned()))
+    }
+
+    #[test]
+    fn independent_of_pool_layout() {
+        let narrow = class(InstructionList::from([
+            (0.into(), Instruction::Ldc(hello())),
+            (2.into(), Instruction::Pop),
+            (3.into(), Instruction::Goto(0.into())),
+        ]));
+        let wide = class(InstructionList::from([
+            (0.into(), Instruction::LdcW(hello())),
+            (3.into(), Instruction::Pop),
+            (4.into(), Instruction::Goto(0.into())),
+        ]));
+        assert_eq!(narrow.structural_hash(), wide.structural_hash());
+    }
+
+    #[test]
+    fn independent_of_debug_info() {
+        let instructions = InstructionList::from([(0.into(), Instruction::Return)]);
+        let mut class = class(instructions);
+        let hash = class.structural_hash();
+        class.source_file = Some("Test.java".to_owned());
+        class.methods[0].body.as_mut().unwrap().line_number_table =
+            Some(vec![LineNumberTableEntry {
+                start_pc: 0.into(),
+                line_number: 42,
+            }]);
+        assert_eq!(hash, class.structural_hash());
+    }
+
+    #[test]
+    fn depends_on_code() {
+        let returns = class(InstructionList::from([(0.into(), Instruction::Return)]));
+        let throws = class(InstructionList::from([
+            (0.into(), Instruction::AConstNull),
+            (1.into(), Instruction::AThrow),
+        ]));
+        assert_ne!(returns.structural_hash(), throws.structural_hash());
+    }
+
+    #[test]
+    fn stable_value() {
+        // Pinned so that a change to the encoding is noticed.
+        let class = class(InstructionList::from([(0.into(), Instruction::Return)]));
+        assert_eq!(0xaefe_0083_94ec_79dd, class.structural_hash());
+    }
+
+    fn bootstrap_method(name: &str) -> BootstrapMethod {
+        BootstrapMethod {
+            method: MethodHandle::RefInvokeStatic(MethodRef {
+                owner: ClassRef::new("org/mokapot/Bootstrap"),
+                name: name.to_owned(),
+                descriptor: "()Ljava/lang/invoke/CallSite;".parse().unwrap(),
+            }),
+            arguments: vec![hello()],
+        }
+    }
+
+    fn invoke_dynamic(bootstrap_method_index: u16) -> Instruction {
+        Instruction::InvokeDynamic {
+            bootstrap_method_index,
+            name: "run".to_owned(),
+            descriptor: "()V".parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn resolves_bootstrap_methods() {
+        let mut first = class(InstructionList::from([
+            (0.into(), invoke_dynamic(0)),
+            (5.into(), Instruction::Return),
+        ]));
+        first.bootstrap_methods = vec![bootstrap_method("a"), bootstrap_method("b")];
+        let mut second = class(InstructionList::from([
+            (0.into(), invoke_dynamic(1)),
+            (5.into(), Instruction::Return),
+        ]));
+        second.bootstrap_methods = vec![bootstrap_method("b"), bootstrap_method("a")];
+        assert_eq!(first.structural_hash(), second.structural_hash());
+
+        second.bootstrap_methods.reverse();
+        assert_ne!(first.structural_hash(), second.structural_hash());
+    }
+
+    #[test]
+    fn recursive_dynamic_constant() {
+        let mut class = class(InstructionList::from([
+            (0.into(), invoke_dynamic(0)),
+            (5.into(), Instruction::Return),
+        ]));
+        let mut bootstrap_method = bootstrap_method("a");
+        bootstrap_method.arguments = vec![ConstantValue::Dynamic(
+            0,
+            "value".to_owned(),
+            FieldType::Object(ClassRef::new("java/lang/Object")),
+        )];
+        class.bootstrap_methods = vec![bootstrap_method];
+        // The constant passed to the bootstrap method refers back to it, which must terminate.
+        let hash = class.structural_hash();
+        class.bootstrap_methods[0].arguments.clear();
+        assert_ne!(hash, class.structural_hash());
+    }
+}
@@ -0,0 +1,324 @@
+//! Compact summaries of classes, omitting method bodies and other implementation details.
+
+use std::fmt;
+
+use crate::jvm::{Annotation, Class, Field, Method};
+
+/// The declarations of a [`Class`], without method bodies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassSummary {
+    /// The binary name of the class.
+    pub name: String,
+    /// The access flags of the class, as stored in the class file.
+    pub access_flags: u16,
+    /// The binary name of the super class, if any.
+    pub super_class: Option<String>,
+    /// The binary names of the implemented interfaces.
+    pub interfaces: Vec<String>,
+    /// The fields declared in the class.
+    pub fields: Vec<MemberSummary>,
+    /// The methods declared in the class.
+    pub methods: Vec<MemberSummary>,
+    /// The descriptors of the annotations on the class.
+    pub annotations: Vec<String>,
+}
+
+/// The declaration of a field or a method.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemberSummary {
+    /// The name of the member.
+    pub name: String,
+    /// The access flags of the member, as stored in the class file.
+    pub access_flags: u16,
+    /// The descriptor of the member.
+    pub descriptor: String,
+    /// The generic signature of the member, if any.
+    pub signature: Option<String>,
+    /// The descriptors of the annotations on the member.
+    pub annotations: Vec<String>,
+}
+
+impl Class {
+    /// Creates a summary of the declarations in the class.
+    #[must_use]
+    pub fn summary(&self) -> ClassSummary {
+        ClassSummary {
+            name: self.binary_name.clone(),
+            access_flags: self.access_flags.bits(),
+            super_class: self.super_class.as_ref().map(|it| it.binary_name.clone()),
+            interfaces: self
+                .interfaces
+                .iter()
+                .map(|it| it.binary_name.clone())
+                .collect(),
+            fields: self.fields.iter().map(MemberSummary::from).collect(),
+            methods: self.methods.iter().map(MemberSummary::from).collect(),
+            annotations: annotation_types(
+                &self.runtime_visible_annotations,
+                &self.runtime_invisible_annotations,
+            ),
+        }
+    }
+}
+
+impl Class {
+    /// Returns a value whose [`Debug`](fmt::Debug) output shows all the contents of the class,
+    /// including the code of every method.
+    /// The [`Debug`](fmt::Debug) output of the class itself only shows its key declarations.
+    #[must_use]
+    pub fn debug_verbose(&self) -> impl fmt::Debug + '_ {
+        VerboseDebug(self)
+    }
+}
+
+impl fmt::Debug for Class {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Class")
+            .field("binary_name", &self.binary_name)
+            .field("version", &self.version)
+            .field("access_flags", &self.access_flags)
+            .field(
+                "super_class",
+                &self.super_class.as_ref().map(|it| it.binary_name.as_str()),
+            )
+            .field(
+                "interfaces",
+                &self
+                    .interfaces
+                    .iter()
+                    .map(|it| it.binary_name.as_str())
+                    .collect::<Vec<_>>(),
+            )
+            .field("fields", &self.fields.len())
+            .field("methods", &self.methods.len())
+            .finish_non_exhaustive()
+    }
+}
+
+struct VerboseDebug<'a>(&'a Class);
+
+impl fmt::Debug for VerboseDebug<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Destructured so that a new field cannot be left out.
+        let Class {
+            version,
+            access_flags,
+            binary_name,
+            super_class,
+            interfaces,
+            fields,
+            methods,
+            source_file,
+            inner_classes,
+            enclosing_method,
+            source_debug_extension,
+            runtime_visible_annotations,
+            runtime_invisible_annotations,
+            runtime_visible_type_annotations,
+            runtime_invisible_type_annotations,
+            bootstrap_methods,
+            module,
+            module_packages,
+            module_main_class,
+            nest_host,
+            nest_members,
+            permitted_subclasses,
+            is_synthetic,
+            is_deprecated,
+            signature,
+            record,
+            free_attributes,
+            raw_attributes,
+        } = self.0;
+        f.debug_struct("Class")
+            .field("version", version)
+            .field("access_flags", access_flags)
+            .field("binary_name", binary_name)
+            .field("super_class", super_class)
+            .field("interfaces", interfaces)
+            .field("fields", fields)
+            .field("methods", methods)
+            .field("source_file", source_file)
+            .field("inner_classes", inner_classes)
+            .field("enclosing_method", enclosing_method)
+            .field("source_debug_extension", source_debug_extension)
+            .field("runtime_visible_annotations", runtime_visible_annotations)
+            .field(
+                "runtime_invisible_annotations",
+                runtime_invisible_annotations,
+            )
+            .field(
+                "runtime_visible_type_annotations",
+                runtime_visible_type_annotations,
+            )
+            .field(
+                "runtime_invisible_type_annotations",
+                runtime_invisible_type_annotations,
+            )
+            .field("bootstrap_methods", bootstrap_methods)
+            .field("module", module)
+            .field("module_packages", module_packages)
+            .field("module_main_class", module_main_class)
+            .field("nest_host", nest_host)
+            .field("nest_members", nest_members)
+            .field("permitted_subclasses", permitted_subclasses)
+            .field("is_synthetic", is_synthetic)
+            .field("is_deprecated", is_deprecated)
+            .field("signature", signature)
+            .field("record", record)
+            .field("free_attributes", free_attributes)
+            .field("raw_attributes", raw_attributes)
+            .finish()
+    }
+}
+
+impl From<&Field> for MemberSummary {
+    fn from(field: &Field) -> Self {
+        Self {
+            name: field.name.clone(),
+            access_flags: field.access_flags.bits(),
+            descriptor: field.field_type.descriptor(),
+            signature: field.signature.clone(),
+            annotations: annotation_types(
+                &field.runtime_visible_annotations,
+                &field.runtime_invisible_annotations,
+            ),
+        }
+    }
+}
+
+impl From<&Method> for MemberSummary {
+    fn from(method: &Method) -> Self {
+        Self {
+            name: method.name.clone(),
+            access_flags: method.access_flags.bits(),
+            descriptor: method.descriptor.to_string(),
+            signature: method.signature.clone(),
+            annotations: annotation_types(
+                &method.runtime_visible_annotations,
+                &method.runtime_invisible_annotations,
+            ),
+        }
+    }
+}
+
+fn annotation_types(visible: &[Annotation], invisible: &[Annotation]) -> Vec<String> {
+    visible
+        .iter()
+        .chain(invisible)
+        .map(|it| it.annotation_type.descriptor())
+        .collect()
+}
+
+#[cfg(feature = "json")]
+impl ClassSummary {
+    /// Converts the summary to a JSON object.
+    #[must_use]
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "access_flags": self.access_flags,
+            "super": self.super_class,
+            "interfaces": self.interfaces,
+            "fields": self.fields.iter().map(MemberSummary::to_json).collect::<Vec<_>>(),
+            "methods": self.methods.iter().map(MemberSummary::to_json).collect::<Vec<_>>(),
+            "annotations": self.annotations,
+        })
+    }
+}
+
+#[cfg(feature = "json")]
+impl MemberSummary {
+    /// Converts the summary to a JSON object.
+    #[must_use]
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "access_flags": self.access_flags,
+            "descriptor": self.descriptor,
+            "signature": self.signature,
+            "annotations": self.annotations,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        jvm::{field, references::ClassRef},
+        types::field_type::{FieldType, PrimitiveType},
+    };
+
+    #[test]
+    fn summary() {
+        let class = Class {
+            binary_name: "org/mokapot/Test".to_owned(),
+            super_class: Some(ClassRef::new("java/lang/Object")),
+            interfaces: vec![ClassRef::new("java/io/Serializable")],
+            fields: vec![Field {
+                access_flags: field::AccessFlags::PRIVATE,
+                name: "count".to_owned(),
+                field_type: FieldType::Base(PrimitiveType::Int),
+                runtime_visible_annotations: vec![Annotation {
+                    annotation_type: FieldType::Object(ClassRef::new("org/mokapot/Anno")),
+                    element_value_pairs: vec![],
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let summary = class.summary();
+        assert_eq!("org/mokapot/Test", summary.name);
+        assert_eq!(0, summary.access_flags);
+        assert_eq!(Some("java/lang/Object"), summary.super_class.as_deref());
+        assert_eq!(vec!["java/io/Serializable"], summary.interfaces);
+        assert_eq!(
+            vec![MemberSummary {
+                name: "count".to_owned(),
+                access_flags: 0x0002,
+                descriptor: "I".to_owned(),
+                signature: None,
+                annotations: vec!["Lorg/mokapot/Anno;".to_owned()],
+            }],
+            summary.fields
+        );
+        assert!(summary.methods.is_empty());
+    }
+
+    #[test]
+    fn debug() {
+        let class = Class {
+            binary_name: "org/mokapot/Test".to_owned(),
+            super_class: Some(ClassRef::new("java/lang/Object")),
+            interfaces: vec![ClassRef::new("java/io/Serializable")],
+            source_file: Some("Test.java".to_owned()),
+            ..Default::default()
+        };
+        let debug = format!("{class:?}");
+        assert!(debug.starts_with(r#"Class { binary_name: "org/mokapot/Test", "#));
+        assert!(debug.contains(r#"super_class: Some("java/lang/Object")"#));
+        assert!(debug.contains(r#"interfaces: ["java/io/Serializable"]"#));
+        assert!(debug.ends_with("fields: 0, methods: 0, .. }"));
+        assert!(!debug.contains("Test.java"));
+
+        let verbose = format!("{:?}", class.debug_verbose());
+        assert!(verbose.contains(r#"source_file: Some("Test.java")"#));
+        assert!(verbose.contains("raw_attributes: []"));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn summary_json() {
+        let class = Class {
+            binary_name: "org/mokapot/Test".to_owned(),
+            access_flags: crate::jvm::class::AccessFlags::PUBLIC,
+            ..Default::default()
+        };
+        let json = class.summary().to_json();
+        assert_eq!("org/mokapot/Test", json["name"]);
+        assert_eq!(0x0001, json["access_flags"]);
+        assert!(json["super"].is_null());
+        assert!(json["methods"].as_array().unwrap().is_empty());
+    }
+}
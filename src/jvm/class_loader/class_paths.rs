@@ -1,7 +1,9 @@
 //! Implementations of [`ClassPath`].
 
-use std::{collections::HashSet, fs::File, io::BufReader};
+use std::{collections::HashSet, fs::File, io::BufReader, path::PathBuf};
 
+#[cfg(feature = "jar")]
+use std::io::Read;
 #[cfg(feature = "jar")]
 use zip::{result::ZipError, ZipArchive};
 
@@ -11,6 +13,20 @@ use crate::{
 };
 
 use super::{ClassPath, Error};
+
+/// Creates a class path for the given directory or JAR file.
+/// Paths with the `jar` extension are searched as [`JarClassPath`] (with the `jar` feature
+/// enabled), and other paths as [`DirectoryClassPath`].
+#[must_use]
+pub fn from_path(path: impl Into<PathBuf>) -> Box<dyn ClassPath + Send + Sync> {
+    let path = path.into();
+    #[cfg(feature = "jar")]
+    if path.extension().is_some_and(|it| it == "jar") {
+        return Box::new(JarClassPath::new(path));
+    }
+    Box::new(DirectoryClassPath::new(path))
+}
+
 /// A class path that searches for classes in a directory.
 #[derive(Debug)]
 pub struct DirectoryClassPath {
@@ -78,15 +94,132 @@ impl JarClassPath {
     }
 }
 
+/// The binary name of a class in a JAR file and the result of parsing it.
 #[cfg(feature = "jar")]
-impl ClassPath for JarClassPath {
-    fn find_class(&self, binary_name: &str) -> Result<Class, Error> {
+pub type JarClass = (String, Result<Class, Error>);
+
+#[cfg(feature = "jar")]
+impl JarClassPath {
+    /// Parses all the classes in the JAR file.
+    /// Each class is read and parsed on its own and returned with its binary name and the result,
+    /// so an entry that cannot be decompressed or parsed does not prevent the other classes from
+    /// being parsed.
+    ///
+    /// # Errors
+    /// See [`Error`]. An error is returned only if the JAR file itself cannot be read.
+    pub fn classes(&self) -> Result<Vec<JarClass>, Error> {
+        let mut jar_archive = self.open()?;
+        let class_entries = class_entries(&jar_archive);
+        Ok(class_entries
+            .into_iter()
+            .map(|(index, binary_name)| (binary_name, read_class(&mut jar_archive, index)))
+            .collect())
+    }
+
+    /// Parses all the classes in the JAR file in parallel.
+    /// The classes are decompressed and parsed on a dedicated thread pool with `num_threads`
+    /// threads, or on as many threads as the available CPUs if `num_threads` is `None`.
+    /// The compressed JAR file is read into memory once and shared by the threads.
+    /// See [`JarClassPath::classes`] for more information.
+    ///
+    /// # Errors
+    /// See [`Error`]. An error is returned only if the JAR file itself cannot be read or the
+    /// thread pool cannot be created.
+    #[cfg(feature = "rayon")]
+    pub fn classes_par(&self, num_threads: Option<usize>) -> Result<Vec<JarClass>, Error> {
+        use rayon::iter::{IntoParallelIterator, ParallelIterator};
+        use std::{io::Cursor, sync::Arc};
+
+        let jar_bytes: Arc<[u8]> = std::fs::read(&self.jar_file)?.into();
+        let jar_archive = ZipArchive::new(Cursor::new(jar_bytes)).map_err(|e| match e {
+            ZipError::Io(io_err) => Error::IO(io_err),
+            e => Error::Other(Box::new(e)),
+        })?;
+        let class_entries = class_entries(&jar_archive);
+        let thread_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads.unwrap_or_default())
+            .build()
+            .map_err(|e| Error::Other(Box::new(e)))?;
+        Ok(thread_pool.install(|| {
+            class_entries
+                .into_par_iter()
+                // Cloning the archive shares the bytes and the parsed central directory.
+                .map_init(
+                    || jar_archive.clone(),
+                    |jar_archive, (index, binary_name)| {
+                        (binary_name, read_class(jar_archive, index))
+                    },
+                )
+                .collect()
+        }))
+    }
+
+    /// Writes the summaries of the classes in the JAR file to `writer` as a JSON array.
+    /// See [`ClassSummary`](crate::jvm::class::summary::ClassSummary) for the information
+    /// included. Classes that cannot be parsed are left out of the array and returned with their
+    /// binary names and the errors, so that the caller can tell whether the export is complete.
+    ///
+    /// # Errors
+    /// See [`Error`]. An error is returned if the JAR file cannot be read or the JSON cannot be
+    /// written.
+    #[cfg(feature = "json")]
+    pub fn export_summary_json(
+        &self,
+        writer: impl std::io::Write,
+    ) -> Result<Vec<(String, Error)>, Error> {
+        let mut summaries = Vec::new();
+        let mut failures = Vec::new();
+        for (binary_name, class) in self.classes()? {
+            match class {
+                Ok(class) => summaries.push(class.summary().to_json()),
+                Err(e) => failures.push((binary_name, e)),
+            }
+        }
+        serde_json::to_writer(writer, &summaries).map_err(|e| Error::IO(e.into()))?;
+        Ok(failures)
+    }
+
+    fn open(&self) -> Result<ZipArchive<BufReader<File>>, Error> {
         let jar_file = File::open(&self.jar_file)?;
         let jar_reader = BufReader::new(jar_file);
-        let mut jar_archive = ZipArchive::new(jar_reader).map_err(|e| match e {
+        ZipArchive::new(jar_reader).map_err(|e| match e {
             ZipError::Io(io_err) => Error::IO(io_err),
             e => Error::Other(Box::new(e)),
-        })?;
+        })
+    }
+}
+
+/// Returns the indices and the binary names of the class files in the JAR file.
+#[cfg(feature = "jar")]
+fn class_entries<R: Read + std::io::Seek>(jar_archive: &ZipArchive<R>) -> Vec<(usize, String)> {
+    (0..jar_archive.len())
+        .filter_map(|index| {
+            let binary_name = jar_archive.name_for_index(index)?.strip_suffix(".class")?;
+            Some((index, binary_name.to_owned()))
+        })
+        .collect()
+}
+
+/// Decompresses and parses the class file at `index` in the JAR file.
+/// Only the bytes of this class file are held in memory.
+#[cfg(feature = "jar")]
+fn read_class<R: Read + std::io::Seek>(
+    jar_archive: &mut ZipArchive<R>,
+    index: usize,
+) -> Result<Class, Error> {
+    let mut entry = jar_archive.by_index(index).map_err(|e| match e {
+        ZipError::Io(io_err) => Error::IO(io_err),
+        e => Error::Other(Box::new(e)),
+    })?;
+    let mut bytes = Vec::new();
+    entry.read_to_end(&mut bytes)?;
+    Class::from_reader(bytes.as_slice()).map_err(Into::into)
+}
+
+#[cfg(feature = "jar")]
+impl ClassPath for JarClassPath {
+    fn find_class(&self, binary_name: &str) -> Result<Class, Error> {
+        let mut jar_archive = self.open()?;
         let mut class_file = jar_archive
             .by_name(&format!("{binary_name}.class"))
             .map_err(|e| match e {
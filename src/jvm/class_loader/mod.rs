@@ -1,6 +1,6 @@
 //! Discovering and loading classes.
 
-use std::{borrow::Borrow, ops::Deref};
+use std::{borrow::Borrow, ops::Deref, path::PathBuf};
 
 use crate::utils::Cache;
 
@@ -43,7 +43,9 @@ where
 }
 
 impl<P> ClassLoader<P> {
-    /// Create a new class loader with the given class paths.
+    /// Loads a class by searching the class paths in order.
+    /// Like the JVM, a class in an earlier class path shadows the classes with the same name in
+    /// later ones.
     ///
     /// # Errors
     /// See [`Error`].
@@ -54,7 +56,7 @@ impl<P> ClassLoader<P> {
         for class_path in &self.class_path {
             match class_path.find_class(binary_name) {
                 Ok(class) => return Ok(class),
-                Err(Error::NotFound) => continue,
+                Err(Error::NotFound) => {}
                 Err(err) => return Err(err),
             }
         }
@@ -102,6 +104,21 @@ impl<P> CachingClassLoader<P> {
     }
 }
 
+impl CachingClassLoader<Box<dyn ClassPath + Send + Sync>> {
+    /// Creates a caching class loader that searches the given directories and JAR files in order.
+    /// See [`class_paths::from_path`] for how each path is interpreted.
+    #[must_use]
+    pub fn from_paths<I>(paths: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<PathBuf>,
+    {
+        Self::from(ClassLoader::new(
+            paths.into_iter().map(class_paths::from_path),
+        ))
+    }
+}
+
 impl<P> From<ClassLoader<P>> for CachingClassLoader<P> {
     fn from(class_loader: ClassLoader<P>) -> Self {
         Self {
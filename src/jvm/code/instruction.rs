@@ -237,7 +237,7 @@ pub enum Instruction {
     } = 0xba,
     New(ClassRef) = 0xbb,
     NewArray(PrimitiveType) = 0xbc,
-    ANewArray(ClassRef) = 0xbd,
+    ANewArray(FieldType) = 0xbd,
     ArrayLength = 0xbe,
     AThrow = 0xbf,
     CheckCast(FieldType) = 0xc0,
@@ -292,6 +292,195 @@ impl Instruction {
         unsafe { *ptr::from_ref(self).cast::<u8>() }
     }
 
+    /// Returns the number of bytes this instruction occupies in the `code` array when it is
+    /// located at the given byte offset.
+    /// The offset only matters for `tableswitch` and `lookupswitch`, whose operands are padded
+    /// to a 4-byte boundary.
+    /// [`Instruction::Ldc`] and [`Instruction::LdcW`] are sized as their opcodes suggest, i.e.,
+    /// with a one-byte and a two-byte constant pool index respectively.
+    #[must_use]
+    pub fn encoded_len(&self, offset: usize) -> usize {
+        #[allow(clippy::enum_glob_use)]
+        use Instruction::*;
+        match self {
+            BiPush(_) | Ldc(_) | ILoad(_) | LLoad(_) | FLoad(_) | DLoad(_) | ALoad(_)
+            | IStore(_) | LStore(_) | FStore(_) | DStore(_) | AStore(_) | Ret(_) | NewArray(_) => 2,
+            SiPush(_)
+            | LdcW(_)
+            | Ldc2W(_)
+            | IInc(_, _)
+            | IfEq(_)
+            | IfNe(_)
+            | IfLt(_)
+            | IfGe(_)
+            | IfGt(_)
+            | IfLe(_)
+            | IfICmpEq(_)
+            | IfICmpNe(_)
+            | IfICmpLt(_)
+            | IfICmpGe(_)
+            | IfICmpGt(_)
+            | IfICmpLe(_)
+            | IfACmpEq(_)
+            | IfACmpNe(_)
+            | Goto(_)
+            | Jsr(_)
+            | IfNull(_)
+            | IfNonNull(_)
+            | GetStatic(_)
+            | PutStatic(_)
+            | GetField(_)
+            | PutField(_)
+            | InvokeVirtual(_)
+            | InvokeSpecial(_)
+            | InvokeStatic(_)
+            | New(_)
+            | ANewArray(_)
+            | CheckCast(_)
+            | InstanceOf(_) => 3,
+            InvokeInterface(_, _) | InvokeDynamic { .. } | GotoW(_) | JsrW(_) => 5,
+            Wide(WideInstruction::IInc(_, _)) => 6,
+            MultiANewArray(_, _) | Wide(_) => 4,
+            TableSwitch { jump_targets, .. } => {
+                1 + switch_padding(offset) + 4 * (3 + jump_targets.len())
+            }
+            LookupSwitch { match_targets, .. } => {
+                1 + switch_padding(offset) + 4 * 2 + 8 * match_targets.len()
+            }
+            _ => 1,
+        }
+    }
+
+    /// Returns the targets of the jumps (including switches and subroutine calls) made by this
+    /// instruction.
+    pub(crate) fn jump_targets(&self) -> Vec<ProgramCounter> {
+        #[allow(clippy::enum_glob_use)]
+        use Instruction::*;
+        match self {
+            IfEq(target) | IfNe(target) | IfLt(target) | IfGe(target) | IfGt(target)
+            | IfLe(target) | IfICmpEq(target) | IfICmpNe(target) | IfICmpLt(target)
+            | IfICmpGe(target) | IfICmpGt(target) | IfICmpLe(target) | IfACmpEq(target)
+            | IfACmpNe(target) | Goto(target) | Jsr(target) | IfNull(target)
+            | IfNonNull(target) | GotoW(target) | JsrW(target) => vec![*target],
+            TableSwitch {
+                jump_targets,
+                default,
+                ..
+            } => jump_targets.iter().chain([default]).copied().collect(),
+            LookupSwitch {
+                default,
+                match_targets,
+            } => match_targets.values().chain([default]).copied().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Checks if the control flow may continue to the next instruction after this one.
+    /// Subroutine calls are considered to fall through since the subroutine returns to the next
+    /// instruction.
+    pub(crate) const fn falls_through(&self) -> bool {
+        #[allow(clippy::enum_glob_use)]
+        use Instruction::*;
+        !matches!(
+            self,
+            Goto(_)
+                | GotoW(_)
+                | TableSwitch { .. }
+                | LookupSwitch { .. }
+                | IReturn
+                | LReturn
+                | FReturn
+                | DReturn
+                | AReturn
+                | Return
+                | AThrow
+                | Ret(_)
+                | Wide(WideInstruction::Ret(_))
+        )
+    }
+
+    /// Returns the constant pushed onto the operand stack by the instruction, i.e., by
+    /// `aconst_null`, `<t>const_<n>`, `bipush`, `sipush`, `ldc`, `ldc_w`, and `ldc2_w`.
+    /// The operands of `bipush` and `sipush` are sign-extended to [`ConstantValue::Integer`].
+    /// Returns `None` for other instructions.
+    #[must_use]
+    pub fn pushed_constant(&self) -> Option<ConstantValue> {
+        #[allow(clippy::enum_glob_use)]
+        use Instruction::*;
+        let constant = match self {
+            AConstNull => ConstantValue::Null,
+            IConstM1 => ConstantValue::Integer(-1),
+            IConst0 => ConstantValue::Integer(0),
+            IConst1 => ConstantValue::Integer(1),
+            IConst2 => ConstantValue::Integer(2),
+            IConst3 => ConstantValue::Integer(3),
+            IConst4 => ConstantValue::Integer(4),
+            IConst5 => ConstantValue::Integer(5),
+            LConst0 => ConstantValue::Long(0),
+            LConst1 => ConstantValue::Long(1),
+            FConst0 => ConstantValue::Float(0.0),
+            FConst1 => ConstantValue::Float(1.0),
+            FConst2 => ConstantValue::Float(2.0),
+            DConst0 => ConstantValue::Double(0.0),
+            DConst1 => ConstantValue::Double(1.0),
+            BiPush(value) => ConstantValue::Integer(i8::from_be_bytes([*value]).into()),
+            SiPush(value) => ConstantValue::Integer(i16::from_be_bytes(value.to_be_bytes()).into()),
+            Ldc(value) | LdcW(value) | Ldc2W(value) => value.clone(),
+            _ => return None,
+        };
+        Some(constant)
+    }
+
+    /// Returns the index of the local variable accessed by the instruction, including the
+    /// implicit indices of the `<op>_<n>` forms (e.g., `2` for `iload_2`), `iinc`, `ret`, and
+    /// the `wide` forms.
+    /// Returns `None` if the instruction does not access a local variable.
+    #[must_use]
+    pub fn local_index(&self) -> Option<u16> {
+        self.local_slots().map(|(index, _)| index)
+    }
+
+    /// Returns the first local variable slot accessed by the instruction and the number of slots.
+    pub(crate) fn local_slots(&self) -> Option<(u16, u16)> {
+        #[allow(clippy::enum_glob_use)]
+        use Instruction::*;
+        let slots = match self {
+            ILoad(index)
+            | FLoad(index)
+            | ALoad(index)
+            | IStore(index)
+            | FStore(index)
+            | AStore(index)
+            | IInc(index, _)
+            | Ret(index) => (u16::from(*index), 1),
+            LLoad(index) | DLoad(index) | LStore(index) | DStore(index) => (u16::from(*index), 2),
+            ILoad0 | FLoad0 | ALoad0 | IStore0 | FStore0 | AStore0 => (0, 1),
+            ILoad1 | FLoad1 | ALoad1 | IStore1 | FStore1 | AStore1 => (1, 1),
+            ILoad2 | FLoad2 | ALoad2 | IStore2 | FStore2 | AStore2 => (2, 1),
+            ILoad3 | FLoad3 | ALoad3 | IStore3 | FStore3 | AStore3 => (3, 1),
+            LLoad0 | DLoad0 | LStore0 | DStore0 => (0, 2),
+            LLoad1 | DLoad1 | LStore1 | DStore1 => (1, 2),
+            LLoad2 | DLoad2 | LStore2 | DStore2 => (2, 2),
+            LLoad3 | DLoad3 | LStore3 | DStore3 => (3, 2),
+            Wide(wide) => match wide {
+                WideInstruction::ILoad(index)
+                | WideInstruction::FLoad(index)
+                | WideInstruction::ALoad(index)
+                | WideInstruction::IStore(index)
+                | WideInstruction::FStore(index)
+                | WideInstruction::AStore(index)
+                | WideInstruction::IInc(index, _)
+                | WideInstruction::Ret(index) => (*index, 1),
+                WideInstruction::LLoad(index)
+                | WideInstruction::DLoad(index)
+                | WideInstruction::LStore(index)
+                | WideInstruction::DStore(index) => (*index, 2),
+            },
+            _ => return None,
+        };
+        Some(slots)
+    }
+
     /// Gets the name of the [Instruction].
     #[must_use]
     #[allow(clippy::too_many_lines)]
@@ -507,10 +696,28 @@ impl Instruction {
             ImpDep2 => "impdep2",
         }
     }
+
+    /// Returns the local variable index and the increment if this is an `iinc` instruction,
+    /// either in the normal or in the `wide` form.
+    #[must_use]
+    pub fn iinc_operands(&self) -> Option<(u16, i32)> {
+        match self {
+            Self::IInc(index, increment) => Some((u16::from(*index), *increment)),
+            Self::Wide(WideInstruction::IInc(index, increment)) => Some((*index, *increment)),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the number of padding bytes following a switch opcode at the given offset.
+const fn switch_padding(offset: usize) -> usize {
+    (4 - (offset + 1) % 4) % 4
 }
 
 #[cfg(test)]
 mod test {
+    use std::collections::BTreeMap;
+
     use super::Instruction::*;
 
     #[test]
@@ -520,4 +727,75 @@ mod test {
         assert_eq!(IConstM1.opcode(), 0x02);
         assert_eq!(ILoad(233).opcode(), 0x15);
     }
+
+    #[test]
+    fn switch_encoded_len() {
+        let table_switch = TableSwitch {
+            range: 0..=1,
+            jump_targets: vec![0.into(), 0.into()],
+            default: 0.into(),
+        };
+        assert_eq!(table_switch.encoded_len(0), 1 + 3 + 20);
+        assert_eq!(table_switch.encoded_len(3), 1 + 20);
+        let lookup_switch = LookupSwitch {
+            default: 0.into(),
+            match_targets: BTreeMap::from([(1, 0.into())]),
+        };
+        assert_eq!(lookup_switch.encoded_len(1), 1 + 2 + 16);
+        assert_eq!(ILoad(0).encoded_len(7), 2);
+    }
+
+    #[test]
+    fn iinc_operands() {
+        use super::WideInstruction;
+
+        assert_eq!(IInc(3, -1).iinc_operands(), Some((3, -1)));
+        assert_eq!(
+            Wide(WideInstruction::IInc(300, 1000)).iinc_operands(),
+            Some((300, 1000))
+        );
+        assert_eq!(Wide(WideInstruction::ILoad(300)).iinc_operands(), None);
+        assert_eq!(ILoad(3).iinc_operands(), None);
+    }
+
+    #[test]
+    fn pushed_constant() {
+        use crate::jvm::{ConstantValue, JavaString};
+
+        assert_eq!(
+            BiPush(42).pushed_constant(),
+            Some(ConstantValue::Integer(42))
+        );
+        assert_eq!(
+            BiPush(0xff).pushed_constant(),
+            Some(ConstantValue::Integer(-1))
+        );
+        assert_eq!(
+            SiPush(1000).pushed_constant(),
+            Some(ConstantValue::Integer(1000))
+        );
+        assert_eq!(IConstM1.pushed_constant(), Some(ConstantValue::Integer(-1)));
+        assert_eq!(LConst1.pushed_constant(), Some(ConstantValue::Long(1)));
+        assert_eq!(DConst1.pushed_constant(), Some(ConstantValue::Double(1.0)));
+        assert_eq!(AConstNull.pushed_constant(), Some(ConstantValue::Null));
+        let string = ConstantValue::String(JavaString::Utf8("mokapot".to_owned()));
+        assert_eq!(LdcW(string.clone()).pushed_constant(), Some(string));
+        assert_eq!(ILoad0.pushed_constant(), None);
+    }
+
+    #[test]
+    fn local_index() {
+        use super::WideInstruction;
+
+        assert_eq!(ILoad2.local_index(), Some(2));
+        assert_eq!(DStore3.local_index(), Some(3));
+        assert_eq!(AStore(200).local_index(), Some(200));
+        assert_eq!(IInc(7, 1).local_index(), Some(7));
+        assert_eq!(Wide(WideInstruction::LLoad(300)).local_index(), Some(300));
+        assert_eq!(
+            Wide(WideInstruction::IInc(1000, -1)).local_index(),
+            Some(1000)
+        );
+        assert_eq!(IAdd.local_index(), None);
+    }
 }
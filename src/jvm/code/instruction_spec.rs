@@ -0,0 +1,531 @@
+//! Static descriptions of the opcodes in the instruction set.
+
+use crate::macros::see_jvm_spec;
+
+use super::Instruction;
+
+/// The static description of an opcode, returned by [`Instruction::describe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InstructionSpec {
+    /// The opcode.
+    pub opcode: u8,
+    /// The mnemonic, e.g., `iload_0`.
+    pub mnemonic: &'static str,
+    /// The operands following the opcode in the order they are encoded.
+    pub operands: &'static [Operand],
+    /// The effect on the operand stack in the notation of the JVM specification, e.g.,
+    /// `..., value1, value2 → ..., result`, where the top of the stack is on the right.
+    pub stack_effect: &'static str,
+}
+
+impl InstructionSpec {
+    /// Returns the length in bytes of the encoded instruction, including the opcode, or `None`
+    /// if it is variable, i.e., for `tableswitch`, `lookupswitch`, and `wide`.
+    #[must_use]
+    pub fn encoded_len(&self) -> Option<usize> {
+        self.operands
+            .iter()
+            .try_fold(1, |len, operand| Some(len + operand.size()?))
+    }
+}
+
+/// An operand of an instruction as encoded in the `code` array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    /// An `i8` immediate value, e.g., the value pushed by `bipush` or the increment of `iinc`.
+    Byte,
+    /// An `i16` immediate value pushed by `sipush`.
+    Short,
+    /// A `u8` index into the constant pool, used by `ldc`.
+    ConstantPoolIndexU8,
+    /// A `u16` index into the constant pool.
+    ConstantPoolIndex,
+    /// A `u8` index of a local variable.
+    LocalIndex,
+    /// An `i16` branch offset relative to the opcode.
+    BranchOffset,
+    /// An `i32` branch offset relative to the opcode, used by `goto_w` and `jsr_w`.
+    WideBranchOffset,
+    /// A `u8` code of the primitive element type of the array created by `newarray`.
+    ArrayType,
+    /// A `u8` number of dimensions of the array created by `multianewarray`.
+    Dimensions,
+    /// A `u8` number of argument slots, including the receiver, of `invokeinterface`.
+    ArgumentSlots,
+    /// A reserved `u8` that must be zero.
+    Zero,
+    /// The variable-length operands of `tableswitch`.
+    TableSwitch,
+    /// The variable-length operands of `lookupswitch`.
+    LookupSwitch,
+    /// The instruction modified by `wide`.
+    Wide,
+}
+
+impl Operand {
+    /// Returns the size in bytes of the operand, or `None` if it is variable.
+    #[must_use]
+    pub const fn size(&self) -> Option<usize> {
+        match self {
+            Self::Byte
+            | Self::ConstantPoolIndexU8
+            | Self::LocalIndex
+            | Self::ArrayType
+            | Self::Dimensions
+            | Self::ArgumentSlots
+            | Self::Zero => Some(1),
+            Self::Short | Self::ConstantPoolIndex | Self::BranchOffset => Some(2),
+            Self::WideBranchOffset => Some(4),
+            Self::TableSwitch | Self::LookupSwitch | Self::Wide => None,
+        }
+    }
+
+    /// Returns a human-readable description of the layout of the operand.
+    #[must_use]
+    pub const fn description(&self) -> &'static str {
+        match self {
+            Self::Byte => "i8 immediate",
+            Self::Short => "i16 immediate",
+            Self::ConstantPoolIndexU8 => "u8 constant pool index",
+            Self::ConstantPoolIndex => "u16 constant pool index",
+            Self::LocalIndex => "u8 local variable index",
+            Self::BranchOffset => "i16 branch offset",
+            Self::WideBranchOffset => "i32 branch offset",
+            Self::ArrayType => "u8 array type code",
+            Self::Dimensions => "u8 dimensions",
+            Self::ArgumentSlots => "u8 argument slot count",
+            Self::Zero => "u8 zero",
+            Self::TableSwitch => {
+                "0-3 bytes of padding to a 4-byte boundary, i32 default offset, i32 low, i32 high, \
+                 and high - low + 1 i32 branch offsets"
+            }
+            Self::LookupSwitch => {
+                "0-3 bytes of padding to a 4-byte boundary, i32 default offset, i32 pair count, \
+                 and that many i32 match and i32 branch offset pairs sorted by match"
+            }
+            Self::Wide => {
+                "u8 opcode of the modified instruction, u16 local variable index, \
+                 and an i16 increment if the opcode is iinc"
+            }
+        }
+    }
+}
+
+const NO_CHANGE: &str = "no change";
+const PUSH: &str = "... → ..., value";
+const LOAD_ARRAY: &str = "..., arrayref, index → ..., value";
+const STORE: &str = "..., value → ...";
+const STORE_ARRAY: &str = "..., arrayref, index, value → ...";
+const UNARY: &str = "..., value → ..., result";
+const BINARY: &str = "..., value1, value2 → ..., result";
+const IF: &str = "..., value → ...";
+const IF_CMP: &str = "..., value1, value2 → ...";
+const RETURN_VALUE: &str = "..., value → [empty]";
+const INVOKE: &str = "..., objectref, [arg1, [arg2 ...]] → ...";
+const INVOKE_STATIC: &str = "..., [arg1, [arg2 ...]] → ...";
+
+impl Instruction {
+    /// Describes the instruction with the given opcode, i.e., its mnemonic, the layout of its
+    /// operands, and its effect on the operand stack.
+    /// Returns `None` if the opcode is not defined.
+    ///
+    /// The reserved opcodes `breakpoint`, `impdep1`, and `impdep2` are described although they
+    /// must not appear in a `class` file.
+    #[doc = see_jvm_spec!(6, 5)]
+    #[must_use]
+    #[allow(clippy::too_many_lines)]
+    pub const fn describe(opcode: u8) -> Option<InstructionSpec> {
+        use Operand::{
+            ArgumentSlots, ArrayType, BranchOffset, Byte, ConstantPoolIndex, ConstantPoolIndexU8,
+            Dimensions, LocalIndex, LookupSwitch, Short, TableSwitch, Wide, WideBranchOffset, Zero,
+        };
+
+        let (mnemonic, operands, stack_effect): (_, &'static [Operand], _) = match opcode {
+            0x00 => ("nop", &[], NO_CHANGE),
+            0x01 => ("aconst_null", &[], "... → ..., null"),
+            0x02 => ("iconst_m1", &[], PUSH),
+            0x03 => ("iconst_0", &[], PUSH),
+            0x04 => ("iconst_1", &[], PUSH),
+            0x05 => ("iconst_2", &[], PUSH),
+            0x06 => ("iconst_3", &[], PUSH),
+            0x07 => ("iconst_4", &[], PUSH),
+            0x08 => ("iconst_5", &[], PUSH),
+            0x09 => ("lconst_0", &[], PUSH),
+            0x0a => ("lconst_1", &[], PUSH),
+            0x0b => ("fconst_0", &[], PUSH),
+            0x0c => ("fconst_1", &[], PUSH),
+            0x0d => ("fconst_2", &[], PUSH),
+            0x0e => ("dconst_0", &[], PUSH),
+            0x0f => ("dconst_1", &[], PUSH),
+            0x10 => ("bipush", &[Byte], PUSH),
+            0x11 => ("sipush", &[Short], PUSH),
+            0x12 => ("ldc", &[ConstantPoolIndexU8], PUSH),
+            0x13 => ("ldc_w", &[ConstantPoolIndex], PUSH),
+            0x14 => ("ldc2_w", &[ConstantPoolIndex], PUSH),
+            0x15 => ("iload", &[LocalIndex], PUSH),
+            0x16 => ("lload", &[LocalIndex], PUSH),
+            0x17 => ("fload", &[LocalIndex], PUSH),
+            0x18 => ("dload", &[LocalIndex], PUSH),
+            0x19 => ("aload", &[LocalIndex], "... → ..., objectref"),
+            0x1a => ("iload_0", &[], PUSH),
+            0x1b => ("iload_1", &[], PUSH),
+            0x1c => ("iload_2", &[], PUSH),
+            0x1d => ("iload_3", &[], PUSH),
+            0x1e => ("lload_0", &[], PUSH),
+            0x1f => ("lload_1", &[], PUSH),
+            0x20 => ("lload_2", &[], PUSH),
+            0x21 => ("lload_3", &[], PUSH),
+            0x22 => ("fload_0", &[], PUSH),
+            0x23 => ("fload_1", &[], PUSH),
+            0x24 => ("fload_2", &[], PUSH),
+            0x25 => ("fload_3", &[], PUSH),
+            0x26 => ("dload_0", &[], PUSH),
+            0x27 => ("dload_1", &[], PUSH),
+            0x28 => ("dload_2", &[], PUSH),
+            0x29 => ("dload_3", &[], PUSH),
+            0x2a => ("aload_0", &[], "... → ..., objectref"),
+            0x2b => ("aload_1", &[], "... → ..., objectref"),
+            0x2c => ("aload_2", &[], "... → ..., objectref"),
+            0x2d => ("aload_3", &[], "... → ..., objectref"),
+            0x2e => ("iaload", &[], LOAD_ARRAY),
+            0x2f => ("laload", &[], LOAD_ARRAY),
+            0x30 => ("faload", &[], LOAD_ARRAY),
+            0x31 => ("daload", &[], LOAD_ARRAY),
+            0x32 => ("aaload", &[], LOAD_ARRAY),
+            0x33 => ("baload", &[], LOAD_ARRAY),
+            0x34 => ("caload", &[], LOAD_ARRAY),
+            0x35 => ("saload", &[], LOAD_ARRAY),
+            0x36 => ("istore", &[LocalIndex], STORE),
+            0x37 => ("lstore", &[LocalIndex], STORE),
+            0x38 => ("fstore", &[LocalIndex], STORE),
+            0x39 => ("dstore", &[LocalIndex], STORE),
+            0x3a => ("astore", &[LocalIndex], "..., objectref → ..."),
+            0x3b => ("istore_0", &[], STORE),
+            0x3c => ("istore_1", &[], STORE),
+            0x3d => ("istore_2", &[], STORE),
+            0x3e => ("istore_3", &[], STORE),
+            0x3f => ("lstore_0", &[], STORE),
+            0x40 => ("lstore_1", &[], STORE),
+            0x41 => ("lstore_2", &[], STORE),
+            0x42 => ("lstore_3", &[], STORE),
+            0x43 => ("fstore_0", &[], STORE),
+            0x44 => ("fstore_1", &[], STORE),
+            0x45 => ("fstore_2", &[], STORE),
+            0x46 => ("fstore_3", &[], STORE),
+            0x47 => ("dstore_0", &[], STORE),
+            0x48 => ("dstore_1", &[], STORE),
+            0x49 => ("dstore_2", &[], STORE),
+            0x4a => ("dstore_3", &[], STORE),
+            0x4b => ("astore_0", &[], "..., objectref → ..."),
+            0x4c => ("astore_1", &[], "..., objectref → ..."),
+            0x4d => ("astore_2", &[], "..., objectref → ..."),
+            0x4e => ("astore_3", &[], "..., objectref → ..."),
+            0x4f => ("iastore", &[], STORE_ARRAY),
+            0x50 => ("lastore", &[], STORE_ARRAY),
+            0x51 => ("fastore", &[], STORE_ARRAY),
+            0x52 => ("dastore", &[], STORE_ARRAY),
+            0x53 => ("aastore", &[], STORE_ARRAY),
+            0x54 => ("bastore", &[], STORE_ARRAY),
+            0x55 => ("castore", &[], STORE_ARRAY),
+            0x56 => ("sastore", &[], STORE_ARRAY),
+            0x57 => ("pop", &[], "..., value → ..."),
+            0x58 => ("pop2", &[], "..., value2, value1 → ..."),
+            0x59 => ("dup", &[], "..., value → ..., value, value"),
+            0x5a => (
+                "dup_x1",
+                &[],
+                "..., value2, value1 → ..., value1, value2, value1",
+            ),
+            0x5b => (
+                "dup_x2",
+                &[],
+                "..., value3, value2, value1 → ..., value1, value3, value2, value1",
+            ),
+            0x5c => (
+                "dup2",
+                &[],
+                "..., value2, value1 → ..., value2, value1, value2, value1",
+            ),
+            0x5d => (
+                "dup2_x1",
+                &[],
+                "..., value3, value2, value1 → ..., value2, value1, value3, value2, value1",
+            ),
+            0x5e => (
+                "dup2_x2",
+                &[],
+                "..., value4, value3, value2, value1 → \
+                 ..., value2, value1, value4, value3, value2, value1",
+            ),
+            0x5f => ("swap", &[], "..., value2, value1 → ..., value1, value2"),
+            0x60 => ("iadd", &[], BINARY),
+            0x61 => ("ladd", &[], BINARY),
+            0x62 => ("fadd", &[], BINARY),
+            0x63 => ("dadd", &[], BINARY),
+            0x64 => ("isub", &[], BINARY),
+            0x65 => ("lsub", &[], BINARY),
+            0x66 => ("fsub", &[], BINARY),
+            0x67 => ("dsub", &[], BINARY),
+            0x68 => ("imul", &[], BINARY),
+            0x69 => ("lmul", &[], BINARY),
+            0x6a => ("fmul", &[], BINARY),
+            0x6b => ("dmul", &[], BINARY),
+            0x6c => ("idiv", &[], BINARY),
+            0x6d => ("ldiv", &[], BINARY),
+            0x6e => ("fdiv", &[], BINARY),
+            0x6f => ("ddiv", &[], BINARY),
+            0x70 => ("irem", &[], BINARY),
+            0x71 => ("lrem", &[], BINARY),
+            0x72 => ("frem", &[], BINARY),
+            0x73 => ("drem", &[], BINARY),
+            0x74 => ("ineg", &[], UNARY),
+            0x75 => ("lneg", &[], UNARY),
+            0x76 => ("fneg", &[], UNARY),
+            0x77 => ("dneg", &[], UNARY),
+            0x78 => ("ishl", &[], BINARY),
+            0x79 => ("lshl", &[], BINARY),
+            0x7a => ("ishr", &[], BINARY),
+            0x7b => ("lshr", &[], BINARY),
+            0x7c => ("iushr", &[], BINARY),
+            0x7d => ("lushr", &[], BINARY),
+            0x7e => ("iand", &[], BINARY),
+            0x7f => ("land", &[], BINARY),
+            0x80 => ("ior", &[], BINARY),
+            0x81 => ("lor", &[], BINARY),
+            0x82 => ("ixor", &[], BINARY),
+            0x83 => ("lxor", &[], BINARY),
+            0x84 => ("iinc", &[LocalIndex, Byte], NO_CHANGE),
+            0x85 => ("i2l", &[], UNARY),
+            0x86 => ("i2f", &[], UNARY),
+            0x87 => ("i2d", &[], UNARY),
+            0x88 => ("l2i", &[], UNARY),
+            0x89 => ("l2f", &[], UNARY),
+            0x8a => ("l2d", &[], UNARY),
+            0x8b => ("f2i", &[], UNARY),
+            0x8c => ("f2l", &[], UNARY),
+            0x8d => ("f2d", &[], UNARY),
+            0x8e => ("d2i", &[], UNARY),
+            0x8f => ("d2l", &[], UNARY),
+            0x90 => ("d2f", &[], UNARY),
+            0x91 => ("i2b", &[], UNARY),
+            0x92 => ("i2c", &[], UNARY),
+            0x93 => ("i2s", &[], UNARY),
+            0x94 => ("lcmp", &[], BINARY),
+            0x95 => ("fcmpl", &[], BINARY),
+            0x96 => ("fcmpg", &[], BINARY),
+            0x97 => ("dcmpl", &[], BINARY),
+            0x98 => ("dcmpg", &[], BINARY),
+            0x99 => ("ifeq", &[BranchOffset], IF),
+            0x9a => ("ifne", &[BranchOffset], IF),
+            0x9b => ("iflt", &[BranchOffset], IF),
+            0x9c => ("ifge", &[BranchOffset], IF),
+            0x9d => ("ifgt", &[BranchOffset], IF),
+            0x9e => ("ifle", &[BranchOffset], IF),
+            0x9f => ("if_icmpeq", &[BranchOffset], IF_CMP),
+            0xa0 => ("if_icmpne", &[BranchOffset], IF_CMP),
+            0xa1 => ("if_icmplt", &[BranchOffset], IF_CMP),
+            0xa2 => ("if_icmpge", &[BranchOffset], IF_CMP),
+            0xa3 => ("if_icmpgt", &[BranchOffset], IF_CMP),
+            0xa4 => ("if_icmple", &[BranchOffset], IF_CMP),
+            0xa5 => ("if_acmpeq", &[BranchOffset], IF_CMP),
+            0xa6 => ("if_acmpne", &[BranchOffset], IF_CMP),
+            0xa7 => ("goto", &[BranchOffset], NO_CHANGE),
+            0xa8 => ("jsr", &[BranchOffset], "... → ..., address"),
+            0xa9 => ("ret", &[LocalIndex], NO_CHANGE),
+            0xaa => ("tableswitch", &[TableSwitch], "..., index → ..."),
+            0xab => ("lookupswitch", &[LookupSwitch], "..., key → ..."),
+            0xac => ("ireturn", &[], RETURN_VALUE),
+            0xad => ("lreturn", &[], RETURN_VALUE),
+            0xae => ("freturn", &[], RETURN_VALUE),
+            0xaf => ("dreturn", &[], RETURN_VALUE),
+            0xb0 => ("areturn", &[], "..., objectref → [empty]"),
+            0xb1 => ("return", &[], "... → [empty]"),
+            0xb2 => ("getstatic", &[ConstantPoolIndex], PUSH),
+            0xb3 => ("putstatic", &[ConstantPoolIndex], STORE),
+            0xb4 => (
+                "getfield",
+                &[ConstantPoolIndex],
+                "..., objectref → ..., value",
+            ),
+            0xb5 => (
+                "putfield",
+                &[ConstantPoolIndex],
+                "..., objectref, value → ...",
+            ),
+            0xb6 => ("invokevirtual", &[ConstantPoolIndex], INVOKE),
+            0xb7 => ("invokespecial", &[ConstantPoolIndex], INVOKE),
+            0xb8 => ("invokestatic", &[ConstantPoolIndex], INVOKE_STATIC),
+            0xb9 => (
+                "invokeinterface",
+                &[ConstantPoolIndex, ArgumentSlots, Zero],
+                INVOKE,
+            ),
+            0xba => (
+                "invokedynamic",
+                &[ConstantPoolIndex, Zero, Zero],
+                INVOKE_STATIC,
+            ),
+            0xbb => ("new", &[ConstantPoolIndex], "... → ..., objectref"),
+            0xbc => ("newarray", &[ArrayType], "..., count → ..., arrayref"),
+            0xbd => (
+                "anewarray",
+                &[ConstantPoolIndex],
+                "..., count → ..., arrayref",
+            ),
+            0xbe => ("arraylength", &[], "..., arrayref → ..., length"),
+            0xbf => ("athrow", &[], "..., objectref → objectref"),
+            0xc0 => (
+                "checkcast",
+                &[ConstantPoolIndex],
+                "..., objectref → ..., objectref",
+            ),
+            0xc1 => (
+                "instanceof",
+                &[ConstantPoolIndex],
+                "..., objectref → ..., result",
+            ),
+            0xc2 => ("monitorenter", &[], "..., objectref → ..."),
+            0xc3 => ("monitorexit", &[], "..., objectref → ..."),
+            0xc4 => ("wide", &[Wide], "same as the modified instruction"),
+            0xc5 => (
+                "multianewarray",
+                &[ConstantPoolIndex, Dimensions],
+                "..., count1, [count2, ...] → ..., arrayref",
+            ),
+            0xc6 => ("ifnull", &[BranchOffset], IF),
+            0xc7 => ("ifnonnull", &[BranchOffset], IF),
+            0xc8 => ("goto_w", &[WideBranchOffset], NO_CHANGE),
+            0xc9 => ("jsr_w", &[WideBranchOffset], "... → ..., address"),
+            0xca => ("breakpoint", &[], "implementation dependent"),
+            0xfe => ("impdep1", &[], "implementation dependent"),
+            0xff => ("impdep2", &[], "implementation dependent"),
+            _ => return None,
+        };
+        Some(InstructionSpec {
+            opcode,
+            mnemonic,
+            operands,
+            stack_effect,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jvm::{class::ConstantPool, code::RawInstruction, parsing::Error};
+
+    #[test]
+    fn operand_layouts() {
+        let ldc = Instruction::describe(0x12).unwrap();
+        assert_eq!("ldc", ldc.mnemonic);
+        assert_eq!(&[Operand::ConstantPoolIndexU8], ldc.operands);
+        assert_eq!(Some(2), ldc.encoded_len());
+
+        let invokeinterface = Instruction::describe(0xb9).unwrap();
+        assert_eq!(Some(5), invokeinterface.encoded_len());
+
+        let tableswitch = Instruction::describe(0xaa).unwrap();
+        assert_eq!(&[Operand::TableSwitch], tableswitch.operands);
+        assert_eq!(None, tableswitch.encoded_len());
+
+        let wide = Instruction::describe(0xc4).unwrap();
+        assert_eq!(&[Operand::Wide], wide.operands);
+        assert_eq!(None, wide.encoded_len());
+    }
+
+    #[test]
+    fn undefined_opcodes() {
+        assert!((0xcb..=0xfd).all(|opcode| Instruction::describe(opcode).is_none()));
+    }
+
+    /// A constant pool with an entry for each kind of constant pool operand:
+    /// `java/lang/Object` at #2, a field at #6, a method at #9, an interface method at #10,
+    /// a dynamic call site at #11, an integer at #12, a long at #13, and `[I` at #16.
+    fn constant_pool() -> ConstantPool {
+        let mut bytes = vec![1, 0, 16];
+        bytes.extend_from_slice(b"java/lang/Object");
+        bytes.extend_from_slice(&[7, 0, 1, 1, 0, 1, b'f', 1, 0, 1, b'I', 12, 0, 3, 0, 4]);
+        bytes.extend_from_slice(&[9, 0, 2, 0, 5, 1, 0, 3]);
+        bytes.extend_from_slice(b"()V");
+        bytes.extend_from_slice(&[12, 0, 3, 0, 7, 10, 0, 2, 0, 8, 11, 0, 2, 0, 8]);
+        bytes.extend_from_slice(&[18, 0, 0, 0, 8, 3, 0, 0, 0, 0]);
+        bytes.extend_from_slice(&[5, 0, 0, 0, 0, 0, 0, 0, 0]);
+        bytes.extend_from_slice(&[1, 0, 2, b'[', b'I', 7, 0, 15]);
+        ConstantPool::from_reader(&mut bytes.as_slice(), 17).unwrap()
+    }
+
+    /// Encodes `opcode` at offset 0 with operands that the parser accepts.
+    fn encode(spec: &InstructionSpec) -> Vec<u8> {
+        let mut bytes = vec![spec.opcode];
+        for operand in spec.operands {
+            match operand {
+                Operand::ConstantPoolIndexU8 => bytes.push(12),
+                Operand::ConstantPoolIndex => {
+                    let index: u8 = match spec.opcode {
+                        0x13 => 12,
+                        0x14 => 13,
+                        0xb2..=0xb5 => 6,
+                        0xb6..=0xb8 => 9,
+                        0xb9 => 10,
+                        0xba => 11,
+                        0xc5 => 16,
+                        _ => 2,
+                    };
+                    bytes.extend_from_slice(&[0, index]);
+                }
+                Operand::ArrayType => bytes.push(10),
+                Operand::Dimensions | Operand::ArgumentSlots => bytes.push(1),
+                // Padding, the default offset, and `low` and `high` of 0 with a single offset.
+                Operand::TableSwitch => bytes.extend_from_slice(&[0; 19]),
+                // Padding, the default offset, and no pairs.
+                Operand::LookupSwitch => bytes.extend_from_slice(&[0; 11]),
+                // `wide iload 0`
+                Operand::Wide => bytes.extend_from_slice(&[0x15, 0, 0]),
+                _ => bytes.resize(bytes.len() + operand.size().unwrap(), 0),
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn consistent_with_instructions() {
+        let constant_pool = constant_pool();
+        for spec in (0..=u8::MAX).filter_map(Instruction::describe) {
+            let bytes = encode(&spec);
+            let reserved = match spec.opcode {
+                0xca => Some(Instruction::Breakpoint),
+                0xfe => Some(Instruction::ImpDep1),
+                0xff => Some(Instruction::ImpDep2),
+                _ => None,
+            };
+            if let Some(instruction) = reserved {
+                // Reserved opcodes must not appear in a `class` file.
+                assert!(matches!(
+                    RawInstruction::from_slice(&bytes),
+                    Err(Error::UnexpectedOpCode(opcode)) if opcode == spec.opcode
+                ));
+                assert_eq!(spec.mnemonic, instruction.name());
+                assert_eq!(spec.encoded_len(), Some(instruction.encoded_len(0)));
+                continue;
+            }
+            let raw_instructions = RawInstruction::from_slice(&bytes).unwrap();
+            let Ok([(pc, raw_instruction)]) =
+                <[_; 1]>::try_from(raw_instructions.into_iter().collect::<Vec<_>>())
+            else {
+                panic!("{} should be parsed as a single instruction", spec.mnemonic);
+            };
+            let instruction =
+                Instruction::from_raw_instruction(raw_instruction, pc, &constant_pool).unwrap();
+            assert_eq!(spec.opcode, instruction.opcode());
+            assert_eq!(spec.mnemonic, instruction.name());
+            assert_eq!(
+                spec.encoded_len().unwrap_or(bytes.len()),
+                instruction.encoded_len(0),
+                "{}",
+                spec.mnemonic
+            );
+            assert_eq!(bytes.len(), instruction.encoded_len(0), "{}", spec.mnemonic);
+        }
+    }
+}
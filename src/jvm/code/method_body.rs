@@ -1,13 +1,13 @@
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     fmt::Display,
-    ops::{Bound, Range, RangeInclusive},
+    ops::{Bound, Range},
 };
 
 use crate::{
     jvm::{class::ConstantPool, parsing::Error, references::ClassRef, TypeAnnotation},
     macros::{malform, see_jvm_spec},
-    types::field_type::FieldType,
+    types::field_type::{FieldType, PrimitiveType},
 };
 
 use super::{Instruction, ProgramCounter, RawInstruction};
@@ -22,7 +22,8 @@ pub struct MethodBody {
     pub max_locals: u16,
     /// The executable instructions.
     pub instructions: InstructionList<Instruction>,
-    /// The exception handlers table.
+    /// The exception handlers table, in the order of the class file.
+    /// The order matters since the first handler matching a thrown exception is selected.
     pub exception_table: Vec<ExceptionTableEntry>,
     /// The line number table.
     pub line_number_table: Option<Vec<LineNumberTableEntry>>,
@@ -36,6 +37,18 @@ pub struct MethodBody {
     pub runtime_invisible_type_annotations: Vec<TypeAnnotation>,
     /// Unrecognized JVM attributes.
     pub free_attributes: Vec<(String, Vec<u8>)>,
+    /// The names and the original bytes of all the attributes, including the recognized ones, in
+    /// the order in which they appear in the class file. The bytes refer to the constant pool of
+    /// the class file.
+    /// Only populated when parsing with [`ParseOptions::retain_raw_attributes`](crate::jvm::parsing::ParseOptions::retain_raw_attributes).
+    pub raw_attributes: Vec<(String, Vec<u8>)>,
+    /// The original bytes of the `code` array, from which the
+    /// [`instructions`](MethodBody::instructions) were parsed.
+    /// The transformations in this crate reset it to `None` when they modify the instructions,
+    /// since the bytes would no longer match them. Code modifying the body directly should do the
+    /// same, e.g., with [`MethodBody::discard_raw`].
+    /// Only populated when parsing with [`ParseOptions::retain_raw_code`](crate::jvm::parsing::ParseOptions::retain_raw_code).
+    pub raw_code: Option<Box<[u8]>>,
 }
 
 impl MethodBody {
@@ -44,8 +57,212 @@ impl MethodBody {
     pub fn instruction_at(&self, pc: ProgramCounter) -> Option<&Instruction> {
         self.instructions.get(&pc)
     }
+
+    /// Returns the exception handlers that are active at the given program counter, in the order
+    /// of the exception table.
+    pub fn handlers_at(&self, pc: ProgramCounter) -> impl Iterator<Item = &ExceptionTableEntry> {
+        self.exception_table.iter().filter(move |it| it.covers(pc))
+    }
+
+    /// Returns the handler selected by the JVM when an exception of `exception_type` is thrown at
+    /// `pc`, i.e., the first entry in the exception table covering `pc` whose catch type is
+    /// `exception_type` or one of its superclasses.
+    /// `is_subclass(class, super_class)` checks whether `class` is a subclass of `super_class`,
+    /// e.g., with [`ClassHierarchy::super_classes`](crate::ir::ClassHierarchy::super_classes).
+    /// It is only called for catch types other than `exception_type` itself.
+    /// An entry without a catch type, e.g., for a `finally` block, matches any exception.
+    /// Returns `None` if the exception propagates to the caller.
+    #[doc = see_jvm_spec!(2, 10)]
+    #[must_use]
+    pub fn handler_for<F>(
+        &self,
+        pc: ProgramCounter,
+        exception_type: &ClassRef,
+        is_subclass: F,
+    ) -> Option<&ExceptionTableEntry>
+    where
+        F: Fn(&ClassRef, &ClassRef) -> bool,
+    {
+        self.handlers_at(pc)
+            .find(|handler| match &handler.catch_type {
+                None => true,
+                Some(catch_type) => {
+                    catch_type == exception_type || is_subclass(exception_type, catch_type)
+                }
+            })
+    }
+
+    /// Returns the program counters the control may flow to after executing the instruction at
+    /// `pc` normally, in ascending order without duplicates, i.e., the next instruction if the
+    /// instruction falls through, and the targets of its jumps, switches, and subroutine calls.
+    /// The result is empty for `return`, `athrow`, and `ret`, whose successors are not known
+    /// from the instruction alone, and for a `pc` without an instruction.
+    /// The exception handlers are not included; see [`MethodBody::handlers_at`].
+    #[must_use]
+    pub fn successors(&self, pc: ProgramCounter) -> Vec<ProgramCounter> {
+        let Some(instruction) = self.instruction_at(pc) else {
+            return Vec::new();
+        };
+        let mut successors = instruction.jump_targets();
+        if instruction.falls_through() {
+            successors.extend(self.instructions.next_pc_of(&pc));
+        }
+        successors.sort_unstable();
+        successors.dedup();
+        successors
+    }
+
+    /// Checks that the execution cannot fall off the end of the code, i.e., that no reachable
+    /// instruction continues to the next instruction when it is the last one.
+    /// An instruction is reachable if the control may flow to it from the entry point through
+    /// the [successors](MethodBody::successors) of the instructions and the exception handlers
+    /// covering them. Unreachable code at the end, e.g., dead code left by an obfuscator, is
+    /// ignored.
+    /// # Errors
+    /// Returns [`FallsOffCode`] with the program counter of the last instruction if it is reachable
+    /// and falls through.
+    #[doc = see_jvm_spec!(4, 9, 2)]
+    pub fn validate_termination(&self) -> Result<(), FallsOffCode> {
+        let (Some((&entry_pc, _)), Some((&last_pc, last_instruction))) = (
+            self.instructions.entry_point(),
+            self.instructions.last_instruction(),
+        ) else {
+            return Ok(());
+        };
+        if !last_instruction.falls_through() {
+            return Ok(());
+        }
+        let mut reachable = HashSet::new();
+        let mut pending = vec![entry_pc];
+        while let Some(pc) = pending.pop() {
+            if self.instruction_at(pc).is_none() || !reachable.insert(pc) {
+                continue;
+            }
+            if pc == last_pc {
+                return Err(FallsOffCode(pc));
+            }
+            pending.extend(self.successors(pc));
+            pending.extend(self.handlers_at(pc).map(|it| it.handler_pc));
+        }
+        Ok(())
+    }
+
+    /// Returns the instructions whose program counters are in the half-open `range`, in order,
+    /// e.g., those covered by an [`ExceptionTableEntry`] given its
+    /// [`covered_pc`](ExceptionTableEntry::covered_pc).
+    /// No instruction is returned if the end of `range` is not after its start.
+    #[must_use]
+    pub fn instructions_in_range(
+        &self,
+        range: Range<ProgramCounter>,
+    ) -> impl DoubleEndedIterator<Item = (ProgramCounter, &Instruction)> {
+        // `BTreeMap::range` panics if the start is after the end.
+        let end = range.end.max(range.start);
+        self.instructions
+            .0
+            .range(range.start..end)
+            .map(|(pc, instruction)| (*pc, instruction))
+    }
+
+    /// Estimates the length in bytes of the `code` array when the instructions are encoded
+    /// back-to-back in their current order.
+    /// The program counters of the instructions are ignored, so the result remains accurate
+    /// after instructions have been inserted or removed.
+    ///
+    /// The result is a lower bound rather than the exact length, since the constant pool is not
+    /// known here. Each [`Instruction::Ldc`] is counted as two bytes, but it takes three if the
+    /// constant lands at an index above 255 and has to be written as `ldc_w`.
+    /// # Errors
+    /// Returns [`CodeTooLarge`] if the length exceeds [`MAX_CODE_LENGTH`].
+    pub fn code_size(&self) -> Result<usize, CodeTooLarge> {
+        let size = self
+            .instructions
+            .iter()
+            .fold(0, |offset, (_, insn)| offset + insn.encoded_len(offset));
+        if size > MAX_CODE_LENGTH {
+            Err(CodeTooLarge(size))
+        } else {
+            Ok(size)
+        }
+    }
+
+    /// Replaces the instruction at `pc` with `instruction`.
+    /// The new instruction must have the same encoded length as the old one, so that the program
+    /// counters of all the other instructions, the jump targets, and the tables referring to them
+    /// remain valid.
+    /// The [`raw_code`](MethodBody::raw_code) is reset to `None`.
+    /// # Errors
+    /// See [`EditError`]. To replace an instruction with one of a different length, the
+    /// instructions following it must be moved, e.g., by rebuilding the [`InstructionList`].
+    pub fn replace_instruction(
+        &mut self,
+        pc: ProgramCounter,
+        instruction: Instruction,
+    ) -> Result<Instruction, EditError> {
+        let existing = self
+            .instructions
+            .0
+            .get_mut(&pc)
+            .ok_or(EditError::NoInstruction(pc))?;
+        let offset = usize::from(u16::from(pc));
+        let expected = existing.encoded_len(offset);
+        let actual = instruction.encoded_len(offset);
+        if expected != actual {
+            return Err(EditError::LengthMismatch {
+                pc,
+                expected,
+                actual,
+            });
+        }
+        let replaced = std::mem::replace(existing, instruction);
+        self.raw_code = None;
+        Ok(replaced)
+    }
+
+    /// Discards the [`raw_code`](MethodBody::raw_code) and the
+    /// [`raw_attributes`](MethodBody::raw_attributes), which no longer match the body once the
+    /// instructions are moved or the constant pool entries they refer to are changed.
+    /// The raw `Code` attribute of the method, if retained, is stale as well and is discarded by
+    /// [`Method::discard_raw_code`](crate::jvm::Method::discard_raw_code).
+    pub fn discard_raw(&mut self) {
+        self.raw_code = None;
+        self.raw_attributes.clear();
+    }
+}
+
+/// An error that occurs when editing the instructions of a [`MethodBody`] in place.
+#[derive(thiserror::Error, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EditError {
+    /// There is no instruction at the given program counter.
+    #[error("No instruction at {0}")]
+    NoInstruction(ProgramCounter),
+    /// The replacement would change the length of the code.
+    #[error("The instruction at {pc} is {expected} bytes long, but the replacement is {actual} bytes long")]
+    LengthMismatch {
+        /// The program counter of the instruction being replaced.
+        pc: ProgramCounter,
+        /// The encoded length of the instruction being replaced.
+        expected: usize,
+        /// The encoded length of the replacement.
+        actual: usize,
+    },
 }
 
+/// The maximum length in bytes of the `code` array of a method.
+#[doc = see_jvm_spec!(4, 7, 3)]
+pub const MAX_CODE_LENGTH: usize = 65535;
+
+/// An error indicating that the code of a method exceeds [`MAX_CODE_LENGTH`].
+#[derive(thiserror::Error, Debug, PartialEq, Eq, Clone, Copy)]
+#[error("The code size {0} exceeds the limit of {MAX_CODE_LENGTH} bytes")]
+pub struct CodeTooLarge(pub usize);
+
+/// An error indicating that the execution of a method may continue past the last instruction,
+/// see [`MethodBody::validate_termination`].
+#[derive(thiserror::Error, Debug, PartialEq, Eq, Clone, Copy)]
+#[error("The control flows past the last instruction at {0}")]
+pub struct FallsOffCode(pub ProgramCounter);
+
 /// A list of instructions.
 #[derive(Debug, Clone)]
 pub struct InstructionList<I>(BTreeMap<ProgramCounter, I>);
@@ -94,6 +311,13 @@ impl<I> InstructionList<I> {
     ) -> impl DoubleEndedIterator<Item = (&ProgramCounter, &I)> + ExactSizeIterator {
         self.into_iter()
     }
+
+    /// Creates an iterator over the instructions that allows modifying each instruction.
+    pub fn iter_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = (&ProgramCounter, &mut I)> + ExactSizeIterator {
+        self.0.iter_mut()
+    }
 }
 
 impl<I> Display for InstructionList<I>
@@ -180,12 +404,21 @@ impl InstructionList<RawInstruction> {
 
 #[cfg(test)]
 mod test {
+    use std::collections::BTreeMap;
+
     use crate::{
-        ir::MokaInstruction,
-        jvm::code::{Instruction, InstructionList},
+        ir::{ClassHierarchy, MokaInstruction},
+        jvm::{
+            code::{Instruction, InstructionList},
+            references::ClassRef,
+            Class, ConstantValue,
+        },
     };
 
-    use super::MethodBody;
+    use super::{
+        CodeTooLarge, EditError, ExceptionTableEntry, FallsOffCode, MethodBody, VerificationType,
+    };
+    use crate::types::field_type::{FieldType, PrimitiveType};
     use Instruction::*;
 
     #[test]
@@ -205,10 +438,246 @@ mod test {
             runtime_visible_type_annotations: vec![],
             runtime_invisible_type_annotations: vec![],
             free_attributes: vec![],
+            raw_attributes: vec![],
+            raw_code: None,
         };
         assert_eq!(Some(&IConst0), body.instruction_at(1.into()));
     }
 
+    #[test]
+    fn handler_for() {
+        let class = |name: &str, super_class: &str| Class {
+            binary_name: name.to_owned(),
+            super_class: Some(ClassRef::new(super_class)),
+            ..Default::default()
+        };
+        let hierarchy = ClassHierarchy::from_classes(&[
+            class("java/lang/Exception", "java/lang/Throwable"),
+            class("java/io/IOException", "java/lang/Exception"),
+            class("java/lang/RuntimeException", "java/lang/Exception"),
+        ]);
+        let mut body = body_of(InstructionList::from([(0.into(), Nop), (1.into(), Return)]));
+        let handler = |handler_pc: u16, catch_type: Option<&str>| ExceptionTableEntry {
+            covered_pc: 0.into()..1.into(),
+            handler_pc: handler_pc.into(),
+            catch_type: catch_type.map(ClassRef::new),
+        };
+        body.exception_table = vec![
+            handler(10, Some("java/lang/RuntimeException")),
+            handler(20, Some("java/lang/Exception")),
+            handler(30, None),
+        ];
+        let is_subclass = |class: &ClassRef, super_class: &ClassRef| {
+            hierarchy.super_classes(class).contains(super_class)
+        };
+        let handler_pc = |exception_type: &str| {
+            body.handler_for(0.into(), &ClassRef::new(exception_type), is_subclass)
+                .map(|it| u16::from(it.handler_pc))
+        };
+        assert_eq!(Some(10), handler_pc("java/lang/RuntimeException"));
+        assert_eq!(Some(20), handler_pc("java/io/IOException"));
+        assert_eq!(Some(30), handler_pc("java/lang/Error"));
+        assert_eq!(
+            None,
+            body.handler_for(1.into(), &ClassRef::new("java/lang/Error"), is_subclass)
+        );
+    }
+
+    #[test]
+    fn validate_termination() {
+        let body = body_of(InstructionList::from([(0.into(), Nop), (1.into(), Return)]));
+        assert_eq!(Ok(()), body.validate_termination());
+
+        // The `nop` at the end is reachable through the branch.
+        let body = body_of(InstructionList::from([
+            (0.into(), IConst0),
+            (1.into(), IfEq(5.into())),
+            (4.into(), Return),
+            (5.into(), Nop),
+        ]));
+        assert_eq!(Err(FallsOffCode(5.into())), body.validate_termination());
+
+        // Dead code at the end is ignored, unless an exception handler leads to it.
+        let mut body = body_of(InstructionList::from([
+            (0.into(), Nop),
+            (1.into(), Return),
+            (2.into(), Pop),
+        ]));
+        assert_eq!(Ok(()), body.validate_termination());
+        body.exception_table = vec![ExceptionTableEntry {
+            covered_pc: 0.into()..1.into(),
+            handler_pc: 2.into(),
+            catch_type: None,
+        }];
+        assert_eq!(Err(FallsOffCode(2.into())), body.validate_termination());
+    }
+
+    #[test]
+    fn handlers_at_adjacent_ranges() {
+        let mut body = body_of(InstructionList::from([
+            (0.into(), Nop),
+            (1.into(), Nop),
+            (2.into(), Nop),
+            (3.into(), Return),
+        ]));
+        body.exception_table = vec![
+            ExceptionTableEntry {
+                covered_pc: 0.into()..2.into(),
+                handler_pc: 3.into(),
+                catch_type: None,
+            },
+            ExceptionTableEntry {
+                covered_pc: 2.into()..3.into(),
+                handler_pc: 3.into(),
+                catch_type: Some(ClassRef::new("java/lang/Exception")),
+            },
+            ExceptionTableEntry {
+                covered_pc: 0.into()..3.into(),
+                handler_pc: 3.into(),
+                catch_type: Some(ClassRef::new("java/lang/Error")),
+            },
+        ];
+        let catch_types_at = |pc: u16| {
+            body.handlers_at(pc.into())
+                .map(|it| it.catch_type.as_ref().map(|it| it.binary_name.as_str()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(catch_types_at(1), vec![None, Some("java/lang/Error")]);
+        assert_eq!(
+            catch_types_at(2),
+            vec![Some("java/lang/Exception"), Some("java/lang/Error")]
+        );
+        assert!(catch_types_at(3).is_empty());
+    }
+
+    fn body_of(instructions: InstructionList<Instruction>) -> MethodBody {
+        MethodBody {
+            instructions,
+            max_stack: 0,
+            max_locals: 0,
+            exception_table: vec![],
+            line_number_table: None,
+            local_variable_table: None,
+            stack_map_table: None,
+            runtime_visible_type_annotations: vec![],
+            runtime_invisible_type_annotations: vec![],
+            free_attributes: vec![],
+            raw_attributes: vec![],
+            raw_code: None,
+        }
+    }
+
+    #[test]
+    fn code_size() {
+        let body = body_of(InstructionList::from([
+            (0.into(), IConst0),
+            (
+                1.into(),
+                TableSwitch {
+                    range: 0..=0,
+                    jump_targets: vec![0.into()],
+                    default: 0.into(),
+                },
+            ),
+            (20.into(), BiPush(1)),
+            (22.into(), LdcW(ConstantValue::Integer(1))),
+            (25.into(), Return),
+        ]));
+        // iconst_0 (1) + tableswitch (1 + 2 padding + 16) + bipush (2) + ldc_w (3) + return (1)
+        assert_eq!(body.code_size(), Ok(26));
+    }
+
+    #[test]
+    fn replace_instruction() {
+        let mut body = body_of(InstructionList::from([
+            (0.into(), ILoad0),
+            (1.into(), IfEq(6.into())),
+            (4.into(), Nop),
+            (5.into(), Nop),
+            (6.into(), Return),
+        ]));
+        body.raw_code = Some(Box::new([0x1a, 0x99, 0x00, 0x05, 0x00, 0x00, 0xb1]));
+        assert_eq!(
+            body.replace_instruction(2.into(), Nop),
+            Err(EditError::NoInstruction(2.into()))
+        );
+        assert!(body.raw_code.is_some());
+        assert_eq!(
+            body.replace_instruction(1.into(), IfNe(6.into())),
+            Ok(IfEq(6.into()))
+        );
+        assert_eq!(body.instruction_at(1.into()), Some(&IfNe(6.into())));
+        assert!(body.raw_code.is_none());
+        assert_eq!(body.replace_instruction(4.into(), Return), Ok(Nop));
+        assert_eq!(
+            body.replace_instruction(1.into(), Nop),
+            Err(EditError::LengthMismatch {
+                pc: 1.into(),
+                expected: 3,
+                actual: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn successors() {
+        let body = body_of(InstructionList::from([
+            (0.into(), ILoad0),
+            (1.into(), IfEq(24.into())),
+            (4.into(), ILoad0),
+            (
+                5.into(),
+                TableSwitch {
+                    range: 0..=1,
+                    jump_targets: vec![24.into(), 4.into()],
+                    default: 24.into(),
+                },
+            ),
+            (24.into(), Return),
+        ]));
+        let successors = |pc: u16| -> Vec<u16> {
+            body.successors(pc.into())
+                .into_iter()
+                .map(u16::from)
+                .collect()
+        };
+        assert_eq!(vec![1], successors(0));
+        assert_eq!(vec![4, 24], successors(1));
+        assert_eq!(vec![4, 24], successors(5));
+        assert!(successors(24).is_empty());
+        assert!(successors(2).is_empty());
+    }
+
+    #[test]
+    fn instructions_in_range() {
+        let body = body_of(InstructionList::from([
+            (0.into(), ILoad0),
+            (1.into(), IfEq(5.into())),
+            (4.into(), Nop),
+            (5.into(), Return),
+        ]));
+        let pcs_in = |start: u16, end: u16| -> Vec<u16> {
+            body.instructions_in_range(start.into()..end.into())
+                .map(|(pc, _)| pc.into())
+                .collect()
+        };
+        assert_eq!(vec![1, 4], pcs_in(1, 5));
+        assert_eq!(vec![1], pcs_in(1, 4));
+        assert_eq!(vec![4, 5], pcs_in(2, 6));
+        assert!(pcs_in(2, 4).is_empty());
+        assert!(pcs_in(5, 5).is_empty());
+        assert!(pcs_in(5, 1).is_empty());
+    }
+
+    #[test]
+    fn code_too_large() {
+        let instructions: BTreeMap<_, _> = (0..=u16::MAX)
+            .map(|pc| (pc.into(), GotoW(0.into())))
+            .collect();
+        let body = body_of(instructions.into());
+        assert_eq!(body.code_size(), Err(CodeTooLarge(65536 * 5)));
+    }
+
     #[test]
     fn last_instruction() {
         let instruction_list = InstructionList::from([
@@ -244,13 +713,70 @@ mod test {
         assert_eq!(Some(0.into()), instruction_list.prev_pc_of(&1.into()));
         assert_eq!(None, instruction_list.prev_pc_of(&0.into()));
     }
+
+    #[test]
+    fn verification_type_display() {
+        assert_eq!("int", VerificationType::IntegerVariable.to_string());
+        assert_eq!("top", VerificationType::TopVariable.to_string());
+        assert_eq!(
+            "Object[java/lang/String]",
+            VerificationType::ObjectVariable(ClassRef::new("java/lang/String")).to_string()
+        );
+        assert_eq!(
+            "uninitialized@12",
+            VerificationType::UninitializedVariable { offset: 12.into() }.to_string()
+        );
+    }
+
+    #[test]
+    fn verification_type_from_field_type() {
+        let from = |descriptor: &str| {
+            VerificationType::from_field_type(&descriptor.parse::<FieldType>().unwrap())
+        };
+        for descriptor in ["Z", "B", "C", "S", "I"] {
+            assert_eq!(VerificationType::IntegerVariable, from(descriptor));
+        }
+        assert_eq!(VerificationType::FloatVariable, from("F"));
+        assert_eq!(VerificationType::LongVariable, from("J"));
+        assert_eq!(VerificationType::DoubleVariable, from("D"));
+        assert_eq!(
+            VerificationType::ObjectVariable(ClassRef::new("java/lang/String")),
+            from("Ljava/lang/String;")
+        );
+        assert_eq!(
+            VerificationType::ObjectVariable(ClassRef::new("[[I")),
+            from("[[I")
+        );
+    }
+
+    #[test]
+    fn verification_type_to_field_type() {
+        assert_eq!(
+            Some(FieldType::Object(ClassRef::new("java/lang/String"))),
+            VerificationType::ObjectVariable(ClassRef::new("java/lang/String")).to_field_type()
+        );
+        assert_eq!(
+            Some(FieldType::Base(PrimitiveType::Int).into_array_type()),
+            VerificationType::ObjectVariable(ClassRef::new("[I")).to_field_type()
+        );
+        assert_eq!(
+            Some(FieldType::Base(PrimitiveType::Long)),
+            VerificationType::LongVariable.to_field_type()
+        );
+        assert_eq!(None, VerificationType::TopVariable.to_field_type());
+        assert_eq!(
+            None,
+            VerificationType::UninitializedVariable { offset: 0.into() }.to_field_type()
+        );
+    }
 }
 
 /// An entry in the exception table.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ExceptionTableEntry {
     /// The locations where the exception handler is active.
-    pub covered_pc: RangeInclusive<ProgramCounter>,
+    /// The end is exclusive, as `end_pc` in the class file.
+    pub covered_pc: Range<ProgramCounter>,
     /// The location of the exception handler.
     pub handler_pc: ProgramCounter,
     /// The type of the exception to be handled.
@@ -281,6 +807,44 @@ pub struct LocalVariableTable {
 }
 
 impl LocalVariableTable {
+    /// Returns the entries in the table, in no particular order.
+    /// The entries from the `LocalVariableTable` and `LocalVariableTypeTable` attributes
+    /// describing the same variable are merged, so an entry carries both the type and the generic
+    /// signature of the variable if available.
+    pub fn entries(&self) -> impl Iterator<Item = (&LocalVariableId, &LocalVariableTableEntry)> {
+        self.entries.iter()
+    }
+
+    /// Returns the entry of the local variable at `index` that is valid at `pc`.
+    #[must_use]
+    pub fn entry_at(&self, index: u16, pc: ProgramCounter) -> Option<&LocalVariableTableEntry> {
+        self.entries
+            .iter()
+            .find(|(id, _)| id.index == index && id.effective_range.contains(&pc))
+            .map(|(_, entry)| entry)
+    }
+
+    pub(crate) fn entries_mut(
+        &mut self,
+    ) -> impl Iterator<Item = (&LocalVariableId, &mut LocalVariableTableEntry)> {
+        self.entries.iter_mut()
+    }
+
+    /// Moves the effective ranges of the variables.
+    pub(crate) fn relocate(&mut self, relocate: impl Fn(ProgramCounter) -> ProgramCounter) {
+        self.entries = std::mem::take(&mut self.entries)
+            .into_iter()
+            .map(|(id, entry)| {
+                let id = LocalVariableId {
+                    effective_range: relocate(id.effective_range.start)
+                        ..relocate(id.effective_range.end),
+                    index: id.index,
+                };
+                (id, entry)
+            })
+            .collect();
+    }
+
     pub(crate) fn merge_type(
         &mut self,
         key: LocalVariableId,
@@ -338,34 +902,90 @@ pub struct LocalVariableTableEntry {
 
 /// The type of a value in the stack map table for verification.
 #[doc = see_jvm_spec!(4, 7, 4)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, derive_more::Display)]
 pub enum VerificationType {
     /// Indicates that the local variable has the verification type `top`.
+    #[display("top")]
     TopVariable,
     /// Indicates that the local variable has the verification type `int`.
+    #[display("int")]
     IntegerVariable,
     /// Indicates that the local variable has the verification type `float`.
+    #[display("float")]
     FloatVariable,
     /// Indicates that the local variable has the verification type `null`.
+    #[display("null")]
     NullVariable,
     /// Indicates that the local variable has the verification type `uninitializedThis`.
+    #[display("uninitializedThis")]
     UninitializedThisVariable,
     /// Indicates that the local variable has the verification type `object` with the given type
+    #[display("Object[{_0}]")]
     ObjectVariable(ClassRef),
     /// Indicates that the local variable has the verification type `uninitialized` with the given offset.
+    #[display("uninitialized@{}", u16::from(*offset))]
     UninitializedVariable {
         /// The location of the [`Instruction::New`] that created the object.
         offset: ProgramCounter,
     },
     /// Indicates that the local variable has the verification type `long`.
+    #[display("long")]
     LongVariable,
     /// Indicates that the local variable has the verification type `double`.
+    #[display("double")]
     DoubleVariable,
 }
 
+impl VerificationType {
+    /// Creates the verification type of a value of `field_type`.
+    /// `boolean`, `byte`, `char`, `short`, and `int` are all `int`, and arrays are referred to by
+    /// their descriptors as [`ObjectVariable`](Self::ObjectVariable)s.
+    /// A `long` or `double` value takes two slots, and the caller is responsible for following it
+    /// with [`TopVariable`](Self::TopVariable) where the slots are listed individually.
+    #[must_use]
+    pub fn from_field_type(field_type: &FieldType) -> Self {
+        match field_type {
+            FieldType::Base(PrimitiveType::Long) => Self::LongVariable,
+            FieldType::Base(PrimitiveType::Double) => Self::DoubleVariable,
+            FieldType::Base(PrimitiveType::Float) => Self::FloatVariable,
+            FieldType::Base(
+                PrimitiveType::Boolean
+                | PrimitiveType::Byte
+                | PrimitiveType::Char
+                | PrimitiveType::Short
+                | PrimitiveType::Int,
+            ) => Self::IntegerVariable,
+            FieldType::Object(class) => Self::ObjectVariable(class.clone()),
+            FieldType::Array(_) => Self::ObjectVariable(ClassRef::new(field_type.descriptor())),
+        }
+    }
+
+    /// Converts the verification type to the corresponding [`FieldType`].
+    /// Returns [`None`] for `top`, `null`, and the uninitialized types, which have no
+    /// corresponding field type.
+    #[must_use]
+    pub fn to_field_type(&self) -> Option<FieldType> {
+        match self {
+            Self::IntegerVariable => Some(PrimitiveType::Int.into()),
+            Self::FloatVariable => Some(PrimitiveType::Float.into()),
+            Self::LongVariable => Some(PrimitiveType::Long.into()),
+            Self::DoubleVariable => Some(PrimitiveType::Double.into()),
+            // Array classes are referred to by their descriptors.
+            Self::ObjectVariable(class) if class.binary_name.starts_with('[') => {
+                class.binary_name.parse().ok()
+            }
+            Self::ObjectVariable(class) => Some(FieldType::Object(class.clone())),
+            Self::TopVariable
+            | Self::NullVariable
+            | Self::UninitializedThisVariable
+            | Self::UninitializedVariable { .. } => None,
+        }
+    }
+}
+
 /// A stack map frame for verification.
 #[doc = see_jvm_spec!(4, 7, 4)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum StackMapFrame {
     /// Indicates that the frame has exactly the same locals as the previous frame and that the operand stack is empty.
     /// Corresponds to the `same_frame` and `same_frame_extended`.
@@ -410,3 +1030,19 @@ pub enum StackMapFrame {
         stack: Vec<VerificationType>,
     },
 }
+
+impl StackMapFrame {
+    /// Returns the offset delta of the frame.
+    /// The frame applies at the offset delta for the first frame, and at the offset of the
+    /// previous frame plus the offset delta plus one for the other frames.
+    #[must_use]
+    pub const fn offset_delta(&self) -> u16 {
+        match self {
+            Self::SameFrame { offset_delta }
+            | Self::SameLocals1StackItemFrame { offset_delta, .. }
+            | Self::ChopFrame { offset_delta, .. }
+            | Self::AppendFrame { offset_delta, .. }
+            | Self::FullFrame { offset_delta, .. } => *offset_delta,
+        }
+    }
+}
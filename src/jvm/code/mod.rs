@@ -1,10 +1,14 @@
 //! Module for the APIs for the executable code in JVM.
 mod instruction;
+mod instruction_spec;
 mod method_body;
 mod pc;
+mod printer;
 mod raw_instruction;
 
 pub use instruction::*;
+pub use instruction_spec::*;
 pub use method_body::*;
 pub use pc::*;
+pub use printer::*;
 pub use raw_instruction::*;
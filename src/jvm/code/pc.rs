@@ -95,7 +95,7 @@ impl Debug for ProgramCounter {
 }
 
 /// An error occurring when trying to offset a program counter.
-#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+#[derive(thiserror::Error, Debug, PartialEq, Eq, Clone, Copy)]
 #[error("Invalid PC Offset")]
 pub struct InvalidOffset;
 
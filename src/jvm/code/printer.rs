@@ -0,0 +1,446 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display, Formatter},
+};
+
+use itertools::Itertools;
+
+use super::{Instruction, MethodBody, ProgramCounter, WideInstruction};
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_instruction(f, self, &ProgramCounter::to_string)
+    }
+}
+
+/// Writes `instruction` with its jump targets rendered by `render_target`.
+fn write_instruction(
+    f: &mut Formatter<'_>,
+    instruction: &Instruction,
+    render_target: &dyn Fn(&ProgramCounter) -> String,
+) -> fmt::Result {
+    #[allow(clippy::enum_glob_use)]
+    use Instruction::*;
+    let name = instruction.name();
+    match instruction {
+        BiPush(value) => write!(f, "{name} {}", i8::from_be_bytes([*value])),
+        SiPush(value) => write!(f, "{name} {}", i16::from_be_bytes(value.to_be_bytes())),
+        Ldc(value) | LdcW(value) | Ldc2W(value) => write!(f, "{name} {value}"),
+        ILoad(index) | LLoad(index) | FLoad(index) | DLoad(index) | ALoad(index)
+        | IStore(index) | LStore(index) | FStore(index) | DStore(index) | AStore(index)
+        | Ret(index) => write!(f, "{name} {index}"),
+        IInc(index, increment) => write!(f, "{name} {index}, {increment}"),
+        IfEq(target) | IfNe(target) | IfLt(target) | IfGe(target) | IfGt(target) | IfLe(target)
+        | IfICmpEq(target) | IfICmpNe(target) | IfICmpLt(target) | IfICmpGe(target)
+        | IfICmpGt(target) | IfICmpLe(target) | IfACmpEq(target) | IfACmpNe(target)
+        | Goto(target) | Jsr(target) | IfNull(target) | IfNonNull(target) | GotoW(target)
+        | JsrW(target) => write!(f, "{name} {}", render_target(target)),
+        TableSwitch {
+            range,
+            jump_targets,
+            default,
+        } => write!(
+            f,
+            "{name} {{ {}, default: {} }}",
+            range
+                .clone()
+                .zip(jump_targets)
+                .map(|(key, target)| format!("{key}: {}", render_target(target)))
+                .join(", "),
+            render_target(default)
+        ),
+        LookupSwitch {
+            default,
+            match_targets,
+        } => write!(
+            f,
+            "{name} {{ {}, default: {} }}",
+            match_targets
+                .iter()
+                .map(|(key, target)| format!("{key}: {}", render_target(target)))
+                .join(", "),
+            render_target(default)
+        ),
+        GetStatic(field) | PutStatic(field) | GetField(field) | PutField(field) => {
+            write!(f, "{name} {field}: {}", field.field_type)
+        }
+        InvokeVirtual(method)
+        | InvokeSpecial(method)
+        | InvokeStatic(method)
+        | InvokeInterface(method, _) => write!(f, "{name} {method}{}", method.descriptor),
+        InvokeDynamic {
+            bootstrap_method_index,
+            name: method_name,
+            descriptor,
+        } => write!(
+            f,
+            "{name} #{bootstrap_method_index}:{method_name}{descriptor}"
+        ),
+        New(class) => write!(f, "{name} {class}"),
+        NewArray(element_type) => write!(f, "{name} {element_type}"),
+        ANewArray(element_type) => write!(f, "{name} {element_type}"),
+        CheckCast(target_type) | InstanceOf(target_type) => write!(f, "{name} {target_type}"),
+        MultiANewArray(array_type, dimensions) => write!(f, "{name} {array_type}, {dimensions}"),
+        Wide(wide) => write!(f, "{name} {wide}"),
+        _ => write!(f, "{name}"),
+    }
+}
+
+impl Display for WideInstruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ILoad(index) => write!(f, "iload {index}"),
+            Self::LLoad(index) => write!(f, "lload {index}"),
+            Self::FLoad(index) => write!(f, "fload {index}"),
+            Self::DLoad(index) => write!(f, "dload {index}"),
+            Self::ALoad(index) => write!(f, "aload {index}"),
+            Self::IStore(index) => write!(f, "istore {index}"),
+            Self::LStore(index) => write!(f, "lstore {index}"),
+            Self::FStore(index) => write!(f, "fstore {index}"),
+            Self::DStore(index) => write!(f, "dstore {index}"),
+            Self::AStore(index) => write!(f, "astore {index}"),
+            Self::IInc(index, increment) => write!(f, "iinc {index}, {increment}"),
+            Self::Ret(index) => write!(f, "ret {index}"),
+        }
+    }
+}
+
+impl MethodBody {
+    /// Returns the source line of the instruction at `pc` according to the line number table.
+    #[must_use]
+    pub fn source_line(&self, pc: ProgramCounter) -> Option<u16> {
+        self.line_number_table
+            .iter()
+            .flatten()
+            .filter(|it| it.start_pc <= pc)
+            .max_by_key(|it| it.start_pc)
+            .map(|it| it.line_number)
+    }
+
+    /// Creates a printer that lists the instructions of the method body.
+    #[must_use]
+    pub fn printer(&self) -> MethodBodyPrinter<'_> {
+        MethodBodyPrinter {
+            body: self,
+            line_numbers: false,
+            local_variables: false,
+            labels: None,
+        }
+    }
+}
+
+/// Assigns labels (`L0`, `L1`, ...) to the program counters referred to by the branch
+/// instructions and the exception table of a [`MethodBody`].
+/// The labels are numbered in the order of their program counters.
+#[derive(Debug, Clone, Default)]
+pub struct LabelMap {
+    labels: BTreeMap<ProgramCounter, usize>,
+}
+
+impl LabelMap {
+    /// Collects the jump targets, and the start, end, and handler of each exception table entry
+    /// in `body`.
+    #[must_use]
+    pub fn new(body: &MethodBody) -> Self {
+        let jump_targets = body
+            .instructions
+            .iter()
+            .flat_map(|(_, instruction)| instruction.jump_targets());
+        let exception_boundaries = body.exception_table.iter().flat_map(|entry| {
+            [
+                entry.covered_pc.start,
+                entry.covered_pc.end,
+                entry.handler_pc,
+            ]
+        });
+        let targets: BTreeSet<_> = jump_targets.chain(exception_boundaries).collect();
+        Self {
+            labels: targets
+                .into_iter()
+                .enumerate()
+                .map(|(n, pc)| (pc, n))
+                .collect(),
+        }
+    }
+
+    /// Returns the label assigned to `pc`, if any.
+    #[must_use]
+    pub fn label(&self, pc: ProgramCounter) -> Option<String> {
+        self.labels.get(&pc).map(|n| format!("L{n}"))
+    }
+
+    /// Returns an iterator over the labelled program counters and their labels, in PC order.
+    pub fn iter(&self) -> impl Iterator<Item = (ProgramCounter, String)> + '_ {
+        self.labels.iter().map(|(pc, n)| (*pc, format!("L{n}")))
+    }
+
+    /// Renders `instruction` with its jump targets replaced by their labels.
+    /// Targets without a label are rendered as program counters.
+    #[must_use]
+    pub fn display<'a>(&'a self, instruction: &'a Instruction) -> impl Display + 'a {
+        LabelledInstruction {
+            labels: self,
+            instruction,
+        }
+    }
+}
+
+struct LabelledInstruction<'a> {
+    labels: &'a LabelMap,
+    instruction: &'a Instruction,
+}
+
+impl Display for LabelledInstruction<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_instruction(f, self.instruction, &|pc| {
+            self.labels.label(*pc).unwrap_or_else(|| pc.to_string())
+        })
+    }
+}
+
+/// Lists the instructions of a [`MethodBody`], optionally annotated with the debug information.
+/// Annotations are skipped if the corresponding debug information is absent.
+#[derive(Debug, Clone)]
+pub struct MethodBodyPrinter<'b> {
+    body: &'b MethodBody,
+    line_numbers: bool,
+    local_variables: bool,
+    labels: Option<LabelMap>,
+}
+
+impl MethodBodyPrinter<'_> {
+    /// Sets whether to print the source line before the instructions of each line.
+    #[must_use]
+    pub fn with_line_numbers(self, line_numbers: bool) -> Self {
+        Self {
+            line_numbers,
+            ..self
+        }
+    }
+
+    /// Sets whether to annotate each instruction with the names of the local variables in scope.
+    #[must_use]
+    pub fn with_local_variables(self, local_variables: bool) -> Self {
+        Self {
+            local_variables,
+            ..self
+        }
+    }
+
+    /// Sets whether to print the jump targets as labels (see [`LabelMap`]).
+    #[must_use]
+    pub fn with_labels(self, labels: bool) -> Self {
+        Self {
+            labels: labels.then(|| LabelMap::new(self.body)),
+            ..self
+        }
+    }
+
+    fn local_variables_at(&self, pc: ProgramCounter) -> Vec<&str> {
+        let Some(local_variable_table) = self.body.local_variable_table.as_ref() else {
+            return Vec::new();
+        };
+        local_variable_table
+            .entries()
+            .filter(|(id, _)| id.effective_range.contains(&pc))
+            .sorted_by_key(|(id, _)| id.index)
+            .filter_map(|(_, entry)| entry.name.as_deref())
+            .collect()
+    }
+}
+
+impl Display for MethodBodyPrinter<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut current_line = None;
+        for (pc, instruction) in &self.body.instructions {
+            if self.line_numbers {
+                let line = self.body.source_line(*pc);
+                if line.is_some() && line != current_line {
+                    current_line = line;
+                    writeln!(f, "// line {}", line.unwrap_or_default())?;
+                }
+            }
+            if let Some(labels) = self.labels.as_ref() {
+                if let Some(label) = labels.label(*pc) {
+                    writeln!(f, "{label}:")?;
+                }
+                write!(f, "{pc}: {}", labels.display(instruction))?;
+            } else {
+                write!(f, "{pc}: {instruction}")?;
+            }
+            if self.local_variables {
+                let locals = self.local_variables_at(*pc);
+                if !locals.is_empty() {
+                    write!(f, " // locals: {}", locals.join(", "))?;
+                }
+            }
+            writeln!(f)?;
+        }
+        // Labels past the last instruction, e.g., the end of an exception range.
+        let end = self.body.instructions.last_instruction().map(|(pc, _)| *pc);
+        for (_, label) in self
+            .labels
+            .iter()
+            .flat_map(LabelMap::iter)
+            .filter(|(pc, _)| Some(*pc) > end)
+        {
+            writeln!(f, "{label}:")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jvm::{
+        code::{
+            ExceptionTableEntry, InstructionList, LineNumberTableEntry, LocalVariableId,
+            LocalVariableTable,
+        },
+        references::{ClassRef, MethodRef},
+    };
+
+    fn body() -> MethodBody {
+        MethodBody {
+            instructions: InstructionList::from([
+                (0.into(), Instruction::ALoad0),
+                (
+                    1.into(),
+                    Instruction::InvokeVirtual(MethodRef {
+                        owner: ClassRef::new("org/mokapot/Foo"),
+                        name: "run".to_owned(),
+                        descriptor: "()V".parse().unwrap(),
+                    }),
+                ),
+                (4.into(), Instruction::BiPush(0xFF)),
+                (6.into(), Instruction::IStore1),
+                (7.into(), Instruction::Wide(WideInstruction::IInc(1, 300))),
+                (13.into(), Instruction::Return),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn instructions() {
+        assert_eq!("bipush -1", Instruction::BiPush(0xFF).to_string());
+        assert_eq!("iinc 1, -2", Instruction::IInc(1, -2).to_string());
+        assert_eq!("goto #000A", Instruction::Goto(10.into()).to_string());
+        assert_eq!(
+            "wide iinc 300, 1",
+            Instruction::Wide(WideInstruction::IInc(300, 1)).to_string()
+        );
+    }
+
+    #[test]
+    fn without_debug_info() {
+        let body = body();
+        let printed = body
+            .printer()
+            .with_line_numbers(true)
+            .with_local_variables(true)
+            .to_string();
+        assert_eq!(body.printer().to_string(), printed);
+        assert_eq!(6, printed.lines().count());
+    }
+
+    #[test]
+    fn with_debug_info() {
+        let mut local_variable_table = LocalVariableTable::default();
+        for (index, name, range) in [(0, "this", 0..14), (1, "count", 7..14)] {
+            let id = LocalVariableId {
+                effective_range: range.start.into()..range.end.into(),
+                index,
+            };
+            local_variable_table
+                .merge_type(id, name.to_owned(), "I".parse().unwrap())
+                .unwrap();
+        }
+        let body = MethodBody {
+            line_number_table: Some(vec![
+                LineNumberTableEntry {
+                    start_pc: 0.into(),
+                    line_number: 3,
+                },
+                LineNumberTableEntry {
+                    start_pc: 4.into(),
+                    line_number: 4,
+                },
+            ]),
+            local_variable_table: Some(local_variable_table),
+            ..body()
+        };
+        assert_eq!(Some(4), body.source_line(6.into()));
+        let printed = body
+            .printer()
+            .with_line_numbers(true)
+            .with_local_variables(true)
+            .to_string();
+        let lines: Vec<_> = printed.lines().collect();
+        assert_eq!("// line 3", lines[0]);
+        assert_eq!("#0000: aload_0 // locals: this", lines[1]);
+        assert_eq!("// line 4", lines[3]);
+        assert_eq!("#0007: wide iinc 1, 300 // locals: this, count", lines[6]);
+    }
+
+    #[test]
+    fn labels() {
+        let body = MethodBody {
+            instructions: InstructionList::from([
+                (0.into(), Instruction::ILoad0),
+                (1.into(), Instruction::IfEq(9.into())),
+                (4.into(), Instruction::IConst1),
+                (5.into(), Instruction::IReturn),
+                (
+                    6.into(),
+                    Instruction::LookupSwitch {
+                        default: 4.into(),
+                        match_targets: [(1, 9.into())].into(),
+                    },
+                ),
+                (9.into(), Instruction::Goto(1.into())),
+            ]),
+            exception_table: vec![ExceptionTableEntry {
+                covered_pc: 0.into()..12.into(),
+                handler_pc: 4.into(),
+                catch_type: None,
+            }],
+            ..Default::default()
+        };
+        let labels = LabelMap::new(&body);
+        assert_eq!(Some("L0".to_owned()), labels.label(0.into()));
+        assert_eq!(Some("L1".to_owned()), labels.label(1.into()));
+        assert_eq!(Some("L2".to_owned()), labels.label(4.into()));
+        assert_eq!(Some("L3".to_owned()), labels.label(9.into()));
+        assert_eq!(Some("L4".to_owned()), labels.label(12.into()));
+        assert_eq!(None, labels.label(5.into()));
+        assert_eq!(
+            "goto L3",
+            labels.display(&Instruction::Goto(9.into())).to_string()
+        );
+        assert_eq!(
+            "goto #0005",
+            labels.display(&Instruction::Goto(5.into())).to_string()
+        );
+
+        let printed = body.printer().with_labels(true).to_string();
+        let lines: Vec<_> = printed.lines().collect();
+        assert_eq!(
+            vec![
+                "L0:",
+                "#0000: iload_0",
+                "L1:",
+                "#0001: ifeq L3",
+                "L2:",
+                "#0004: iconst_1",
+                "#0005: ireturn",
+                "#0006: lookupswitch { 1: L3, default: L2 }",
+                "L3:",
+                "#0009: goto L1",
+                "L4:",
+            ],
+            lines
+        );
+    }
+}
@@ -2,7 +2,14 @@
 
 use bitflags::bitflags;
 
-use super::{references::MethodRef, Method};
+use crate::macros::see_jvm_spec;
+
+use super::{
+    annotation::TargetKind,
+    code::VerificationType,
+    references::{ClassRef, MethodRef},
+    Method, TypeAnnotation,
+};
 
 /// A generic type signature for a method.
 pub type Signature = String;
@@ -25,6 +32,84 @@ impl Method {
         self.name == Self::CLASS_INITIALIZER_NAME
     }
 
+    /// Returns the type of the receiver, i.e., `this`, in local variable 0 when the method is
+    /// invoked, or `None` for a static method.
+    /// The receiver of a constructor is `uninitializedThis` until the constructor of the super
+    /// class or another constructor of the same class is invoked, except in `java/lang/Object`,
+    /// which has no super class. Otherwise, it is the [owner](Method::owner) of the method.
+    #[doc = see_jvm_spec!(4, 10, 1, 6)]
+    #[must_use]
+    pub fn receiver_type(&self) -> Option<VerificationType> {
+        if self.access_flags.contains(AccessFlags::STATIC) {
+            None
+        } else if self.is_constructor() && self.owner.binary_name != "java/lang/Object" {
+            Some(VerificationType::UninitializedThisVariable)
+        } else {
+            Some(VerificationType::ObjectVariable(self.owner.clone()))
+        }
+    }
+
+    /// Returns the types of the local variables when the method is invoked, indexed by their
+    /// local variable slots.
+    /// The [receiver](Method::receiver_type) comes first for instance methods, followed by the
+    /// parameters. A `long` or `double` parameter occupies two slots, the second of which is
+    /// `top`.
+    #[doc = see_jvm_spec!(4, 10, 1, 6)]
+    #[must_use]
+    pub fn initial_frame(&self) -> Vec<VerificationType> {
+        let mut locals: Vec<_> = self.receiver_type().into_iter().collect();
+        for parameter_type in &self.descriptor.parameters_types {
+            let verification_type = VerificationType::from_field_type(parameter_type);
+            let is_wide = matches!(
+                verification_type,
+                VerificationType::LongVariable | VerificationType::DoubleVariable
+            );
+            locals.push(verification_type);
+            if is_wide {
+                locals.push(VerificationType::TopVariable);
+            }
+        }
+        locals
+    }
+
+    /// Returns the checked exceptions declared to be thrown by the method in its `throws` clause,
+    /// i.e., the classes in the `Exceptions` attribute.
+    /// The JVM does not enforce them, so the method may throw other exceptions.
+    #[doc = see_jvm_spec!(4, 7, 5)]
+    #[must_use]
+    pub fn thrown_exceptions(&self) -> &[ClassRef] {
+        &self.exceptions
+    }
+
+    /// Returns the type annotations on the targets of the given kind, e.g., the annotations on
+    /// the types of the formal parameters for [`TargetKind::FormalParameter`].
+    /// Both the runtime visible and invisible type annotations are included, first those on the
+    /// method and then those in its [body](Method::body), where javac puts the annotations on the
+    /// types in the code, e.g., local variables and casts.
+    pub fn type_annotations_on(&self, kind: TargetKind) -> impl Iterator<Item = &TypeAnnotation> {
+        let body_annotations = self.body.iter().flat_map(|body| {
+            body.runtime_visible_type_annotations
+                .iter()
+                .chain(&body.runtime_invisible_type_annotations)
+        });
+        self.runtime_visible_type_annotations
+            .iter()
+            .chain(&self.runtime_invisible_type_annotations)
+            .chain(body_annotations)
+            .filter(move |it| it.target_kind == kind)
+    }
+
+    /// Discards the raw `Code` attribute from the [`raw_attributes`](Method::raw_attributes) and
+    /// the raw bytes retained in the [body](Method::body), see
+    /// [`MethodBody::discard_raw`](crate::jvm::code::MethodBody::discard_raw).
+    /// The transformations in this crate call it when they modify the body of the method.
+    pub fn discard_raw_code(&mut self) {
+        self.raw_attributes.retain(|(name, _)| name != "Code");
+        if let Some(body) = self.body.as_mut() {
+            body.discard_raw();
+        }
+    }
+
     /// Creates a [`MethodRef`] pointting to this method.
     #[must_use]
     pub fn as_ref(&self) -> MethodRef {
@@ -91,7 +176,7 @@ bitflags! {
 
 #[cfg(test)]
 mod tests {
-    use crate::{jvm::references::ClassRef, tests::arb_identifier};
+    use crate::tests::arb_identifier;
 
     use super::*;
     use proptest::prelude::*;
@@ -102,6 +187,7 @@ mod tests {
             access_flags: AccessFlags::PUBLIC,
             descriptor: "()V".parse().unwrap(),
             owner: ClassRef::new("org/mokapot/Test"),
+            index: 0,
             body: None,
             exceptions: vec![],
             runtime_visible_annotations: vec![],
@@ -116,6 +202,7 @@ mod tests {
             is_deprecated: false,
             signature: None,
             free_attributes: vec![],
+            raw_attributes: vec![],
         }
     }
 
@@ -180,4 +267,38 @@ mod tests {
             assert_eq!(lhs.bits() & rhs.bits(), 0);
         }
     }
+
+    #[test]
+    fn initial_frame() {
+        let mut method = empty_method("run".to_owned());
+        method.descriptor = "(J[ILjava/lang/String;D)V".parse().unwrap();
+        assert_eq!(
+            vec![
+                VerificationType::ObjectVariable(ClassRef::new("org/mokapot/Test")),
+                VerificationType::LongVariable,
+                VerificationType::TopVariable,
+                VerificationType::ObjectVariable(ClassRef::new("[I")),
+                VerificationType::ObjectVariable(ClassRef::new("java/lang/String")),
+                VerificationType::DoubleVariable,
+                VerificationType::TopVariable,
+            ],
+            method.initial_frame()
+        );
+
+        method.access_flags |= AccessFlags::STATIC;
+        method.descriptor = "(ZF)V".parse().unwrap();
+        assert_eq!(
+            vec![
+                VerificationType::IntegerVariable,
+                VerificationType::FloatVariable
+            ],
+            method.initial_frame()
+        );
+
+        let constructor = empty_method(Method::CONSTRUCTOR_NAME.to_owned());
+        assert_eq!(
+            vec![VerificationType::UninitializedThisVariable],
+            constructor.initial_frame()
+        );
+    }
 }
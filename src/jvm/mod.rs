@@ -1,6 +1,6 @@
 //! JVM elements, such as classes, methods, fields, and annotations.
 
-use itertools::Itertools;
+use std::fmt::Write;
 
 use crate::{
     macros::see_jvm_spec,
@@ -29,8 +29,11 @@ pub struct ClassLoader<P> {
 }
 
 /// A JVM class
+///
+/// Its [`Debug`](std::fmt::Debug) output only shows the key declarations of the class, use
+/// [`Class::debug_verbose`] to show everything.
 #[doc = see_jvm_spec!(4)]
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct Class {
     /// The version of the class file.
     pub version: class::Version,
@@ -43,9 +46,9 @@ pub struct Class {
     pub super_class: Option<ClassRef>,
     /// The interfaces implemented by the class.
     pub interfaces: Vec<ClassRef>,
-    /// The fields declared the class.
+    /// The fields declared the class, in the order they appear in the class file.
     pub fields: Vec<Field>,
-    /// The methods declared in the class.
+    /// The methods declared in the class, in the order they appear in the class file.
     pub methods: Vec<Method>,
     /// The path to the source file of the class.
     pub source_file: Option<String>,
@@ -87,6 +90,11 @@ pub struct Class {
     pub record: Option<Vec<class::RecordComponent>>,
     /// Unrecognized JVM attributes.
     pub free_attributes: Vec<(String, Vec<u8>)>,
+    /// The names and the original bytes of all the attributes, including the recognized ones, in
+    /// the order in which they appear in the class file. The bytes refer to the constant pool of
+    /// the class file.
+    /// Only populated when parsing with [`ParseOptions::retain_raw_attributes`](crate::jvm::parsing::ParseOptions::retain_raw_attributes).
+    pub raw_attributes: Vec<(String, Vec<u8>)>,
 }
 
 /// An annotation on a class, field, method, or parameter.
@@ -112,6 +120,9 @@ pub struct TypeAnnotation {
     /// Denotes which type of declaration this annotation is on.
     #[doc = see_jvm_spec!(4, 7, 20, 1)]
     pub target_info: annotation::TargetInfo,
+    /// The kind of the annotated type, e.g., whether it is the return type or the receiver type
+    /// of a method.
+    pub target_kind: annotation::TargetKind,
     /// The path to the annotated type.
     #[doc = see_jvm_spec!(4, 7, 20, 2)]
     pub target_path: Vec<annotation::TypePathElement>,
@@ -149,6 +160,11 @@ pub struct Field {
     pub runtime_invisible_type_annotations: Vec<TypeAnnotation>,
     /// Unrecognized JVM attributes.
     pub free_attributes: Vec<(String, Vec<u8>)>,
+    /// The names and the original bytes of all the attributes, including the recognized ones, in
+    /// the order in which they appear in the class file. The bytes refer to the constant pool of
+    /// the class file.
+    /// Only populated when parsing with [`ParseOptions::retain_raw_attributes`](crate::jvm::parsing::ParseOptions::retain_raw_attributes).
+    pub raw_attributes: Vec<(String, Vec<u8>)>,
 }
 
 /// A JVM method.
@@ -163,6 +179,8 @@ pub struct Method {
     pub descriptor: MethodDescriptor,
     /// The class containing the method.
     pub owner: ClassRef,
+    /// The position of the method in the `methods` table of the class file it was parsed from.
+    pub index: usize,
     /// The body of the method if it is not `abstract` or `native`.
     pub body: Option<code::MethodBody>,
     /// The checked exceptions that may be thrown by the method.
@@ -191,6 +209,11 @@ pub struct Method {
     pub signature: Option<method::Signature>,
     /// Unrecognized JVM attributes.
     pub free_attributes: Vec<(String, Vec<u8>)>,
+    /// The names and the original bytes of all the attributes, including the recognized ones, in
+    /// the order in which they appear in the class file. The bytes refer to the constant pool of
+    /// the class file.
+    /// Only populated when parsing with [`ParseOptions::retain_raw_attributes`](crate::jvm::parsing::ParseOptions::retain_raw_attributes).
+    pub raw_attributes: Vec<(String, Vec<u8>)>,
 }
 
 /// A JVM module.
@@ -216,53 +239,164 @@ pub struct Module {
 }
 
 /// A string in the JVM bytecode.
-#[derive(PartialEq, Eq, Debug, Clone, PartialOrd, Ord, derive_more::Display)]
+#[derive(PartialEq, Eq, Debug, Clone, PartialOrd, Ord)]
 #[cfg_attr(test, derive(proptest_derive::Arbitrary))]
 pub enum JavaString {
     /// A valid UTF-8 string.
-    #[display("String(\"{_0}\")")]
     Utf8(String),
     /// An string that is not valid UTF-8.
-    #[display("String({}) // Invalid UTF-8", _0.iter().map(|it| format!("0x{it:02X}")).join(" "))]
     InvalidUtf8(Vec<u8>),
 }
 
 /// Denotes a compile-time constant value.
 #[doc = see_jvm_spec!(4, 4)]
-#[derive(Debug, Clone, derive_more::Display)]
+#[derive(Debug, Clone)]
 pub enum ConstantValue {
     /// The `null` value.
-    #[display("null")]
     Null,
     /// A primitive integer value (i.e., `int`).
-    #[display("int({_0})")]
     Integer(i32),
     /// A primitive floating point value (i.e., `float`).
-    #[display("float({_0})")]
     Float(f32),
     /// A primitive long value (i.e., `long`).
-    #[display("long({_0})")]
     Long(i64),
     /// A primitive double value (i.e., `double`).
-    #[display("double({_0})")]
     Double(f64),
     /// A string literal.
-    #[display("{_0}")]
     String(JavaString),
     /// A class literal.
-    #[display("{_0}.class")]
     Class(ClassRef),
     /// A method handle.
-    #[display("{_0:?}")]
     Handle(MethodHandle),
     /// A method type.
-    #[display("{_0:?}")]
     MethodType(MethodDescriptor),
     /// A dynamic constant.
-    #[display("Dynamic({_0}, {_1}, {_2})")]
     Dynamic(u16, String, FieldType),
 }
 
+impl JavaString {
+    /// Returns the string as a `&str`, or `None` if it is not valid UTF-8.
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Utf8(string) => Some(string),
+            Self::InvalidUtf8(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for JavaString {
+    /// Formats the string as a quoted and escaped Java string literal.
+    /// An invalid UTF-8 string is decoded as modified UTF-8 into UTF-16 code units, and units that
+    /// are not characters on their own (e.g., unpaired surrogates) are escaped as `\uNNNN`, so the
+    /// output is a valid Java literal.
+    /// A byte that does not start a well-formed sequence is taken as the Latin-1 character with
+    /// the same value.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_char('"')?;
+        match self {
+            Self::Utf8(string) => {
+                for c in string.chars() {
+                    write_escaped(f, c)?;
+                }
+            }
+            Self::InvalidUtf8(bytes) => {
+                for unit in utf16_units(bytes) {
+                    match char::from_u32(u32::from(unit)) {
+                        Some(c) => write_escaped(f, c)?,
+                        None => write!(f, "\\u{unit:04x}")?,
+                    }
+                }
+            }
+        }
+        f.write_char('"')
+    }
+}
+
+/// Decodes modified UTF-8 into UTF-16 code units without pairing surrogates.
+fn utf16_units(bytes: &[u8]) -> impl Iterator<Item = u16> + '_ {
+    let is_continuation = |byte: Option<&u8>| byte.is_some_and(|b| b & 0xC0 == 0x80);
+    let mut rest = bytes;
+    std::iter::from_fn(move || {
+        let (&first, tail) = rest.split_first()?;
+        let first_bits = u16::from(first);
+        let (unit, length) = match first {
+            0xC0..=0xDF if is_continuation(tail.first()) => {
+                ((first_bits & 0x1F) << 6 | u16::from(tail[0] & 0x3F), 2)
+            }
+            0xE0..=0xEF if is_continuation(tail.first()) && is_continuation(tail.get(1)) => (
+                (first_bits & 0x0F) << 12
+                    | u16::from(tail[0] & 0x3F) << 6
+                    | u16::from(tail[1] & 0x3F),
+                3,
+            ),
+            _ => (first_bits, 1),
+        };
+        rest = &rest[length..];
+        Some(unit)
+    })
+}
+
+fn write_escaped(f: &mut std::fmt::Formatter<'_>, c: char) -> std::fmt::Result {
+    match c {
+        '"' => f.write_str("\\\""),
+        '\\' => f.write_str("\\\\"),
+        '\n' => f.write_str("\\n"),
+        '\r' => f.write_str("\\r"),
+        '\t' => f.write_str("\\t"),
+        '\u{8}' => f.write_str("\\b"),
+        '\u{c}' => f.write_str("\\f"),
+        c if c.is_control() => write!(f, "\\u{:04x}", u32::from(c)),
+        c => f.write_char(c),
+    }
+}
+
+impl std::fmt::Display for ConstantValue {
+    /// Formats the constant as a Java literal.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Null => f.write_str("null"),
+            Self::Integer(value) => write!(f, "{value}"),
+            Self::Long(value) => write!(f, "{value}L"),
+            Self::Float(value) if value.is_nan() => f.write_str("Float.NaN"),
+            Self::Float(value) if value.is_infinite() => {
+                let sign = if value.is_sign_positive() {
+                    "POSITIVE"
+                } else {
+                    "NEGATIVE"
+                };
+                write!(f, "Float.{sign}_INFINITY")
+            }
+            Self::Float(value) => write!(f, "{value:?}f"),
+            Self::Double(value) if value.is_nan() => f.write_str("Double.NaN"),
+            Self::Double(value) if value.is_infinite() => {
+                let sign = if value.is_sign_positive() {
+                    "POSITIVE"
+                } else {
+                    "NEGATIVE"
+                };
+                write!(f, "Double.{sign}_INFINITY")
+            }
+            Self::Double(value) => write!(f, "{value:?}"),
+            Self::String(string) => write!(f, "{string}"),
+            Self::Class(class) if class.binary_name.starts_with('[') => {
+                match class.binary_name.parse::<FieldType>() {
+                    Ok(array_type) => write!(f, "{}.class", array_type.qualified_name()),
+                    Err(_) => write!(f, "{}.class", class.dotted_name()),
+                }
+            }
+            Self::Class(class) => write!(f, "{}.class", class.dotted_name()),
+            Self::Handle(handle) => write!(f, "{handle}"),
+            Self::MethodType(descriptor) => write!(f, "{}", descriptor.qualified_name()),
+            Self::Dynamic(bootstrap_method_index, name, field_type) => write!(
+                f,
+                "Dynamic(#{bootstrap_method_index}, {name}, {})",
+                field_type.qualified_name()
+            ),
+        }
+    }
+}
+
 impl PartialEq<Self> for ConstantValue {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
@@ -332,3 +466,54 @@ impl Ord for ConstantValue {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jvm::references::MethodRef;
+
+    #[test]
+    fn java_literals() {
+        assert_eq!("null", ConstantValue::Null.to_string());
+        assert_eq!("-3", ConstantValue::Integer(-3).to_string());
+        assert_eq!("3L", ConstantValue::Long(3).to_string());
+        assert_eq!("1.0f", ConstantValue::Float(1.0).to_string());
+        assert_eq!("0.5", ConstantValue::Double(0.5).to_string());
+        assert_eq!("Float.NaN", ConstantValue::Float(f32::NAN).to_string());
+        assert_eq!(
+            "Double.NEGATIVE_INFINITY",
+            ConstantValue::Double(f64::NEG_INFINITY).to_string()
+        );
+        assert_eq!(
+            "java.lang.String.class",
+            ConstantValue::Class(ClassRef::new("java/lang/String")).to_string()
+        );
+        assert_eq!(
+            "int[][].class",
+            ConstantValue::Class(ClassRef::new("[[I")).to_string()
+        );
+        assert_eq!(
+            "(int,java.lang.String)void",
+            ConstantValue::MethodType("(ILjava/lang/String;)V".parse().unwrap()).to_string()
+        );
+        let handle = MethodHandle::RefInvokeStatic(MethodRef {
+            owner: ClassRef::new("java/lang/Integer"),
+            name: "valueOf".to_owned(),
+            descriptor: "(I)Ljava/lang/Integer;".parse().unwrap(),
+        });
+        assert_eq!(
+            "REF_invokeStatic java.lang.Integer.valueOf:(int)java.lang.Integer",
+            ConstantValue::Handle(handle).to_string()
+        );
+    }
+
+    #[test]
+    fn string_escapes() {
+        let string = JavaString::Utf8("say \"hi\"\\\n\t\u{1}é".to_owned());
+        assert_eq!(r#""say \"hi\"\\\n\t\u0001é""#, string.to_string());
+        let invalid = JavaString::InvalidUtf8(vec![b'a', b'"', 0xED, 0xA0, 0x80, 0xC0, 0x80, 0xFF]);
+        assert_eq!(r#""a\"\ud800\u0000ÿ""#, invalid.to_string());
+        assert_eq!(None, invalid.as_str());
+        assert_eq!(Some("say \"hi\"\\\n\t\u{1}é"), string.as_str());
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
     jvm::{
-        annotation::{ElementValue, TargetInfo, TypePathElement},
-        class::constant_pool,
+        annotation::{ElementValue, TargetInfo, TargetKind, TypePathElement},
+        class::ConstantPool,
         code::LocalVariableId,
         Annotation, ConstantValue, TypeAnnotation,
     },
@@ -52,12 +52,15 @@ impl ClassElement for TypeAnnotation {
 
     fn from_raw(raw: Self::Raw, ctx: &Context) -> Result<Self, Error> {
         let Self::Raw {
+            target_type,
             target_info,
             target_path,
             type_index,
             element_value_pairs,
         } = raw;
 
+        let target_kind = TargetKind::from_target_type(target_type)
+            .ok_or(Error::InvalidTargetType(target_type))?;
         let target_info = TargetInfo::from_raw(target_info, ctx)?;
         let target_path = target_path
             .into_iter()
@@ -75,6 +78,7 @@ impl ClassElement for TypeAnnotation {
         Ok(TypeAnnotation {
             annotation_type,
             target_info,
+            target_kind,
             target_path,
             element_value_pairs,
         })
@@ -127,45 +131,7 @@ impl ClassElement for ElementValue {
     fn from_raw(raw: Self::Raw, ctx: &Context) -> Result<Self, Error> {
         let cp = &ctx.constant_pool;
         match raw {
-            Self::Raw::Const(b'B', idx) => match cp.get_constant_value(idx)? {
-                it @ ConstantValue::Integer(_) => Ok(Self::Primitive(PrimitiveType::Byte, it)),
-                _ => Err(Error::Other("Expected integer constant value")),
-            },
-            Self::Raw::Const(b'C', idx) => match cp.get_constant_value(idx)? {
-                it @ ConstantValue::Integer(_) => Ok(Self::Primitive(PrimitiveType::Char, it)),
-                _ => Err(Error::Other("Expected integer constant value")),
-            },
-            Self::Raw::Const(b'I', idx) => match cp.get_constant_value(idx)? {
-                it @ ConstantValue::Integer(_) => Ok(Self::Primitive(PrimitiveType::Int, it)),
-                _ => Err(Error::Other("Expected integer constant value")),
-            },
-            Self::Raw::Const(b'S', idx) => match cp.get_constant_value(idx)? {
-                it @ ConstantValue::Integer(_) => Ok(Self::Primitive(PrimitiveType::Short, it)),
-                _ => Err(Error::Other("Expected integer constant value")),
-            },
-            Self::Raw::Const(b'Z', idx) => match cp.get_constant_value(idx)? {
-                it @ ConstantValue::Integer(_) => Ok(Self::Primitive(PrimitiveType::Boolean, it)),
-                _ => Err(Error::Other("Expected integer constant value")),
-            },
-            Self::Raw::Const(b'D', idx) => match cp.get_constant_value(idx)? {
-                it @ ConstantValue::Double(_) => Ok(Self::Primitive(PrimitiveType::Double, it)),
-                _ => Err(Error::Other("Expected double constant value")),
-            },
-            Self::Raw::Const(b'F', idx) => match cp.get_constant_value(idx)? {
-                it @ ConstantValue::Float(_) => Ok(Self::Primitive(PrimitiveType::Float, it)),
-                _ => Err(Error::Other("Expected float constant value")),
-            },
-            Self::Raw::Const(b'J', idx) => match cp.get_constant_value(idx)? {
-                it @ ConstantValue::Long(_) => Ok(Self::Primitive(PrimitiveType::Long, it)),
-                _ => Err(Error::Other("Expected long constant value")),
-            },
-            Self::Raw::Const(b's', idx) => match cp.get_entry(idx)? {
-                constant_pool::Entry::Utf8(s) => {
-                    Ok(Self::String(ConstantValue::String(s.to_owned())))
-                }
-                _ => Err(Error::Other("Expected string constant value")),
-            },
-            Self::Raw::Const(_, _) => Err(Error::Other("Invalid constant value tag")),
+            Self::Raw::Const(tag, idx) => Self::from_const(tag, idx, cp),
             Self::Raw::Enum {
                 type_name_index,
                 const_name_index,
@@ -194,3 +160,50 @@ impl ClassElement for ElementValue {
         }
     }
 }
+
+impl ElementValue {
+    /// Resolves a constant element value.
+    /// This is separate from [`ElementValue::from_raw`] to keep the stack frames of the recursion
+    /// over nested element values small.
+    fn from_const(tag: u8, idx: u16, cp: &ConstantPool) -> Result<Self, Error> {
+        match tag {
+            b'B' => match cp.get_constant_value(idx)? {
+                it @ ConstantValue::Integer(_) => Ok(Self::Primitive(PrimitiveType::Byte, it)),
+                _ => Err(Error::Other("Expected integer constant value")),
+            },
+            b'C' => match cp.get_constant_value(idx)? {
+                it @ ConstantValue::Integer(_) => Ok(Self::Primitive(PrimitiveType::Char, it)),
+                _ => Err(Error::Other("Expected integer constant value")),
+            },
+            b'I' => match cp.get_constant_value(idx)? {
+                it @ ConstantValue::Integer(_) => Ok(Self::Primitive(PrimitiveType::Int, it)),
+                _ => Err(Error::Other("Expected integer constant value")),
+            },
+            b'S' => match cp.get_constant_value(idx)? {
+                it @ ConstantValue::Integer(_) => Ok(Self::Primitive(PrimitiveType::Short, it)),
+                _ => Err(Error::Other("Expected integer constant value")),
+            },
+            b'Z' => match cp.get_constant_value(idx)? {
+                it @ ConstantValue::Integer(_) => Ok(Self::Primitive(PrimitiveType::Boolean, it)),
+                _ => Err(Error::Other("Expected integer constant value")),
+            },
+            b'D' => match cp.get_constant_value(idx)? {
+                it @ ConstantValue::Double(_) => Ok(Self::Primitive(PrimitiveType::Double, it)),
+                _ => Err(Error::Other("Expected double constant value")),
+            },
+            b'F' => match cp.get_constant_value(idx)? {
+                it @ ConstantValue::Float(_) => Ok(Self::Primitive(PrimitiveType::Float, it)),
+                _ => Err(Error::Other("Expected float constant value")),
+            },
+            b'J' => match cp.get_constant_value(idx)? {
+                it @ ConstantValue::Long(_) => Ok(Self::Primitive(PrimitiveType::Long, it)),
+                _ => Err(Error::Other("Expected long constant value")),
+            },
+            b's' => cp
+                .get_java_string(idx)
+                .cloned()
+                .map(|it| Self::String(ConstantValue::String(it))),
+            _ => Err(Error::Other("Invalid constant value tag")),
+        }
+    }
+}
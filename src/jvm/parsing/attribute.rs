@@ -5,7 +5,7 @@ use itertools::Itertools;
 use crate::{
     jvm::{
         annotation::ElementValue,
-        class::{BootstrapMethod, EnclosingMethod, InnerClassInfo, RecordComponent},
+        class::{BootstrapMethod, ConstantPool, EnclosingMethod, InnerClassInfo, RecordComponent},
         code::{LineNumberTableEntry, MethodBody, StackMapFrame},
         method::ParameterInfo,
         references::{ClassRef, PackageRef},
@@ -16,17 +16,19 @@ use crate::{
 
 use super::{
     code::{LocalVariableDescAttr, LocalVariableTypeAttr},
+    errors::EofContext,
     jvm_element_parser::ClassElement,
+    raw_attributes,
     reader_utils::{read_byte_chunk, ReadBytes, ValueReaderExt},
     Context, Error,
 };
 
 /// Represent an attribute of a class file, method, field, or code.
 #[doc = see_jvm_spec!(4, 7)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct AttributeInfo {
-    name_idx: u16,
-    info: Vec<u8>,
+    pub(super) name_idx: u16,
+    pub(super) info: Vec<u8>,
 }
 
 impl AttributeInfo {
@@ -35,6 +37,32 @@ impl AttributeInfo {
     }
 }
 
+/// Reads an `attributes` table of `owner`, e.g., `method #3`.
+/// A premature end of the input is reported as [`Error::UnexpectedEof`] naming the attribute
+/// being read.
+pub(super) fn read_attributes<R: Read + ?Sized>(
+    reader: &mut R,
+    constant_pool: &ConstantPool,
+    owner: &str,
+) -> Result<Vec<AttributeInfo>, Error> {
+    let attributes_table = || format!("the attributes of {owner}");
+    let attributes_count: u16 = reader.read_value().while_parsing(attributes_table)?;
+    (0..attributes_count)
+        .map(|_| {
+            let name_idx = reader.read_value().while_parsing(attributes_table)?;
+            let attribute = || match constant_pool.get_str(name_idx) {
+                Ok(name) => format!("the {name} attribute of {owner}"),
+                Err(_) => format!("an attribute of {owner}"),
+            };
+            let attribute_length: u32 = reader.read_value().while_parsing(attribute)?;
+            let attribute_length = usize::try_from(attribute_length)
+                .expect("32-bit size is not supported on the current platform");
+            let info = read_byte_chunk(reader, attribute_length).while_parsing(attribute)?;
+            Ok(AttributeInfo::from_raw_parts(name_idx, info))
+        })
+        .collect()
+}
+
 impl ReadBytes for AttributeInfo {
     fn read_bytes<R: Read + ?Sized>(reader: &mut R) -> io::Result<Self> {
         let name_idx = reader.read_value()?;
@@ -46,7 +74,25 @@ impl ReadBytes for AttributeInfo {
     }
 }
 
-#[derive(Debug)]
+/// Returns the names and the bytes of `attributes` if they are to be retained according to `ctx`,
+/// or an empty list otherwise.
+pub(crate) fn retained_attributes(
+    attributes: &[AttributeInfo],
+    ctx: &Context,
+) -> Result<Vec<(String, Vec<u8>)>, Error> {
+    if !ctx.retain_raw_attributes {
+        return Ok(Vec::new());
+    }
+    attributes
+        .iter()
+        .map(|it| {
+            let name = ctx.constant_pool.get_str(it.name_idx)?;
+            Ok((name.to_owned(), it.info.clone()))
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
 #[non_exhaustive]
 pub(crate) enum Attribute {
     ConstantValue(ConstantValue),
@@ -168,22 +214,34 @@ impl ClassElement for Attribute {
             "LineNumberTable" => parse![u16; reader, ctx => LineNumberTable],
             "LocalVariableTable" => parse![u16; reader, ctx => LocalVariableTable],
             "LocalVariableTypeTable" => parse![u16; reader, ctx => LocalVariableTypeTable],
-            "RuntimeVisibleAnnotations" => parse![u16; reader, ctx => RuntimeVisibleAnnotations],
+            "RuntimeVisibleAnnotations" => {
+                parse![u16; reader, || parse_annotation(reader, ctx) => RuntimeVisibleAnnotations]
+            }
             "RuntimeInvisibleAnnotations" => {
-                parse![u16; reader, ctx => RuntimeInvisibleAnnotations]
+                parse![u16; reader, || parse_annotation(reader, ctx) => RuntimeInvisibleAnnotations]
             }
-            "RuntimeVisibleParameterAnnotations" => parse![u8; reader, || parse![u16; reader, ctx]]
-                .map(Self::RuntimeVisibleParameterAnnotations),
-            "RuntimeInvisibleParameterAnnotations" => {
-                parse![u8; reader, || parse![u16; reader, ctx] => RuntimeInvisibleParameterAnnotations]
+            "RuntimeVisibleParameterAnnotations" => {
+                parse![u8; reader, || parse![u16; reader, || parse_annotation(reader, ctx)]]
+                    .map(Self::RuntimeVisibleParameterAnnotations)
             }
-            "RuntimeVisibleTypeAnnotations" => {
-                parse![u16; reader, ctx => RuntimeVisibleTypeAnnotations]
+            "RuntimeInvisibleParameterAnnotations" => {
+                parse![u8; reader, || parse![u16; reader, || parse_annotation(reader, ctx)]]
+                    .map(Self::RuntimeInvisibleParameterAnnotations)
             }
-            "RuntimeInvisibleTypeAnnotations" => {
-                parse![u16; reader, ctx => RuntimeInvisibleTypeAnnotations]
+            "RuntimeVisibleTypeAnnotations" => parse![u16; reader, || {
+                parse_type_annotation(reader, ctx)
+            } => RuntimeVisibleTypeAnnotations],
+            "RuntimeInvisibleTypeAnnotations" => parse![u16; reader, || {
+                parse_type_annotation(reader, ctx)
+            } => RuntimeInvisibleTypeAnnotations],
+            "AnnotationDefault" => {
+                let raw = raw_attributes::ElementValueInfo::read_from(
+                    reader,
+                    1,
+                    ctx.max_element_value_depth,
+                )?;
+                ClassElement::from_raw(raw, ctx).map(Self::AnnotationDefault)
             }
-            "AnnotationDefault" => parse!(reader, ctx => AnnotationDefault),
             "BootstrapMethods" => parse![u16; reader, ctx => BootstrapMethods],
             "MethodParameters" => parse![u8; reader, ctx => MethodParameters],
             "Module" => parse!(reader, ctx => Module),
@@ -234,3 +292,118 @@ fn parse_string<R: Read + ?Sized>(reader: &mut R, ctx: &Context) -> Result<Strin
     let str_idx = reader.read_value()?;
     ctx.constant_pool.get_str(str_idx).map(str::to_owned)
 }
+
+fn parse_annotation<R: Read + ?Sized>(reader: &mut R, ctx: &Context) -> Result<Annotation, Error> {
+    let raw = raw_attributes::Annotation::read_from(reader, 1, ctx.max_element_value_depth)?;
+    ClassElement::from_raw(raw, ctx)
+}
+
+fn parse_type_annotation<R: Read + ?Sized>(
+    reader: &mut R,
+    ctx: &Context,
+) -> Result<TypeAnnotation, Error> {
+    let raw = raw_attributes::TypeAnnotation::read_from(reader, 1, ctx.max_element_value_depth)?;
+    ClassElement::from_raw(raw, ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        jvm::{
+            class::{ConstantPool, Version},
+            parsing::ParseOptions,
+        },
+        types::method_descriptor::ReturnType,
+    };
+
+    use super::*;
+
+    fn context() -> Context {
+        let mut pool_bytes = vec![1, 0, 25];
+        pool_bytes.extend_from_slice(b"RuntimeVisibleAnnotations");
+        pool_bytes.extend_from_slice(&[1, 0, 5]);
+        pool_bytes.extend_from_slice(b"LFoo;");
+        pool_bytes.extend_from_slice(&[1, 0, 1, b'V']);
+        Context {
+            constant_pool: ConstantPool::from_reader(&mut pool_bytes.as_slice(), 4).unwrap(),
+            class_version: Version::Jdk8,
+            current_class_binary_name: "Test".to_owned(),
+            retain_raw_attributes: false,
+            retain_raw_code: false,
+            max_element_value_depth: ParseOptions::DEFAULT_MAX_ELEMENT_VALUE_DEPTH,
+        }
+    }
+
+    fn annotations_attribute(info: Vec<u8>) -> Result<Attribute, Error> {
+        Attribute::from_raw(AttributeInfo { name_idx: 1, info }, &context())
+    }
+
+    #[test]
+    fn annotations_length() {
+        // num_annotations = 1, type_index = 2, num_element_value_pairs = 0
+        let info = vec![0, 1, 0, 2, 0, 0];
+        assert!(matches!(
+            annotations_attribute(info.clone()),
+            Ok(Attribute::RuntimeVisibleAnnotations(it)) if it.len() == 1
+        ));
+
+        let mut too_long = info.clone();
+        too_long.push(0);
+        assert!(annotations_attribute(too_long).is_err());
+
+        let too_short = info[..info.len() - 1].to_vec();
+        assert!(annotations_attribute(too_short).is_err());
+    }
+
+    #[test]
+    fn void_class_literal() {
+        // num_annotations = 1, type_index = 2, num_element_value_pairs = 1, element_name_index = 2,
+        // tag = 'c', class_info_index = 3 pointing to `V`, i.e., `void.class`.
+        let info = vec![0, 1, 0, 2, 0, 1, 0, 2, b'c', 0, 3];
+        let Ok(Attribute::RuntimeVisibleAnnotations(annotations)) = annotations_attribute(info)
+        else {
+            panic!("The annotation should be parsed");
+        };
+        assert!(matches!(
+            annotations[0].element_value_pairs.as_slice(),
+            [(
+                _,
+                ElementValue::Class {
+                    return_descriptor: ReturnType::Void
+                }
+            )]
+        ));
+    }
+
+    #[test]
+    fn element_value_depth() {
+        // num_annotations = 1, type_index = 2, num_element_value_pairs = 1, element_name_index = 2,
+        // followed by `depth` nested arrays with a single element, except the innermost one.
+        let nested_arrays = |depth: usize| {
+            let mut info = vec![0, 1, 0, 2, 0, 1, 0, 2];
+            for _ in 1..depth {
+                info.extend_from_slice(&[b'[', 0, 1]);
+            }
+            info.extend_from_slice(&[b'[', 0, 0]);
+            annotations_attribute(info)
+        };
+        let max_depth = usize::from(ParseOptions::DEFAULT_MAX_ELEMENT_VALUE_DEPTH);
+        assert!(nested_arrays(max_depth).is_ok());
+        assert!(matches!(
+            nested_arrays(max_depth + 1),
+            Err(Error::ElementValueTooDeep { max_depth: 255 })
+        ));
+    }
+
+    #[test]
+    fn element_value_depth_at_limit() {
+        // An array with a single array element, read at the deepest representable depth.
+        let mut reader = [b'[', 0, 1, b'[', 0, 0].as_slice();
+        assert!(matches!(
+            raw_attributes::ElementValueInfo::read_from(&mut reader, u16::MAX, u16::MAX),
+            Err(Error::ElementValueTooDeep {
+                max_depth: u16::MAX
+            })
+        ));
+    }
+}
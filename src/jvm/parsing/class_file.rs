@@ -3,84 +3,167 @@ use std::io::{self, Read};
 use crate::{
     jvm::{
         class::{
-            self, BootstrapMethod, ConstantPool, EnclosingMethod, InnerClassInfo,
-            NestedClassAccessFlags, RecordComponent, Version,
+            self, constant_pool::Entry, BootstrapMethod, ConstantPool, EnclosingMethod,
+            InnerClassInfo, NestedClassAccessFlags, RecordComponent, Version,
         },
         parsing::reader_utils::ValueReaderExt,
         references::ClassRef,
-        Class,
+        Class, Method,
     },
     macros::{extract_attributes, malform, see_jvm_spec},
 };
 
 use super::{
-    attribute::AttributeInfo, field_info::FieldInfo, jvm_element_parser::ClassElement,
-    method_info::MethodInfo, raw_attributes, reader_utils::ReadBytes, Context, Error,
+    attribute::{read_attributes, retained_attributes, AttributeInfo},
+    errors::EofContext,
+    field_info::FieldInfo,
+    jvm_element_parser::ClassElement,
+    method_info::MethodInfo,
+    raw_attributes, strict, Context, Error, ParseOptions,
 };
 
 /// The raw representation of a class file.
 #[doc = see_jvm_spec!(4, 1)]
 #[derive(Debug)]
 pub(crate) struct ClassFile {
-    minor_version: u16,
-    major_version: u16,
-    constant_pool: ConstantPool,
-    access_flags: u16,
-    this_class: u16,
-    super_class: u16,
-    interfaces: Vec<u16>,
-    fields: Vec<FieldInfo>,
-    methods: Vec<MethodInfo>,
-    attributes: Vec<AttributeInfo>,
+    pub(super) minor_version: u16,
+    pub(super) major_version: u16,
+    pub(super) constant_pool: ConstantPool,
+    pub(super) access_flags: u16,
+    pub(super) this_class: u16,
+    pub(super) super_class: u16,
+    pub(super) interfaces: Vec<u16>,
+    pub(super) fields: Vec<FieldInfo>,
+    pub(super) methods: Vec<MethodInfo>,
+    pub(super) attributes: Vec<AttributeInfo>,
 }
-const JAVA_CLASS_MAIGC: u32 = 0xCAFE_BABE;
+const JAVA_CLASS_MAGIC: u32 = 0xCAFE_BABE;
 
 impl Class {
     /// Parses a class file from the given reader.
     /// # Errors
     /// See [`Error`] for more information.
     pub fn from_reader<R>(reader: R) -> Result<Class, Error>
+    where
+        R: std::io::Read,
+    {
+        Self::from_reader_with_options(reader, &ParseOptions::default())
+    }
+
+    /// Parses a class file from the given reader with the given options.
+    /// # Errors
+    /// See [`Error`] for more information.
+    pub fn from_reader_with_options<R>(reader: R, options: &ParseOptions) -> Result<Class, Error>
     where
         R: std::io::Read,
     {
         let mut reader = reader;
-        let class_file = ClassFile::read_bytes(&mut reader)?;
-        Class::from_raw(class_file)
+        let class_file = ClassFile::read_from(&mut reader, options.max_version)?;
+        let class = Class::from_raw(class_file, options)?;
+        if options.strict {
+            strict::check_operands(&class)?;
+            strict::check_module_attributes(&class)?;
+            strict::check_stack_map_frames(&class)?;
+            strict::check_termination(&class)?;
+        }
+        Ok(class)
     }
 }
 
-impl ReadBytes for ClassFile {
-    fn read_bytes<R: Read + ?Sized>(reader: &mut R) -> io::Result<Self> {
-        let magic: u32 = reader.read_value()?;
-        if magic != JAVA_CLASS_MAIGC {
+/// The part of a class file preceding the fields, i.e., the version, the constant pool, and the
+/// declaration of the class.
+#[derive(Debug)]
+pub(super) struct ClassFileHeader {
+    pub(super) minor_version: u16,
+    pub(super) major_version: u16,
+    pub(super) constant_pool: ConstantPool,
+    pub(super) access_flags: u16,
+    pub(super) this_class: u16,
+    pub(super) super_class: u16,
+    pub(super) interfaces: Vec<u16>,
+}
+
+impl ClassFileHeader {
+    /// Reads the header of a class file, reporting a premature end of the input as
+    /// [`Error::UnexpectedEof`] naming the structure being read.
+    /// A class file newer than `max_version` is rejected as soon as its version is read, so that
+    /// the rest of it, which the parser may not understand, is never read.
+    pub(super) fn read_from<R: Read + ?Sized>(
+        reader: &mut R,
+        max_version: Option<Version>,
+    ) -> Result<Self, Error> {
+        let header = || "the class file header";
+        let magic: u32 = reader.read_value().while_parsing(header)?;
+        if magic != JAVA_CLASS_MAGIC {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
                 "This is not a Java class file",
-            ));
+            )
+            .into());
+        }
+        let minor_version = reader.read_value().while_parsing(header)?;
+        let major_version = reader.read_value().while_parsing(header)?;
+        if let Some(max) = max_version {
+            // Compared before building a `Version`, which fails for versions the parser does not
+            // know.
+            if major_version > max.major() {
+                return Err(Error::UnsupportedClassVersion {
+                    major: major_version,
+                    minor: minor_version,
+                    max,
+                });
+            }
         }
-        let minor_version = reader.read_value()?;
-        let major_version = reader.read_value()?;
-        let constant_pool_count = reader.read_value()?;
-        let constant_pool = ConstantPool::from_reader(reader, constant_pool_count)?;
-        let access_flags = reader.read_value()?;
-        let this_class = reader.read_value()?;
-        let super_class = reader.read_value()?;
-        let interfaces_count: u16 = reader.read_value()?;
+        let constant_pool_count = reader.read_value().while_parsing(header)?;
+        let constant_pool = ConstantPool::from_reader(reader, constant_pool_count)
+            .while_parsing(|| "the constant pool")?;
+        let class_info = || "the class declaration";
+        let access_flags = reader.read_value().while_parsing(class_info)?;
+        let this_class = reader.read_value().while_parsing(class_info)?;
+        let super_class = reader.read_value().while_parsing(class_info)?;
+        let interfaces_count: u16 = reader.read_value().while_parsing(class_info)?;
         let interfaces = (0..interfaces_count)
             .map(|_| reader.read_value())
-            .collect::<io::Result<_>>()?;
-        let fields_count: u16 = reader.read_value()?;
+            .collect::<io::Result<_>>()
+            .while_parsing(|| "the interfaces")?;
+        Ok(Self {
+            minor_version,
+            major_version,
+            constant_pool,
+            access_flags,
+            this_class,
+            super_class,
+            interfaces,
+        })
+    }
+}
+
+impl ClassFile {
+    /// Reads a class file, reporting a premature end of the input as [`Error::UnexpectedEof`]
+    /// naming the structure being read.
+    /// See [`ClassFileHeader::read_from`] for `max_version`.
+    pub(super) fn read_from<R: Read + ?Sized>(
+        reader: &mut R,
+        max_version: Option<Version>,
+    ) -> Result<Self, Error> {
+        let ClassFileHeader {
+            minor_version,
+            major_version,
+            constant_pool,
+            access_flags,
+            this_class,
+            super_class,
+            interfaces,
+        } = ClassFileHeader::read_from(reader, max_version)?;
+        let fields_count: u16 = reader.read_value().while_parsing(|| "the fields")?;
         let fields = (0..fields_count)
-            .map(|_| FieldInfo::read_bytes(reader))
-            .collect::<io::Result<_>>()?;
-        let methods_count: u16 = reader.read_value()?;
+            .map(|index| FieldInfo::read_from(reader, &constant_pool, index.into()))
+            .collect::<Result<_, _>>()?;
+        let methods_count: u16 = reader.read_value().while_parsing(|| "the methods")?;
         let methods = (0..methods_count)
-            .map(|_| MethodInfo::read_bytes(reader))
-            .collect::<io::Result<_>>()?;
-        let attributes_count: u16 = reader.read_value()?;
-        let attributes = (0..attributes_count)
-            .map(|_| AttributeInfo::read_bytes(reader))
-            .collect::<io::Result<_>>()?;
+            .map(|index| MethodInfo::read_from(reader, &constant_pool, index.into()))
+            .collect::<Result<_, _>>()?;
+        let attributes = read_attributes(reader, &constant_pool, "the class")?;
 
         // Make sure there is no extra data in the reader
         if let Ok(0) = reader.read(&mut [0; 1]) {
@@ -97,13 +180,26 @@ impl ReadBytes for ClassFile {
                 attributes,
             })
         } else {
-            Err(io::Error::new(io::ErrorKind::InvalidData, "Extra data"))
+            Err(io::Error::new(io::ErrorKind::InvalidData, "Extra data").into())
         }
     }
 }
 
+/// Checks that the constant pool of a non-module class has no module-only entries.
+#[doc = see_jvm_spec!(4, 4, 11)]
+fn check_no_module_constants(constant_pool: &ConstantPool) -> Result<(), Error> {
+    if constant_pool
+        .entries()
+        .any(|it| matches!(it, Entry::Module { .. } | Entry::Package { .. }))
+    {
+        malform!("CONSTANT_Module and CONSTANT_Package are only allowed in a module");
+    }
+    Ok(())
+}
+
 impl Class {
-    pub(crate) fn from_raw(raw: ClassFile) -> Result<Self, Error> {
+    #[allow(clippy::too_many_lines)]
+    pub(crate) fn from_raw(raw: ClassFile, options: &ParseOptions) -> Result<Self, Error> {
         let ClassFile {
             minor_version,
             major_version,
@@ -119,6 +215,9 @@ impl Class {
         let version = Version::from_versions(major_version, minor_version)?;
         let access_flags = class::AccessFlags::from_bits(access_flags)
             .ok_or(Error::UnknownFlags("ClassAccessFlags", access_flags))?;
+        if !access_flags.contains(class::AccessFlags::MODULE) {
+            check_no_module_constants(&constant_pool)?;
+        }
         let ClassRef { binary_name } = constant_pool.get_class_ref(this_class)?;
         let super_class = match super_class {
             0 if binary_name == "java/lang/Object" => None,
@@ -131,6 +230,9 @@ impl Class {
             constant_pool,
             class_version: version,
             current_class_binary_name: binary_name.clone(),
+            retain_raw_attributes: options.retain_raw_attributes,
+            retain_raw_code: options.retain_raw_code,
+            max_element_value_depth: options.max_element_value_depth,
         };
 
         let ctx = &parsing_context;
@@ -145,8 +247,12 @@ impl Class {
             .collect::<Result<_, _>>()?;
         let methods = methods
             .into_iter()
-            .map(|it| ClassElement::from_raw(it, ctx))
+            .enumerate()
+            .map(|(index, it)| {
+                ClassElement::from_raw(it, ctx).map(|method| Method { index, ..method })
+            })
             .collect::<Result<_, _>>()?;
+        let raw_attributes = retained_attributes(&attributes, ctx)?;
         let attributes: Vec<Attribute> = attributes
             .into_iter()
             .map(|it| ClassElement::from_raw(it, ctx))
@@ -205,6 +311,7 @@ impl Class {
             signature,
             record,
             free_attributes,
+            raw_attributes,
         })
     }
 }
@@ -270,6 +377,8 @@ impl ClassElement for RecordComponent {
         let name = ctx.constant_pool.get_str(name_index)?.to_owned();
         let component_type = ctx.constant_pool.get_str(descriptor_index)?.parse()?;
 
+        let raw_attributes = retained_attributes(&attributes, ctx)?;
+
         let attributes: Vec<Attribute> = attributes
             .into_iter()
             .map(|it| ClassElement::from_raw(it, ctx))
@@ -296,6 +405,7 @@ impl ClassElement for RecordComponent {
             runtime_visible_type_annotations,
             runtime_invisible_type_annotations,
             free_attributes,
+            raw_attributes,
         })
     }
 }
@@ -310,7 +420,7 @@ impl ClassElement for EnclosingMethod {
         } = raw;
         let class = ctx.constant_pool.get_class_ref(class_index)?;
         let method_name_and_desc = if method_index > 0 {
-            let name_and_desc = ctx.constant_pool.get_name_and_type(method_index)?;
+            let name_and_desc = ctx.constant_pool.get_typed_name_and_type(method_index)?;
             Some(name_and_desc)
         } else {
             None
@@ -321,3 +431,59 @@ impl ClassElement for EnclosingMethod {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::empty_class_with_version;
+
+    #[test]
+    fn module_constant_in_non_module_class() {
+        let pool_bytes: &[u8] = &[
+            1, 0, 3, b'F', b'o', b'o', // #1 = Utf8 "Foo"
+            7, 0, 1, // #2 = Class #1
+            19, 0, 1, // #3 = Module #1
+        ];
+        let constant_pool = ConstantPool::from_reader(&mut &pool_bytes[..], 4).unwrap();
+        let class_file = ClassFile {
+            minor_version: 0,
+            major_version: 53,
+            constant_pool,
+            access_flags: class::AccessFlags::PUBLIC.bits(),
+            this_class: 2,
+            super_class: 0,
+            interfaces: Vec::new(),
+            fields: Vec::new(),
+            methods: Vec::new(),
+            attributes: Vec::new(),
+        };
+        assert!(matches!(
+            Class::from_raw(class_file, &ParseOptions::default()),
+            Err(Error::Other(_))
+        ));
+    }
+
+    #[test]
+    fn class_newer_than_parser() {
+        let bytes = empty_class_with_version(class::MAX_MAJOR_VERSION + 1, 0);
+        let options = ParseOptions {
+            max_version: Some(Version::Jdk8),
+            ..Default::default()
+        };
+        assert!(matches!(
+            Class::from_reader_with_options(bytes.as_slice(), &options),
+            Err(Error::UnsupportedClassVersion { major, minor: 0, max: Version::Jdk8 })
+                if major == class::MAX_MAJOR_VERSION + 1
+        ));
+        // A known major version with a nonstandard minor version.
+        let bytes = empty_class_with_version(61, 3);
+        assert!(matches!(
+            Class::from_reader_with_options(bytes.as_slice(), &options),
+            Err(Error::UnsupportedClassVersion {
+                major: 61,
+                minor: 3,
+                ..
+            })
+        ));
+    }
+}
@@ -289,7 +289,7 @@ impl Instruction {
             } => {
                 let targets = jump_offsets
                     .into_iter()
-                    .map(|offset| (pc + offset))
+                    .map(|offset| pc + offset)
                     .try_collect()?;
                 Self::TableSwitch {
                     default: (pc + default)?,
@@ -365,7 +365,8 @@ impl Instruction {
                         found: entry.constant_kind(),
                     })?
                 };
-                let (name, descriptor) = constant_pool.get_name_and_type(name_and_type_index)?;
+                let (name, descriptor) =
+                    constant_pool.get_typed_name_and_type(name_and_type_index)?;
                 Self::InvokeDynamic {
                     bootstrap_method_index,
                     name,
@@ -391,7 +392,7 @@ impl Instruction {
                 Self::NewArray(element_type)
             }
             ANewArray { index } => {
-                let element_type = constant_pool.get_class_ref(index)?;
+                let element_type = constant_pool.get_type_ref(index)?;
                 Self::ANewArray(element_type)
             }
             ArrayLength => Self::ArrayLength,
@@ -425,8 +426,8 @@ impl Instruction {
                 RawWideInstruction::Ret { index } => WideInstruction::Ret(index),
             }),
             MultiANewArray { index, dimensions } => {
-                let class_ref = constant_pool.get_type_ref(index)?;
-                Self::MultiANewArray(class_ref, dimensions)
+                let array_type = constant_pool.get_type_ref(index)?;
+                Self::MultiANewArray(array_type, dimensions)
             }
             IfNull { offset } => Self::IfNull((pc + offset)?),
             IfNonNull { offset } => Self::IfNonNull((pc + offset)?),
@@ -442,3 +443,45 @@ impl Instruction {
         Ok(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{jvm::references::ClassRef, types::field_type::FieldType};
+
+    /// A constant pool with `java/lang/String` at #2 and `[[I` at #4.
+    fn constant_pool() -> ConstantPool {
+        let mut bytes = vec![1, 0, 16];
+        bytes.extend_from_slice(b"java/lang/String");
+        bytes.extend_from_slice(&[7, 0, 1, 1, 0, 3]);
+        bytes.extend_from_slice(b"[[I");
+        bytes.extend_from_slice(&[7, 0, 3]);
+        ConstantPool::from_reader(&mut bytes.as_slice(), 5).unwrap()
+    }
+
+    #[test]
+    fn array_creation() {
+        let constant_pool = constant_pool();
+        let anewarray = Instruction::from_raw_instruction(
+            RawInstruction::ANewArray { index: 2 },
+            0.into(),
+            &constant_pool,
+        )
+        .unwrap();
+        let element_type = FieldType::Object(ClassRef::new("java/lang/String"));
+        assert_eq!("Ljava/lang/String;", element_type.descriptor());
+        assert_eq!(Instruction::ANewArray(element_type), anewarray);
+
+        let multianewarray = Instruction::from_raw_instruction(
+            RawInstruction::MultiANewArray {
+                index: 4,
+                dimensions: 2,
+            },
+            0.into(),
+            &constant_pool,
+        )
+        .unwrap();
+        let array_type = FieldType::array_of(FieldType::Base(PrimitiveType::Int), 2);
+        assert_eq!(Instruction::MultiANewArray(array_type, 2), multianewarray);
+    }
+}
@@ -20,20 +20,21 @@ use crate::{
 };
 
 use super::{
+    attribute::retained_attributes,
     jvm_element_parser::ClassElement,
     raw_attributes::{self, Code},
     reader_utils::{ReadBytes, ValueReaderExt},
     Context, Error,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct LocalVariableDescAttr {
     pub id: LocalVariableId,
     pub name: String,
     pub field_type: FieldType,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct LocalVariableTypeAttr {
     pub id: LocalVariableId,
     pub name: String,
@@ -71,7 +72,7 @@ impl ClassElement for ExceptionTableEntry {
         } = raw;
         let start_pc = ProgramCounter::from(start_pc);
         let end_pc = ProgramCounter::from(end_pc);
-        let covered_pc = start_pc..=end_pc;
+        let covered_pc = start_pc..end_pc;
         let handler_pc = ProgramCounter::from(handler_pc);
         let catch_type = if catch_type_idx == 0 {
             None
@@ -165,26 +166,36 @@ impl ClassElement for MethodBody {
             attributes,
         } = raw;
 
-        let raw_instructions = RawInstruction::from_bytes(instruction_bytes)?;
+        let raw_instructions = RawInstruction::from_slice(&instruction_bytes)?;
         let instructions = ClassElement::from_raw(raw_instructions, ctx)?;
+        let raw_code = ctx
+            .retain_raw_code
+            .then(|| instruction_bytes.into_boxed_slice());
 
         let exception_table = exception_table
             .into_iter()
             .map(|it| ClassElement::from_raw(it, ctx))
             .collect::<Result<_, _>>()?;
+        let raw_attributes = retained_attributes(&attributes, ctx)?;
         let attributes: Vec<Attribute> = attributes
             .into_iter()
             .map(|it| ClassElement::from_raw(it, ctx))
             .collect::<Result<_, _>>()?;
         let mut local_variable_table = None;
+        let mut line_number_table: Option<Vec<LineNumberTableEntry>> = None;
+        // `StackMapTable` and the type annotation attributes may appear at most once in a `Code`
+        // attribute, while `LineNumberTable`, `LocalVariableTable`, and `LocalVariableTypeTable`
+        // may appear multiple times and are merged.
         extract_attributes! {
             for attributes in "code" {
-                let line_number_table: LineNumberTable,
                 let stack_map_table: StackMapTable,
                 let runtime_visible_type_annotations:
                     RuntimeVisibleTypeAnnotations as unwrap_or_default,
                 let runtime_invisible_type_annotations:
                     RuntimeInvisibleTypeAnnotations as unwrap_or_default,
+                match Attribute::LineNumberTable(it) => {
+                    line_number_table.get_or_insert_with(Vec::new).extend(it);
+                },
                 match Attribute::LocalVariableTable(it) => {
                     let table = local_variable_table.get_or_insert(LocalVariableTable::default());
                     for LocalVariableDescAttr { id, name, field_type } in it {
@@ -201,6 +212,10 @@ impl ClassElement for MethodBody {
             }
         }
 
+        if let Some(line_number_table) = line_number_table.as_mut() {
+            line_number_table.sort_by_key(|it| it.start_pc);
+        }
+
         Ok(Self {
             max_stack,
             max_locals,
@@ -212,6 +227,91 @@ impl ClassElement for MethodBody {
             runtime_visible_type_annotations,
             runtime_invisible_type_annotations,
             free_attributes,
+            raw_attributes,
+            raw_code,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::jvm::{
+        class::{ConstantPool, Version},
+        code::{StackMapFrame, VerificationType},
+        parsing::{attribute::AttributeInfo, ParseOptions},
+    };
+
+    use super::*;
+
+    /// Parses a `Code` attribute containing a `return` instruction and the given attributes.
+    fn code(attributes: &[&[u8]]) -> Result<MethodBody, Error> {
+        let mut pool_bytes = vec![1, 0, 13];
+        pool_bytes.extend_from_slice(b"StackMapTable");
+        pool_bytes.extend_from_slice(&[1, 0, 15]);
+        pool_bytes.extend_from_slice(b"LineNumberTable");
+        let ctx = Context {
+            constant_pool: ConstantPool::from_reader(&mut pool_bytes.as_slice(), 3).unwrap(),
+            class_version: Version::Jdk8,
+            current_class_binary_name: "Test".to_owned(),
+            retain_raw_attributes: false,
+            retain_raw_code: false,
+            max_element_value_depth: ParseOptions::DEFAULT_MAX_ELEMENT_VALUE_DEPTH,
+        };
+        let attributes = attributes
+            .iter()
+            .map(|bytes| AttributeInfo::read_bytes(&mut &bytes[..]).unwrap())
+            .collect();
+        let code = Code {
+            max_stack: 0,
+            max_locals: 0,
+            instruction_bytes: vec![0xb1], // return
+            exception_table: Vec::new(),
+            attributes,
+        };
+        MethodBody::from_raw(code, &ctx)
+    }
+
+    // name_index = 1, attribute_length = 2, number_of_entries = 0
+    const STACK_MAP_TABLE: &[u8] = &[0, 1, 0, 0, 0, 2, 0, 0];
+
+    #[test]
+    fn duplicated_stack_map_table() {
+        assert!(code(&[STACK_MAP_TABLE]).is_ok());
+        assert!(matches!(
+            code(&[STACK_MAP_TABLE, STACK_MAP_TABLE]),
+            Err(Error::Other(_))
+        ));
+    }
+
+    #[test]
+    fn split_line_number_tables() {
+        // name_index = 2, attribute_length = 6, line_number_table_length = 1,
+        // start_pc = 3, line_number = 12
+        let second: &[u8] = &[0, 2, 0, 0, 0, 6, 0, 1, 0, 3, 0, 12];
+        // start_pc = 0, line_number = 10
+        let first: &[u8] = &[0, 2, 0, 0, 0, 6, 0, 1, 0, 0, 0, 10];
+        let body = code(&[second, first]).unwrap();
+        let line_numbers: Vec<_> = body
+            .line_number_table
+            .unwrap()
+            .into_iter()
+            .map(|it| (u16::from(it.start_pc), it.line_number))
+            .collect();
+        assert_eq!(vec![(0, 10), (3, 12)], line_numbers);
+    }
+
+    #[test]
+    fn same_locals_1_stack_item_frame_extended() {
+        // name_index = 1, attribute_length = 6, number_of_entries = 1,
+        // frame_type = 247, offset_delta = 300, stack = [int]
+        let stack_map_table: &[u8] = &[0, 1, 0, 0, 0, 6, 0, 1, 247, 1, 44, 1];
+        let body = code(&[stack_map_table]).unwrap();
+        assert_eq!(
+            Some(vec![StackMapFrame::SameLocals1StackItemFrame {
+                offset_delta: 300,
+                stack: VerificationType::IntegerVariable,
+            }]),
+            body.stack_map_table
+        );
+    }
+}
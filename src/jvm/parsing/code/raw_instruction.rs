@@ -13,17 +13,28 @@ impl RawInstruction {
     /// Parses a list of [`RawInstruction`]s from the given bytes.
     /// # Errors
     /// See [`Error`] for more information.
+    #[allow(
+        clippy::needless_pass_by_value,
+        reason = "Kept for compatibility, use `from_slice` to avoid the allocation"
+    )]
     pub fn from_bytes(bytes: Vec<u8>) -> Result<InstructionList<RawInstruction>, Error> {
-        let mut cursor = Cursor::new(bytes);
+        Self::from_slice(&bytes)
+    }
+
+    /// Parses a list of [`RawInstruction`]s from the given bytes without copying them.
+    /// # Errors
+    /// See [`Error`] for more information.
+    pub fn from_slice(bytes: &[u8]) -> Result<InstructionList<RawInstruction>, Error> {
+        let mut reader = Cursor::new(bytes);
         let mut inner = BTreeMap::new();
-        while let Some((pc, instruction)) = RawInstruction::parse(&mut cursor)? {
+        while let Some((pc, instruction)) = RawInstruction::parse(&mut reader)? {
             inner.insert(pc, instruction);
         }
         Ok(InstructionList::from(inner))
     }
 
     #[allow(clippy::too_many_lines)]
-    fn parse(reader: &mut Cursor<Vec<u8>>) -> Result<Option<(ProgramCounter, Self)>, Error> {
+    fn parse(reader: &mut Cursor<&[u8]>) -> Result<Option<(ProgramCounter, Self)>, Error> {
         #[allow(clippy::enum_glob_use)]
         use RawInstruction::*;
 
@@ -310,7 +321,7 @@ impl RawInstruction {
             0x69 => LMul,
             0x75 => LNeg,
             0xab => {
-                while reader.position() % 4 != 0 {
+                while !reader.position().is_multiple_of(4) {
                     let _padding_byte: u8 = reader.read_value()?;
                 }
                 let default = reader.read_value()?;
@@ -328,7 +339,7 @@ impl RawInstruction {
                 }
             }
             0xaa => {
-                while reader.position() % 4 != 0 {
+                while !reader.position().is_multiple_of(4) {
                     let _padding_byte: u8 = reader.read_value()?;
                 }
                 let default = reader.read_value()?;
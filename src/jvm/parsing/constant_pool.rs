@@ -3,10 +3,7 @@ use std::{
     str::FromStr,
 };
 
-use super::{
-    reader_utils::{read_byte_chunk, ValueReaderExt},
-    Error,
-};
+use super::{reader_utils::ValueReaderExt, Error};
 use crate::{
     jvm::{
         class::{constant_pool::Entry, ConstantPool, MethodHandle},
@@ -26,15 +23,25 @@ const fn mismatch<T>(expected: &'static str, entry: &Entry) -> Result<T, Error>
 }
 
 impl ConstantPool {
-    pub(super) fn get_str(&self, index: u16) -> Result<&str, Error> {
-        let entry = self.get_entry(index)?;
-        match entry {
-            Entry::Utf8(JavaString::Utf8(string)) => Ok(string),
-            Entry::Utf8(JavaString::InvalidUtf8(_)) => Err(Error::BrokenUTF8),
+    /// Gets the string in the `CONSTANT_Utf8` entry at `index`.
+    /// A string that is not valid modified UTF-8 is returned as [`JavaString::InvalidUtf8`] with
+    /// its raw bytes rather than failing.
+    /// # Errors
+    /// - [`Error::BadConstantPoolIndex`] if `index` does not point to an entry.
+    /// - [`Error::MismatchedConstantPoolEntryType`] if the entry is not a `CONSTANT_Utf8`.
+    pub fn get_java_string(&self, index: u16) -> Result<&JavaString, Error> {
+        match self.get_entry(index)? {
+            Entry::Utf8(string) => Ok(string),
             it => mismatch("Utf8", it),
         }
     }
 
+    pub(super) fn get_str(&self, index: u16) -> Result<&str, Error> {
+        self.get_java_string(index)?
+            .as_str()
+            .ok_or(Error::BrokenUTF8)
+    }
+
     pub(super) fn get_class_ref(&self, index: u16) -> Result<ClassRef, Error> {
         let entry = self.get_entry(index)?;
         if let &Entry::Class { name_index } = entry {
@@ -52,13 +59,10 @@ impl ConstantPool {
             &Entry::Long(it) => Ok(ConstantValue::Long(it)),
             &Entry::Float(it) => Ok(ConstantValue::Float(it)),
             &Entry::Double(it) => Ok(ConstantValue::Double(it)),
-            &Entry::String { string_index } => {
-                if let Entry::Utf8(java_str) = self.get_entry(string_index)? {
-                    Ok(ConstantValue::String(java_str.clone()))
-                } else {
-                    mismatch("Utf8", entry)
-                }
-            }
+            &Entry::String { string_index } => self
+                .get_java_string(string_index)
+                .cloned()
+                .map(ConstantValue::String),
             &Entry::MethodType { descriptor_index } => self
                 .get_str(descriptor_index)
                 .and_then(|it| it.parse().map_err(Into::into))
@@ -74,7 +78,7 @@ impl ConstantPool {
                 bootstrap_method_attr_index,
                 name_and_type_index,
             } => {
-                let (name, descriptor) = self.get_name_and_type(name_and_type_index)?;
+                let (name, descriptor) = self.get_typed_name_and_type(name_and_type_index)?;
                 Ok(ConstantValue::Dynamic(
                     bootstrap_method_attr_index,
                     name,
@@ -121,7 +125,7 @@ impl ConstantPool {
         } = entry
         {
             let owner = self.get_class_ref(class_index)?;
-            let (name, field_type) = self.get_name_and_type(name_and_type_index)?;
+            let (name, field_type) = self.get_typed_name_and_type(name_and_type_index)?;
             Ok(FieldRef {
                 owner,
                 name,
@@ -132,11 +136,14 @@ impl ConstantPool {
         }
     }
 
-    pub(super) fn get_name_and_type<T>(&self, index: u16) -> Result<(String, T), Error>
-    where
-        T: FromStr,
-        <T as FromStr>::Err: Into<Error>,
-    {
+    /// Gets the name and the descriptor of the `CONSTANT_NameAndType` entry at `index`.
+    /// The descriptor is returned as it is, without being parsed.
+    /// # Errors
+    /// - [`Error::BadConstantPoolIndex`] if `index` does not point to an entry.
+    /// - [`Error::MismatchedConstantPoolEntryType`] if the entry, or one of the entries it refers
+    ///   to, has an unexpected type.
+    /// - [`Error::BrokenUTF8`] if the name or the descriptor is not valid UTF-8.
+    pub fn get_name_and_type(&self, index: u16) -> Result<(String, String), Error> {
         let entry = self.get_entry(index)?;
         if let &Entry::NameAndType {
             name_index,
@@ -144,16 +151,24 @@ impl ConstantPool {
         } = entry
         {
             let name = self.get_str(name_index)?;
-            let descriptor = self
-                .get_str(descriptor_index)?
-                .parse()
-                .map_err(Into::into)?;
-            Ok((name.to_owned(), descriptor))
+            let descriptor = self.get_str(descriptor_index)?;
+            Ok((name.to_owned(), descriptor.to_owned()))
         } else {
             mismatch("NameAndType", entry)
         }
     }
 
+    /// Gets the name and the parsed descriptor of the `CONSTANT_NameAndType` entry at `index`.
+    pub(super) fn get_typed_name_and_type<T>(&self, index: u16) -> Result<(String, T), Error>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: Into<Error>,
+    {
+        let (name, descriptor) = self.get_name_and_type(index)?;
+        let descriptor = descriptor.parse().map_err(Into::into)?;
+        Ok((name, descriptor))
+    }
+
     pub(super) fn get_method_ref(&self, index: u16) -> Result<MethodRef, Error> {
         let entry = self.get_entry(index)?;
         if let &Entry::MethodRef {
@@ -166,7 +181,7 @@ impl ConstantPool {
         } = entry
         {
             let owner = self.get_class_ref(class_index)?;
-            let (name, descriptor) = self.get_name_and_type(name_and_type_index)?;
+            let (name, descriptor) = self.get_typed_name_and_type(name_and_type_index)?;
             Ok(MethodRef {
                 owner,
                 name,
@@ -219,10 +234,10 @@ impl Entry {
         let tag: u8 = reader.read_value()?;
         match tag {
             1 => Self::parse_utf8(reader),
-            3 => reader.read_value().map(Self::Integer).map_err(Into::into),
-            4 => reader.read_value().map(Self::Float).map_err(Into::into),
-            5 => reader.read_value().map(Self::Long).map_err(Into::into),
-            6 => reader.read_value().map(Self::Double).map_err(Into::into),
+            3 => reader.read_value().map(Self::Integer),
+            4 => reader.read_value().map(Self::Float),
+            5 => reader.read_value().map(Self::Long),
+            6 => reader.read_value().map(Self::Double),
             7 => Ok(Self::Class {
                 name_index: reader.read_value()?,
             }),
@@ -275,7 +290,17 @@ impl Entry {
 
     fn parse_utf8<R: Read + ?Sized>(reader: &mut R) -> io::Result<Self> {
         let length: u16 = reader.read_value()?;
-        let cesu8_content = read_byte_chunk(reader, length.into())?;
+        let mut cesu8_content = Vec::with_capacity(length.into());
+        reader.take(length.into()).read_to_end(&mut cesu8_content)?;
+        if cesu8_content.len() < usize::from(length) {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "CONSTANT_Utf8 entry declares {length} bytes, but only {} bytes are left",
+                    cesu8_content.len()
+                ),
+            ));
+        }
         match cesu8::from_java_cesu8(cesu8_content.as_slice()) {
             Ok(result) => Ok(Self::Utf8(JavaString::Utf8(result.into_owned()))),
             Err(_) => Ok(Self::Utf8(JavaString::InvalidUtf8(cesu8_content))),
@@ -287,10 +312,100 @@ impl Entry {
 pub(crate) mod tests {
 
     use super::*;
+    use crate::jvm::code::{Instruction, RawInstruction};
     use proptest::prelude::*;
 
     const MAX_BYTES: usize = 255;
 
+    #[test]
+    fn ldc_method_handle_and_method_type() {
+        let utf8 = |value: &str| {
+            let mut bytes = vec![1];
+            bytes.extend(u16::try_from(value.len()).unwrap().to_be_bytes());
+            bytes.extend(value.as_bytes());
+            bytes
+        };
+        let bytes = [
+            utf8("java/lang/Integer"),
+            vec![7, 0, 1],
+            utf8("valueOf"),
+            utf8("(I)Ljava/lang/Integer;"),
+            vec![12, 0, 3, 0, 4],
+            vec![10, 0, 2, 0, 5],
+            // REF_invokeStatic
+            vec![15, 6, 0, 6],
+            vec![16, 0, 4],
+        ]
+        .concat();
+        let constant_pool = ConstantPool::from_reader(&mut bytes.as_slice(), 9).unwrap();
+        let value_of = MethodRef {
+            owner: ClassRef::new("java/lang/Integer"),
+            name: "valueOf".to_owned(),
+            descriptor: "(I)Ljava/lang/Integer;".parse().unwrap(),
+        };
+
+        let ldc_handle = RawInstruction::Ldc { const_index: 7 };
+        assert_eq!(
+            Instruction::Ldc(ConstantValue::Handle(MethodHandle::RefInvokeStatic(
+                value_of.clone()
+            ))),
+            Instruction::from_raw_instruction(ldc_handle, 0.into(), &constant_pool).unwrap()
+        );
+        let ldc_type = RawInstruction::LdcW { const_index: 8 };
+        assert_eq!(
+            Instruction::LdcW(ConstantValue::MethodType(value_of.descriptor)),
+            Instruction::from_raw_instruction(ldc_type, 0.into(), &constant_pool).unwrap()
+        );
+    }
+
+    #[test]
+    fn name_and_type() {
+        let mut bytes = vec![1, 0, 3];
+        bytes.extend_from_slice(b"foo");
+        bytes.extend_from_slice(&[1, 0, 3]);
+        bytes.extend_from_slice(b"()V");
+        bytes.extend_from_slice(&[12, 0, 1, 0, 2]);
+        let constant_pool = ConstantPool::from_reader(&mut bytes.as_slice(), 4).unwrap();
+        assert_eq!(
+            ("foo".to_owned(), "()V".to_owned()),
+            constant_pool.get_name_and_type(3).unwrap()
+        );
+        assert!(matches!(
+            constant_pool.get_name_and_type(1),
+            Err(Error::MismatchedConstantPoolEntryType {
+                expected: "NameAndType",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn invalid_utf8_is_preserved() {
+        let invalid = [0x61, 0xFF, 0x62];
+        let mut bytes = vec![1, 0, 3];
+        bytes.extend_from_slice(&invalid);
+        bytes.extend_from_slice(&[8, 0, 1]);
+        let constant_pool = ConstantPool::from_reader(&mut bytes.as_slice(), 3).unwrap();
+        let expected = JavaString::InvalidUtf8(invalid.to_vec());
+        assert_eq!(&expected, constant_pool.get_java_string(1).unwrap());
+        assert!(matches!(
+            constant_pool.get_constant_value(2),
+            Ok(ConstantValue::String(it)) if it == expected
+        ));
+        assert!(matches!(constant_pool.get_str(1), Err(Error::BrokenUTF8)));
+    }
+
+    #[test]
+    fn truncated_utf8() {
+        let bytes = [1, 0, 5, b'a', b'b'];
+        let err = ConstantPool::from_reader(&mut bytes.as_slice(), 2).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        assert_eq!(
+            "CONSTANT_Utf8 entry declares 5 bytes, but only 2 bytes are left",
+            err.to_string()
+        );
+    }
+
     proptest! {
 
         #[test]
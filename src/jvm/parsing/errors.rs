@@ -1,10 +1,17 @@
+use std::io;
+
 use crate::{
-    jvm::{class::constant_pool::BadConstantPoolIndex, code::InvalidOffset},
+    jvm::{
+        class::{constant_pool::BadConstantPoolIndex, Version},
+        code::{InvalidOffset, ProgramCounter},
+    },
     types::method_descriptor::InvalidDescriptor,
 };
 
 /// An error that occurs when parsing a Java class file.
+/// New variants may be added as the parser checks more constraints.
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum Error {
     /// An error that occurs when reading from a buffer.
     #[error("Failed to read from buffer: {0}")]
@@ -29,6 +36,13 @@ pub enum Error {
     /// The value of an element in an annotation is invalid.
     #[error("Invalid element tag {0}")]
     InvalidElementValueTag(char),
+    /// The element values in an annotation are nested deeper than
+    /// [`ParseOptions::max_element_value_depth`](super::ParseOptions::max_element_value_depth).
+    #[error("Element values are nested deeper than {max_depth} levels")]
+    ElementValueTooDeep {
+        /// The maximum depth of nesting allowed.
+        max_depth: u16,
+    },
     /// The target type of an annotation is invalid.
     #[error("Invalid target type {0}")]
     InvalidTargetType(u8),
@@ -38,6 +52,17 @@ pub enum Error {
     /// The stack map frame type is invalid.
     #[error("Unknown stack map frame type {0}")]
     UnknownStackMapFrameType(u8),
+    /// A stack map frame is inconsistent with the frames before it or with the method.
+    /// Only checked when parsing in [strict mode](super::ParseOptions::strict).
+    #[error("Invalid stack map frame #{frame_index} in method {method}: {reason}")]
+    InvalidStackMapFrame {
+        /// The name of the method containing the frame.
+        method: String,
+        /// The position of the frame in the stack map table.
+        frame_index: usize,
+        /// The reason why the frame is invalid.
+        reason: &'static str,
+    },
     /// The verification type info tag is invalid.
     #[error("Invalid verification type info tag {0}")]
     InvalidVerificationTypeInfoTag(u8),
@@ -59,7 +84,84 @@ pub enum Error {
     /// Tries to reads a string for constructing JVM components (e.g., class name) but got an invalid UTF-8 string.
     #[error("Invalid UTF-8 string")]
     BrokenUTF8,
+    /// An operand of an instruction is inconsistent with the method or the type it refers to.
+    /// Only checked when parsing in [strict mode](super::ParseOptions::strict).
+    #[error("Invalid operand at {pc} in method {method}: {reason}")]
+    InvalidOperand {
+        /// The name of the method containing the instruction.
+        method: String,
+        /// The location of the instruction.
+        pc: ProgramCounter,
+        /// The reason why the operand is invalid.
+        reason: &'static str,
+    },
+    /// The execution of a method may fall off the end of its code.
+    /// Only checked when parsing in [strict mode](super::ParseOptions::strict).
+    #[error("The control flows past the last instruction at {pc} in method {method}{descriptor}")]
+    FallsOffCode {
+        /// The name of the method.
+        method: String,
+        /// The descriptor of the method, which tells apart overloaded methods.
+        descriptor: String,
+        /// The location of the last instruction of the method.
+        pc: ProgramCounter,
+    },
+    /// The version of the class file is newer than the maximum version allowed by
+    /// [`ParseOptions::max_version`](super::ParseOptions::max_version).
+    /// The versions are kept as raw numbers since the class file may be newer than any
+    /// [`Version`] known to the parser.
+    #[error(
+        "Unsupported class version {major}.{minor}, the maximum supported version is {}.{}",
+        max.major(),
+        max.minor()
+    )]
+    UnsupportedClassVersion {
+        /// The major version of the class file.
+        major: u16,
+        /// The minor version of the class file.
+        minor: u16,
+        /// The maximum supported version.
+        max: Version,
+    },
     /// The instruction list is too long.
     #[error("The instruction list is too long, it should be at most 65536 bytes")]
     TooLongInstructionList,
+    /// The class file ends in the middle of a structure, e.g., because it is truncated.
+    #[error("Unexpected end of file while parsing {while_parsing}")]
+    UnexpectedEof {
+        /// A description of the structure being parsed, e.g., `the Code attribute of method #3`.
+        while_parsing: String,
+    },
+}
+
+/// Labels a premature end of the input with the structure being parsed.
+pub(super) trait EofContext<T> {
+    /// Converts an [`io::ErrorKind::UnexpectedEof`] into [`Error::UnexpectedEof`] with the
+    /// description returned by `while_parsing`, followed by the message of the error if it has
+    /// one, and other errors into [`Error::IO`].
+    fn while_parsing<S, F>(self, while_parsing: F) -> Result<T, Error>
+    where
+        S: Into<String>,
+        F: FnOnce() -> S;
+}
+
+impl<T> EofContext<T> for io::Result<T> {
+    fn while_parsing<S, F>(self, while_parsing: F) -> Result<T, Error>
+    where
+        S: Into<String>,
+        F: FnOnce() -> S,
+    {
+        self.map_err(|err| match err.kind() {
+            io::ErrorKind::UnexpectedEof => {
+                let mut while_parsing = while_parsing().into();
+                // Errors raised by `read_exact` carry no message, only those created by the parser
+                // to describe the truncated structure do.
+                if let Some(details) = err.get_ref() {
+                    while_parsing = format!("{while_parsing} ({details})");
+                }
+                Error::UnexpectedEof { while_parsing }
+            }
+            _ => Error::IO(err),
+        })
+    }
 }
@@ -1,7 +1,8 @@
-use std::io::{self, Read};
+use std::io::Read;
 
 use crate::{
     jvm::{
+        class::ConstantPool,
         field::{self},
         references::ClassRef,
         Field,
@@ -10,9 +11,10 @@ use crate::{
 };
 
 use super::{
-    attribute::AttributeInfo,
+    attribute::{read_attributes, retained_attributes, AttributeInfo},
+    errors::EofContext,
     jvm_element_parser::ClassElement,
-    reader_utils::{ReadBytes, ValueReaderExt},
+    reader_utils::ValueReaderExt,
     Context, Error,
 };
 
@@ -20,21 +22,24 @@ use super::{
 #[doc = see_jvm_spec!(4, 5)]
 #[derive(Debug)]
 pub(crate) struct FieldInfo {
-    access_flags: u16,
-    name_index: u16,
-    descriptor_index: u16,
-    attributes: Vec<AttributeInfo>,
+    pub(super) access_flags: u16,
+    pub(super) name_index: u16,
+    pub(super) descriptor_index: u16,
+    pub(super) attributes: Vec<AttributeInfo>,
 }
 
-impl ReadBytes for FieldInfo {
-    fn read_bytes<R: Read + ?Sized>(reader: &mut R) -> io::Result<Self> {
-        let access_flags = reader.read_value()?;
-        let name_index = reader.read_value()?;
-        let descriptor_index = reader.read_value()?;
-        let attributes_count: u16 = reader.read_value()?;
-        let attributes = (0..attributes_count)
-            .map(|_| AttributeInfo::read_bytes(reader))
-            .collect::<io::Result<_>>()?;
+impl FieldInfo {
+    /// Reads the structure of the field at `index` in the class file.
+    pub(super) fn read_from<R: Read + ?Sized>(
+        reader: &mut R,
+        constant_pool: &ConstantPool,
+        index: usize,
+    ) -> Result<Self, Error> {
+        let owner = format!("field #{index}");
+        let access_flags = reader.read_value().while_parsing(|| owner.as_str())?;
+        let name_index = reader.read_value().while_parsing(|| owner.as_str())?;
+        let descriptor_index = reader.read_value().while_parsing(|| owner.as_str())?;
+        let attributes = read_attributes(reader, constant_pool, &owner)?;
         Ok(Self {
             access_flags,
             name_index,
@@ -61,6 +66,7 @@ impl ClassElement for Field {
         let owner = ClassRef {
             binary_name: ctx.current_class_binary_name.clone(),
         };
+        let raw_attributes = retained_attributes(&attributes, ctx)?;
         let attributes: Vec<Attribute> = attributes
             .into_iter()
             .map(|it| Attribute::from_raw(it, ctx))
@@ -98,6 +104,7 @@ impl ClassElement for Field {
             runtime_visible_type_annotations,
             runtime_invisible_type_annotations,
             free_attributes,
+            raw_attributes,
         })
     }
 }
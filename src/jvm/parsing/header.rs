@@ -0,0 +1,166 @@
+use std::io::{self, Read};
+
+use crate::{
+    jvm::{
+        class::{self, Version},
+        field, method,
+        references::ClassRef,
+        Class,
+    },
+    types::{field_type::FieldType, method_descriptor::MethodDescriptor},
+};
+
+use super::{class_file::ClassFileHeader, errors::EofContext, reader_utils::ValueReaderExt, Error};
+
+/// The declarations in a class file, parsed by [`Class::parse_header`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassHeader {
+    /// The version of the class file.
+    pub version: Version,
+    /// The access flags of the class.
+    pub access_flags: class::AccessFlags,
+    /// The binary name of the class.
+    pub binary_name: String,
+    /// The super class, or `None` for `java/lang/Object` and modules.
+    pub super_class: Option<ClassRef>,
+    /// The implemented interfaces.
+    pub interfaces: Vec<ClassRef>,
+    /// The fields declared in the class.
+    pub fields: Vec<FieldHeader>,
+    /// The methods declared in the class.
+    pub methods: Vec<MethodHeader>,
+}
+
+/// The declaration of a field in a [`ClassHeader`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldHeader {
+    /// The access flags of the field.
+    pub access_flags: field::AccessFlags,
+    /// The name of the field.
+    pub name: String,
+    /// The type of the field.
+    pub field_type: FieldType,
+}
+
+/// The declaration of a method in a [`ClassHeader`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodHeader {
+    /// The access flags of the method.
+    pub access_flags: method::AccessFlags,
+    /// The name of the method.
+    pub name: String,
+    /// The descriptor of the method.
+    pub descriptor: MethodDescriptor,
+}
+
+impl Class {
+    /// Parses the declarations of the class, its fields, and its methods from the given reader,
+    /// skipping all the attributes, including the code of the methods, by their declared lengths.
+    /// This is much faster than [`Class::from_reader`] when only the signatures are needed, e.g.,
+    /// to index the classes in a large JAR file.
+    ///
+    /// The attributes are not validated, so a class file accepted by this function may still be
+    /// rejected by [`Class::from_reader`].
+    /// # Errors
+    /// See [`Error`] for more information.
+    pub fn parse_header<R>(reader: R) -> Result<ClassHeader, Error>
+    where
+        R: Read,
+    {
+        let mut reader = reader;
+        let reader = &mut reader;
+        let ClassFileHeader {
+            minor_version,
+            major_version,
+            constant_pool,
+            access_flags,
+            this_class,
+            super_class,
+            interfaces,
+        } = ClassFileHeader::read_from(reader, None)?;
+        let version = Version::from_versions(major_version, minor_version)?;
+        let access_flags = class::AccessFlags::from_bits(access_flags)
+            .ok_or(Error::UnknownFlags("ClassAccessFlags", access_flags))?;
+        let ClassRef { binary_name } = constant_pool.get_class_ref(this_class)?;
+        let super_class = match super_class {
+            0 => None,
+            it => Some(constant_pool.get_class_ref(it)?),
+        };
+        let interfaces = interfaces
+            .into_iter()
+            .map(|it| constant_pool.get_class_ref(it))
+            .collect::<Result<_, _>>()?;
+
+        let fields_count: u16 = reader.read_value().while_parsing(|| "the fields")?;
+        let fields = (0..fields_count)
+            .map(|index| {
+                let owner = format!("field #{index}");
+                let (access_flags, name_index, descriptor_index) = read_member(reader, &owner)?;
+                let access_flags = field::AccessFlags::from_bits(access_flags)
+                    .ok_or(Error::UnknownFlags("FieldAccessFlag", access_flags))?;
+                let name = constant_pool.get_str(name_index)?.to_owned();
+                let field_type = constant_pool.get_str(descriptor_index)?.parse()?;
+                Ok(FieldHeader {
+                    access_flags,
+                    name,
+                    field_type,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        let methods_count: u16 = reader.read_value().while_parsing(|| "the methods")?;
+        let methods = (0..methods_count)
+            .map(|index| {
+                let owner = format!("method #{index}");
+                let (access_flags, name_index, descriptor_index) = read_member(reader, &owner)?;
+                let access_flags = method::AccessFlags::from_bits(access_flags)
+                    .ok_or(Error::UnknownFlags("MethodAccessFlags", access_flags))?;
+                let name = constant_pool.get_str(name_index)?.to_owned();
+                let descriptor = constant_pool.get_str(descriptor_index)?.parse()?;
+                Ok(MethodHeader {
+                    access_flags,
+                    name,
+                    descriptor,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        skip_attributes(reader, "the class")?;
+
+        Ok(ClassHeader {
+            version,
+            access_flags,
+            binary_name,
+            super_class,
+            interfaces,
+            fields,
+            methods,
+        })
+    }
+}
+
+/// Reads the access flags, the name index, and the descriptor index of `owner`, e.g.,
+/// `field #3`, and skips its attributes.
+fn read_member<R: Read + ?Sized>(reader: &mut R, owner: &str) -> Result<(u16, u16, u16), Error> {
+    let access_flags = reader.read_value().while_parsing(|| owner)?;
+    let name_index = reader.read_value().while_parsing(|| owner)?;
+    let descriptor_index = reader.read_value().while_parsing(|| owner)?;
+    skip_attributes(reader, owner)?;
+    Ok((access_flags, name_index, descriptor_index))
+}
+
+/// Skips an `attributes` table of `owner`, i.e., the count and the attributes following it.
+fn skip_attributes<R: Read + ?Sized>(reader: &mut R, owner: &str) -> Result<(), Error> {
+    let attributes_table = || format!("the attributes of {owner}");
+    let attributes_count: u16 = reader.read_value().while_parsing(attributes_table)?;
+    for _ in 0..attributes_count {
+        let _name_index: u16 = reader.read_value().while_parsing(attributes_table)?;
+        let length: u32 = reader.read_value().while_parsing(attributes_table)?;
+        let skipped = io::copy(&mut reader.take(u64::from(length)), &mut io::sink())
+            .while_parsing(attributes_table)?;
+        if skipped != u64::from(length) {
+            return Err(Error::UnexpectedEof {
+                while_parsing: attributes_table(),
+            });
+        }
+    }
+    Ok(())
+}
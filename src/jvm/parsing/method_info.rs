@@ -1,7 +1,8 @@
-use std::io::{self, Read};
+use std::io::Read;
 
 use crate::{
     jvm::{
+        class::ConstantPool,
         method::{self},
         parsing::Context,
         references::ClassRef,
@@ -12,9 +13,10 @@ use crate::{
 };
 
 use super::{
-    attribute::AttributeInfo,
+    attribute::{read_attributes, retained_attributes, AttributeInfo},
+    errors::EofContext,
     jvm_element_parser::ClassElement,
-    reader_utils::{ReadBytes, ValueReaderExt},
+    reader_utils::ValueReaderExt,
     Error,
 };
 
@@ -22,21 +24,24 @@ use super::{
 #[doc = see_jvm_spec!(4, 6)]
 #[derive(Debug)]
 pub(super) struct MethodInfo {
-    access_flags: u16,
-    name_index: u16,
-    descriptor_index: u16,
-    attributes: Vec<AttributeInfo>,
+    pub(super) access_flags: u16,
+    pub(super) name_index: u16,
+    pub(super) descriptor_index: u16,
+    pub(super) attributes: Vec<AttributeInfo>,
 }
 
-impl ReadBytes for MethodInfo {
-    fn read_bytes<R: Read + ?Sized>(reader: &mut R) -> io::Result<Self> {
-        let access_flags = reader.read_value()?;
-        let name_index = reader.read_value()?;
-        let descriptor_index = reader.read_value()?;
-        let attributes_count: u16 = reader.read_value()?;
-        let attributes = (0..attributes_count)
-            .map(|_| AttributeInfo::read_bytes(reader))
-            .collect::<io::Result<_>>()?;
+impl MethodInfo {
+    /// Reads the structure of the method at `index` in the class file.
+    pub(super) fn read_from<R: Read + ?Sized>(
+        reader: &mut R,
+        constant_pool: &ConstantPool,
+        index: usize,
+    ) -> Result<Self, Error> {
+        let owner = format!("method #{index}");
+        let access_flags = reader.read_value().while_parsing(|| owner.as_str())?;
+        let name_index = reader.read_value().while_parsing(|| owner.as_str())?;
+        let descriptor_index = reader.read_value().while_parsing(|| owner.as_str())?;
+        let attributes = read_attributes(reader, constant_pool, &owner)?;
         Ok(Self {
             access_flags,
             name_index,
@@ -64,6 +69,8 @@ impl ClassElement for Method {
             binary_name: ctx.current_class_binary_name.clone(),
         };
 
+        let raw_attributes = retained_attributes(&attributes, ctx)?;
+
         let attributes: Vec<Attribute> = attributes
             .into_iter()
             .map(|it| Attribute::from_raw(it, ctx))
@@ -127,6 +134,7 @@ impl ClassElement for Method {
             name,
             descriptor,
             owner,
+            index: 0,
             body,
             exceptions,
             runtime_visible_annotations,
@@ -141,6 +149,7 @@ impl ClassElement for Method {
             is_deprecated,
             signature,
             free_attributes,
+            raw_attributes,
         })
     }
 }
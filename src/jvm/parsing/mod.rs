@@ -6,14 +6,19 @@ mod code;
 pub(super) mod constant_pool;
 pub(super) mod errors;
 mod field_info;
+mod header;
 mod jvm_element_parser;
 mod method_info;
 mod module;
+mod pool_usage;
 mod raw_attributes;
 mod reader_utils;
+mod strict;
 
 use crate::jvm::class::{ConstantPool, Version};
 pub use errors::Error;
+pub use header::{ClassHeader, FieldHeader, MethodHeader};
+pub use pool_usage::ConstantPoolUsage;
 
 /// Context used to parse a class file.
 #[derive(Debug, Clone)]
@@ -24,4 +29,62 @@ pub struct Context {
     pub class_version: Version,
     /// The binary name of the class being parsed.
     pub current_class_binary_name: String,
+    /// Whether to keep the original bytes of the attributes.
+    /// See [`ParseOptions::retain_raw_attributes`].
+    pub retain_raw_attributes: bool,
+    /// Whether to keep the original bytes of the code arrays.
+    /// See [`ParseOptions::retain_raw_code`].
+    pub retain_raw_code: bool,
+    /// The maximum depth of nested element values in annotations.
+    /// See [`ParseOptions::max_element_value_depth`].
+    pub max_element_value_depth: u16,
+}
+
+/// Options controlling how a class file is parsed.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Whether to check the consistency of the class after parsing, e.g., that the local
+    /// variable indices of the instructions and the locals of the stack map frames are within
+    /// `max_locals`, and that the execution of no method falls off the end of its code.
+    /// This is disabled by default to tolerate class files produced by obfuscators.
+    pub strict: bool,
+    /// The newest class file version accepted by the parser.
+    /// Parsing a class file with a greater major version fails with
+    /// [`Error::UnsupportedClassVersion`], regardless of whether preview features are enabled.
+    /// All versions known to the parser are accepted if this is `None`.
+    pub max_version: Option<Version>,
+    /// Whether to keep the original bytes of all the attributes of the class, its fields, its
+    /// methods, and their code in the `raw_attributes` of each element, so that a tool rewriting
+    /// the class file can copy the attributes it leaves unchanged byte-for-byte.
+    /// This is disabled by default to save memory.
+    pub retain_raw_attributes: bool,
+    /// Whether to keep the original bytes of the `code` array of each method in
+    /// [`MethodBody::raw_code`](crate::jvm::code::MethodBody::raw_code), e.g., for hashing the
+    /// code or copying an unmodified method byte-for-byte.
+    /// This is disabled by default to save memory.
+    pub retain_raw_code: bool,
+    /// The maximum depth of nested element values in annotations, where the values of the
+    /// elements of an annotation are at depth 1 and each array or nested annotation adds a level.
+    /// Parsing a class file with deeper element values fails with
+    /// [`Error::ElementValueTooDeep`] instead of overflowing the stack.
+    /// Defaults to [`ParseOptions::DEFAULT_MAX_ELEMENT_VALUE_DEPTH`].
+    pub max_element_value_depth: u16,
+}
+
+impl ParseOptions {
+    /// The default of [`ParseOptions::max_element_value_depth`], which is far deeper than any
+    /// annotation written by hand.
+    pub const DEFAULT_MAX_ELEMENT_VALUE_DEPTH: u16 = 255;
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            strict: false,
+            max_version: None,
+            retain_raw_attributes: false,
+            retain_raw_code: false,
+            max_element_value_depth: Self::DEFAULT_MAX_ELEMENT_VALUE_DEPTH,
+        }
+    }
 }
@@ -1,6 +1,6 @@
 use crate::jvm::{
-    class::constant_pool::Entry,
     module::{Export, Open, Provide, Require},
+    references::ModuleRef,
     Module,
 };
 
@@ -100,14 +100,7 @@ impl ClassElement for Module {
             uses,
             provides,
         } = raw;
-        let module_info_entry = ctx.constant_pool.get_entry(info_index)?;
-        let &Entry::Module { name_index } = module_info_entry else {
-            Err(Error::MismatchedConstantPoolEntryType {
-                expected: "Module",
-                found: module_info_entry.constant_kind(),
-            })?
-        };
-        let name = ctx.constant_pool.get_str(name_index)?.to_owned();
+        let ModuleRef { name } = ctx.constant_pool.get_module_ref(info_index)?;
         let flags = ClassElement::from_raw(flags, ctx)?;
         let version = if version_index > 0 {
             Some(ctx.constant_pool.get_str(version_index)?.to_owned())
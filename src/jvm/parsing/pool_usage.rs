@@ -0,0 +1,459 @@
+use std::io::Read;
+
+use crate::jvm::{
+    class::{constant_pool::Entry, ConstantPool},
+    code::RawInstruction,
+};
+
+use super::{
+    attribute::AttributeInfo,
+    class_file::ClassFile,
+    raw_attributes::{
+        Annotation, BootstrapMethod, Code, ElementValueInfo, EnclosingMethod, InnerClass,
+        LocalVariableInfo, ModuleInfo, ParameterInfo, RecordComponentInfo, StackMapFrameInfo,
+        TypeAnnotation, VerificationTypeInfo,
+    },
+    reader_utils::ValueReaderExt,
+    Error, ParseOptions,
+};
+
+/// The number of references to each entry in the constant pool of a class file, computed by
+/// [`ConstantPoolUsage::from_reader`].
+///
+/// An entry is referenced by the structures of the class file, e.g., `this_class`, the names and
+/// descriptors of the fields and methods, the names of the attributes, and the indices in the
+/// attributes known to the parser, including the operands of the instructions in the `Code`
+/// attributes. An entry referenced by another entry, e.g., the [`Entry::Utf8`] holding the name
+/// of an [`Entry::Class`], is counted once if the referencing entry is itself referenced, so an
+/// entry only reachable from unused entries is unused as well.
+///
+/// The contents of unrecognized attributes are opaque to the parser, so the entries referenced
+/// only by them are reported as unused. A rewriter removing the [unused
+/// entries](ConstantPoolUsage::unused_entries) must drop or rewrite such attributes.
+#[derive(Debug, Clone)]
+pub struct ConstantPoolUsage {
+    constant_pool: ConstantPool,
+    reference_counts: Vec<usize>,
+}
+
+impl ConstantPoolUsage {
+    /// Parses a class file from the given reader and counts the references to each entry in its
+    /// constant pool.
+    /// # Errors
+    /// See [`Error`] for more information.
+    pub fn from_reader<R>(reader: R) -> Result<Self, Error>
+    where
+        R: Read,
+    {
+        let mut reader = reader;
+        let class_file = ClassFile::read_from(&mut reader, None)?;
+        let mut usage = Self {
+            reference_counts: vec![0; class_file.constant_pool.len() + 1],
+            constant_pool: class_file.constant_pool,
+        };
+        usage.reference(class_file.this_class);
+        usage.reference(class_file.super_class);
+        class_file
+            .interfaces
+            .iter()
+            .for_each(|&it| usage.reference(it));
+        for field in &class_file.fields {
+            usage.reference(field.name_index);
+            usage.reference(field.descriptor_index);
+            usage.scan_attributes(&field.attributes)?;
+        }
+        for method in &class_file.methods {
+            usage.reference(method.name_index);
+            usage.reference(method.descriptor_index);
+            usage.scan_attributes(&method.attributes)?;
+        }
+        usage.scan_attributes(&class_file.attributes)?;
+        Ok(usage)
+    }
+
+    /// Returns the constant pool of the class file.
+    #[must_use]
+    pub fn constant_pool(&self) -> &ConstantPool {
+        &self.constant_pool
+    }
+
+    /// Returns the number of references to the entry at `index`, or `0` if `index` does not
+    /// point to a valid entry.
+    #[must_use]
+    pub fn reference_count(&self, index: u16) -> usize {
+        self.reference_counts
+            .get(usize::from(index))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Returns the indices of the entries that are not referenced, in ascending order.
+    /// The slots following [`Entry::Long`] and [`Entry::Double`] are not entries and are never
+    /// reported.
+    pub fn unused_entries(&self) -> impl Iterator<Item = u16> + '_ {
+        (1..=u16::try_from(self.constant_pool.len()).unwrap_or(u16::MAX)).filter(|&index| {
+            self.constant_pool.get_entry(index).is_ok() && self.reference_count(index) == 0
+        })
+    }
+
+    /// Records a reference to the entry at `index`, and to the entries it references if this is
+    /// the first reference to it. Invalid indices, including `0`, are ignored.
+    fn reference(&mut self, index: u16) {
+        #[allow(clippy::enum_glob_use)]
+        use Entry::*;
+
+        let mut pending = vec![index];
+        while let Some(index) = pending.pop() {
+            let Ok(entry) = self.constant_pool.get_entry(index) else {
+                continue;
+            };
+            let count = &mut self.reference_counts[usize::from(index)];
+            *count += 1;
+            if *count > 1 {
+                continue;
+            }
+            match *entry {
+                Class { name_index } | Module { name_index } | Package { name_index } => {
+                    pending.push(name_index);
+                }
+                String { string_index } => pending.push(string_index),
+                FieldRef {
+                    class_index,
+                    name_and_type_index,
+                }
+                | MethodRef {
+                    class_index,
+                    name_and_type_index,
+                }
+                | InterfaceMethodRef {
+                    class_index,
+                    name_and_type_index,
+                } => pending.extend([class_index, name_and_type_index]),
+                NameAndType {
+                    name_index,
+                    descriptor_index,
+                } => pending.extend([name_index, descriptor_index]),
+                MethodHandle {
+                    reference_index, ..
+                } => pending.push(reference_index),
+                MethodType { descriptor_index } => pending.push(descriptor_index),
+                // The bootstrap methods are referenced by the `BootstrapMethods` attribute.
+                Dynamic {
+                    name_and_type_index,
+                    ..
+                }
+                | InvokeDynamic {
+                    name_and_type_index,
+                    ..
+                } => pending.push(name_and_type_index),
+                Utf8(_) | Integer(_) | Float(_) | Long(_) | Double(_) => {}
+            }
+        }
+    }
+
+    fn scan_attributes(&mut self, attributes: &[AttributeInfo]) -> Result<(), Error> {
+        for attribute in attributes {
+            self.reference(attribute.name_idx);
+            let name = self.constant_pool.get_str(attribute.name_idx)?.to_owned();
+            self.scan_attribute(&name, &mut attribute.info.as_slice())?;
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn scan_attribute(&mut self, name: &str, reader: &mut &[u8]) -> Result<(), Error> {
+        let max_depth = ParseOptions::DEFAULT_MAX_ELEMENT_VALUE_DEPTH;
+        match name {
+            "ConstantValue" | "Signature" | "SourceFile" | "ModuleMainClass" | "NestHost" => {
+                let index = reader.read_value()?;
+                self.reference(index);
+            }
+            "Exceptions" | "ModulePackages" | "NestMembers" | "PermittedSubclasses" => {
+                let count: u16 = reader.read_value()?;
+                for _ in 0..count {
+                    let index = reader.read_value()?;
+                    self.reference(index);
+                }
+            }
+            "Code" => {
+                let code: Code = reader.read_value()?;
+                self.scan_code(&code)?;
+            }
+            "StackMapTable" => {
+                let count: u16 = reader.read_value()?;
+                for _ in 0..count {
+                    let frame: StackMapFrameInfo = reader.read_value()?;
+                    self.scan_frame(&frame);
+                }
+            }
+            "InnerClasses" => {
+                let count: u16 = reader.read_value()?;
+                for _ in 0..count {
+                    let inner_class: InnerClass = reader.read_value()?;
+                    self.reference(inner_class.info_index);
+                    self.reference(inner_class.outer_class_info_index);
+                    self.reference(inner_class.inner_name_index);
+                }
+            }
+            "EnclosingMethod" => {
+                let enclosing_method: EnclosingMethod = reader.read_value()?;
+                self.reference(enclosing_method.class_index);
+                self.reference(enclosing_method.method_index);
+            }
+            "LocalVariableTable" | "LocalVariableTypeTable" => {
+                let count: u16 = reader.read_value()?;
+                for _ in 0..count {
+                    let local_variable: LocalVariableInfo = reader.read_value()?;
+                    self.reference(local_variable.name_index);
+                    self.reference(local_variable.desc_or_signature_idx);
+                }
+            }
+            "RuntimeVisibleAnnotations" | "RuntimeInvisibleAnnotations" => {
+                let count: u16 = reader.read_value()?;
+                for _ in 0..count {
+                    let annotation = Annotation::read_from(reader, 1, max_depth)?;
+                    self.scan_annotation(&annotation);
+                }
+            }
+            "RuntimeVisibleParameterAnnotations" | "RuntimeInvisibleParameterAnnotations" => {
+                let parameter_count: u8 = reader.read_value()?;
+                for _ in 0..parameter_count {
+                    let count: u16 = reader.read_value()?;
+                    for _ in 0..count {
+                        let annotation = Annotation::read_from(reader, 1, max_depth)?;
+                        self.scan_annotation(&annotation);
+                    }
+                }
+            }
+            "RuntimeVisibleTypeAnnotations" | "RuntimeInvisibleTypeAnnotations" => {
+                let count: u16 = reader.read_value()?;
+                for _ in 0..count {
+                    let annotation = TypeAnnotation::read_from(reader, 1, max_depth)?;
+                    self.reference(annotation.type_index);
+                    self.scan_element_value_pairs(&annotation.element_value_pairs);
+                }
+            }
+            "AnnotationDefault" => {
+                let value = ElementValueInfo::read_from(reader, 1, max_depth)?;
+                self.scan_element_value(&value);
+            }
+            "BootstrapMethods" => {
+                let count: u16 = reader.read_value()?;
+                for _ in 0..count {
+                    let bootstrap_method: BootstrapMethod = reader.read_value()?;
+                    self.reference(bootstrap_method.method_ref_idx);
+                    bootstrap_method
+                        .arguments
+                        .iter()
+                        .for_each(|&it| self.reference(it));
+                }
+            }
+            "MethodParameters" => {
+                let count: u8 = reader.read_value()?;
+                for _ in 0..count {
+                    let ParameterInfo(name_index, _) = reader.read_value()?;
+                    self.reference(name_index);
+                }
+            }
+            "Module" => {
+                let module: ModuleInfo = reader.read_value()?;
+                self.scan_module(&module);
+            }
+            "Record" => {
+                let count: u16 = reader.read_value()?;
+                for _ in 0..count {
+                    let component: RecordComponentInfo = reader.read_value()?;
+                    self.reference(component.name_index);
+                    self.reference(component.descriptor_index);
+                    self.scan_attributes(&component.attributes)?;
+                }
+            }
+            // `Synthetic`, `Deprecated`, `SourceDebugExtension`, and `LineNumberTable` do not
+            // reference the constant pool, and unrecognized attributes are opaque.
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn scan_code(&mut self, code: &Code) -> Result<(), Error> {
+        #[allow(clippy::enum_glob_use)]
+        use RawInstruction::*;
+
+        let instructions = RawInstruction::from_slice(&code.instruction_bytes)?;
+        for (_, instruction) in instructions.iter() {
+            match *instruction {
+                Ldc { const_index } => self.reference(const_index.into()),
+                LdcW { const_index } | Ldc2W { const_index } => self.reference(const_index),
+                GetStatic { field_ref_index }
+                | PutStatic { field_ref_index }
+                | GetField { field_ref_index }
+                | PutField { field_ref_index } => self.reference(field_ref_index),
+                InvokeVirtual { method_index }
+                | InvokeSpecial { method_index }
+                | InvokeStatic { method_index }
+                | InvokeInterface { method_index, .. } => self.reference(method_index),
+                InvokeDynamic { dynamic_index } => self.reference(dynamic_index),
+                New { index } | ANewArray { index } | MultiANewArray { index, .. } => {
+                    self.reference(index);
+                }
+                CheckCast { target_type_index } | InstanceOf { target_type_index } => {
+                    self.reference(target_type_index);
+                }
+                _ => {}
+            }
+        }
+        code.exception_table
+            .iter()
+            .for_each(|it| self.reference(it.catch_type_idx));
+        self.scan_attributes(&code.attributes)
+    }
+
+    fn scan_frame(&mut self, frame: &StackMapFrameInfo) {
+        match frame {
+            StackMapFrameInfo::SameLocals1StackItemFrame { stack, .. }
+            | StackMapFrameInfo::SameLocals1StackItemFrameExtended { stack, .. } => {
+                self.scan_verification_type(stack);
+            }
+            StackMapFrameInfo::AppendFrame { locals, .. } => {
+                for verification_type in locals {
+                    self.scan_verification_type(verification_type);
+                }
+            }
+            StackMapFrameInfo::FullFrame { locals, stack, .. } => {
+                for verification_type in locals.iter().chain(stack) {
+                    self.scan_verification_type(verification_type);
+                }
+            }
+            StackMapFrameInfo::SameFrame { .. }
+            | StackMapFrameInfo::ChopFrame { .. }
+            | StackMapFrameInfo::SameFrameExtended { .. } => {}
+        }
+    }
+
+    fn scan_verification_type(&mut self, verification_type: &VerificationTypeInfo) {
+        if let VerificationTypeInfo::Object { class_info_index } = verification_type {
+            self.reference(*class_info_index);
+        }
+    }
+
+    fn scan_annotation(&mut self, annotation: &Annotation) {
+        self.reference(annotation.type_index);
+        self.scan_element_value_pairs(&annotation.element_value_pairs);
+    }
+
+    fn scan_element_value_pairs(&mut self, pairs: &[(u16, ElementValueInfo)]) {
+        for (name_index, value) in pairs {
+            self.reference(*name_index);
+            self.scan_element_value(value);
+        }
+    }
+
+    fn scan_element_value(&mut self, value: &ElementValueInfo) {
+        match value {
+            ElementValueInfo::Const(_, index) | ElementValueInfo::ClassInfo(index) => {
+                self.reference(*index);
+            }
+            ElementValueInfo::Enum {
+                type_name_index,
+                const_name_index,
+            } => {
+                self.reference(*type_name_index);
+                self.reference(*const_name_index);
+            }
+            ElementValueInfo::Annotation(annotation) => self.scan_annotation(annotation),
+            ElementValueInfo::Array(values) => {
+                for value in values {
+                    self.scan_element_value(value);
+                }
+            }
+        }
+    }
+
+    fn scan_module(&mut self, module: &ModuleInfo) {
+        self.reference(module.info_index);
+        self.reference(module.version_index);
+        for requires in &module.requires {
+            self.reference(requires.requires_index);
+            self.reference(requires.version_index);
+        }
+        for exports in &module.exports {
+            self.reference(exports.exports_index);
+            exports.to.iter().for_each(|&it| self.reference(it));
+        }
+        for opens in &module.opens {
+            self.reference(opens.opens_index);
+            opens.to.iter().for_each(|&it| self.reference(it));
+        }
+        module.uses.iter().for_each(|&it| self.reference(it));
+        for provides in &module.provides {
+            self.reference(provides.provides_index);
+            provides.with.iter().for_each(|&it| self.reference(it));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jvm::Class;
+
+    fn utf8(pool: &mut Vec<u8>, value: &str) {
+        pool.push(1);
+        pool.extend_from_slice(&u16::try_from(value.len()).unwrap().to_be_bytes());
+        pool.extend_from_slice(value.as_bytes());
+    }
+
+    /// A class with a method `static void run() { "hello"; }` and some unused constants.
+    fn class_bytes() -> Vec<u8> {
+        let mut pool = Vec::new();
+        utf8(&mut pool, "Test"); // #1
+        pool.extend_from_slice(&[7, 0, 1]); // #2
+        utf8(&mut pool, "java/lang/Object"); // #3
+        pool.extend_from_slice(&[7, 0, 3]); // #4
+        utf8(&mut pool, "run"); // #5
+        utf8(&mut pool, "()V"); // #6
+        utf8(&mut pool, "Code"); // #7
+        utf8(&mut pool, "hello"); // #8
+        pool.extend_from_slice(&[8, 0, 8]); // #9
+        pool.extend_from_slice(&[3, 0, 0, 0, 42]); // #10, unused
+        utf8(&mut pool, "unused"); // #11, unused
+        pool.extend_from_slice(&[5, 0, 0, 0, 0, 0, 0, 0, 7]); // #12 and #13, unused
+        utf8(&mut pool, "gone"); // #14, only referenced by #15
+        pool.extend_from_slice(&[8, 0, 14]); // #15, unused
+
+        let mut bytes = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 52, 0, 16];
+        bytes.extend(pool);
+        // access_flags, this_class, super_class, interfaces_count, fields_count, methods_count
+        bytes.extend_from_slice(&[0, 0x21, 0, 2, 0, 4, 0, 0, 0, 0, 0, 1]);
+        // access_flags, name_index, descriptor_index, attributes_count
+        bytes.extend_from_slice(&[0, 0x09, 0, 5, 0, 6, 0, 1]);
+        // attribute_name_index, attribute_length, max_stack, max_locals
+        bytes.extend_from_slice(&[0, 7, 0, 0, 0, 16, 0, 1, 0, 0]);
+        // code_length, ldc #9, pop, return, exception_table_length, attributes_count
+        bytes.extend_from_slice(&[0, 0, 0, 4, 0x12, 9, 0x57, 0xB1, 0, 0, 0, 0]);
+        // attributes_count
+        bytes.extend_from_slice(&[0, 0]);
+        bytes
+    }
+
+    #[test]
+    fn unused_entries() {
+        let bytes = class_bytes();
+        assert!(Class::from_reader(bytes.as_slice()).is_ok());
+
+        let usage = ConstantPoolUsage::from_reader(bytes.as_slice()).unwrap();
+        let constant_pool = usage.constant_pool();
+        assert_eq!(15, constant_pool.len());
+        assert_eq!(14, constant_pool.entry_count());
+        assert!(!constant_pool.is_empty());
+        assert_eq!(
+            vec![10, 11, 12, 14, 15],
+            usage.unused_entries().collect::<Vec<_>>()
+        );
+        assert_eq!(1, usage.reference_count(1));
+        assert_eq!(1, usage.reference_count(9));
+        assert_eq!(1, usage.reference_count(8));
+        assert_eq!(0, usage.reference_count(13));
+        assert_eq!(0, usage.reference_count(16));
+    }
+}
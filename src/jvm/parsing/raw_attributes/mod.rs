@@ -8,6 +8,7 @@ use super::attribute::AttributeInfo;
 use super::reader_utils::read_byte_chunk;
 use super::reader_utils::ReadBytes;
 use super::reader_utils::ValueReaderExt;
+use super::Error;
 
 /// The `Code` atribute.
 #[doc = see_jvm_spec!(4, 7, 3)]
@@ -246,17 +247,16 @@ pub struct Annotation {
     pub element_value_pairs: Vec<(u16, ElementValueInfo)>,
 }
 
-impl ReadBytes for Annotation {
-    fn read_bytes<R: Read + ?Sized>(reader: &mut R) -> io::Result<Self> {
+impl Annotation {
+    /// Reads an annotation whose element values are nested at `depth`.
+    /// See [`ElementValueInfo::read_from`] for the depth limit.
+    pub fn read_from<R: Read + ?Sized>(
+        reader: &mut R,
+        depth: u16,
+        max_depth: u16,
+    ) -> Result<Self, Error> {
         let type_index = reader.read_value()?;
-        let num_element_value_pairs: u16 = reader.read_value()?;
-        let element_value_pairs = (0..num_element_value_pairs)
-            .map(|_| {
-                let element_name_index = reader.read_value()?;
-                let element_value = reader.read_value()?;
-                Ok((element_name_index, element_value))
-            })
-            .collect::<io::Result<_>>()?;
+        let element_value_pairs = read_element_value_pairs(reader, depth, max_depth)?;
         Ok(Self {
             type_index,
             element_value_pairs,
@@ -264,6 +264,21 @@ impl ReadBytes for Annotation {
     }
 }
 
+fn read_element_value_pairs<R: Read + ?Sized>(
+    reader: &mut R,
+    depth: u16,
+    max_depth: u16,
+) -> Result<Vec<(u16, ElementValueInfo)>, Error> {
+    let num_element_value_pairs: u16 = reader.read_value()?;
+    (0..num_element_value_pairs)
+        .map(|_| {
+            let element_name_index = reader.read_value()?;
+            let element_value = ElementValueInfo::read_from(reader, depth, max_depth)?;
+            Ok((element_name_index, element_value))
+        })
+        .collect()
+}
+
 pub enum ElementValueInfo {
     Const(u8, u16),
     Enum {
@@ -275,8 +290,21 @@ pub enum ElementValueInfo {
     Array(Vec<ElementValueInfo>),
 }
 
-impl ReadBytes for ElementValueInfo {
-    fn read_bytes<R: Read + ?Sized>(reader: &mut R) -> io::Result<Self> {
+impl ElementValueInfo {
+    /// Reads an element value nested at `depth`, where the values of the element-value pairs of
+    /// an attribute are at depth 1, and the values in an array or a nested annotation are one
+    /// level deeper than the array or the annotation.
+    /// # Errors
+    /// Returns [`Error::ElementValueTooDeep`] if `depth` exceeds `max_depth`, so that a crafted
+    /// class file cannot overflow the stack.
+    pub fn read_from<R: Read + ?Sized>(
+        reader: &mut R,
+        depth: u16,
+        max_depth: u16,
+    ) -> Result<Self, Error> {
+        if depth > max_depth {
+            return Err(Error::ElementValueTooDeep { max_depth });
+        }
         let tag: u8 = reader.read_value()?;
         match tag {
             tag @ (b'B' | b'C' | b'D' | b'F' | b'I' | b'J' | b'S' | b'Z' | b's') => {
@@ -287,32 +315,51 @@ impl ReadBytes for ElementValueInfo {
                 const_name_index: reader.read_value()?,
             }),
             b'c' => Ok(Self::ClassInfo(reader.read_value()?)),
-            b'@' => Ok(Self::Annotation(reader.read_value()?)),
+            b'@' => Annotation::read_from(reader, nested_depth(depth, max_depth)?, max_depth)
+                .map(Self::Annotation),
             b'[' => {
+                let depth = nested_depth(depth, max_depth)?;
                 let num_values: u16 = reader.read_value()?;
                 let values = (0..num_values)
-                    .map(|_| reader.read_value())
-                    .collect::<io::Result<_>>()?;
+                    .map(|_| Self::read_from(reader, depth, max_depth))
+                    .collect::<Result<_, _>>()?;
                 Ok(Self::Array(values))
             }
             _ => Err(io::Error::new(
                 io::ErrorKind::InvalidData,
                 format!("Unknown element value tag: {tag}"),
-            )),
+            )
+            .into()),
         }
     }
 }
 
+/// Returns the depth of the values nested one level below `depth`.
+/// The depth is already beyond `max_depth` if it does not fit in a [`u16`].
+fn nested_depth(depth: u16, max_depth: u16) -> Result<u16, Error> {
+    depth
+        .checked_add(1)
+        .ok_or(Error::ElementValueTooDeep { max_depth })
+}
+
 pub struct TypeAnnotation {
+    pub target_type: u8,
     pub target_info: TargetInfo,
     pub target_path: Vec<(u8, u8)>,
     pub type_index: u16,
     pub element_value_pairs: Vec<(u16, ElementValueInfo)>,
 }
 
-impl ReadBytes for TypeAnnotation {
-    fn read_bytes<R: Read + ?Sized>(reader: &mut R) -> io::Result<Self> {
-        let target_info = reader.read_value()?;
+impl TypeAnnotation {
+    /// Reads a type annotation whose element values are nested at `depth`.
+    /// See [`ElementValueInfo::read_from`] for the depth limit.
+    pub fn read_from<R: Read + ?Sized>(
+        reader: &mut R,
+        depth: u16,
+        max_depth: u16,
+    ) -> Result<Self, Error> {
+        let target_type = reader.read_value()?;
+        let target_info = TargetInfo::read_from(reader, target_type)?;
         let target_path_length: u8 = reader.read_value()?;
         let target_path = (0..target_path_length)
             .map(|_| {
@@ -322,15 +369,9 @@ impl ReadBytes for TypeAnnotation {
             })
             .collect::<io::Result<_>>()?;
         let type_index = reader.read_value()?;
-        let num_element_value_pairs: u16 = reader.read_value()?;
-        let element_value_pairs = (0..num_element_value_pairs)
-            .map(|_| {
-                let element_name_index = reader.read_value()?;
-                let element_value = reader.read_value()?;
-                Ok((element_name_index, element_value))
-            })
-            .collect::<io::Result<_>>()?;
+        let element_value_pairs = read_element_value_pairs(reader, depth, max_depth)?;
         Ok(Self {
+            target_type,
             target_info,
             target_path,
             type_index,
@@ -352,9 +393,9 @@ pub enum TargetInfo {
     TypeArgument { offset: ProgramCounter, index: u8 },
 }
 
-impl ReadBytes for TargetInfo {
-    fn read_bytes<R: Read + ?Sized>(reader: &mut R) -> io::Result<Self> {
-        let target_type: u8 = reader.read_value()?;
+impl TargetInfo {
+    /// Reads the `target_info` item following the `target_type` item of a type annotation.
+    fn read_from<R: Read + ?Sized>(reader: &mut R, target_type: u8) -> io::Result<Self> {
         let target_info = match target_type {
             0x00 | 0x01 => Self::TypeParameter {
                 index: reader.read_value()?,
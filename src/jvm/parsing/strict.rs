@@ -0,0 +1,297 @@
+//! Validation of instruction operands performed in strict mode.
+
+use crate::{
+    jvm::{
+        code::{
+            FallsOffCode, Instruction, MethodBody, ProgramCounter, StackMapFrame, VerificationType,
+        },
+        Class, Method,
+    },
+    macros::{malform, see_jvm_spec},
+    types::field_type::FieldType,
+};
+
+use super::Error;
+
+/// The maximum number of dimensions of an array type.
+const MAX_ARRAY_DIMENSIONS: usize = 255;
+
+/// Checks that the operands of the instructions in all methods of `class` are consistent with the
+/// method and the types they refer to.
+pub(super) fn check_operands(class: &Class) -> Result<(), Error> {
+    for method in &class.methods {
+        let Some(body) = method.body.as_ref() else {
+            continue;
+        };
+        check_body(body).map_err(|(pc, reason)| Error::InvalidOperand {
+            method: method.name.clone(),
+            pc,
+            reason,
+        })?;
+    }
+    Ok(())
+}
+
+/// Checks that the execution of no method in `class` may fall off the end of its code.
+/// See [`MethodBody::validate_termination`].
+pub(super) fn check_termination(class: &Class) -> Result<(), Error> {
+    for method in &class.methods {
+        let Some(body) = method.body.as_ref() else {
+            continue;
+        };
+        body.validate_termination()
+            .map_err(|FallsOffCode(pc)| Error::FallsOffCode {
+                method: method.name.clone(),
+                descriptor: method.descriptor.to_string(),
+                pc,
+            })?;
+    }
+    Ok(())
+}
+
+/// Checks that the `ModulePackages` and `ModuleMainClass` attributes only appear along with the
+/// `Module` attribute.
+#[doc = see_jvm_spec!(4, 7, 26)]
+pub(super) fn check_module_attributes(class: &Class) -> Result<(), Error> {
+    if class.module.is_none() {
+        if !class.module_packages.is_empty() {
+            malform!("ModulePackages is only allowed with the Module attribute");
+        }
+        if class.module_main_class.is_some() {
+            malform!("ModuleMainClass is only allowed with the Module attribute");
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the stack map frames of all methods in `class` keep the local variables within
+/// `max_locals` and do not chop more local variables than defined.
+/// The frames are interpreted in order starting from the [initial frame](Method::initial_frame)
+/// of each method.
+#[doc = see_jvm_spec!(4, 7, 4)]
+pub(super) fn check_stack_map_frames(class: &Class) -> Result<(), Error> {
+    for method in &class.methods {
+        let Some(body) = method.body.as_ref() else {
+            continue;
+        };
+        check_frames(method, body).map_err(|(frame_index, reason)| {
+            Error::InvalidStackMapFrame {
+                method: method.name.clone(),
+                frame_index,
+                reason,
+            }
+        })?;
+    }
+    Ok(())
+}
+
+fn check_frames(method: &Method, body: &MethodBody) -> Result<(), (usize, &'static str)> {
+    // The locals as in the stack map table, where `long` and `double` take one entry each.
+    let mut locals: Vec<_> = method.initial_frame();
+    let mut index = 0;
+    while index < locals.len() {
+        if is_wide(&locals[index]) {
+            locals.remove(index + 1);
+        }
+        index += 1;
+    }
+    for (frame_index, frame) in body.stack_map_table.iter().flatten().enumerate() {
+        match frame {
+            StackMapFrame::SameFrame { .. } | StackMapFrame::SameLocals1StackItemFrame { .. } => {}
+            StackMapFrame::ChopFrame { chop_count, .. } => {
+                let chop_count = usize::from(*chop_count);
+                if chop_count > locals.len() {
+                    return Err((frame_index, "chop_frame removes more locals than defined"));
+                }
+                locals.truncate(locals.len() - chop_count);
+            }
+            StackMapFrame::AppendFrame {
+                locals: appended, ..
+            } => locals.extend(appended.iter().cloned()),
+            StackMapFrame::FullFrame {
+                locals: full_locals,
+                ..
+            } => locals.clone_from(full_locals),
+        }
+        let slots: usize = locals
+            .iter()
+            .map(|it| if is_wide(it) { 2 } else { 1 })
+            .sum();
+        if slots > usize::from(body.max_locals) {
+            return Err((frame_index, "The locals exceed max_locals"));
+        }
+    }
+    Ok(())
+}
+
+const fn is_wide(verification_type: &VerificationType) -> bool {
+    matches!(
+        verification_type,
+        VerificationType::LongVariable | VerificationType::DoubleVariable
+    )
+}
+
+fn check_body(body: &MethodBody) -> Result<(), (ProgramCounter, &'static str)> {
+    for (pc, instruction) in &body.instructions {
+        if let Some((index, width)) = instruction.local_slots() {
+            if u32::from(index) + u32::from(width) > u32::from(body.max_locals) {
+                return Err((*pc, "Local variable index exceeds max_locals"));
+            }
+        }
+        match instruction {
+            Instruction::MultiANewArray(array_type, dimensions) => {
+                if *dimensions == 0 {
+                    return Err((*pc, "multianewarray must create at least one dimension"));
+                }
+                if array_dimensions(array_type) < usize::from(*dimensions) {
+                    return Err((*pc, "multianewarray dimensions exceed the array type"));
+                }
+            }
+            Instruction::ANewArray(element_type)
+                if array_dimensions(element_type) >= MAX_ARRAY_DIMENSIONS =>
+            {
+                return Err((*pc, "anewarray creates an array with too many dimensions"));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn array_dimensions(field_type: &FieldType) -> usize {
+    match field_type {
+        FieldType::Array(element) => 1 + array_dimensions(element),
+        FieldType::Base(_) | FieldType::Object(_) => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        jvm::{
+            code::{InstructionList, WideInstruction},
+            method, module,
+            references::{ClassRef, PackageRef},
+            Module,
+        },
+        types::field_type::PrimitiveType,
+    };
+
+    fn body(max_locals: u16, instruction: Instruction) -> MethodBody {
+        MethodBody {
+            max_locals,
+            instructions: InstructionList::from([(0.into(), instruction)]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn local_indices() {
+        assert!(check_body(&body(1, Instruction::ALoad0)).is_ok());
+        assert!(check_body(&body(1, Instruction::ILoad1)).is_err());
+        assert!(check_body(&body(2, Instruction::LLoad(0))).is_ok());
+        assert!(check_body(&body(2, Instruction::DStore1)).is_err());
+        assert!(check_body(&body(300, Instruction::Wide(WideInstruction::IInc(299, 1)))).is_ok());
+        assert!(check_body(&body(3, Instruction::IInc(3, 1))).is_err());
+    }
+
+    #[test]
+    fn array_creation() {
+        let array_type: FieldType = "[[I".parse().unwrap();
+        assert!(check_body(&body(0, Instruction::MultiANewArray(array_type.clone(), 2))).is_ok());
+        assert!(check_body(&body(0, Instruction::MultiANewArray(array_type.clone(), 3))).is_err());
+        assert!(check_body(&body(0, Instruction::MultiANewArray(array_type, 0))).is_err());
+        let element_type = FieldType::array_of(FieldType::Base(PrimitiveType::Int), 255);
+        assert!(check_body(&body(0, Instruction::ANewArray(element_type))).is_err());
+    }
+
+    #[test]
+    fn module_attributes() {
+        let main_class = Some(ClassRef::new("org/mokapot/Main"));
+        let mut class = Class {
+            binary_name: "module-info".to_owned(),
+            module_main_class: main_class.clone(),
+            ..Default::default()
+        };
+        assert!(check_module_attributes(&class).is_err());
+        class.module = Some(Module {
+            name: "mokapot".to_owned(),
+            flags: module::Flags::empty(),
+            version: None,
+            requires: vec![],
+            exports: vec![],
+            opens: vec![],
+            uses: vec![],
+            provides: vec![],
+        });
+        class.module_packages = vec![PackageRef {
+            binary_name: "org/mokapot".to_owned(),
+        }];
+        assert!(check_module_attributes(&class).is_ok());
+        assert_eq!(main_class.as_ref(), class.module_main_class());
+        assert_eq!(1, class.module_packages().len());
+        class.module = None;
+        class.module_main_class = None;
+        assert!(check_module_attributes(&class).is_err());
+    }
+
+    #[test]
+    fn stack_map_frames() {
+        let method = |max_locals, frames| Method {
+            access_flags: method::AccessFlags::STATIC,
+            name: "test".to_owned(),
+            descriptor: "(J)V".parse().unwrap(),
+            body: Some(MethodBody {
+                max_locals,
+                stack_map_table: Some(frames),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let check = |method: Method| check_frames(&method, method.body.as_ref().unwrap());
+        let append = StackMapFrame::AppendFrame {
+            offset_delta: 0,
+            locals: vec![VerificationType::IntegerVariable],
+        };
+        // The `long` parameter takes two slots but one entry.
+        let chop_one = StackMapFrame::ChopFrame {
+            offset_delta: 0,
+            chop_count: 1,
+        };
+        assert!(check(method(3, vec![append.clone()])).is_ok());
+        assert_eq!(
+            Err((0, "The locals exceed max_locals")),
+            check(method(2, vec![append.clone()]))
+        );
+        assert!(check(method(3, vec![append, chop_one.clone(), chop_one.clone()])).is_ok());
+        assert_eq!(
+            Err((1, "chop_frame removes more locals than defined")),
+            check(method(2, vec![chop_one.clone(), chop_one]))
+        );
+    }
+
+    #[test]
+    fn termination() {
+        let class = Class {
+            methods: vec![Method {
+                name: "test".to_owned(),
+                descriptor: "(I)V".parse().unwrap(),
+                body: Some(body(1, Instruction::Nop)),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let Err(Error::FallsOffCode {
+            method,
+            descriptor,
+            pc,
+        }) = check_termination(&class)
+        else {
+            panic!("The method should fall off the end of its code");
+        };
+        assert_eq!("test", method);
+        assert_eq!("(I)V", descriptor);
+        assert_eq!(ProgramCounter::from(0), pc);
+    }
+}
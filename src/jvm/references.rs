@@ -1,8 +1,11 @@
 //! References to JVM elements.
 
+use std::str::FromStr;
+
 use crate::types::{
     field_type::FieldType,
     method_descriptor::{MethodDescriptor, ReturnType},
+    names,
 };
 
 use super::Method;
@@ -22,8 +25,35 @@ impl ClassRef {
             binary_name: binary_name.into(),
         }
     }
+
+    /// Returns the name of the class in the dotted form (e.g., `java.lang.String`).
+    #[must_use]
+    pub fn dotted_name(&self) -> String {
+        names::internal_to_binary(&self.binary_name)
+    }
 }
 
+impl FromStr for ClassRef {
+    type Err = InvalidClassName;
+
+    /// Parses a class name in either the internal form (e.g., `java/lang/String`) or the dotted
+    /// form (e.g., `java.lang.String`) into a reference with the internal name.
+    /// Array descriptors (e.g., `[I`) are rejected since they do not name a class declared in a
+    /// class file.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let binary_name = names::binary_to_internal(name);
+        if name.starts_with('[') || !names::is_valid_internal_name(&binary_name) {
+            return Err(InvalidClassName(name.to_owned()));
+        }
+        Ok(Self { binary_name })
+    }
+}
+
+/// An error indicating that a string is not a valid class name.
+#[derive(Debug, PartialEq, Eq, Clone, thiserror::Error)]
+#[error("Invalid class name `{0}`")]
+pub struct InvalidClassName(pub String);
+
 /// A reference to a [`Field`](crate::jvm::Field).
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, derive_more::Display)]
 #[display("{owner}.{name}")]
@@ -100,6 +130,57 @@ pub(crate) mod tests {
         )
     }
 
+    #[test]
+    fn class_ref_from_str() {
+        let internal: ClassRef = "java/lang/String".parse().unwrap();
+        let dotted: ClassRef = "java.lang.String".parse().unwrap();
+        assert_eq!(ClassRef::new("java/lang/String"), internal);
+        assert_eq!(internal, dotted);
+        assert_eq!(
+            Ok(ClassRef::new("Outer$Inner")),
+            "Outer$Inner".parse::<ClassRef>()
+        );
+        assert_eq!(
+            Err(InvalidClassName("[Ljava/lang/String;".to_owned())),
+            "[Ljava/lang/String;".parse::<ClassRef>()
+        );
+        assert!("java..String".parse::<ClassRef>().is_err());
+        assert!("".parse::<ClassRef>().is_err());
+        assert!("a;b".parse::<ClassRef>().is_err());
+        assert_eq!(
+            Ok(ClassRef::new("org/mokapot/module-info")),
+            "org.mokapot.module-info".parse::<ClassRef>()
+        );
+        assert_eq!(Ok(ClassRef::new("1abc")), "1abc".parse::<ClassRef>());
+    }
+
+    #[test]
+    fn method_ref_ordering() {
+        let method_ref = |owner: &str, name: &str, descriptor: &str| MethodRef {
+            owner: ClassRef::new(owner),
+            name: name.to_owned(),
+            descriptor: descriptor.parse().unwrap(),
+        };
+        let mut refs = vec![
+            method_ref("b/B", "a", "()V"),
+            method_ref("a/A", "b", "()V"),
+            method_ref("a/A", "a", "(I)V"),
+            method_ref("a/A", "a", "()V"),
+        ];
+        refs.sort();
+        assert_eq!(
+            vec![
+                method_ref("a/A", "a", "()V"),
+                method_ref("a/A", "a", "(I)V"),
+                method_ref("a/A", "b", "()V"),
+                method_ref("b/B", "a", "()V"),
+            ],
+            refs
+        );
+        let set: std::collections::HashSet<_> = refs.iter().cloned().chain(refs.clone()).collect();
+        assert_eq!(4, set.len());
+    }
+
     proptest! {
 
         #[test]
@@ -19,6 +19,9 @@ pub mod analysis;
 pub mod ir;
 pub mod jvm;
 pub(crate) mod macros;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_support;
+pub mod transform;
 pub mod types;
 pub(crate) mod utils;
 
@@ -0,0 +1,199 @@
+//! Helpers for unit-testing analyses and transformations without `.class` fixtures.
+//!
+//! The helpers build the in-memory [`Method`] and [`Class`] models only. They do not compute
+//! stack map frames or build a constant pool, since mokapot has no class file writer; the
+//! classes they build are Java 6 classes so that the missing frames are acceptable.
+
+use std::collections::BTreeMap;
+
+use crate::{
+    jvm::{
+        class::{self, Version},
+        code::{Instruction, InstructionList, MethodBody, ProgramCounter},
+        method,
+        references::ClassRef,
+        Class, Method,
+    },
+    transform::retarget,
+};
+
+/// The binary name of the class assembled by [`assemble_class`] and owning the methods built by
+/// [`build_method`].
+pub const TEST_CLASS: &str = "org/mokapot/Test";
+
+/// Builds a `public static void test()` method in [`TEST_CLASS`] whose body consists of
+/// `instructions`.
+///
+/// The instructions are laid out back-to-back, and the jump targets in them are the indices of
+/// the target instructions in `instructions` rather than program counters, e.g., `Goto(0.into())`
+/// jumps to the first instruction. They are converted to the program counters of the layout.
+/// [`max_locals`](MethodBody::max_locals) covers all the local variables accessed by the
+/// instructions, and [`max_stack`](MethodBody::max_stack) is an upper bound of the operand stack
+/// depth since an instruction pushes at most two slots.
+/// The body has no exception table, debug information, or stack map table; no frames are
+/// computed.
+///
+/// # Panics
+/// Panics if a jump target is not an index in `instructions`, or if the code is longer than
+/// 65535 bytes.
+#[must_use]
+pub fn build_method(instructions: &[Instruction]) -> Method {
+    let mut offset = 0;
+    let pcs: Vec<_> = instructions
+        .iter()
+        .map(|insn| {
+            let pc = ProgramCounter::from(
+                u16::try_from(offset).expect("The code should be at most 65535 bytes"),
+            );
+            offset += insn.encoded_len(offset);
+            pc
+        })
+        .collect();
+    let instructions: BTreeMap<_, _> = pcs
+        .iter()
+        .zip(instructions)
+        .map(|(pc, insn)| {
+            let mut insn = insn.clone();
+            retarget(&mut insn, |index| pcs[usize::from(u16::from(index))]);
+            (*pc, insn)
+        })
+        .collect();
+    let max_locals = instructions
+        .values()
+        .filter_map(Instruction::local_slots)
+        .map(|(index, width)| index + width)
+        .max()
+        .unwrap_or(0);
+    let max_stack = u16::try_from(instructions.len() * 2).unwrap_or(u16::MAX);
+    let body = MethodBody {
+        max_stack,
+        max_locals,
+        instructions: InstructionList::from(instructions),
+        exception_table: Vec::new(),
+        line_number_table: None,
+        local_variable_table: None,
+        stack_map_table: None,
+        runtime_visible_type_annotations: Vec::new(),
+        runtime_invisible_type_annotations: Vec::new(),
+        free_attributes: Vec::new(),
+        raw_attributes: Vec::new(),
+        raw_code: None,
+    };
+    Method {
+        access_flags: method::AccessFlags::PUBLIC | method::AccessFlags::STATIC,
+        name: "test".to_owned(),
+        descriptor: "()V".parse().expect("The descriptor is valid"),
+        owner: ClassRef::new(TEST_CLASS),
+        index: 0,
+        body: Some(body),
+        exceptions: Vec::new(),
+        runtime_visible_annotations: Vec::new(),
+        runtime_invisible_annotations: Vec::new(),
+        runtime_visible_type_annotations: Vec::new(),
+        runtime_invisible_type_annotations: Vec::new(),
+        runtime_visible_parameter_annotations: Vec::new(),
+        runtime_invisible_parameter_annotations: Vec::new(),
+        annotation_default: None,
+        parameters: Vec::new(),
+        is_synthetic: false,
+        is_deprecated: false,
+        signature: None,
+        free_attributes: Vec::new(),
+        raw_attributes: Vec::new(),
+    }
+}
+
+/// Assembles a `public class` named [`TEST_CLASS`] extending `java/lang/Object` with the given
+/// methods, e.g., those built by [`build_method`].
+/// The [owners](Method::owner) and the [indices](Method::index) of the methods are set to match
+/// the class.
+///
+/// No constant pool is built and no stack map frames are computed for the methods. Instead, the
+/// [version](Class::version) is forced to Java 6, which is the latest version whose verifier
+/// accepts methods without stack map tables.
+#[must_use]
+pub fn assemble_class<I>(methods: I) -> Class
+where
+    I: IntoIterator<Item = Method>,
+{
+    let methods = methods
+        .into_iter()
+        .enumerate()
+        .map(|(index, method)| Method {
+            owner: ClassRef::new(TEST_CLASS),
+            index,
+            ..method
+        })
+        .collect();
+    Class {
+        version: Version::Jdk6,
+        access_flags: class::AccessFlags::PUBLIC | class::AccessFlags::SUPER,
+        binary_name: TEST_CLASS.to_owned(),
+        super_class: Some(ClassRef::new("java/lang/Object")),
+        interfaces: Vec::new(),
+        fields: Vec::new(),
+        methods,
+        source_file: None,
+        inner_classes: Vec::new(),
+        enclosing_method: None,
+        source_debug_extension: None,
+        runtime_visible_annotations: Vec::new(),
+        runtime_invisible_annotations: Vec::new(),
+        runtime_visible_type_annotations: Vec::new(),
+        runtime_invisible_type_annotations: Vec::new(),
+        bootstrap_methods: Vec::new(),
+        module: None,
+        module_packages: Vec::new(),
+        module_main_class: None,
+        nest_host: None,
+        nest_members: Vec::new(),
+        permitted_subclasses: Vec::new(),
+        is_synthetic: false,
+        is_deprecated: false,
+        signature: None,
+        record: None,
+        free_attributes: Vec::new(),
+        raw_attributes: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_method_lays_out_instructions() {
+        let method = build_method(&[
+            Instruction::IConst0,
+            Instruction::IStore(3),
+            Instruction::ILoad(3),
+            Instruction::IfEq(0.into()),
+            Instruction::Return,
+        ]);
+        let body = method.body.unwrap();
+        let pcs: Vec<_> = body
+            .instructions
+            .iter()
+            .map(|(pc, _)| u16::from(*pc))
+            .collect();
+        assert_eq!(vec![0, 1, 3, 5, 8], pcs);
+        assert_eq!(
+            Some(&Instruction::IfEq(0.into())),
+            body.instruction_at(5.into())
+        );
+        assert_eq!(4, body.max_locals);
+    }
+
+    #[test]
+    fn assemble_class_owns_methods() {
+        let class = assemble_class([
+            build_method(&[Instruction::Return]),
+            build_method(&[Instruction::Return]),
+        ]);
+        assert_eq!(TEST_CLASS, class.binary_name);
+        assert_eq!(
+            vec![0, 1],
+            class.methods.iter().map(|it| it.index).collect::<Vec<_>>()
+        );
+    }
+}
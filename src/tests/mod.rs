@@ -1,10 +1,13 @@
+//! Utilities shared by unit tests.
+
 use proptest::prelude::*;
 
 use crate::{
-    jvm::{class, references::ClassRef, Class},
+    jvm::{class, code::MethodBody, field, references::ClassRef, Class, Field, Method},
     types::field_type::{FieldType, PrimitiveType},
 };
 
+/// Returns the bytes of an empty class file with the given version.
 #[rustfmt::skip]
 #[must_use]
 pub const fn empty_class_with_version(major: u16, minor: u16) -> [u8;40] {
@@ -29,6 +32,41 @@ pub const fn empty_class_with_version(major: u16, minor: u16) -> [u8;40] {
     ]
 }
 
+impl Default for MethodBody {
+    fn default() -> Self {
+        crate::test_support::build_method(&[])
+            .body
+            .expect("The method built for tests has a body")
+    }
+}
+
+impl Default for Method {
+    fn default() -> Self {
+        crate::test_support::build_method(&[])
+    }
+}
+
+impl Default for Field {
+    fn default() -> Self {
+        Self {
+            access_flags: field::AccessFlags::empty(),
+            name: String::default(),
+            owner: ClassRef::new(crate::test_support::TEST_CLASS),
+            field_type: PrimitiveType::Int.into(),
+            constant_value: None,
+            is_synthetic: false,
+            is_deperecated: false,
+            signature: None,
+            runtime_visible_annotations: Vec::default(),
+            runtime_invisible_annotations: Vec::default(),
+            runtime_visible_type_annotations: Vec::default(),
+            runtime_invisible_type_annotations: Vec::default(),
+            free_attributes: Vec::default(),
+            raw_attributes: Vec::default(),
+        }
+    }
+}
+
 impl Default for Class {
     fn default() -> Self {
         Self {
@@ -59,6 +97,7 @@ impl Default for Class {
             signature: None,
             record: None,
             free_attributes: Vec::default(),
+            raw_attributes: Vec::default(),
         }
     }
 }
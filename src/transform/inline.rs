@@ -0,0 +1,254 @@
+use crate::{
+    jvm::{
+        code::{EditError, Instruction, MethodBody, ProgramCounter},
+        method,
+        references::FieldRef,
+        Method,
+    },
+    types::{
+        field_type::{FieldType, PrimitiveType},
+        method_descriptor::ReturnType,
+    },
+};
+
+use super::{instrument::load, normalize::short_form};
+
+/// An error that occurs when inlining an accessor.
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum InlineError {
+    /// The instruction at the call site does not invoke the accessor.
+    #[error("The instruction at {0} does not invoke the accessor")]
+    NotAnInvocation(ProgramCounter),
+    /// The accessor does more than reading or writing a field.
+    #[error("The accessor is not a trivial getter or setter")]
+    NotTrivial,
+    /// The call site cannot be replaced.
+    #[error(transparent)]
+    EditError(#[from] EditError),
+}
+
+/// Replaces the invocation of `accessor` at `call_pc` in `caller` with the field access performed
+/// by the accessor, if it is a trivial getter or setter, i.e., its body consists of
+/// - `aload_0; getfield; <t>return` for an instance getter,
+/// - `aload_0; <t>load_1; putfield; return` for an instance setter,
+/// - `getstatic; <t>return` for a static getter, or
+/// - `<t>load_0; putstatic; return` for a static setter.
+///
+/// The invocation (`invokevirtual` or `invokespecial` for an instance accessor, `invokestatic`
+/// for a static one) takes the same operands from the operand stack and has the same encoded
+/// length as the field access, so the rest of the code is left unchanged.
+///
+/// The caller is responsible for ensuring that `accessor` is the method selected at runtime,
+/// e.g., it is `private`, `final`, or declared in a `final` class, and that the field is
+/// accessible from the class of `caller`.
+/// # Errors
+/// See [`InlineError`] for more information.
+pub fn inline_trivial_accessor(
+    caller: &mut MethodBody,
+    call_pc: ProgramCounter,
+    accessor: &Method,
+) -> Result<(), InlineError> {
+    let is_static = accessor.access_flags.contains(method::AccessFlags::STATIC);
+    let invokes_accessor = match caller.instruction_at(call_pc) {
+        Some(Instruction::InvokeVirtual(target) | Instruction::InvokeSpecial(target)) => {
+            !is_static && !accessor.is_constructor() && *target == accessor.as_ref()
+        }
+        Some(Instruction::InvokeStatic(target)) => is_static && *target == accessor.as_ref(),
+        _ => false,
+    };
+    if !invokes_accessor {
+        return Err(InlineError::NotAnInvocation(call_pc));
+    }
+    let field_access = field_access(accessor).ok_or(InlineError::NotTrivial)?;
+    caller.replace_instruction(call_pc, field_access)?;
+    Ok(())
+}
+
+/// Returns the field access performed by `accessor` if it is a trivial getter or setter.
+fn field_access(accessor: &Method) -> Option<Instruction> {
+    #[allow(clippy::enum_glob_use)]
+    use Instruction::*;
+
+    if accessor
+        .access_flags
+        .intersects(method::AccessFlags::SYNCHRONIZED | method::AccessFlags::NATIVE)
+    {
+        return None;
+    }
+    let body = accessor.body.as_ref()?;
+    if !body.exception_table.is_empty() {
+        return None;
+    }
+    // The loads may be in either the `<op> <n>` or the `<op>_<n>` form.
+    let instructions: Vec<_> = body
+        .instructions
+        .iter()
+        .map(|(_, insn)| short_form(insn.clone()))
+        .collect();
+    let descriptor = &accessor.descriptor;
+    let is_static = accessor.access_flags.contains(method::AccessFlags::STATIC);
+    let accesses_field = |field: &FieldRef, expected_static: bool| {
+        expected_static == is_static && (is_static || field.owner == accessor.owner)
+    };
+    let access = match (instructions.as_slice(), &descriptor.return_type) {
+        ([ALoad0, GetField(field), ret], ReturnType::Some(return_type))
+            if accesses_field(field, false)
+                && descriptor.parameters_types.is_empty()
+                && field.field_type == *return_type
+                && *ret == return_of(return_type) =>
+        {
+            GetField(field.clone())
+        }
+        ([GetStatic(field), ret], ReturnType::Some(return_type))
+            if accesses_field(field, true)
+                && descriptor.parameters_types.is_empty()
+                && field.field_type == *return_type
+                && *ret == return_of(return_type) =>
+        {
+            GetStatic(field.clone())
+        }
+        ([ALoad0, value, PutField(field), Return], ReturnType::Void)
+            if accesses_field(field, false)
+                && descriptor.parameters_types == [field.field_type.clone()]
+                && *value == short_form(load(&field.field_type, 1)) =>
+        {
+            PutField(field.clone())
+        }
+        ([value, PutStatic(field), Return], ReturnType::Void)
+            if accesses_field(field, true)
+                && descriptor.parameters_types == [field.field_type.clone()]
+                && *value == short_form(load(&field.field_type, 0)) =>
+        {
+            PutStatic(field.clone())
+        }
+        _ => return None,
+    };
+    Some(access)
+}
+
+/// Returns the instruction returning a value of `return_type`.
+fn return_of(return_type: &FieldType) -> Instruction {
+    use PrimitiveType::{Double, Float, Long};
+    match return_type {
+        FieldType::Base(Long) => Instruction::LReturn,
+        FieldType::Base(Float) => Instruction::FReturn,
+        FieldType::Base(Double) => Instruction::DReturn,
+        FieldType::Base(_) => Instruction::IReturn,
+        FieldType::Object(_) | FieldType::Array(_) => Instruction::AReturn,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        jvm::references::ClassRef,
+        test_support::{build_method, TEST_CLASS},
+    };
+
+    fn field(name: &str, field_type: &str) -> FieldRef {
+        FieldRef {
+            owner: ClassRef::new(TEST_CLASS),
+            name: name.to_owned(),
+            field_type: field_type.parse().unwrap(),
+        }
+    }
+
+    fn accessor(name: &str, descriptor: &str, instructions: &[Instruction]) -> Method {
+        let mut method = build_method(instructions);
+        method.access_flags = method::AccessFlags::PUBLIC | method::AccessFlags::FINAL;
+        method.name = name.to_owned();
+        method.descriptor = descriptor.parse().unwrap();
+        method
+    }
+
+    fn caller(invocation: Instruction) -> MethodBody {
+        build_method(&[Instruction::ALoad0, invocation, Instruction::Return])
+            .body
+            .unwrap()
+    }
+
+    #[test]
+    fn inline_getter() {
+        let getter = accessor(
+            "getCount",
+            "()J",
+            &[
+                Instruction::ALoad0,
+                Instruction::GetField(field("count", "J")),
+                Instruction::LReturn,
+            ],
+        );
+        let mut body = caller(Instruction::InvokeVirtual(getter.as_ref()));
+        assert_eq!(
+            Ok(()),
+            inline_trivial_accessor(&mut body, 1.into(), &getter)
+        );
+        assert_eq!(
+            Some(&Instruction::GetField(field("count", "J"))),
+            body.instruction_at(1.into())
+        );
+    }
+
+    #[test]
+    fn inline_setter() {
+        let setter = accessor(
+            "setName",
+            "(Ljava/lang/String;)V",
+            &[
+                Instruction::ALoad0,
+                Instruction::ALoad1,
+                Instruction::PutField(field("name", "Ljava/lang/String;")),
+                Instruction::Return,
+            ],
+        );
+        let mut body = caller(Instruction::InvokeSpecial(setter.as_ref()));
+        assert_eq!(
+            Ok(()),
+            inline_trivial_accessor(&mut body, 1.into(), &setter)
+        );
+        assert_eq!(
+            Some(&Instruction::PutField(field("name", "Ljava/lang/String;"))),
+            body.instruction_at(1.into())
+        );
+    }
+
+    #[test]
+    fn bail_out() {
+        let not_trivial = accessor(
+            "getDoubled",
+            "()I",
+            &[
+                Instruction::ALoad0,
+                Instruction::GetField(field("count", "I")),
+                Instruction::IConst2,
+                Instruction::IMul,
+                Instruction::IReturn,
+            ],
+        );
+        let mut body = caller(Instruction::InvokeVirtual(not_trivial.as_ref()));
+        assert_eq!(
+            Err(InlineError::NotTrivial),
+            inline_trivial_accessor(&mut body, 1.into(), &not_trivial)
+        );
+        assert_eq!(
+            Err(InlineError::NotAnInvocation(0.into())),
+            inline_trivial_accessor(&mut body, 0.into(), &not_trivial)
+        );
+
+        let mismatched_return = accessor(
+            "getCount",
+            "()I",
+            &[
+                Instruction::ALoad0,
+                Instruction::GetField(field("count", "I")),
+                Instruction::AReturn,
+            ],
+        );
+        let mut body = caller(Instruction::InvokeVirtual(mismatched_return.as_ref()));
+        assert_eq!(
+            Err(InlineError::NotTrivial),
+            inline_trivial_accessor(&mut body, 1.into(), &mismatched_return)
+        );
+    }
+}
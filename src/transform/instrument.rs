@@ -0,0 +1,515 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    jvm::{
+        annotation::TargetInfo,
+        code::{
+            CodeTooLarge, ExceptionTableEntry, Instruction, MethodBody, ProgramCounter,
+            StackMapFrame, VerificationType, WideInstruction, MAX_CODE_LENGTH,
+        },
+        references::{ClassRef, MethodRef},
+        TypeAnnotation,
+    },
+    types::{
+        field_type::{FieldType, PrimitiveType},
+        method_descriptor::{MethodDescriptor, ReturnType},
+    },
+};
+
+use super::split_method::{retarget, to_pc};
+
+/// An error that occurs when instrumenting a method.
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum InstrumentError {
+    /// The parameters of the invoked method do not match the values that can be passed to it.
+    #[error("The parameters of the invoked method do not match the instrumented method")]
+    IncompatibleTarget,
+    /// The method body has no instructions to guard.
+    #[error("The method body has no instructions")]
+    NoInstructions,
+    /// The instrumented code is too large.
+    #[error(transparent)]
+    CodeTooLarge(#[from] CodeTooLarge),
+}
+
+/// Inserts an invocation of the static method `target` at the entry of `body`, which is the body
+/// of a method described by `descriptor` and `is_static`.
+///
+/// The values passed to `target` are determined by its parameters, which must be one of
+/// - none,
+/// - the same as the parameters of the instrumented method, or
+/// - a reference type followed by the parameters of the instrumented method, in which case
+///   `this` is passed first. This is only possible for instance methods other than constructors,
+///   whose `this` is uninitialized at the entry.
+///
+/// The value returned by `target`, if any, is discarded.
+/// The injected code only reads the local variables, so the existing slots are left untouched.
+/// The instructions are moved down, and the jump targets, the exception table, the stack map
+/// table, the debug information, and the type annotations on the code are updated accordingly,
+/// and the raw bytes retained from the class file are discarded (see [`MethodBody::discard_raw`]).
+/// # Errors
+/// See [`InstrumentError`] for more information.
+pub fn inject_entry_call(
+    body: &mut MethodBody,
+    descriptor: &MethodDescriptor,
+    is_static: bool,
+    target: &MethodRef,
+) -> Result<(), InstrumentError> {
+    let parameters = &descriptor.parameters_types;
+    let arguments = &target.descriptor.parameters_types;
+    let passes_this = match arguments.split_first() {
+        _ if arguments == parameters => false,
+        None => false,
+        Some((FieldType::Object(_) | FieldType::Array(_), rest))
+            if !is_static && rest == parameters.as_slice() =>
+        {
+            true
+        }
+        Some(_) => {
+            return Err(InstrumentError::IncompatibleTarget);
+        }
+    };
+    let mut prologue = Vec::new();
+    if passes_this {
+        prologue.push(Instruction::ALoad0);
+    }
+    if !arguments.is_empty() {
+        let mut slot = u16::from(!is_static);
+        for parameter in parameters {
+            prologue.push(load(parameter, slot));
+            slot += parameter.slot_width();
+        }
+    }
+    prologue.push(Instruction::InvokeStatic(target.clone()));
+    match &target.descriptor.return_type {
+        ReturnType::Void => {}
+        ReturnType::Some(it) if it.slot_width() == 2 => prologue.push(Instruction::Pop2),
+        ReturnType::Some(_) => prologue.push(Instruction::Pop),
+    }
+    let argument_slots = arguments.iter().map(FieldType::slot_width).sum::<u16>();
+    let return_slots = match &target.descriptor.return_type {
+        ReturnType::Some(it) => it.slot_width(),
+        ReturnType::Void => 0,
+    };
+
+    let mut instrumented = body.clone();
+    prepend(&mut instrumented, prologue);
+    instrumented.code_size()?;
+    instrumented.max_stack = body.max_stack.max(argument_slots).max(return_slots);
+    *body = instrumented;
+    Ok(())
+}
+
+/// Guards all the instructions in `body` with an exception handler catching `exception_type`, or
+/// any exception if it is `None`, and appends `handler` to the end of the code.
+///
+/// The handler starts with the caught exception on the operand stack and no local variables, as
+/// they may hold values of different types when the exception is thrown. Its instructions are
+/// placed at [`MethodBody::code_size`] of the original body, which the jump targets in it should
+/// be relative to, and it should end with an instruction leaving the method, e.g., `athrow`.
+/// The new entry is added to the end of the exception table, so the existing handlers take
+/// precedence, and a frame for the handler is added to the stack map table.
+/// [`max_stack`](MethodBody::max_stack) is raised to hold at least the exception, and should be
+/// adjusted by the caller if the handler needs more.
+/// The raw bytes retained from the class file are discarded (see [`MethodBody::discard_raw`]).
+///
+/// Constructors are not supported since the handler cannot cover the code before the
+/// invocation of the super constructor.
+/// # Errors
+/// See [`InstrumentError`] for more information.
+pub fn wrap_in_try_catch(
+    body: &mut MethodBody,
+    exception_type: Option<ClassRef>,
+    handler: Vec<Instruction>,
+) -> Result<(), InstrumentError> {
+    let (start_pc, _) = body
+        .instructions
+        .entry_point()
+        .ok_or(InstrumentError::NoInstructions)?;
+    let start_pc = *start_pc;
+    let code_end = body.code_size()?;
+    let mut handler_pcs = Vec::with_capacity(handler.len());
+    let mut offset = code_end;
+    for insn in &handler {
+        handler_pcs.push(offset);
+        offset += insn.encoded_len(offset);
+    }
+    if offset > MAX_CODE_LENGTH {
+        return Err(CodeTooLarge(offset).into());
+    }
+    let handler_pc = to_pc(code_end);
+    let mut instrumented = body.clone();
+    let instructions: BTreeMap<_, _> = body
+        .instructions
+        .iter()
+        .map(|(pc, insn)| (*pc, insn.clone()))
+        .chain(handler_pcs.into_iter().map(to_pc).zip(handler))
+        .collect();
+    instrumented.instructions = instructions.into();
+    instrumented.discard_raw();
+
+    let caught_type = exception_type
+        .clone()
+        .unwrap_or_else(|| ClassRef::new("java/lang/Throwable"));
+    instrumented.exception_table.push(ExceptionTableEntry {
+        covered_pc: start_pc..handler_pc,
+        handler_pc,
+        catch_type: exception_type,
+    });
+    let frames = instrumented.stack_map_table.get_or_insert_with(Vec::new);
+    let last_offset = frames.iter().fold(None, |offset: Option<u16>, frame| {
+        Some(offset.map_or(frame.offset_delta(), |it| it + frame.offset_delta() + 1))
+    });
+    let handler_offset = u16::from(handler_pc);
+    frames.push(StackMapFrame::FullFrame {
+        offset_delta: last_offset.map_or(handler_offset, |it| handler_offset - it - 1),
+        locals: Vec::new(),
+        stack: vec![VerificationType::ObjectVariable(caught_type)],
+    });
+    instrumented.max_stack = instrumented.max_stack.max(1);
+    *body = instrumented;
+    Ok(())
+}
+
+/// Builds the instruction loading a value of `field_type` from the local variable at `slot`.
+pub(super) fn load(field_type: &FieldType, slot: u16) -> Instruction {
+    use PrimitiveType::{Double, Float, Long};
+    let Ok(narrow_slot) = u8::try_from(slot) else {
+        return Instruction::Wide(match field_type {
+            FieldType::Base(Long) => WideInstruction::LLoad(slot),
+            FieldType::Base(Float) => WideInstruction::FLoad(slot),
+            FieldType::Base(Double) => WideInstruction::DLoad(slot),
+            FieldType::Base(_) => WideInstruction::ILoad(slot),
+            FieldType::Object(_) | FieldType::Array(_) => WideInstruction::ALoad(slot),
+        });
+    };
+    match field_type {
+        FieldType::Base(Long) => Instruction::LLoad(narrow_slot),
+        FieldType::Base(Float) => Instruction::FLoad(narrow_slot),
+        FieldType::Base(Double) => Instruction::DLoad(narrow_slot),
+        FieldType::Base(_) => Instruction::ILoad(narrow_slot),
+        FieldType::Object(_) | FieldType::Array(_) => Instruction::ALoad(narrow_slot),
+    }
+}
+
+/// Lays out the instructions of `body` back-to-back from the start of the code, and moves
+/// everything that refers to their program counters.
+pub(super) fn lay_out(body: &mut MethodBody) {
+    prepend(body, Vec::new());
+}
+
+/// Inserts `prologue` before the first instruction of `body` and moves everything that refers to
+/// the program counters of the existing instructions.
+/// The existing jumps to the entry point keep targeting the original first instruction.
+fn prepend(body: &mut MethodBody, prologue: Vec<Instruction>) {
+    let mut instructions = BTreeMap::new();
+    let mut offset = 0;
+    for insn in prologue {
+        let next_offset = offset + insn.encoded_len(offset);
+        instructions.insert(to_pc(offset), insn);
+        offset = next_offset;
+    }
+    // The padding of switches may change, so the instructions are laid out again.
+    let mut new_pcs = BTreeMap::new();
+    for (pc, insn) in &body.instructions {
+        new_pcs.insert(*pc, to_pc(offset));
+        offset += insn.encoded_len(offset);
+    }
+    let code_end = to_pc(offset);
+    let relocate = |pc: ProgramCounter| {
+        new_pcs
+            .range(pc..)
+            .next()
+            .map_or(code_end, |(_, new_pc)| *new_pc)
+    };
+
+    for (pc, insn) in &body.instructions {
+        let mut insn = insn.clone();
+        retarget(&mut insn, relocate);
+        instructions.insert(relocate(*pc), insn);
+    }
+    body.instructions = instructions.into();
+    body.discard_raw();
+    for entry in &mut body.exception_table {
+        entry.covered_pc = relocate(entry.covered_pc.start)..relocate(entry.covered_pc.end);
+        entry.handler_pc = relocate(entry.handler_pc);
+    }
+    for entry in body.line_number_table.iter_mut().flatten() {
+        entry.start_pc = relocate(entry.start_pc);
+    }
+    if let Some(table) = body.local_variable_table.as_mut() {
+        // The variables valid from the entry point, e.g., the parameters, remain valid there.
+        table.relocate(|pc| {
+            if pc.is_entry_point() {
+                pc
+            } else {
+                relocate(pc)
+            }
+        });
+    }
+    if let Some(frames) = body.stack_map_table.as_mut() {
+        relocate_frames(frames, relocate);
+    }
+    for annotation in body
+        .runtime_visible_type_annotations
+        .iter_mut()
+        .chain(&mut body.runtime_invisible_type_annotations)
+    {
+        relocate_type_annotation(annotation, relocate);
+    }
+}
+
+fn relocate_frames(
+    frames: &mut [StackMapFrame],
+    relocate: impl Fn(ProgramCounter) -> ProgramCounter,
+) {
+    let relocate_types = |types: &mut Vec<VerificationType>| {
+        for it in types {
+            if let VerificationType::UninitializedVariable { offset } = it {
+                *offset = relocate(*offset);
+            }
+        }
+    };
+    let mut old_offset: Option<u16> = None;
+    let mut new_offset: Option<u16> = None;
+    for frame in frames {
+        let offset_delta = match frame {
+            StackMapFrame::SameFrame { offset_delta }
+            | StackMapFrame::ChopFrame { offset_delta, .. } => offset_delta,
+            StackMapFrame::SameLocals1StackItemFrame {
+                offset_delta,
+                stack,
+            } => {
+                if let VerificationType::UninitializedVariable { offset } = stack {
+                    *offset = relocate(*offset);
+                }
+                offset_delta
+            }
+            StackMapFrame::AppendFrame {
+                offset_delta,
+                locals,
+            } => {
+                relocate_types(locals);
+                offset_delta
+            }
+            StackMapFrame::FullFrame {
+                offset_delta,
+                locals,
+                stack,
+            } => {
+                relocate_types(locals);
+                relocate_types(stack);
+                offset_delta
+            }
+        };
+        // The offset of a frame is the offset delta plus one more than the offset of the
+        // previous frame, except for the first frame.
+        let old = old_offset.map_or(*offset_delta, |it| it + *offset_delta + 1);
+        let new = u16::from(relocate(old.into()));
+        *offset_delta = new_offset.map_or(new, |it| new - it - 1);
+        old_offset = Some(old);
+        new_offset = Some(new);
+    }
+}
+
+fn relocate_type_annotation(
+    annotation: &mut TypeAnnotation,
+    relocate: impl Fn(ProgramCounter) -> ProgramCounter,
+) {
+    match &mut annotation.target_info {
+        TargetInfo::LocalVar(ids) => {
+            for id in ids {
+                id.effective_range =
+                    relocate(id.effective_range.start)..relocate(id.effective_range.end);
+            }
+        }
+        TargetInfo::Offset(offset) => *offset = relocate((*offset).into()).into(),
+        TargetInfo::TypeArgument { offset, .. } => *offset = relocate(*offset),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jvm::code::{InstructionList, LineNumberTableEntry};
+    use Instruction::*;
+
+    fn log_ref(descriptor: &str) -> MethodRef {
+        MethodRef {
+            owner: ClassRef::new("org/mokapot/Log"),
+            name: "enter".to_owned(),
+            descriptor: descriptor.parse().unwrap(),
+        }
+    }
+
+    /// The body of `int max(int a, long b)` with a loop back to the entry point.
+    fn body() -> MethodBody {
+        MethodBody {
+            max_stack: 4,
+            max_locals: 4,
+            instructions: InstructionList::from([
+                (0.into(), ILoad1),
+                (1.into(), IfEq(0.into())),
+                (4.into(), ILoad1),
+                (5.into(), IReturn),
+            ]),
+            exception_table: vec![ExceptionTableEntry {
+                covered_pc: 0.into()..4.into(),
+                handler_pc: 4.into(),
+                catch_type: None,
+            }],
+            line_number_table: Some(vec![LineNumberTableEntry {
+                start_pc: 4.into(),
+                line_number: 42,
+            }]),
+            stack_map_table: Some(vec![
+                StackMapFrame::SameFrame { offset_delta: 0 },
+                StackMapFrame::SameLocals1StackItemFrame {
+                    offset_delta: 3,
+                    stack: VerificationType::ObjectVariable(ClassRef::new("java/lang/Throwable")),
+                },
+            ]),
+            // iload_1; ifeq -1; iload_1; ireturn
+            raw_code: Some(Box::new([0x1b, 0x99, 0xff, 0xff, 0x1b, 0xac])),
+            raw_attributes: vec![("LineNumberTable".to_owned(), vec![0, 1, 0, 4, 0, 42])],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn inject_without_arguments() {
+        let mut body = body();
+        inject_entry_call(&mut body, &"(IJ)I".parse().unwrap(), false, &log_ref("()V")).unwrap();
+        let instructions: Vec<_> = body.instructions.into_iter().collect();
+        assert_eq!(
+            vec![
+                (0.into(), InvokeStatic(log_ref("()V"))),
+                (3.into(), ILoad1),
+                (4.into(), IfEq(3.into())),
+                (7.into(), ILoad1),
+                (8.into(), IReturn),
+            ],
+            instructions
+        );
+        assert_eq!(
+            3.into()..7.into(),
+            body.exception_table[0].covered_pc.clone()
+        );
+        assert_eq!(ProgramCounter::from(7), body.exception_table[0].handler_pc);
+        assert_eq!(
+            ProgramCounter::from(7),
+            body.line_number_table.unwrap()[0].start_pc
+        );
+        assert_eq!(
+            Some(vec![
+                StackMapFrame::SameFrame { offset_delta: 3 },
+                StackMapFrame::SameLocals1StackItemFrame {
+                    offset_delta: 3,
+                    stack: VerificationType::ObjectVariable(ClassRef::new("java/lang/Throwable")),
+                },
+            ]),
+            body.stack_map_table
+        );
+        assert_eq!(4, body.max_stack);
+        assert!(body.raw_code.is_none());
+        assert!(body.raw_attributes.is_empty());
+    }
+
+    #[test]
+    fn inject_with_this_and_arguments() {
+        let mut body = body();
+        let target = log_ref("(Ljava/lang/Object;IJ)Z");
+        inject_entry_call(&mut body, &"(IJ)I".parse().unwrap(), false, &target).unwrap();
+        let prologue: Vec<_> = body
+            .instructions
+            .into_iter()
+            .map(|(_, insn)| insn)
+            .take(5)
+            .collect();
+        assert_eq!(
+            vec![ALoad0, ILoad(1), LLoad(2), InvokeStatic(target), Pop],
+            prologue
+        );
+        assert_eq!(4, body.max_stack);
+    }
+
+    #[test]
+    fn incompatible_target() {
+        let mut body = body();
+        let descriptor = "(IJ)I".parse().unwrap();
+        assert!(matches!(
+            inject_entry_call(&mut body, &descriptor, false, &log_ref("(J)V")),
+            Err(InstrumentError::IncompatibleTarget)
+        ));
+        assert!(matches!(
+            inject_entry_call(
+                &mut body,
+                &descriptor,
+                true,
+                &log_ref("(Ljava/lang/Object;IJ)V")
+            ),
+            Err(InstrumentError::IncompatibleTarget)
+        ));
+        assert_eq!(Some((&0.into(), &ILoad1)), body.instructions.entry_point());
+    }
+
+    #[test]
+    fn wrap_whole_body() {
+        let mut body = body();
+        let report = MethodRef {
+            owner: ClassRef::new("org/mokapot/Log"),
+            name: "crash".to_owned(),
+            descriptor: "(Ljava/lang/Throwable;)V".parse().unwrap(),
+        };
+        wrap_in_try_catch(
+            &mut body,
+            None,
+            vec![Dup, InvokeStatic(report.clone()), AThrow],
+        )
+        .unwrap();
+        assert_eq!(
+            Some(&ExceptionTableEntry {
+                covered_pc: 0.into()..6.into(),
+                handler_pc: 6.into(),
+                catch_type: None,
+            }),
+            body.exception_table.last()
+        );
+        let handler: Vec<_> = body
+            .instructions
+            .iter()
+            .filter(|(pc, _)| **pc >= 6.into())
+            .map(|(pc, insn)| (*pc, insn.clone()))
+            .collect();
+        assert_eq!(
+            vec![
+                (6.into(), Dup),
+                (7.into(), InvokeStatic(report)),
+                (10.into(), AThrow)
+            ],
+            handler
+        );
+        assert_eq!(
+            Some(&StackMapFrame::FullFrame {
+                offset_delta: 1,
+                locals: vec![],
+                stack: vec![VerificationType::ObjectVariable(ClassRef::new(
+                    "java/lang/Throwable"
+                ))],
+            }),
+            body.stack_map_table.unwrap().last()
+        );
+        assert!(body.raw_code.is_none());
+        assert!(body.raw_attributes.is_empty());
+    }
+
+    #[test]
+    fn wrap_empty_body() {
+        let mut body = MethodBody::default();
+        assert_eq!(
+            Err(InstrumentError::NoInstructions),
+            wrap_in_try_catch(&mut body, None, vec![AThrow])
+        );
+    }
+}
@@ -0,0 +1,523 @@
+//! Name mappings between two namespaces, e.g., obfuscated and deobfuscated names.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{BufRead, Write},
+};
+
+use crate::{
+    jvm::references::{ClassRef, FieldRef, MethodRef},
+    types::{
+        field_type::{FieldType, PrimitiveType},
+        method_descriptor::{MethodDescriptor, ReturnType},
+        names,
+    },
+};
+
+use super::rename::ClassNames;
+
+/// A set of mappings from the names in a source namespace to the names in a target namespace.
+/// Classes are identified by their binary names in the internal form, and members by their
+/// owners, names, and types, all in the source namespace.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MappingSet {
+    /// The mappings of the classes.
+    pub classes: BTreeMap<String, String>,
+    /// The mappings of the fields to their new names.
+    pub fields: BTreeMap<FieldRef, String>,
+    /// The mappings of the methods to their new names.
+    pub methods: BTreeMap<MethodRef, String>,
+}
+
+/// An error that occurs when loading mappings.
+#[derive(Debug, thiserror::Error)]
+pub enum MappingError {
+    /// An I/O error occurred when reading the mappings.
+    #[error("Failed to read the mappings: {0}")]
+    Io(#[from] std::io::Error),
+    /// A line in the mappings is malformed.
+    #[error("Malformed mapping at line {line}: {reason}")]
+    Malformed {
+        /// The line number, starting from 1.
+        line: usize,
+        /// The reason why the line is malformed.
+        reason: &'static str,
+    },
+    /// The requested namespace is not declared in the mappings.
+    #[error("Unknown namespace: {0}")]
+    UnknownNamespace(String),
+}
+
+impl MappingSet {
+    /// Returns the name that the class `name` is mapped to, if any.
+    #[must_use]
+    pub fn class(&self, name: &str) -> Option<&str> {
+        self.classes.get(name).map(String::as_str)
+    }
+
+    /// Returns the name that the given field is mapped to, if any.
+    #[must_use]
+    pub fn field(&self, field: &FieldRef) -> Option<&str> {
+        self.fields.get(field).map(String::as_str)
+    }
+
+    /// Returns the name that the given method is mapped to, if any.
+    #[must_use]
+    pub fn method(&self, method: &MethodRef) -> Option<&str> {
+        self.methods.get(method).map(String::as_str)
+    }
+
+    /// Creates a mapping set that maps the names in the opposite direction.
+    #[must_use]
+    pub fn reversed(&self) -> Self {
+        let classes = self
+            .classes
+            .iter()
+            .map(|(from, to)| (to.clone(), from.clone()))
+            .collect();
+        let fields = self
+            .fields
+            .iter()
+            .map(|(field, to)| {
+                let mut field = field.clone();
+                self.rename_classes(&mut field);
+                let name = std::mem::replace(&mut field.name, to.clone());
+                (field, name)
+            })
+            .collect();
+        let methods = self
+            .methods
+            .iter()
+            .map(|(method, to)| {
+                let mut method = method.clone();
+                self.rename_classes(&mut method);
+                let name = std::mem::replace(&mut method.name, to.clone());
+                (method, name)
+            })
+            .collect();
+        Self {
+            classes,
+            fields,
+            methods,
+        }
+    }
+
+    /// Renames the classes in `element` to the target namespace.
+    pub(super) fn rename_classes<T: ClassNames>(&self, element: &mut T) {
+        element.visit_class_names(&mut |name| {
+            if let Some(new_name) = self.classes.get(name.as_str()) {
+                new_name.clone_into(name);
+            }
+        });
+    }
+
+    /// Parses mappings in the Proguard format, e.g.,
+    /// ```text
+    /// org.mokapot.Foo -> a:
+    ///     int count -> a
+    ///     1:3:void run(java.lang.String[]) -> b
+    /// ```
+    /// The names on the left are in the source namespace.
+    /// Members inlined from other classes are ignored.
+    /// # Errors
+    /// See [`MappingError`] for more information.
+    pub fn from_proguard<R: BufRead>(reader: R) -> Result<Self, MappingError> {
+        let mut mappings = Self::default();
+        let mut current_class = None;
+        for (line_index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let malformed = |reason| MappingError::Malformed {
+                line: line_index + 1,
+                reason,
+            };
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let (from, to) = trimmed
+                .split_once(" -> ")
+                .ok_or_else(|| malformed("Expected ` -> `"))?;
+            if !line.starts_with(char::is_whitespace) {
+                let to = to
+                    .strip_suffix(':')
+                    .ok_or_else(|| malformed("Expected `:` after the class name"))?;
+                let from = names::binary_to_internal(from);
+                mappings
+                    .classes
+                    .insert(from.clone(), names::binary_to_internal(to));
+                current_class = Some(ClassRef::new(from));
+                continue;
+            }
+            let owner = current_class
+                .clone()
+                .ok_or_else(|| malformed("Member mapping outside of a class"))?;
+            // Strip the line numbers, i.e., `1:3:` at the start and `:1:3` after the parameters.
+            let from = from.trim_start_matches(|c: char| c.is_ascii_digit() || c == ':');
+            let (member_type, member) = from
+                .split_once(' ')
+                .ok_or_else(|| malformed("Expected the type of the member"))?;
+            if let Some((name, parameters)) = member.split_once('(') {
+                let (parameters, _) = parameters
+                    .split_once(')')
+                    .ok_or_else(|| malformed("Expected `)` after the parameters"))?;
+                if name.contains('.') {
+                    continue;
+                }
+                let descriptor = proguard_descriptor(member_type, parameters)
+                    .ok_or_else(|| malformed("Invalid method signature"))?;
+                let method = MethodRef {
+                    owner,
+                    name: name.to_owned(),
+                    descriptor,
+                };
+                mappings.methods.insert(method, to.to_owned());
+            } else {
+                if member.contains('.') {
+                    continue;
+                }
+                let field = FieldRef {
+                    owner,
+                    name: member.to_owned(),
+                    field_type: parse_java_type(member_type)
+                        .ok_or_else(|| malformed("Invalid field type"))?,
+                };
+                mappings.fields.insert(field, to.to_owned());
+            }
+        }
+        Ok(mappings)
+    }
+
+    /// Parses mappings in the Tiny v2 format, where the columns are separated by tabs, the header
+    /// declares the namespaces (e.g., `tiny 2 0 official named`), and each class line
+    /// (e.g., `c a org/mokapot/Foo`) is followed by its indented field and method lines
+    /// (e.g., `f I a count`) with the descriptors in the first namespace.
+    /// The mappings are from the names in `source_namespace` to the names in `target_namespace`.
+    /// Parameters, local variables, and comments are ignored.
+    /// Names that are empty in either namespace are not mapped.
+    /// # Errors
+    /// See [`MappingError`] for more information.
+    pub fn from_tiny<R: BufRead>(
+        reader: R,
+        source_namespace: &str,
+        target_namespace: &str,
+    ) -> Result<Self, MappingError> {
+        let mut lines = reader.lines();
+        let header = lines.next().transpose()?.unwrap_or_default();
+        let header: Vec<_> = header.split('\t').collect();
+        let ["tiny", "2", _, namespaces @ ..] = header.as_slice() else {
+            return Err(MappingError::Malformed {
+                line: 1,
+                reason: "Expected the Tiny v2 header",
+            });
+        };
+        let namespace_index = |namespace: &str| {
+            namespaces
+                .iter()
+                .position(|it| *it == namespace)
+                .ok_or_else(|| MappingError::UnknownNamespace(namespace.to_owned()))
+        };
+        let source = namespace_index(source_namespace)?;
+        let target = namespace_index(target_namespace)?;
+
+        // Descriptors are written in the first namespace, so the members are resolved after all
+        // the classes are known.
+        let mut classes = Vec::new();
+        let mut members = Vec::new();
+        for (line_index, line) in lines.enumerate() {
+            let line = line?;
+            let malformed = |reason| MappingError::Malformed {
+                line: line_index + 2,
+                reason,
+            };
+            let columns: Vec<_> = line.split('\t').collect();
+            match columns.as_slice() {
+                ["c", names @ ..] => {
+                    if names.len() != namespaces.len() {
+                        return Err(malformed("Expected a class name in each namespace"));
+                    }
+                    classes.push(names.iter().map(|it| (*it).to_owned()).collect::<Vec<_>>());
+                }
+                ["", kind @ ("f" | "m"), descriptor, names @ ..] => {
+                    if names.len() != namespaces.len() {
+                        return Err(malformed("Expected a member name in each namespace"));
+                    }
+                    let owner = classes
+                        .last()
+                        .map(|it: &Vec<String>| it[0].clone())
+                        .ok_or_else(|| malformed("Member mapping outside of a class"))?;
+                    let descriptor = (*descriptor).to_owned();
+                    let names: Vec<_> = names.iter().map(|it| (*it).to_owned()).collect();
+                    members.push((line_index + 2, *kind == "f", owner, descriptor, names));
+                }
+                // Properties, parameters, local variables, and comments.
+                [""] | ["", ..] => {}
+                _ => return Err(malformed("Unexpected line")),
+            }
+        }
+
+        let mut mappings = Self::default();
+        let to_source: HashMap<_, _> = classes
+            .iter()
+            .filter(|names| !names[source].is_empty())
+            .map(|names| (names[0].clone(), names[source].clone()))
+            .collect();
+        let source_class = |name: &str| to_source.get(name).cloned().unwrap_or(name.to_owned());
+        let rename_to_source = |element: &mut dyn ClassNames| {
+            element.visit_class_names(&mut |name| *name = source_class(name));
+        };
+        for names in &classes {
+            if !names[source].is_empty() && !names[target].is_empty() {
+                mappings
+                    .classes
+                    .insert(names[source].clone(), names[target].clone());
+            }
+        }
+        for (line, is_field, owner, descriptor, names) in members {
+            if names[source].is_empty() || names[target].is_empty() {
+                continue;
+            }
+            let malformed = |reason| MappingError::Malformed { line, reason };
+            let owner = ClassRef::new(source_class(&owner));
+            let name = names[source].clone();
+            if is_field {
+                let mut field_type: FieldType = descriptor
+                    .parse()
+                    .map_err(|_| malformed("Invalid field descriptor"))?;
+                rename_to_source(&mut field_type);
+                let field = FieldRef {
+                    owner,
+                    name,
+                    field_type,
+                };
+                mappings.fields.insert(field, names[target].clone());
+            } else {
+                let mut descriptor: MethodDescriptor = descriptor
+                    .parse()
+                    .map_err(|_| malformed("Invalid method descriptor"))?;
+                rename_to_source(&mut descriptor);
+                let method = MethodRef {
+                    owner,
+                    name,
+                    descriptor,
+                };
+                mappings.methods.insert(method, names[target].clone());
+            }
+        }
+        Ok(mappings)
+    }
+
+    /// Writes the mappings in the Tiny v2 format with the given namespace names.
+    /// The output can be read back with [`MappingSet::from_tiny`].
+    /// # Errors
+    /// Returns an error if writing to `writer` fails.
+    pub fn write_tiny<W: Write>(
+        &self,
+        mut writer: W,
+        source_namespace: &str,
+        target_namespace: &str,
+    ) -> std::io::Result<()> {
+        writeln!(writer, "tiny\t2\t0\t{source_namespace}\t{target_namespace}")?;
+        let mut members: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for (field, to) in &self.fields {
+            members
+                .entry(field.owner.binary_name.as_str())
+                .or_default()
+                .push(format!(
+                    "\tf\t{}\t{}\t{to}",
+                    field.field_type.descriptor(),
+                    field.name
+                ));
+        }
+        for (method, to) in &self.methods {
+            members
+                .entry(method.owner.binary_name.as_str())
+                .or_default()
+                .push(format!(
+                    "\tm\t{}\t{}\t{to}",
+                    method_descriptor(&method.descriptor),
+                    method.name
+                ));
+        }
+        let owners = self
+            .classes
+            .keys()
+            .map(String::as_str)
+            .chain(members.keys().copied())
+            .collect::<std::collections::BTreeSet<_>>();
+        for owner in owners {
+            let target = self.class(owner).unwrap_or(owner);
+            writeln!(writer, "c\t{owner}\t{target}")?;
+            for member in members.get(owner).into_iter().flatten() {
+                writeln!(writer, "{member}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the descriptor string of a method descriptor.
+fn method_descriptor(descriptor: &MethodDescriptor) -> String {
+    let parameters: String = descriptor
+        .parameters_types
+        .iter()
+        .map(FieldType::descriptor)
+        .collect();
+    let return_type = match &descriptor.return_type {
+        ReturnType::Some(it) => it.descriptor(),
+        ReturnType::Void => "V".to_owned(),
+    };
+    format!("({parameters}){return_type}")
+}
+
+/// Creates a method descriptor from the Java type names in a Proguard method signature.
+fn proguard_descriptor(return_type: &str, parameters: &str) -> Option<MethodDescriptor> {
+    let parameters_types = parameters
+        .split(',')
+        .filter(|it| !it.is_empty())
+        .map(parse_java_type)
+        .collect::<Option<_>>()?;
+    let return_type = match return_type {
+        "void" => ReturnType::Void,
+        it => ReturnType::Some(parse_java_type(it)?),
+    };
+    Some(MethodDescriptor {
+        parameters_types,
+        return_type,
+    })
+}
+
+/// Parses a type name in the Java source form (e.g., `java.lang.String[]`).
+fn parse_java_type(name: &str) -> Option<FieldType> {
+    if let Some(element_type) = name.strip_suffix("[]") {
+        return parse_java_type(element_type).map(FieldType::into_array_type);
+    }
+    let primitive_type = match name {
+        "boolean" => PrimitiveType::Boolean,
+        "char" => PrimitiveType::Char,
+        "float" => PrimitiveType::Float,
+        "double" => PrimitiveType::Double,
+        "byte" => PrimitiveType::Byte,
+        "short" => PrimitiveType::Short,
+        "int" => PrimitiveType::Int,
+        "long" => PrimitiveType::Long,
+        _ => {
+            let internal_name = names::binary_to_internal(name);
+            return names::is_valid_internal_name(&internal_name)
+                .then(|| FieldType::Object(ClassRef::new(internal_name)));
+        }
+    };
+    Some(primitive_type.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGUARD: &str = "\
+# A comment
+org.mokapot.Foo -> a:
+    int count -> b
+    org.mokapot.Foo next -> c
+    1:3:void run(java.lang.String[],int) -> d
+    4:4:void org.mokapot.Bar.inlined():12:12 -> d
+org.mokapot.Bar -> e:
+    org.mokapot.Foo get() -> f
+";
+
+    const TINY: &str = "\
+tiny\t2\t0\tofficial\tintermediary\tnamed
+\tsorted
+c\ta\tclass_1\torg/mokapot/Foo
+\tc\tA class.
+\tf\tI\tb\tfield_1\tcount
+\tm\t([Ljava/lang/String;I)V\td\tmethod_1\trun
+\t\tp\t1\t\t\targs
+c\te\tclass_2\torg/mokapot/Bar
+\tm\t()La;\tf\tmethod_2\tget
+";
+
+    fn method(owner: &str, name: &str, descriptor: &str) -> MethodRef {
+        MethodRef {
+            owner: ClassRef::new(owner),
+            name: name.to_owned(),
+            descriptor: descriptor.parse().unwrap(),
+        }
+    }
+
+    fn field(owner: &str, name: &str, field_type: FieldType) -> FieldRef {
+        FieldRef {
+            owner: ClassRef::new(owner),
+            name: name.to_owned(),
+            field_type,
+        }
+    }
+
+    #[test]
+    fn proguard() {
+        let mappings = MappingSet::from_proguard(PROGUARD.as_bytes()).unwrap();
+        assert_eq!(Some("a"), mappings.class("org/mokapot/Foo"));
+        assert_eq!(None, mappings.class("java/lang/Object"));
+        assert_eq!(
+            Some("b"),
+            mappings.field(&field(
+                "org/mokapot/Foo",
+                "count",
+                PrimitiveType::Int.into()
+            ))
+        );
+        assert_eq!(
+            Some("d"),
+            mappings.method(&method("org/mokapot/Foo", "run", "([Ljava/lang/String;I)V"))
+        );
+        assert_eq!(2, mappings.methods.len());
+
+        let reversed = mappings.reversed();
+        assert_eq!(Some("org/mokapot/Foo"), reversed.class("a"));
+        assert_eq!(
+            Some("next"),
+            reversed.field(&field("a", "c", FieldType::Object(ClassRef::new("a"))))
+        );
+        assert_eq!(Some("get"), reversed.method(&method("e", "f", "()La;")));
+
+        let error = MappingSet::from_proguard("    int count -> b".as_bytes()).unwrap_err();
+        assert!(matches!(error, MappingError::Malformed { line: 1, .. }));
+    }
+
+    #[test]
+    fn tiny() {
+        let mappings = MappingSet::from_tiny(TINY.as_bytes(), "named", "official").unwrap();
+        assert_eq!(Some("a"), mappings.class("org/mokapot/Foo"));
+        assert_eq!(
+            Some("b"),
+            mappings.field(&field(
+                "org/mokapot/Foo",
+                "count",
+                PrimitiveType::Int.into()
+            ))
+        );
+        // The descriptors are translated to the source namespace.
+        assert_eq!(
+            Some("f"),
+            mappings.method(&method("org/mokapot/Bar", "get", "()Lorg/mokapot/Foo;"))
+        );
+
+        let proguard = MappingSet::from_proguard(PROGUARD.as_bytes()).unwrap();
+        assert_eq!(proguard.classes, mappings.classes);
+        assert_eq!(
+            proguard.methods.keys().collect::<Vec<_>>(),
+            mappings.methods.keys().collect::<Vec<_>>()
+        );
+
+        let error = MappingSet::from_tiny(TINY.as_bytes(), "named", "srg").unwrap_err();
+        assert!(matches!(error, MappingError::UnknownNamespace(it) if it == "srg"));
+    }
+
+    #[test]
+    fn tiny_round_trip() {
+        let mappings = MappingSet::from_proguard(PROGUARD.as_bytes()).unwrap();
+        let mut output = Vec::new();
+        mappings.write_tiny(&mut output, "named", "obf").unwrap();
+        let parsed = MappingSet::from_tiny(output.as_slice(), "named", "obf").unwrap();
+        assert_eq!(mappings, parsed);
+    }
+}
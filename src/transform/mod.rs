@@ -0,0 +1,17 @@
+//! Transformations on JVM elements.
+mod inline;
+mod instrument;
+pub mod mappings;
+mod normalize;
+mod remap;
+mod rename;
+mod split_method;
+mod strip;
+
+pub use inline::*;
+pub use instrument::*;
+pub use normalize::*;
+pub use remap::*;
+pub use rename::*;
+pub use split_method::*;
+pub use strip::*;
@@ -0,0 +1,237 @@
+use crate::jvm::{
+    code::{Instruction, MethodBody, WideInstruction},
+    ConstantValue,
+};
+
+use super::instrument::lay_out;
+
+/// Replaces the instructions in `body` with their shortest equivalent forms.
+/// - `wide` instructions are narrowed if their operands fit in the normal form.
+/// - Local variable loads and stores use the `<op>_<n>` forms (e.g., `iload_0`) when applicable.
+/// - Integer constants are pushed with `iconst_<i>`, `bipush`, or `sipush` when the value fits,
+///   instead of being loaded from the constant pool.
+///
+/// The instructions are then laid out again without the freed bytes, and the jump targets, the
+/// exception table, the stack map table, the debug information, and the type annotations on the
+/// code are updated accordingly, and the raw bytes retained from the class file are discarded (see
+/// [`MethodBody::discard_raw`]). The code never grows, since an instruction moved backwards
+/// never ends after its original end, even with the padding of `tableswitch` and
+/// `lookupswitch`.
+pub fn normalize_widths(body: &mut MethodBody) {
+    let mut changed = false;
+    for (_, instruction) in body.instructions.iter_mut() {
+        if let Some(normalized) = normalize(instruction) {
+            *instruction = normalized;
+            changed = true;
+        }
+    }
+    if changed {
+        lay_out(body);
+    }
+}
+
+fn normalize(instruction: &Instruction) -> Option<Instruction> {
+    #[allow(clippy::enum_glob_use)]
+    use Instruction::*;
+    let normalized = match instruction {
+        BiPush(value) => push_int(i8::from_be_bytes([*value]).into())?,
+        SiPush(value) => push_int(i16::from_be_bytes(value.to_be_bytes()).into())?,
+        Ldc(ConstantValue::Integer(value)) | LdcW(ConstantValue::Integer(value)) => {
+            push_int(*value)?
+        }
+        Wide(wide) => short_form(narrow(wide)?),
+        ILoad(_) | LLoad(_) | FLoad(_) | DLoad(_) | ALoad(_) | IStore(_) | LStore(_)
+        | FStore(_) | DStore(_) | AStore(_) => short_form(instruction.clone()),
+        _ => return None,
+    };
+    Some(normalized).filter(|it| it != instruction)
+}
+
+/// Returns the shortest instruction pushing `value` onto the operand stack without using the
+/// constant pool.
+fn push_int(value: i32) -> Option<Instruction> {
+    #[allow(clippy::enum_glob_use)]
+    use Instruction::*;
+    let instruction = match value {
+        -1 => IConstM1,
+        0 => IConst0,
+        1 => IConst1,
+        2 => IConst2,
+        3 => IConst3,
+        4 => IConst4,
+        5 => IConst5,
+        _ => {
+            if let Ok(value) = i8::try_from(value) {
+                BiPush(value.to_be_bytes()[0])
+            } else {
+                let value = i16::try_from(value).ok()?;
+                SiPush(u16::from_be_bytes(value.to_be_bytes()))
+            }
+        }
+    };
+    Some(instruction)
+}
+
+/// Converts a `wide` instruction to its normal form if the operands fit.
+fn narrow(wide: &WideInstruction) -> Option<Instruction> {
+    let instruction = match *wide {
+        WideInstruction::ILoad(index) => Instruction::ILoad(index.try_into().ok()?),
+        WideInstruction::LLoad(index) => Instruction::LLoad(index.try_into().ok()?),
+        WideInstruction::FLoad(index) => Instruction::FLoad(index.try_into().ok()?),
+        WideInstruction::DLoad(index) => Instruction::DLoad(index.try_into().ok()?),
+        WideInstruction::ALoad(index) => Instruction::ALoad(index.try_into().ok()?),
+        WideInstruction::IStore(index) => Instruction::IStore(index.try_into().ok()?),
+        WideInstruction::LStore(index) => Instruction::LStore(index.try_into().ok()?),
+        WideInstruction::FStore(index) => Instruction::FStore(index.try_into().ok()?),
+        WideInstruction::DStore(index) => Instruction::DStore(index.try_into().ok()?),
+        WideInstruction::AStore(index) => Instruction::AStore(index.try_into().ok()?),
+        WideInstruction::IInc(index, increment) => {
+            i8::try_from(increment).ok()?;
+            Instruction::IInc(index.try_into().ok()?, increment)
+        }
+        WideInstruction::Ret(index) => Instruction::Ret(index.try_into().ok()?),
+    };
+    Some(instruction)
+}
+
+/// Converts a local variable load or store to its `<op>_<n>` form if applicable.
+pub(super) fn short_form(instruction: Instruction) -> Instruction {
+    #[allow(clippy::enum_glob_use)]
+    use Instruction::*;
+    match instruction {
+        ILoad(0) => ILoad0,
+        ILoad(1) => ILoad1,
+        ILoad(2) => ILoad2,
+        ILoad(3) => ILoad3,
+        LLoad(0) => LLoad0,
+        LLoad(1) => LLoad1,
+        LLoad(2) => LLoad2,
+        LLoad(3) => LLoad3,
+        FLoad(0) => FLoad0,
+        FLoad(1) => FLoad1,
+        FLoad(2) => FLoad2,
+        FLoad(3) => FLoad3,
+        DLoad(0) => DLoad0,
+        DLoad(1) => DLoad1,
+        DLoad(2) => DLoad2,
+        DLoad(3) => DLoad3,
+        ALoad(0) => ALoad0,
+        ALoad(1) => ALoad1,
+        ALoad(2) => ALoad2,
+        ALoad(3) => ALoad3,
+        IStore(0) => IStore0,
+        IStore(1) => IStore1,
+        IStore(2) => IStore2,
+        IStore(3) => IStore3,
+        LStore(0) => LStore0,
+        LStore(1) => LStore1,
+        LStore(2) => LStore2,
+        LStore(3) => LStore3,
+        FStore(0) => FStore0,
+        FStore(1) => FStore1,
+        FStore(2) => FStore2,
+        FStore(3) => FStore3,
+        DStore(0) => DStore0,
+        DStore(1) => DStore1,
+        DStore(2) => DStore2,
+        DStore(3) => DStore3,
+        AStore(0) => AStore0,
+        AStore(1) => AStore1,
+        AStore(2) => AStore2,
+        AStore(3) => AStore3,
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jvm::code::{ExceptionTableEntry, InstructionList};
+
+    #[test]
+    fn normalize_wide_method() {
+        let mut body = MethodBody {
+            instructions: InstructionList::from([
+                (0.into(), Instruction::Wide(WideInstruction::ILoad(1))),
+                (4.into(), Instruction::Wide(WideInstruction::IStore(300))),
+                (8.into(), Instruction::Wide(WideInstruction::IInc(7, 100))),
+                (14.into(), Instruction::Wide(WideInstruction::IInc(7, 1000))),
+                (20.into(), Instruction::Wide(WideInstruction::ALoad(5))),
+                (24.into(), Instruction::SiPush(5)),
+                (27.into(), Instruction::SiPush(0xFFFF)),
+                (30.into(), Instruction::BiPush(0x80)),
+                (32.into(), Instruction::SiPush(1000)),
+                (35.into(), Instruction::LdcW(ConstantValue::Integer(-200))),
+                (38.into(), Instruction::Ldc(ConstantValue::Integer(100_000))),
+                (40.into(), Instruction::Goto(0.into())),
+            ]),
+            raw_code: Some(Box::new([0xc4, 0x15, 0x00, 0x01])),
+            raw_attributes: vec![("StackMapTable".to_owned(), vec![0, 1, 0])],
+            ..Default::default()
+        };
+        normalize_widths(&mut body);
+        assert!(body.raw_code.is_none());
+        assert!(body.raw_attributes.is_empty());
+        let expected = [
+            (0, Instruction::ILoad1),
+            (1, Instruction::Wide(WideInstruction::IStore(300))),
+            (5, Instruction::IInc(7, 100)),
+            (8, Instruction::Wide(WideInstruction::IInc(7, 1000))),
+            (14, Instruction::ALoad(5)),
+            (16, Instruction::IConst5),
+            (17, Instruction::IConstM1),
+            (18, Instruction::BiPush(0x80)),
+            (20, Instruction::SiPush(1000)),
+            (23, Instruction::SiPush(0xFF38)),
+            (26, Instruction::Ldc(ConstantValue::Integer(100_000))),
+            (28, Instruction::Goto(0.into())),
+        ];
+        let actual: Vec<_> = body.instructions.into_iter().collect();
+        assert_eq!(
+            expected
+                .into_iter()
+                .map(|(pc, insn)| (pc.into(), insn))
+                .collect::<Vec<_>>(),
+            actual
+        );
+    }
+
+    #[test]
+    fn relocate_after_narrowing() {
+        let mut body = MethodBody {
+            instructions: InstructionList::from([
+                (0.into(), Instruction::Wide(WideInstruction::ILoad(1))),
+                (4.into(), Instruction::IfEq(11.into())),
+                (7.into(), Instruction::BiPush(2)),
+                (9.into(), Instruction::Pop),
+                (10.into(), Instruction::Nop),
+                (11.into(), Instruction::Return),
+            ]),
+            exception_table: vec![ExceptionTableEntry {
+                covered_pc: 4.into()..10.into(),
+                handler_pc: 11.into(),
+                catch_type: None,
+            }],
+            ..Default::default()
+        };
+        normalize_widths(&mut body);
+        let pcs: Vec<u16> = body
+            .instructions
+            .iter()
+            .map(|(pc, _)| (*pc).into())
+            .collect();
+        assert_eq!(vec![0, 1, 4, 5, 6, 7], pcs);
+        assert_eq!(
+            Some(&Instruction::IfEq(7.into())),
+            body.instruction_at(1.into())
+        );
+        assert_eq!(
+            vec![ExceptionTableEntry {
+                covered_pc: 1.into()..6.into(),
+                handler_pc: 7.into(),
+                catch_type: None,
+            }],
+            body.exception_table
+        );
+    }
+}
@@ -0,0 +1,293 @@
+use std::io::BufRead;
+
+use crate::jvm::{
+    class::MethodHandle,
+    code::Instruction,
+    references::{ClassRef, FieldRef, MethodRef},
+    Class, ConstantValue,
+};
+
+use super::mappings::{MappingError, MappingSet};
+
+/// A remapper that renames classes, fields, and methods according to a [`MappingSet`].
+/// The names that are not in the mappings are left unchanged.
+///
+/// A reference to a member is only remapped if the member is mapped for the class the reference
+/// refers to, i.e., mappings are not inherited by subclasses.
+#[derive(Debug, Clone, Default)]
+pub struct Remapper {
+    mappings: MappingSet,
+}
+
+impl Remapper {
+    /// Creates a remapper with the given mappings.
+    #[must_use]
+    pub fn new(mappings: MappingSet) -> Self {
+        Self { mappings }
+    }
+
+    /// Creates a remapper with the mappings in the Proguard format.
+    /// See [`MappingSet::from_proguard`] for more information.
+    /// # Errors
+    /// See [`MappingError`] for more information.
+    pub fn from_proguard<R: BufRead>(reader: R) -> Result<Self, MappingError> {
+        MappingSet::from_proguard(reader).map(Self::new)
+    }
+
+    /// Creates a remapper with the mappings in the Tiny v2 format.
+    /// See [`MappingSet::from_tiny`] for more information.
+    /// # Errors
+    /// See [`MappingError`] for more information.
+    pub fn from_tiny<R: BufRead>(
+        reader: R,
+        source_namespace: &str,
+        target_namespace: &str,
+    ) -> Result<Self, MappingError> {
+        MappingSet::from_tiny(reader, source_namespace, target_namespace).map(Self::new)
+    }
+
+    /// Returns the mappings used by the remapper.
+    #[must_use]
+    pub fn mappings(&self) -> &MappingSet {
+        &self.mappings
+    }
+
+    /// Creates a remapper that maps the names in the opposite direction.
+    #[must_use]
+    pub fn reversed(&self) -> Self {
+        Self::new(self.mappings.reversed())
+    }
+
+    /// Returns the name that the class `name` is mapped to.
+    #[must_use]
+    pub fn map_class<'a>(&'a self, name: &'a str) -> &'a str {
+        self.mappings.class(name).unwrap_or(name)
+    }
+
+    /// Returns the name that the given field is mapped to.
+    #[must_use]
+    pub fn map_field<'a>(&'a self, field: &'a FieldRef) -> &'a str {
+        self.mappings.field(field).unwrap_or(&field.name)
+    }
+
+    /// Returns the name that the given method is mapped to.
+    #[must_use]
+    pub fn map_method<'a>(&'a self, method: &'a MethodRef) -> &'a str {
+        self.mappings.method(method).unwrap_or(&method.name)
+    }
+
+    /// Remaps the names of the class, its members, and everything it refers to.
+    /// The member names of `invokedynamic` call sites and annotation elements are left unchanged.
+    pub fn remap_class(&self, class: &mut Class) {
+        // Members are looked up by their names before remapping, so they are remapped first.
+        let owner = ClassRef::new(class.binary_name.as_str());
+        for field in &mut class.fields {
+            let field_ref = FieldRef {
+                owner: owner.clone(),
+                name: field.name.clone(),
+                field_type: field.field_type.clone(),
+            };
+            self.map_field(&field_ref).clone_into(&mut field.name);
+        }
+        for method in &mut class.methods {
+            let method_ref = MethodRef {
+                owner: owner.clone(),
+                name: method.name.clone(),
+                descriptor: method.descriptor.clone(),
+            };
+            self.map_method(&method_ref).clone_into(&mut method.name);
+            let Some(body) = method.body.as_mut() else {
+                continue;
+            };
+            for (_, instruction) in body.instructions.iter_mut() {
+                self.remap_instruction(instruction);
+            }
+            method.discard_raw_code();
+        }
+        if let Some(record) = class.record.as_mut() {
+            for component in record {
+                let field_ref = FieldRef {
+                    owner: owner.clone(),
+                    name: component.name.clone(),
+                    field_type: component.component_type.clone(),
+                };
+                self.map_field(&field_ref).clone_into(&mut component.name);
+            }
+        }
+        if let Some(enclosing_method) = class.enclosing_method.as_mut() {
+            if let Some((name, descriptor)) = enclosing_method.method_name_and_desc.as_mut() {
+                let method_ref = MethodRef {
+                    owner: enclosing_method.class.clone(),
+                    name: name.clone(),
+                    descriptor: descriptor.clone(),
+                };
+                self.map_method(&method_ref).clone_into(name);
+            }
+        }
+        for bootstrap_method in &mut class.bootstrap_methods {
+            self.remap_handle(&mut bootstrap_method.method);
+            bootstrap_method
+                .arguments
+                .iter_mut()
+                .for_each(|it| self.remap_constant(it));
+        }
+        self.mappings.rename_classes(class);
+    }
+
+    /// Remaps all the given classes.
+    /// See [`Remapper::remap_class`] for more information.
+    pub fn remap_classes<'c, I>(&self, classes: I)
+    where
+        I: IntoIterator<Item = &'c mut Class>,
+    {
+        for class in classes {
+            self.remap_class(class);
+        }
+    }
+
+    fn remap_instruction(&self, instruction: &mut Instruction) {
+        #[allow(clippy::enum_glob_use)]
+        use Instruction::*;
+        match instruction {
+            Ldc(value) | LdcW(value) | Ldc2W(value) => self.remap_constant(value),
+            GetStatic(field) | PutStatic(field) | GetField(field) | PutField(field) => {
+                self.remap_field_ref(field);
+            }
+            InvokeVirtual(method)
+            | InvokeSpecial(method)
+            | InvokeStatic(method)
+            | InvokeInterface(method, _) => self.remap_method_ref(method),
+            _ => {}
+        }
+    }
+
+    fn remap_constant(&self, value: &mut ConstantValue) {
+        if let ConstantValue::Handle(handle) = value {
+            self.remap_handle(handle);
+        }
+    }
+
+    fn remap_handle(&self, handle: &mut MethodHandle) {
+        match handle {
+            MethodHandle::RefGetField(field)
+            | MethodHandle::RefGetStatic(field)
+            | MethodHandle::RefPutField(field)
+            | MethodHandle::RefPutStatic(field) => self.remap_field_ref(field),
+            MethodHandle::RefInvokeVirtual(method)
+            | MethodHandle::RefInvokeStatic(method)
+            | MethodHandle::RefInvokeSpecial(method)
+            | MethodHandle::RefNewInvokeSpecial(method)
+            | MethodHandle::RefInvokeInterface(method) => self.remap_method_ref(method),
+        }
+    }
+
+    fn remap_field_ref(&self, field: &mut FieldRef) {
+        if let Some(name) = self.mappings.field(field) {
+            field.name = name.to_owned();
+        }
+    }
+
+    fn remap_method_ref(&self, method: &mut MethodRef) {
+        if let Some(name) = self.mappings.method(method) {
+            method.name = name.to_owned();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jvm::{
+        class::BootstrapMethod,
+        code::{InstructionList, MethodBody},
+        field, method, Field, Method,
+    };
+    use crate::types::field_type::{FieldType, PrimitiveType};
+
+    const MAPPINGS: &str = "\
+# A comment
+org.mokapot.Foo -> a:
+    int count -> b
+    org.mokapot.Foo next -> c
+    1:3:void run(java.lang.String[],int) -> d
+    4:4:void org.mokapot.Bar.inlined():12:12 -> d
+org.mokapot.Bar -> e:
+    org.mokapot.Foo get() -> f
+";
+
+    #[test]
+    fn remap_class() {
+        let remapper = Remapper::from_proguard(MAPPINGS.as_bytes()).unwrap();
+        let foo = ClassRef::new("org/mokapot/Foo");
+        let get = MethodRef {
+            owner: ClassRef::new("org/mokapot/Bar"),
+            name: "get".to_owned(),
+            descriptor: "()Lorg/mokapot/Foo;".parse().unwrap(),
+        };
+        let mut class = Class {
+            binary_name: foo.binary_name.clone(),
+            fields: vec![Field {
+                access_flags: field::AccessFlags::PRIVATE,
+                name: "next".to_owned(),
+                owner: foo.clone(),
+                field_type: FieldType::Object(foo.clone()),
+                ..Default::default()
+            }],
+            bootstrap_methods: vec![BootstrapMethod {
+                method: MethodHandle::RefInvokeStatic(get.clone()),
+                arguments: vec![ConstantValue::Handle(MethodHandle::RefInvokeVirtual(
+                    get.clone(),
+                ))],
+            }],
+            methods: vec![Method {
+                access_flags: method::AccessFlags::PUBLIC,
+                name: "run".to_owned(),
+                descriptor: "([Ljava/lang/String;I)V".parse().unwrap(),
+                owner: foo.clone(),
+                body: Some(MethodBody {
+                    instructions: InstructionList::from([
+                        (0.into(), Instruction::InvokeStatic(get)),
+                        (
+                            3.into(),
+                            Instruction::GetField(FieldRef {
+                                owner: foo.clone(),
+                                name: "count".to_owned(),
+                                field_type: PrimitiveType::Int.into(),
+                            }),
+                        ),
+                    ]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        remapper.remap_class(&mut class);
+
+        assert_eq!("a", class.binary_name);
+        let field = &class.fields[0];
+        assert_eq!("c", field.name);
+        assert_eq!(FieldType::Object(ClassRef::new("a")), field.field_type);
+        let MethodHandle::RefInvokeStatic(bootstrap_method) = &class.bootstrap_methods[0].method
+        else {
+            panic!("Unexpected method handle");
+        };
+        assert_eq!("e", bootstrap_method.owner.binary_name);
+        assert_eq!("f", bootstrap_method.name);
+        assert_eq!(
+            vec![ConstantValue::Handle(MethodHandle::RefInvokeVirtual(
+                bootstrap_method.clone()
+            ))],
+            class.bootstrap_methods[0].arguments
+        );
+        let method = &class.methods[0];
+        assert_eq!("d", method.name);
+        let body = method.body.as_ref().unwrap();
+        let Some(Instruction::GetField(count)) = body.instruction_at(3.into()) else {
+            panic!("Unexpected instruction");
+        };
+        assert_eq!("b", count.name);
+        assert_eq!("a", count.owner.binary_name);
+        assert!(!class.referenced_classes().contains(&foo));
+    }
+}
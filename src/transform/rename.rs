@@ -0,0 +1,662 @@
+use std::{collections::BTreeSet, iter::Peekable, str::Chars};
+
+use crate::{
+    jvm::{
+        annotation::ElementValue,
+        class::{BootstrapMethod, EnclosingMethod, InnerClassInfo, MethodHandle, RecordComponent},
+        code::{ExceptionTableEntry, Instruction, MethodBody, StackMapFrame, VerificationType},
+        module::Provide,
+        references::{ClassRef, FieldRef, MethodRef},
+        Annotation, Class, ConstantValue, Field, Method, Module, TypeAnnotation,
+    },
+    macros::see_jvm_spec,
+    types::{
+        field_type::FieldType,
+        method_descriptor::{MethodDescriptor, ReturnType},
+    },
+};
+
+/// Renames the class `from` to `to` wherever it is referred to in `class`, including the name of
+/// `class` itself.
+/// Both names are binary names in the internal form (e.g., `org/mokapot/Foo`).
+///
+/// Besides the references to the class, the descriptors and the generic signatures embedding its
+/// name (e.g., `Lorg/mokapot/Foo;`) are rewritten.
+/// In a generic signature, the name of an inner class of a parameterized outer class (e.g.,
+/// `Inner` in `Lorg/mokapot/Outer<TT;>.Inner;`) is written relative to the outer class, so it is
+/// only renamed when the outer class is renamed.
+pub fn rename_class(class: &mut Class, from: &str, to: &str) {
+    class.visit_class_names(&mut |name| {
+        if name == from {
+            to.clone_into(name);
+        }
+    });
+}
+
+impl Class {
+    /// Returns the classes that are referred to in the class, including the class itself.
+    /// Array types are resolved to their element types.
+    #[must_use]
+    pub fn referenced_classes(&self) -> BTreeSet<ClassRef> {
+        let mut classes = BTreeSet::new();
+        self.clone().visit_class_names(&mut |name| {
+            classes.insert(ClassRef::new(name.as_str()));
+        });
+        classes
+    }
+}
+
+/// An element containing the binary names of classes.
+pub(crate) trait ClassNames {
+    /// Calls `visitor` with every binary name of a class in `self`, which may be modified by the
+    /// visitor.
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String));
+}
+
+impl<T: ClassNames> ClassNames for Vec<T> {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        for it in self {
+            it.visit_class_names(visitor);
+        }
+    }
+}
+
+impl<T: ClassNames> ClassNames for Option<T> {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        if let Some(it) = self {
+            it.visit_class_names(visitor);
+        }
+    }
+}
+
+impl ClassNames for Class {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        visitor(&mut self.binary_name);
+        self.super_class.visit_class_names(visitor);
+        self.interfaces.visit_class_names(visitor);
+        self.fields.visit_class_names(visitor);
+        self.methods.visit_class_names(visitor);
+        self.inner_classes.visit_class_names(visitor);
+        self.enclosing_method.visit_class_names(visitor);
+        self.runtime_visible_annotations.visit_class_names(visitor);
+        self.runtime_invisible_annotations
+            .visit_class_names(visitor);
+        self.runtime_visible_type_annotations
+            .visit_class_names(visitor);
+        self.runtime_invisible_type_annotations
+            .visit_class_names(visitor);
+        self.bootstrap_methods.visit_class_names(visitor);
+        self.module.visit_class_names(visitor);
+        self.module_main_class.visit_class_names(visitor);
+        self.nest_host.visit_class_names(visitor);
+        self.nest_members.visit_class_names(visitor);
+        self.permitted_subclasses.visit_class_names(visitor);
+        visit_signature(self.signature.as_mut(), visitor);
+        self.record.visit_class_names(visitor);
+    }
+}
+
+impl ClassNames for Field {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        self.owner.visit_class_names(visitor);
+        self.field_type.visit_class_names(visitor);
+        self.constant_value.visit_class_names(visitor);
+        visit_signature(self.signature.as_mut(), visitor);
+        self.runtime_visible_annotations.visit_class_names(visitor);
+        self.runtime_invisible_annotations
+            .visit_class_names(visitor);
+        self.runtime_visible_type_annotations
+            .visit_class_names(visitor);
+        self.runtime_invisible_type_annotations
+            .visit_class_names(visitor);
+    }
+}
+
+impl ClassNames for Method {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        self.owner.visit_class_names(visitor);
+        self.descriptor.visit_class_names(visitor);
+        self.body.visit_class_names(visitor);
+        if self.body.is_some() {
+            self.discard_raw_code();
+        }
+        self.exceptions.visit_class_names(visitor);
+        self.runtime_visible_annotations.visit_class_names(visitor);
+        self.runtime_invisible_annotations
+            .visit_class_names(visitor);
+        self.runtime_visible_type_annotations
+            .visit_class_names(visitor);
+        self.runtime_invisible_type_annotations
+            .visit_class_names(visitor);
+        self.runtime_visible_parameter_annotations
+            .visit_class_names(visitor);
+        self.runtime_invisible_parameter_annotations
+            .visit_class_names(visitor);
+        self.annotation_default.visit_class_names(visitor);
+        visit_signature(self.signature.as_mut(), visitor);
+    }
+}
+
+impl ClassNames for MethodBody {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        for (_, instruction) in self.instructions.iter_mut() {
+            instruction.visit_class_names(visitor);
+        }
+        // The names are in the constant pool, which the raw bytes refer to.
+        self.discard_raw();
+        self.exception_table.visit_class_names(visitor);
+        if let Some(local_variable_table) = self.local_variable_table.as_mut() {
+            for (_, entry) in local_variable_table.entries_mut() {
+                entry.var_type.visit_class_names(visitor);
+                visit_signature(entry.signature.as_mut(), visitor);
+            }
+        }
+        self.stack_map_table.visit_class_names(visitor);
+        self.runtime_visible_type_annotations
+            .visit_class_names(visitor);
+        self.runtime_invisible_type_annotations
+            .visit_class_names(visitor);
+    }
+}
+
+impl ClassNames for Instruction {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        #[allow(clippy::enum_glob_use)]
+        use Instruction::*;
+        match self {
+            Ldc(value) | LdcW(value) | Ldc2W(value) => value.visit_class_names(visitor),
+            GetStatic(field) | PutStatic(field) | GetField(field) | PutField(field) => {
+                field.visit_class_names(visitor);
+            }
+            InvokeVirtual(method)
+            | InvokeSpecial(method)
+            | InvokeStatic(method)
+            | InvokeInterface(method, _) => method.visit_class_names(visitor),
+            InvokeDynamic { descriptor, .. } => descriptor.visit_class_names(visitor),
+            New(class) => class.visit_class_names(visitor),
+            ANewArray(field_type)
+            | CheckCast(field_type)
+            | InstanceOf(field_type)
+            | MultiANewArray(field_type, _) => {
+                field_type.visit_class_names(visitor);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl ClassNames for ExceptionTableEntry {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        self.catch_type.visit_class_names(visitor);
+    }
+}
+
+impl ClassNames for StackMapFrame {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        match self {
+            Self::SameFrame { .. } | Self::ChopFrame { .. } => {}
+            Self::SameLocals1StackItemFrame { stack, .. } => stack.visit_class_names(visitor),
+            Self::AppendFrame { locals, .. } => locals.visit_class_names(visitor),
+            Self::FullFrame { locals, stack, .. } => {
+                locals.visit_class_names(visitor);
+                stack.visit_class_names(visitor);
+            }
+        }
+    }
+}
+
+impl ClassNames for VerificationType {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        if let Self::ObjectVariable(class) = self {
+            class.visit_class_names(visitor);
+        }
+    }
+}
+
+impl ClassNames for ClassRef {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        // Array classes are referred to by their descriptors.
+        if self.binary_name.starts_with('[') {
+            if let Ok(mut array_type) = self.binary_name.parse::<FieldType>() {
+                array_type.visit_class_names(visitor);
+                self.binary_name = array_type.descriptor();
+            }
+        } else {
+            visitor(&mut self.binary_name);
+        }
+    }
+}
+
+impl ClassNames for FieldRef {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        self.owner.visit_class_names(visitor);
+        self.field_type.visit_class_names(visitor);
+    }
+}
+
+impl ClassNames for MethodRef {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        self.owner.visit_class_names(visitor);
+        self.descriptor.visit_class_names(visitor);
+    }
+}
+
+impl ClassNames for FieldType {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        match self {
+            Self::Base(_) => {}
+            Self::Object(class) => visitor(&mut class.binary_name),
+            Self::Array(element) => element.visit_class_names(visitor),
+        }
+    }
+}
+
+impl ClassNames for MethodDescriptor {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        self.parameters_types.visit_class_names(visitor);
+        self.return_type.visit_class_names(visitor);
+    }
+}
+
+impl ClassNames for ReturnType {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        if let Self::Some(field_type) = self {
+            field_type.visit_class_names(visitor);
+        }
+    }
+}
+
+impl ClassNames for ConstantValue {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        match self {
+            Self::Class(class) => class.visit_class_names(visitor),
+            Self::Handle(handle) => handle.visit_class_names(visitor),
+            Self::MethodType(descriptor) => descriptor.visit_class_names(visitor),
+            Self::Dynamic(_, _, field_type) => field_type.visit_class_names(visitor),
+            Self::Null
+            | Self::Integer(_)
+            | Self::Float(_)
+            | Self::Long(_)
+            | Self::Double(_)
+            | Self::String(_) => {}
+        }
+    }
+}
+
+impl ClassNames for MethodHandle {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        match self {
+            Self::RefGetField(field)
+            | Self::RefGetStatic(field)
+            | Self::RefPutField(field)
+            | Self::RefPutStatic(field) => field.visit_class_names(visitor),
+            Self::RefInvokeVirtual(method)
+            | Self::RefInvokeStatic(method)
+            | Self::RefInvokeSpecial(method)
+            | Self::RefNewInvokeSpecial(method)
+            | Self::RefInvokeInterface(method) => method.visit_class_names(visitor),
+        }
+    }
+}
+
+impl ClassNames for BootstrapMethod {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        self.method.visit_class_names(visitor);
+        self.arguments.visit_class_names(visitor);
+    }
+}
+
+impl ClassNames for InnerClassInfo {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        self.inner_class.visit_class_names(visitor);
+        self.outer_class.visit_class_names(visitor);
+    }
+}
+
+impl ClassNames for EnclosingMethod {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        self.class.visit_class_names(visitor);
+        if let Some((_, descriptor)) = self.method_name_and_desc.as_mut() {
+            descriptor.visit_class_names(visitor);
+        }
+    }
+}
+
+impl ClassNames for RecordComponent {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        self.component_type.visit_class_names(visitor);
+        visit_signature(self.signature.as_mut(), visitor);
+        self.runtime_visible_annotations.visit_class_names(visitor);
+        self.runtime_invisible_annotations
+            .visit_class_names(visitor);
+        self.runtime_visible_type_annotations
+            .visit_class_names(visitor);
+        self.runtime_invisible_type_annotations
+            .visit_class_names(visitor);
+    }
+}
+
+impl ClassNames for Module {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        self.uses.visit_class_names(visitor);
+        self.provides.visit_class_names(visitor);
+    }
+}
+
+impl ClassNames for Provide {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        self.service.visit_class_names(visitor);
+        self.with.visit_class_names(visitor);
+    }
+}
+
+impl ClassNames for Annotation {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        self.annotation_type.visit_class_names(visitor);
+        for (_, value) in &mut self.element_value_pairs {
+            value.visit_class_names(visitor);
+        }
+    }
+}
+
+impl ClassNames for TypeAnnotation {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        self.annotation_type.visit_class_names(visitor);
+        for (_, value) in &mut self.element_value_pairs {
+            value.visit_class_names(visitor);
+        }
+    }
+}
+
+impl ClassNames for ElementValue {
+    fn visit_class_names(&mut self, visitor: &mut dyn FnMut(&mut String)) {
+        match self {
+            Self::Primitive(..) | Self::String(_) => {}
+            Self::EnumConstant { enum_type_name, .. } => {
+                // The name of the enum type is a field descriptor.
+                if let Ok(mut enum_type) = enum_type_name.parse::<FieldType>() {
+                    enum_type.visit_class_names(visitor);
+                    *enum_type_name = enum_type.descriptor();
+                }
+            }
+            Self::Class { return_descriptor } => return_descriptor.visit_class_names(visitor),
+            Self::AnnotationInterface(annotation) => annotation.visit_class_names(visitor),
+            Self::Array(values) => values.visit_class_names(visitor),
+        }
+    }
+}
+
+/// Visits the class names in a generic class, method, or field signature.
+/// Malformed signatures are left unchanged.
+#[doc = see_jvm_spec!(4, 7, 9, 1)]
+fn visit_signature(signature: Option<&mut String>, visitor: &mut dyn FnMut(&mut String)) {
+    let Some(signature) = signature else {
+        return;
+    };
+    let mut parser = SignatureParser {
+        chars: signature.chars().peekable(),
+        output: String::with_capacity(signature.len()),
+        visitor,
+    };
+    if parser.signature().is_some() {
+        *signature = parser.output;
+    }
+}
+
+/// A parser that copies a generic signature to `output` while rewriting the class names in it.
+struct SignatureParser<'s, 'v> {
+    chars: Peekable<Chars<'s>>,
+    output: String,
+    visitor: &'v mut dyn FnMut(&mut String),
+}
+
+impl SignatureParser<'_, '_> {
+    fn signature(&mut self) -> Option<()> {
+        if self.chars.peek() == Some(&'<') {
+            self.type_parameters()?;
+        }
+        while let Some(&c) = self.chars.peek() {
+            match c {
+                '(' | ')' | '^' | 'B' | 'C' | 'D' | 'F' | 'I' | 'J' | 'S' | 'Z' | 'V' => {
+                    self.copy();
+                }
+                _ => self.reference_type()?,
+            }
+        }
+        Some(())
+    }
+
+    fn type_parameters(&mut self) -> Option<()> {
+        self.expect('<')?;
+        while self.chars.peek() != Some(&'>') {
+            self.copy_until(|c| c == ':')?;
+            while self.chars.peek() == Some(&':') {
+                self.copy();
+                if matches!(self.chars.peek(), Some('L' | 'T' | '[')) {
+                    self.reference_type()?;
+                }
+            }
+        }
+        self.expect('>')
+    }
+
+    fn reference_type(&mut self) -> Option<()> {
+        match self.chars.next()? {
+            'L' => {
+                self.output.push('L');
+                let mut name = String::new();
+                while let Some(&c) = self.chars.peek().filter(|c| !matches!(c, '<' | '.' | ';')) {
+                    name.push(c);
+                    self.chars.next();
+                }
+                (self.visitor)(&mut name);
+                self.output.push_str(&name);
+                self.type_arguments()?;
+                while self.chars.peek() == Some(&'.') {
+                    self.copy();
+                    self.copy_until(|c| matches!(c, '<' | '.' | ';'))?;
+                    self.type_arguments()?;
+                }
+                self.expect(';')
+            }
+            'T' => {
+                self.output.push('T');
+                self.copy_until(|c| c == ';')?;
+                self.expect(';')
+            }
+            '[' => {
+                self.output.push('[');
+                match self.chars.peek()? {
+                    'L' | 'T' | '[' => self.reference_type(),
+                    _ => {
+                        self.copy();
+                        Some(())
+                    }
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn type_arguments(&mut self) -> Option<()> {
+        if self.chars.peek() != Some(&'<') {
+            return Some(());
+        }
+        self.copy();
+        while self.chars.peek() != Some(&'>') {
+            match self.chars.peek()? {
+                '*' => self.copy(),
+                '+' | '-' => {
+                    self.copy();
+                    self.reference_type()?;
+                }
+                _ => self.reference_type()?,
+            }
+        }
+        self.expect('>')
+    }
+
+    fn copy(&mut self) {
+        if let Some(c) = self.chars.next() {
+            self.output.push(c);
+        }
+    }
+
+    /// Copies the characters up to, but excluding, the first one satisfying `is_end`.
+    fn copy_until(&mut self, is_end: impl Fn(char) -> bool) -> Option<()> {
+        loop {
+            let c = *self.chars.peek()?;
+            if is_end(c) {
+                return Some(());
+            }
+            self.copy();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Option<()> {
+        let c = self.chars.next().filter(|it| *it == expected)?;
+        self.output.push(c);
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jvm::{
+        code::{InstructionList, ProgramCounter},
+        method,
+    };
+
+    fn rename_in_signature(signature: &str, from: &str, to: &str) -> String {
+        let mut signature = signature.to_owned();
+        visit_signature(Some(&mut signature), &mut |name| {
+            if name == from {
+                to.clone_into(name);
+            }
+        });
+        signature
+    }
+
+    #[test]
+    fn signatures() {
+        assert_eq!(
+            "<LT:La/New;>Ljava/lang/Object;Ljava/util/List<La/New;>;",
+            rename_in_signature(
+                "<LT:La/Old;>Ljava/lang/Object;Ljava/util/List<La/Old;>;",
+                "a/Old",
+                "a/New"
+            )
+        );
+        assert_eq!(
+            "<T::La/New;>([TT;ILa/New<+La/New;*>.Inner<-TT;>;)[La/New;^La/New;",
+            rename_in_signature(
+                "<T::La/Old;>([TT;ILa/Old<+La/Old;*>.Inner<-TT;>;)[La/Old;^La/Old;",
+                "a/Old",
+                "a/New"
+            )
+        );
+        // Type variables whose names look like classes are not renamed.
+        assert_eq!(
+            "Ljava/util/Map<TLa/Old;La/New;>;",
+            rename_in_signature("Ljava/util/Map<TLa/Old;La/Old;>;", "a/Old", "a/New")
+        );
+        assert_eq!(
+            "Lbroken<",
+            rename_in_signature("Lbroken<", "broken", "fixed")
+        );
+    }
+
+    #[test]
+    fn rename() {
+        let old = ClassRef::new("org/mokapot/Old");
+        let old_type = FieldType::Object(old.clone());
+        let body = MethodBody {
+            instructions: InstructionList::from([
+                (ProgramCounter::from(0), Instruction::New(old.clone())),
+                (
+                    3.into(),
+                    Instruction::ANewArray(old_type.clone().into_array_type()),
+                ),
+                (
+                    6.into(),
+                    Instruction::Ldc(ConstantValue::Class(old.clone())),
+                ),
+                (8.into(), Instruction::CheckCast(old_type.clone())),
+                (11.into(), Instruction::AReturn),
+            ]),
+            stack_map_table: Some(vec![StackMapFrame::SameLocals1StackItemFrame {
+                offset_delta: 11,
+                stack: VerificationType::ObjectVariable(old.clone()),
+            }]),
+            raw_attributes: vec![("StackMapTable".to_owned(), vec![0, 1, 75, 7, 0, 2])],
+            raw_code: Some(Box::new([0xbb, 0x00, 0x02])),
+            ..Default::default()
+        };
+        let mut class = Class {
+            binary_name: old.binary_name.clone(),
+            super_class: Some(ClassRef::new("java/lang/Object")),
+            signature: Some("Ljava/lang/Comparable<Lorg/mokapot/Old;>;".to_owned()),
+            methods: vec![Method {
+                access_flags: method::AccessFlags::PUBLIC,
+                name: "copy".to_owned(),
+                descriptor: "([Lorg/mokapot/Old;)Lorg/mokapot/Old;".parse().unwrap(),
+                owner: old.clone(),
+                body: Some(body),
+                runtime_visible_annotations: vec![Annotation {
+                    annotation_type: old_type.clone(),
+                    element_value_pairs: vec![(
+                        "value".to_owned(),
+                        ElementValue::EnumConstant {
+                            enum_type_name: "Lorg/mokapot/Old;".to_owned(),
+                            const_name: "A".to_owned(),
+                        },
+                    )],
+                }],
+                raw_attributes: vec![
+                    ("Code".to_owned(), vec![0, 1]),
+                    ("Deprecated".to_owned(), Vec::new()),
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert!(class.referenced_classes().contains(&old));
+
+        rename_class(&mut class, "org/mokapot/Old", "org/mokapot/New");
+        let new = ClassRef::new("org/mokapot/New");
+        assert_eq!(
+            BTreeSet::from([
+                new.clone(),
+                ClassRef::new("java/lang/Comparable"),
+                ClassRef::new("java/lang/Object")
+            ]),
+            class.referenced_classes()
+        );
+        assert_eq!(new.binary_name, class.binary_name);
+        assert_eq!(
+            Some("Ljava/lang/Comparable<Lorg/mokapot/New;>;"),
+            class.signature.as_deref()
+        );
+        let method = &class.methods[0];
+        assert_eq!(
+            "([Lorg/mokapot/New;)Lorg/mokapot/New;"
+                .parse::<MethodDescriptor>()
+                .unwrap(),
+            method.descriptor
+        );
+        let body = method.body.as_ref().unwrap();
+        assert_eq!(
+            Some(&Instruction::ANewArray(
+                "[Lorg/mokapot/New;".parse().unwrap()
+            )),
+            body.instruction_at(3.into())
+        );
+        assert!(body.raw_code.is_none());
+        assert!(body.raw_attributes.is_empty());
+        assert_eq!(
+            vec![("Deprecated".to_owned(), Vec::new())],
+            method.raw_attributes
+        );
+    }
+}
@@ -0,0 +1,857 @@
+use std::{collections::BTreeMap, convert::Infallible};
+
+use crate::{
+    analysis::{
+        dataflow::{DataflowAnalysis, Lattice},
+        liveness, Liveness,
+    },
+    ir::{operand_stack_depths, MokaIRBrewingError},
+    jvm::{
+        code::{
+            ExceptionTableEntry, Instruction, InstructionList, LineNumberTableEntry, MethodBody,
+            ProgramCounter, StackMapFrame, VerificationType, WideInstruction,
+        },
+        method,
+        references::MethodRef,
+        Method,
+    },
+    types::{
+        field_type::{FieldType, PrimitiveType},
+        method_descriptor::{MethodDescriptor, ReturnType},
+    },
+};
+
+/// The maximum number of parameter slots of a static method.
+const MAX_PARAMETER_SLOTS: u16 = 255;
+
+/// A method split into two by [`split_method`].
+#[derive(Debug, Clone)]
+pub struct SplitMethod {
+    /// The original method whose tail is replaced by a call to [`helper`](SplitMethod::helper).
+    pub head: Method,
+    /// The synthetic method containing the extracted tail.
+    /// It carries the [`index`](Method::index) of the original method, which should be updated
+    /// when the helper is added to a class.
+    pub helper: Method,
+    /// The location in the original method where the extracted tail starts.
+    pub split_pc: ProgramCounter,
+}
+
+/// An error that occurs when splitting a method.
+#[derive(Debug, thiserror::Error)]
+pub enum SplitError {
+    /// The method does not have a body.
+    #[error("The method does not have a body")]
+    NoMethodBody,
+    /// The method is a constructor, whose `this` may be uninitialized.
+    #[error("Splitting constructors is not supported")]
+    Constructor,
+    /// The method contains subroutines.
+    #[error("Splitting methods with subroutines is not supported")]
+    Subroutine,
+    /// The operand stack of the method cannot be analyzed.
+    #[error("Failed to analyze the method: {0}")]
+    Analysis(#[from] MokaIRBrewingError),
+    /// There is no location to split the method at such that the head fits in the limit.
+    #[error("No split point satisfies the size limit")]
+    NoSplitPoint,
+    /// The type of a local variable that is live at the split point cannot be determined, so it
+    /// cannot be passed to the helper.
+    /// Only returned if no other split point satisfies the size limit.
+    #[error("The type of local variable {slot} at {pc} cannot be determined")]
+    UnknownLocalType {
+        /// The slot of the local variable.
+        slot: u16,
+        /// The location of the split point.
+        pc: ProgramCounter,
+    },
+}
+
+/// Extracts a tail of `method` into a `private static` synthetic method named `helper_name`,
+/// and replaces the tail with an invocation of it.
+///
+/// The method is split at the last instruction boundary such that
+/// - the operand stack is empty,
+/// - no jump or exception handler crosses the boundary, and
+/// - the code size of the head, including the invocation, does not exceed `limit`.
+///
+/// The local variables that are live at the split point are passed to the helper as parameters
+/// in the same slots, so the instructions in the tail are kept as they are.
+/// Their types are tracked along the control flow to the split point. A reference keeps the type
+/// of `this` or the method parameter it was passed as until it is overwritten; otherwise, its type
+/// is taken from the stack map frame or the local variable table at the split point.
+///
+/// The stack map tables, the local variable tables, and the type annotations on the code of both
+/// methods are dropped since they refer to the original layout, and should be recomputed.
+/// # Errors
+/// See [`SplitError`] for more information.
+pub fn split_method(
+    method: &Method,
+    helper_name: &str,
+    limit: usize,
+) -> Result<SplitMethod, SplitError> {
+    let body = method.body.as_ref().ok_or(SplitError::NoMethodBody)?;
+    if method.is_constructor() {
+        return Err(SplitError::Constructor);
+    }
+    if body.instructions.iter().any(|(_, insn)| {
+        matches!(
+            insn,
+            Instruction::Jsr(_)
+                | Instruction::JsrW(_)
+                | Instruction::Ret(_)
+                | Instruction::Wide(WideInstruction::Ret(_))
+        )
+    }) {
+        return Err(SplitError::Subroutine);
+    }
+    let stack_depths = operand_stack_depths(method)?;
+    let liveness = liveness(body);
+    let local_types = LocalTypeAnalysis { method }.analyze(body);
+    let entry_pc = body.instructions.entry_point().map(|(pc, _)| *pc);
+    let mut unknown_type = None;
+
+    for (&split_pc, _) in body.instructions.iter().rev() {
+        if Some(split_pc) == entry_pc
+            || stack_depths.get(&split_pc) != Some(&0)
+            || !is_separable(body, split_pc)
+        {
+            continue;
+        }
+        let parameters = match parameters_at(method, body, &liveness, &local_types, split_pc) {
+            Ok(Some(parameters)) => parameters,
+            Ok(None) => continue,
+            Err(e) => {
+                unknown_type.get_or_insert(e);
+                continue;
+            }
+        };
+        let helper_descriptor = MethodDescriptor {
+            parameters_types: parameters.iter().map(|(it, _)| it.clone()).collect(),
+            return_type: method.descriptor.return_type.clone(),
+        };
+        let helper_ref = MethodRef {
+            owner: method.owner.clone(),
+            name: helper_name.to_owned(),
+            descriptor: helper_descriptor,
+        };
+        let prologue = invocation(&parameters, helper_ref, &method.descriptor.return_type);
+        let head_size = prologue
+            .iter()
+            .fold(usize::from(u16::from(split_pc)), |offset, insn| {
+                offset + insn.encoded_len(offset)
+            });
+        if head_size > limit {
+            continue;
+        }
+        return Ok(split_at(method, body, split_pc, helper_name, prologue));
+    }
+    Err(unknown_type.unwrap_or(SplitError::NoSplitPoint))
+}
+
+#[allow(clippy::too_many_lines)]
+fn split_at(
+    method: &Method,
+    body: &MethodBody,
+    split_pc: ProgramCounter,
+    helper_name: &str,
+    prologue: Vec<Instruction>,
+) -> SplitMethod {
+    let Instruction::InvokeStatic(helper_ref) = &prologue[prologue.len() - 2] else {
+        unreachable!("The prologue ends with an invocation and a return");
+    };
+    let helper_ref = helper_ref.clone();
+    let parameter_slots: u16 = helper_ref
+        .descriptor
+        .parameters_types
+        .iter()
+        .map(FieldType::slot_width)
+        .sum();
+
+    let mut head_instructions: BTreeMap<_, _> = body
+        .instructions
+        .iter()
+        .filter(|(pc, _)| **pc < split_pc)
+        .map(|(pc, insn)| (*pc, insn.clone()))
+        .collect();
+    let mut offset = usize::from(u16::from(split_pc));
+    for insn in prologue {
+        let next_offset = offset + insn.encoded_len(offset);
+        head_instructions.insert(to_pc(offset), insn);
+        offset = next_offset;
+    }
+    let return_slots = match &method.descriptor.return_type {
+        ReturnType::Some(it) => it.slot_width(),
+        ReturnType::Void => 0,
+    };
+    let head_body = MethodBody {
+        max_stack: body.max_stack.max(parameter_slots).max(return_slots),
+        max_locals: body.max_locals,
+        instructions: InstructionList::from(head_instructions),
+        exception_table: body
+            .exception_table
+            .iter()
+            .filter(|it| it.covered_pc.end <= split_pc)
+            .cloned()
+            .collect(),
+        line_number_table: body.line_number_table.as_ref().map(|table| {
+            table
+                .iter()
+                .filter(|it| it.start_pc < split_pc)
+                .cloned()
+                .collect()
+        }),
+        local_variable_table: None,
+        stack_map_table: None,
+        runtime_visible_type_annotations: Vec::new(),
+        runtime_invisible_type_annotations: Vec::new(),
+        free_attributes: body.free_attributes.clone(),
+        raw_attributes: Vec::new(),
+        raw_code: None,
+    };
+
+    // Lays out the tail from offset 0 since the padding of switches may change.
+    let mut new_pcs = BTreeMap::new();
+    let mut offset = 0;
+    for (pc, insn) in body.instructions.iter().filter(|(pc, _)| **pc >= split_pc) {
+        new_pcs.insert(*pc, to_pc(offset));
+        offset += insn.encoded_len(offset);
+    }
+    let tail_end = to_pc(offset);
+    let relocate = |pc: ProgramCounter| {
+        new_pcs
+            .range(pc..)
+            .next()
+            .map_or(tail_end, |(_, new_pc)| *new_pc)
+    };
+    let helper_instructions = body
+        .instructions
+        .iter()
+        .filter(|(pc, _)| **pc >= split_pc)
+        .map(|(pc, insn)| {
+            let mut insn = insn.clone();
+            retarget(&mut insn, relocate);
+            (relocate(*pc), insn)
+        })
+        .collect::<BTreeMap<_, _>>();
+    let helper_body = MethodBody {
+        max_stack: body.max_stack,
+        max_locals: body.max_locals.max(parameter_slots),
+        instructions: InstructionList::from(helper_instructions),
+        exception_table: body
+            .exception_table
+            .iter()
+            .filter(|it| it.covered_pc.start >= split_pc)
+            .map(|it| ExceptionTableEntry {
+                covered_pc: relocate(it.covered_pc.start)..relocate(it.covered_pc.end),
+                handler_pc: relocate(it.handler_pc),
+                catch_type: it.catch_type.clone(),
+            })
+            .collect(),
+        line_number_table: body.line_number_table.as_ref().map(|table| {
+            table
+                .iter()
+                .filter(|it| it.start_pc >= split_pc)
+                .map(|it| LineNumberTableEntry {
+                    start_pc: relocate(it.start_pc),
+                    line_number: it.line_number,
+                })
+                .collect()
+        }),
+        local_variable_table: None,
+        stack_map_table: None,
+        runtime_visible_type_annotations: Vec::new(),
+        runtime_invisible_type_annotations: Vec::new(),
+        free_attributes: Vec::new(),
+        raw_attributes: Vec::new(),
+        raw_code: None,
+    };
+
+    let helper = Method {
+        access_flags: method::AccessFlags::PRIVATE
+            | method::AccessFlags::STATIC
+            | method::AccessFlags::SYNTHETIC,
+        name: helper_name.to_owned(),
+        descriptor: helper_ref.descriptor,
+        owner: method.owner.clone(),
+        index: method.index,
+        body: Some(helper_body),
+        exceptions: method.exceptions.clone(),
+        runtime_visible_annotations: Vec::new(),
+        runtime_invisible_annotations: Vec::new(),
+        runtime_visible_type_annotations: Vec::new(),
+        runtime_invisible_type_annotations: Vec::new(),
+        runtime_visible_parameter_annotations: Vec::new(),
+        runtime_invisible_parameter_annotations: Vec::new(),
+        annotation_default: None,
+        parameters: Vec::new(),
+        is_synthetic: true,
+        is_deprecated: false,
+        signature: None,
+        free_attributes: Vec::new(),
+        raw_attributes: Vec::new(),
+    };
+    let mut head = Method {
+        body: Some(head_body),
+        ..method.clone()
+    };
+    head.discard_raw_code();
+    SplitMethod {
+        head,
+        helper,
+        split_pc,
+    }
+}
+
+/// Checks whether no jump or exception handler crosses the boundary at `split_pc`.
+fn is_separable(body: &MethodBody, split_pc: ProgramCounter) -> bool {
+    let jumps_are_separated = body.instructions.iter().all(|(pc, insn)| {
+        insn.jump_targets().into_iter().all(|target| {
+            if *pc < split_pc {
+                target <= split_pc
+            } else {
+                target >= split_pc
+            }
+        })
+    });
+    let handlers_are_separated = body.exception_table.iter().all(|entry| {
+        let in_head = entry.covered_pc.end <= split_pc && entry.handler_pc < split_pc;
+        let in_tail = entry.covered_pc.start >= split_pc && entry.handler_pc >= split_pc;
+        in_head || in_tail
+    });
+    jumps_are_separated && handlers_are_separated
+}
+
+/// A parameter of the helper method, with the local variable slot passed to it, if any.
+type Parameter = (FieldType, Option<u16>);
+
+/// Returns the parameters of the helper method, each with the local variable slot passed to it,
+/// or [`None`] if there are too many of them.
+/// # Errors
+/// Returns [`SplitError::UnknownLocalType`] if the type of a live local variable cannot be
+/// determined.
+fn parameters_at(
+    method: &Method,
+    body: &MethodBody,
+    liveness: &Liveness,
+    local_types: &BTreeMap<ProgramCounter, LocalTypes>,
+    split_pc: ProgramCounter,
+) -> Result<Option<Vec<Parameter>>, SplitError> {
+    let (Some(live), Some(LocalTypes(types))) =
+        (liveness.live_in(split_pc), local_types.get(&split_pc))
+    else {
+        return Ok(None);
+    };
+    let frame = frame_at(method, body, split_pc);
+    let mut parameters = Vec::new();
+    let mut slot = 0;
+    for live_slot in live.iter() {
+        if live_slot < slot {
+            // The second slot of a `long` or a `double`.
+            continue;
+        }
+        // Fills the gap with dummy `int` parameters so that the slots are kept.
+        while slot < live_slot {
+            parameters.push((PrimitiveType::Int.into(), None));
+            slot += 1;
+        }
+        let unknown = SplitError::UnknownLocalType {
+            slot: live_slot,
+            pc: split_pc,
+        };
+        let param_type = match types.get(usize::from(live_slot)) {
+            Some(LocalType::Int) => PrimitiveType::Int.into(),
+            Some(LocalType::Long) => PrimitiveType::Long.into(),
+            Some(LocalType::Float) => PrimitiveType::Float.into(),
+            Some(LocalType::Double) => PrimitiveType::Double.into(),
+            Some(LocalType::Reference(Some(known_type))) => known_type.clone(),
+            Some(LocalType::Reference(None)) => frame
+                .as_ref()
+                .and_then(|it| it.get(usize::from(live_slot)))
+                .and_then(VerificationType::to_field_type)
+                .filter(|it| matches!(it, FieldType::Object(_) | FieldType::Array(_)))
+                .or_else(|| {
+                    body.local_variable_table
+                        .as_ref()
+                        .and_then(|it| it.entry_at(live_slot, split_pc))
+                        .and_then(|entry| entry.var_type.clone())
+                })
+                .ok_or(unknown)?,
+            Some(LocalType::Top) | None => return Err(unknown),
+        };
+        let Some(next_slot) = live_slot.checked_add(param_type.slot_width()) else {
+            return Ok(None);
+        };
+        slot = next_slot;
+        parameters.push((param_type, Some(live_slot)));
+    }
+    Ok((slot <= MAX_PARAMETER_SLOTS).then_some(parameters))
+}
+
+/// Returns the types of the local variables in the stack map frame at `pc`, indexed by their
+/// slots, or [`None`] if there is no frame at `pc`.
+fn frame_at(
+    method: &Method,
+    body: &MethodBody,
+    pc: ProgramCounter,
+) -> Option<Vec<VerificationType>> {
+    // The locals as in the stack map table, where `long` and `double` take one entry each.
+    let mut locals: Vec<_> = method.initial_frame();
+    let mut index = 0;
+    while index < locals.len() {
+        if is_wide(&locals[index]) {
+            locals.remove(index + 1);
+        }
+        index += 1;
+    }
+    let mut frame_pc: Option<u16> = None;
+    for frame in body.stack_map_table.iter().flatten() {
+        frame_pc = Some(frame_pc.map_or(Some(frame.offset_delta()), |it| {
+            it.checked_add(frame.offset_delta())?.checked_add(1)
+        })?);
+        match frame {
+            StackMapFrame::SameFrame { .. } | StackMapFrame::SameLocals1StackItemFrame { .. } => {}
+            StackMapFrame::ChopFrame { chop_count, .. } => {
+                locals.truncate(locals.len().saturating_sub(usize::from(*chop_count)));
+            }
+            StackMapFrame::AppendFrame {
+                locals: appended, ..
+            } => locals.extend(appended.iter().cloned()),
+            StackMapFrame::FullFrame {
+                locals: full_locals,
+                ..
+            } => locals.clone_from(full_locals),
+        }
+        match frame_pc.map(ProgramCounter::from).cmp(&Some(pc)) {
+            std::cmp::Ordering::Less => {}
+            std::cmp::Ordering::Equal => {
+                return Some(
+                    locals
+                        .into_iter()
+                        .flat_map(|it| {
+                            let second = is_wide(&it).then_some(VerificationType::TopVariable);
+                            std::iter::once(it).chain(second)
+                        })
+                        .collect(),
+                );
+            }
+            std::cmp::Ordering::Greater => break,
+        }
+    }
+    None
+}
+
+const fn is_wide(verification_type: &VerificationType) -> bool {
+    matches!(
+        verification_type,
+        VerificationType::LongVariable | VerificationType::DoubleVariable
+    )
+}
+
+/// Builds the instructions that invoke the helper and return its result.
+fn invocation(
+    parameters: &[Parameter],
+    helper_ref: MethodRef,
+    return_type: &ReturnType,
+) -> Vec<Instruction> {
+    use PrimitiveType::{Double, Float, Long};
+    let loads = parameters.iter().map(|(param_type, slot)| {
+        let Some(slot) = slot else {
+            return Instruction::IConst0;
+        };
+        let slot = u8::try_from(*slot).expect("Parameter slots are within u8");
+        match param_type {
+            FieldType::Base(Long) => Instruction::LLoad(slot),
+            FieldType::Base(Float) => Instruction::FLoad(slot),
+            FieldType::Base(Double) => Instruction::DLoad(slot),
+            FieldType::Base(_) => Instruction::ILoad(slot),
+            FieldType::Object(_) | FieldType::Array(_) => Instruction::ALoad(slot),
+        }
+    });
+    let ret = match return_type {
+        ReturnType::Void => Instruction::Return,
+        ReturnType::Some(FieldType::Base(Long)) => Instruction::LReturn,
+        ReturnType::Some(FieldType::Base(Float)) => Instruction::FReturn,
+        ReturnType::Some(FieldType::Base(Double)) => Instruction::DReturn,
+        ReturnType::Some(FieldType::Base(_)) => Instruction::IReturn,
+        ReturnType::Some(FieldType::Object(_) | FieldType::Array(_)) => Instruction::AReturn,
+    };
+    loads
+        .chain([Instruction::InvokeStatic(helper_ref), ret])
+        .collect()
+}
+
+/// The type of a local variable as far as it can be told without tracking the operand stack.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum LocalType {
+    /// The variable cannot be used, e.g., it is not assigned, it is the second slot of a `long`
+    /// or a `double`, or it holds values of different kinds on different paths.
+    Top,
+    Int,
+    Long,
+    Float,
+    Double,
+    /// A reference, with its type if it is known, e.g., if it is a parameter that has not been
+    /// overwritten.
+    Reference(Option<FieldType>),
+}
+
+impl LocalType {
+    fn from_verification_type(verification_type: &VerificationType) -> Self {
+        match verification_type {
+            VerificationType::IntegerVariable => Self::Int,
+            VerificationType::FloatVariable => Self::Float,
+            VerificationType::LongVariable => Self::Long,
+            VerificationType::DoubleVariable => Self::Double,
+            VerificationType::ObjectVariable(_) => {
+                Self::Reference(verification_type.to_field_type())
+            }
+            VerificationType::NullVariable => Self::Reference(None),
+            VerificationType::TopVariable
+            | VerificationType::UninitializedThisVariable
+            | VerificationType::UninitializedVariable { .. } => Self::Top,
+        }
+    }
+
+    /// Returns the type of the value stored by the instruction, if it is a store.
+    fn stored_by(insn: &Instruction) -> Option<Self> {
+        #[allow(clippy::enum_glob_use)]
+        use Instruction::*;
+        let stored = match insn {
+            IStore(_)
+            | IStore0
+            | IStore1
+            | IStore2
+            | IStore3
+            | Wide(WideInstruction::IStore(_)) => Self::Int,
+            LStore(_)
+            | LStore0
+            | LStore1
+            | LStore2
+            | LStore3
+            | Wide(WideInstruction::LStore(_)) => Self::Long,
+            FStore(_)
+            | FStore0
+            | FStore1
+            | FStore2
+            | FStore3
+            | Wide(WideInstruction::FStore(_)) => Self::Float,
+            DStore(_)
+            | DStore0
+            | DStore1
+            | DStore2
+            | DStore3
+            | Wide(WideInstruction::DStore(_)) => Self::Double,
+            AStore(_)
+            | AStore0
+            | AStore1
+            | AStore2
+            | AStore3
+            | Wide(WideInstruction::AStore(_)) => Self::Reference(None),
+            _ => return None,
+        };
+        Some(stored)
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        match (self, other) {
+            (lhs, rhs) if lhs == rhs => lhs.clone(),
+            (Self::Reference(_), Self::Reference(_)) => Self::Reference(None),
+            _ => Self::Top,
+        }
+    }
+}
+
+/// The types of the local variables, indexed by their slots.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct LocalTypes(Vec<LocalType>);
+
+impl Lattice for LocalTypes {
+    fn join(&self, other: &Self) -> Self {
+        let len = self.0.len().max(other.0.len());
+        let top = LocalType::Top;
+        Self(
+            (0..len)
+                .map(|index| {
+                    let lhs = self.0.get(index).unwrap_or(&top);
+                    let rhs = other.0.get(index).unwrap_or(&top);
+                    lhs.join(rhs)
+                })
+                .collect(),
+        )
+    }
+}
+
+/// A forward analysis of the types of the local variables along the control flow.
+struct LocalTypeAnalysis<'a> {
+    method: &'a Method,
+}
+
+impl DataflowAnalysis<LocalTypes> for LocalTypeAnalysis<'_> {
+    type Err = Infallible;
+
+    fn entry_state(&self) -> LocalTypes {
+        LocalTypes(
+            self.method
+                .initial_frame()
+                .iter()
+                .map(LocalType::from_verification_type)
+                .collect(),
+        )
+    }
+
+    fn transfer(
+        &mut self,
+        _pc: ProgramCounter,
+        instruction: &Instruction,
+        state: &LocalTypes,
+    ) -> Result<LocalTypes, Self::Err> {
+        let (Some(stored), Some((slot, width))) =
+            (LocalType::stored_by(instruction), instruction.local_slots())
+        else {
+            return Ok(state.clone());
+        };
+        let mut types = state.0.clone();
+        let slot = usize::from(slot);
+        let end = slot + usize::from(width);
+        if types.len() < end {
+            types.resize(end, LocalType::Top);
+        }
+        // Overwriting the second slot of a `long` or a `double` invalidates the first one.
+        if let Some(previous) = slot.checked_sub(1) {
+            if matches!(types[previous], LocalType::Long | LocalType::Double) {
+                types[previous] = LocalType::Top;
+            }
+        }
+        types[slot] = stored;
+        if width == 2 {
+            types[slot + 1] = LocalType::Top;
+        }
+        Ok(LocalTypes(types))
+    }
+}
+
+pub(crate) fn retarget(
+    insn: &mut Instruction,
+    relocate: impl Fn(ProgramCounter) -> ProgramCounter,
+) {
+    #[allow(clippy::enum_glob_use)]
+    use Instruction::*;
+    match insn {
+        IfEq(target) | IfNe(target) | IfLt(target) | IfGe(target) | IfGt(target) | IfLe(target)
+        | IfICmpEq(target) | IfICmpNe(target) | IfICmpLt(target) | IfICmpGe(target)
+        | IfICmpGt(target) | IfICmpLe(target) | IfACmpEq(target) | IfACmpNe(target)
+        | Goto(target) | Jsr(target) | IfNull(target) | IfNonNull(target) | GotoW(target)
+        | JsrW(target) => *target = relocate(*target),
+        TableSwitch {
+            jump_targets,
+            default,
+            ..
+        } => {
+            for target in jump_targets {
+                *target = relocate(*target);
+            }
+            *default = relocate(*default);
+        }
+        LookupSwitch {
+            default,
+            match_targets,
+        } => {
+            for target in match_targets.values_mut() {
+                *target = relocate(*target);
+            }
+            *default = relocate(*default);
+        }
+        _ => {}
+    }
+}
+
+pub(super) fn to_pc(offset: usize) -> ProgramCounter {
+    u16::try_from(offset)
+        .expect("The code size should be within u16")
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jvm::references::ClassRef;
+    use Instruction::*;
+
+    fn static_method(descriptor: &str, instructions: InstructionList<Instruction>) -> Method {
+        Method {
+            access_flags: method::AccessFlags::PUBLIC | method::AccessFlags::STATIC,
+            name: "test".to_owned(),
+            descriptor: descriptor.parse().unwrap(),
+            body: Some(MethodBody {
+                max_stack: 1,
+                max_locals: 2,
+                instructions,
+                exception_table: Vec::new(),
+                line_number_table: None,
+                local_variable_table: None,
+                stack_map_table: None,
+                runtime_visible_type_annotations: Vec::new(),
+                runtime_invisible_type_annotations: Vec::new(),
+                free_attributes: Vec::new(),
+                raw_attributes: Vec::new(),
+                raw_code: None,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn split_passes_live_locals() {
+        let method = static_method(
+            "(I)I",
+            InstructionList::from([
+                (0.into(), ILoad0),
+                (1.into(), IStore1),
+                (2.into(), IInc(1, 1)),
+                (5.into(), ILoad1),
+                (6.into(), IReturn),
+            ]),
+        );
+        let split = split_method(&method, "test$tail", 12).unwrap();
+        assert_eq!(split.split_pc, 5.into());
+
+        let helper_desc: MethodDescriptor = "(II)I".parse().unwrap();
+        assert_eq!(split.helper.descriptor, helper_desc);
+        let helper_body = split.helper.body.unwrap();
+        assert_eq!(helper_body.instruction_at(0.into()), Some(&ILoad1));
+        assert_eq!(helper_body.instruction_at(1.into()), Some(&IReturn));
+
+        let head_body = split.head.body.unwrap();
+        assert_eq!(head_body.instruction_at(5.into()), Some(&IConst0));
+        assert_eq!(head_body.instruction_at(6.into()), Some(&ILoad(1)));
+        assert_eq!(
+            head_body.instruction_at(8.into()),
+            Some(&InvokeStatic(MethodRef {
+                owner: ClassRef::new("org/mokapot/Test"),
+                name: "test$tail".to_owned(),
+                descriptor: helper_desc,
+            }))
+        );
+        assert_eq!(head_body.instruction_at(11.into()), Some(&IReturn));
+        assert_eq!(head_body.code_size(), Ok(12));
+    }
+
+    #[test]
+    fn jump_to_split_point() {
+        let method = static_method(
+            "(I)V",
+            InstructionList::from([
+                (0.into(), ILoad0),
+                (1.into(), IfEq(5.into())),
+                (4.into(), Nop),
+                (5.into(), Return),
+            ]),
+        );
+        // Splitting at #0004 would separate the jump from its target.
+        let split = split_method(&method, "test$tail", 100).unwrap();
+        assert_eq!(split.split_pc, 5.into());
+        let head_body = split.head.body.unwrap();
+        assert_eq!(head_body.instruction_at(1.into()), Some(&IfEq(5.into())));
+        assert!(matches!(
+            head_body.instruction_at(5.into()),
+            Some(InvokeStatic(_))
+        ));
+    }
+
+    #[test]
+    fn no_split_across_jumps() {
+        let method = static_method(
+            "()V",
+            InstructionList::from([(0.into(), Nop), (1.into(), Nop), (2.into(), Goto(0.into()))]),
+        );
+        assert!(matches!(
+            split_method(&method, "test$tail", 100),
+            Err(SplitError::NoSplitPoint)
+        ));
+    }
+
+    #[test]
+    fn reference_parameter_keeps_its_type() {
+        let method = static_method(
+            "(Ljava/lang/String;)Ljava/lang/String;",
+            InstructionList::from([
+                (0.into(), Nop),
+                (1.into(), Nop),
+                (2.into(), ALoad0),
+                (3.into(), AReturn),
+            ]),
+        );
+        let split = split_method(&method, "test$tail", 100).unwrap();
+        assert_eq!(split.split_pc, 2.into());
+        let helper_desc: MethodDescriptor =
+            "(Ljava/lang/String;)Ljava/lang/String;".parse().unwrap();
+        assert_eq!(split.helper.descriptor, helper_desc);
+    }
+
+    #[test]
+    fn reference_type_from_stack_map_frame() {
+        let mut method = static_method(
+            "()Ljava/lang/Object;",
+            InstructionList::from([
+                (0.into(), AConstNull),
+                (1.into(), AStore0),
+                (2.into(), Nop),
+                (3.into(), ALoad0),
+                (4.into(), AReturn),
+            ]),
+        );
+        let body = method.body.as_mut().unwrap();
+        body.stack_map_table = Some(vec![StackMapFrame::FullFrame {
+            offset_delta: 3,
+            locals: vec![VerificationType::ObjectVariable(ClassRef::new(
+                "java/lang/String",
+            ))],
+            stack: Vec::new(),
+        }]);
+        let split = split_method(&method, "test$tail", 100).unwrap();
+        assert_eq!(split.split_pc, 3.into());
+        let helper_desc: MethodDescriptor =
+            "(Ljava/lang/String;)Ljava/lang/Object;".parse().unwrap();
+        assert_eq!(split.helper.descriptor, helper_desc);
+    }
+
+    #[test]
+    fn unknown_reference_type() {
+        let method = static_method(
+            "()Ljava/lang/Object;",
+            InstructionList::from([
+                (0.into(), AConstNull),
+                (1.into(), AStore0),
+                (2.into(), Nop),
+                (3.into(), ALoad0),
+                (4.into(), AReturn),
+            ]),
+        );
+        assert!(matches!(
+            split_method(&method, "test$tail", 100),
+            Err(SplitError::UnknownLocalType { slot: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn reused_slot_takes_the_type_at_split_point() {
+        let method = static_method(
+            "()I",
+            InstructionList::from([
+                (0.into(), FConst0),
+                (1.into(), FStore1),
+                (2.into(), FLoad1),
+                (3.into(), F2I),
+                (4.into(), IStore1),
+                (5.into(), Nop),
+                (6.into(), ILoad1),
+                (7.into(), IReturn),
+            ]),
+        );
+        let split = split_method(&method, "test$tail", 100).unwrap();
+        assert_eq!(split.split_pc, 6.into());
+        let helper_desc: MethodDescriptor = "(II)I".parse().unwrap();
+        assert_eq!(split.helper.descriptor, helper_desc);
+    }
+}
@@ -0,0 +1,384 @@
+use crate::jvm::Class;
+
+/// The attributes removed by [`strip_debug`].
+const DEBUG_ATTRIBUTES: [&str; 5] = [
+    "SourceFile",
+    "SourceDebugExtension",
+    "LineNumberTable",
+    "LocalVariableTable",
+    "LocalVariableTypeTable",
+];
+
+/// Removes the debug information from `class`, i.e., the `SourceFile`, `SourceDebugExtension`,
+/// `LineNumberTable`, `LocalVariableTable`, and `LocalVariableTypeTable` attributes.
+/// The attributes are also removed from the retained
+/// [raw attributes](crate::jvm::parsing::ParseOptions::retain_raw_attributes), including those
+/// nested in the raw `Code` attributes of the methods.
+pub fn strip_debug(class: &mut Class) {
+    let is_stripped = |name: &str| DEBUG_ATTRIBUTES.contains(&name);
+    class.source_file = None;
+    class.source_debug_extension = None;
+    class.raw_attributes.retain(|(name, _)| !is_stripped(name));
+    for method in &mut class.methods {
+        let Some(body) = method.body.as_mut() else {
+            continue;
+        };
+        body.line_number_table = None;
+        body.local_variable_table = None;
+        strip_raw_code(
+            &mut method.raw_attributes,
+            &body.raw_attributes,
+            is_stripped,
+        );
+        body.raw_attributes.retain(|(name, _)| !is_stripped(name));
+    }
+}
+
+/// Removes the attributes that `is_stripped` from the raw `Code` attribute in `raw_attributes`,
+/// given the names and the bytes of the attributes nested in it, i.e., the
+/// [raw attributes](crate::jvm::code::MethodBody::raw_attributes) of the method body.
+/// The `Code` attribute is dropped if the nested attributes do not match it, e.g., because the
+/// body was modified, since it cannot be rebuilt without the constant pool.
+fn strip_raw_code(
+    raw_attributes: &mut Vec<(String, Vec<u8>)>,
+    nested: &[(String, Vec<u8>)],
+    is_stripped: impl Fn(&str) -> bool,
+) {
+    raw_attributes.retain_mut(|(name, info)| {
+        if name != "Code" {
+            return true;
+        }
+        if let Some(stripped) = without_nested(info, nested, &is_stripped) {
+            *info = stripped;
+            true
+        } else {
+            false
+        }
+    });
+}
+
+/// Returns the bytes of a `Code` attribute without the nested attributes that `is_stripped`, or
+/// `None` if `nested` does not list the attributes nested in `code` in order.
+fn without_nested(
+    code: &[u8],
+    nested: &[(String, Vec<u8>)],
+    is_stripped: impl Fn(&str) -> bool,
+) -> Option<Vec<u8>> {
+    let read_u16 = |at: usize| Some(u16::from_be_bytes(code.get(at..at + 2)?.try_into().ok()?));
+    let read_u32 = |at: usize| Some(u32::from_be_bytes(code.get(at..at + 4)?.try_into().ok()?));
+    // max_stack (2), max_locals (2), code_length (4), code, exception_table_length (2), and
+    // exception_table (8 each) precede the attributes.
+    let code_length = usize::try_from(read_u32(4)?).ok()?;
+    let exception_table_length = usize::from(read_u16(8 + code_length)?);
+    let count_offset = 10 + code_length + 8 * exception_table_length;
+    if usize::from(read_u16(count_offset)?) != nested.len() {
+        return None;
+    }
+    let mut kept_count: u16 = 0;
+    let mut kept = Vec::new();
+    let mut offset = count_offset + 2;
+    for (name, info) in nested {
+        // Each attribute starts with attribute_name_index (2) and attribute_length (4).
+        let length = usize::try_from(read_u32(offset + 2)?).ok()?;
+        let attribute = code.get(offset..offset + 6 + length)?;
+        if attribute[6..] != info[..] {
+            return None;
+        }
+        if !is_stripped(name) {
+            kept.extend_from_slice(attribute);
+            kept_count += 1;
+        }
+        offset += attribute.len();
+    }
+    if offset != code.len() {
+        return None;
+    }
+    let mut stripped = code[..count_offset].to_vec();
+    stripped.extend_from_slice(&kept_count.to_be_bytes());
+    stripped.extend(kept);
+    Some(stripped)
+}
+
+/// The attributes holding the annotations retained at runtime.
+const VISIBLE_ANNOTATION_ATTRIBUTES: [&str; 3] = [
+    "RuntimeVisibleAnnotations",
+    "RuntimeVisibleTypeAnnotations",
+    "RuntimeVisibleParameterAnnotations",
+];
+
+/// The attributes holding the annotations not retained at runtime.
+const INVISIBLE_ANNOTATION_ATTRIBUTES: [&str; 3] = [
+    "RuntimeInvisibleAnnotations",
+    "RuntimeInvisibleTypeAnnotations",
+    "RuntimeInvisibleParameterAnnotations",
+];
+
+/// Removes the annotations, type annotations, and parameter annotations from `class`, its fields,
+/// its methods, the method bodies, and the record components.
+/// If `keep_runtime` is `true`, the `RuntimeVisible*` annotations are kept and only the
+/// `RuntimeInvisible*` ones, i.e., those only used at compile time, are removed.
+/// The default values of annotation interface elements (`AnnotationDefault`) are not annotations
+/// and are kept.
+/// The annotations are also removed from the retained
+/// [raw attributes](crate::jvm::parsing::ParseOptions::retain_raw_attributes), including the type
+/// annotations nested in the raw `Code` attributes of the methods.
+pub fn strip_annotations(class: &mut Class, keep_runtime: bool) {
+    let is_stripped = |name: &str| {
+        INVISIBLE_ANNOTATION_ATTRIBUTES.contains(&name)
+            || (!keep_runtime && VISIBLE_ANNOTATION_ATTRIBUTES.contains(&name))
+    };
+    let strip_raw = |raw_attributes: &mut Vec<(String, Vec<u8>)>| {
+        raw_attributes.retain(|(name, _)| !is_stripped(name));
+    };
+
+    class.runtime_invisible_annotations.clear();
+    class.runtime_invisible_type_annotations.clear();
+    if !keep_runtime {
+        class.runtime_visible_annotations.clear();
+        class.runtime_visible_type_annotations.clear();
+    }
+    strip_raw(&mut class.raw_attributes);
+    for field in &mut class.fields {
+        field.runtime_invisible_annotations.clear();
+        field.runtime_invisible_type_annotations.clear();
+        if !keep_runtime {
+            field.runtime_visible_annotations.clear();
+            field.runtime_visible_type_annotations.clear();
+        }
+        strip_raw(&mut field.raw_attributes);
+    }
+    for method in &mut class.methods {
+        method.runtime_invisible_annotations.clear();
+        method.runtime_invisible_type_annotations.clear();
+        method.runtime_invisible_parameter_annotations.clear();
+        if !keep_runtime {
+            method.runtime_visible_annotations.clear();
+            method.runtime_visible_type_annotations.clear();
+            method.runtime_visible_parameter_annotations.clear();
+        }
+        strip_raw(&mut method.raw_attributes);
+        if let Some(body) = method.body.as_mut() {
+            strip_raw_code(
+                &mut method.raw_attributes,
+                &body.raw_attributes,
+                is_stripped,
+            );
+            body.runtime_invisible_type_annotations.clear();
+            if !keep_runtime {
+                body.runtime_visible_type_annotations.clear();
+            }
+            strip_raw(&mut body.raw_attributes);
+        }
+    }
+    for component in class.record.iter_mut().flatten() {
+        component.runtime_invisible_annotations.clear();
+        component.runtime_invisible_type_annotations.clear();
+        if !keep_runtime {
+            component.runtime_visible_annotations.clear();
+            component.runtime_visible_type_annotations.clear();
+        }
+        strip_raw(&mut component.raw_attributes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jvm::{
+        code::{LineNumberTableEntry, LocalVariableTable, MethodBody},
+        method, Annotation, Method,
+    };
+
+    fn method(body: Option<MethodBody>) -> Method {
+        Method {
+            access_flags: method::AccessFlags::STATIC,
+            name: "main".to_owned(),
+            descriptor: "()V".parse().unwrap(),
+            body,
+            ..Default::default()
+        }
+    }
+
+    fn annotation(annotation_type: &str) -> Annotation {
+        Annotation {
+            annotation_type: annotation_type.parse().unwrap(),
+            element_value_pairs: vec![],
+        }
+    }
+
+    /// Builds the bytes of a `Code` attribute containing a `return` instruction and the given
+    /// nested attributes, whose name indices are their positions.
+    fn raw_code(nested: &[(String, Vec<u8>)]) -> Vec<u8> {
+        let mut code = vec![0, 0, 0, 0, 0, 0, 0, 1, 0xb1, 0, 0];
+        code.extend_from_slice(&u16::try_from(nested.len()).unwrap().to_be_bytes());
+        for (index, (_, info)) in nested.iter().enumerate() {
+            code.extend_from_slice(&u16::try_from(index).unwrap().to_be_bytes());
+            code.extend_from_slice(&u32::try_from(info.len()).unwrap().to_be_bytes());
+            code.extend_from_slice(info);
+        }
+        code
+    }
+
+    #[test]
+    fn strip_debug_info() {
+        let body = MethodBody {
+            line_number_table: Some(vec![LineNumberTableEntry {
+                start_pc: 0.into(),
+                line_number: 1,
+            }]),
+            local_variable_table: Some(LocalVariableTable::default()),
+            raw_attributes: vec![
+                ("LineNumberTable".to_owned(), vec![0, 0]),
+                ("StackMapTable".to_owned(), vec![0, 0]),
+            ],
+            raw_code: Some(Box::new([0xb1])),
+            ..Default::default()
+        };
+        let mut method = method(Some(body));
+        method.raw_attributes = vec![(
+            "Code".to_owned(),
+            raw_code(&method.body.as_ref().unwrap().raw_attributes),
+        )];
+        let mut class = Class {
+            binary_name: "org/mokapot/Test".to_owned(),
+            methods: vec![method],
+            source_file: Some("Test.java".to_owned()),
+            source_debug_extension: Some(b"SMAP".to_vec()),
+            raw_attributes: vec![
+                ("SourceFile".to_owned(), vec![0, 1]),
+                ("InnerClasses".to_owned(), vec![0, 0]),
+            ],
+            ..Default::default()
+        };
+        strip_debug(&mut class);
+
+        assert_eq!(None, class.source_file);
+        assert_eq!(None, class.source_debug_extension);
+        assert_eq!(
+            vec![("InnerClasses".to_owned(), vec![0, 0])],
+            class.raw_attributes
+        );
+        let body = class.methods[0].body.as_ref().unwrap();
+        assert!(body.line_number_table.is_none());
+        assert!(body.local_variable_table.is_none());
+        // The instructions are unchanged, so their bytes still match.
+        assert_eq!(Some(&[0xb1][..]), body.raw_code.as_deref());
+        assert_eq!(
+            vec![("StackMapTable".to_owned(), vec![0, 0])],
+            body.raw_attributes
+        );
+        let mut expected_code = raw_code(&body.raw_attributes);
+        // The name index of the `StackMapTable` attribute is kept.
+        expected_code[13..15].copy_from_slice(&[0, 1]);
+        assert_eq!(
+            vec![("Code".to_owned(), expected_code)],
+            class.methods[0].raw_attributes
+        );
+    }
+
+    #[test]
+    fn drop_mismatched_raw_code() {
+        let body = MethodBody {
+            raw_attributes: vec![("LineNumberTable".to_owned(), vec![0, 0])],
+            ..Default::default()
+        };
+        let mut method = method(Some(body));
+        method.raw_attributes = vec![
+            ("Code".to_owned(), raw_code(&[])),
+            ("Exceptions".to_owned(), vec![0, 0]),
+        ];
+        let mut class = Class {
+            methods: vec![method],
+            ..Default::default()
+        };
+        strip_debug(&mut class);
+        assert_eq!(
+            vec![("Exceptions".to_owned(), vec![0, 0])],
+            class.methods[0].raw_attributes
+        );
+    }
+
+    fn annotated_class() -> Class {
+        let mut method = method(None);
+        method.runtime_visible_annotations = vec![annotation("Ljava/lang/Deprecated;")];
+        method.runtime_invisible_annotations = vec![annotation("Ljavax/annotation/Nonnull;")];
+        method.runtime_visible_parameter_annotations = vec![vec![annotation("Lorg/mokapot/A;")]];
+        method.runtime_invisible_parameter_annotations = vec![vec![annotation("Lorg/mokapot/B;")]];
+        method.raw_attributes = vec![
+            ("RuntimeVisibleAnnotations".to_owned(), vec![0, 0]),
+            ("RuntimeInvisibleAnnotations".to_owned(), vec![0, 0]),
+        ];
+        Class {
+            binary_name: "org/mokapot/Test".to_owned(),
+            methods: vec![method],
+            runtime_visible_annotations: vec![annotation("Lorg/mokapot/A;")],
+            runtime_invisible_annotations: vec![annotation("Lorg/mokapot/B;")],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn strip_all_annotations() {
+        let mut class = annotated_class();
+        strip_annotations(&mut class, false);
+        assert!(class.runtime_visible_annotations.is_empty());
+        assert!(class.runtime_invisible_annotations.is_empty());
+        let method = &class.methods[0];
+        assert!(method.runtime_visible_annotations.is_empty());
+        assert!(method.runtime_invisible_annotations.is_empty());
+        assert!(method.runtime_visible_parameter_annotations.is_empty());
+        assert!(method.runtime_invisible_parameter_annotations.is_empty());
+        assert!(method.raw_attributes.is_empty());
+    }
+
+    #[test]
+    fn keep_runtime_annotations() {
+        let mut class = annotated_class();
+        strip_annotations(&mut class, true);
+        assert_eq!(1, class.runtime_visible_annotations.len());
+        assert!(class.runtime_invisible_annotations.is_empty());
+        let method = &class.methods[0];
+        assert_eq!(1, method.runtime_visible_annotations.len());
+        assert!(method.runtime_invisible_annotations.is_empty());
+        assert_eq!(1, method.runtime_visible_parameter_annotations.len());
+        assert!(method.runtime_invisible_parameter_annotations.is_empty());
+        assert_eq!(
+            vec![("RuntimeVisibleAnnotations".to_owned(), vec![0, 0])],
+            method.raw_attributes
+        );
+    }
+
+    #[test]
+    fn strip_code_type_annotations() {
+        let body = MethodBody {
+            raw_attributes: vec![
+                ("RuntimeVisibleTypeAnnotations".to_owned(), vec![0, 0]),
+                ("RuntimeInvisibleTypeAnnotations".to_owned(), vec![0, 0]),
+            ],
+            ..Default::default()
+        };
+        let mut method = method(Some(body));
+        method.raw_attributes = vec![(
+            "Code".to_owned(),
+            raw_code(&method.body.as_ref().unwrap().raw_attributes),
+        )];
+        let mut class = Class {
+            methods: vec![method],
+            ..Default::default()
+        };
+        let mut kept = class.clone();
+
+        strip_annotations(&mut kept, true);
+        let expected_code = raw_code(&[("RuntimeVisibleTypeAnnotations".to_owned(), vec![0, 0])]);
+        assert_eq!(
+            vec![("Code".to_owned(), expected_code)],
+            kept.methods[0].raw_attributes
+        );
+
+        strip_annotations(&mut class, false);
+        assert_eq!(
+            vec![("Code".to_owned(), raw_code(&[]))],
+            class.methods[0].raw_attributes
+        );
+    }
+}
@@ -50,6 +50,22 @@ impl PrimitiveType {
             Self::Long => 'J',
         }
     }
+
+    /// Returns the class boxing values of this type, e.g., `java/lang/Integer` for `int`.
+    #[must_use]
+    pub fn wrapper_class(self) -> ClassRef {
+        let binary_name = match self {
+            Self::Boolean => "java/lang/Boolean",
+            Self::Char => "java/lang/Character",
+            Self::Float => "java/lang/Float",
+            Self::Double => "java/lang/Double",
+            Self::Byte => "java/lang/Byte",
+            Self::Short => "java/lang/Short",
+            Self::Int => "java/lang/Integer",
+            Self::Long => "java/lang/Long",
+        };
+        ClassRef::new(binary_name)
+    }
 }
 
 impl TryFrom<char> for PrimitiveType {
@@ -65,7 +81,11 @@ impl TryFrom<char> for PrimitiveType {
             'S' => Ok(Self::Short),
             'I' => Ok(Self::Int),
             'J' => Ok(Self::Long),
-            _ => Err(InvalidDescriptor),
+            _ => Err(InvalidDescriptor::new(
+                descriptor.to_string(),
+                0,
+                "a primitive type",
+            )),
         }
     }
 }
@@ -76,8 +96,14 @@ impl FromStr for PrimitiveType {
     fn from_str(descriptor: &str) -> Result<Self, Self::Err> {
         let mut chars = descriptor.chars();
         match (chars.next(), chars.next()) {
-            (Some(c), None) => Self::try_from(c),
-            _ => Err(InvalidDescriptor),
+            (Some(c), None) => Self::try_from(c)
+                .map_err(|_| InvalidDescriptor::new(descriptor, 0, "a primitive type")),
+            (Some(c), Some(_)) => Err(InvalidDescriptor::new(
+                descriptor,
+                c.len_utf8(),
+                "the end of the descriptor",
+            )),
+            (None, _) => Err(InvalidDescriptor::new(descriptor, 0, "a primitive type")),
         }
     }
 }
@@ -95,12 +121,20 @@ pub enum FieldType {
 }
 
 impl FieldType {
+    /// Returns the number of local variable or operand stack slots a value of this type takes.
+    pub(crate) const fn slot_width(&self) -> u16 {
+        match self {
+            Self::Base(PrimitiveType::Long | PrimitiveType::Double) => 2,
+            _ => 1,
+        }
+    }
+
     /// Returns the qualified name of this type.
     #[must_use]
     pub fn qualified_name(&self) -> String {
         match self {
             Self::Base(pt) => pt.to_string(),
-            Self::Object(ClassRef { binary_name }) => binary_name.replace('/', "."),
+            Self::Object(class) => class.dotted_name(),
             Self::Array(inner) => format!("{}[]", inner.qualified_name()),
         }
     }
@@ -110,22 +144,72 @@ impl FromStr for FieldType {
     type Err = InvalidDescriptor;
 
     fn from_str(descriptor: &str) -> Result<Self, Self::Err> {
-        if descriptor.chars().count() == 1 {
-            PrimitiveType::from_str(descriptor).map(Into::into)
-        } else if descriptor.starts_with('[') {
-            let element_type_desc = &descriptor['['.len_utf8()..];
-            Self::from_str(element_type_desc).map(FieldType::into_array_type)
-        } else if descriptor.starts_with('L') && descriptor.ends_with(';') {
-            let binary_name = &descriptor['L'.len_utf8()..(descriptor.len() - ';'.len_utf8())];
-            if binary_name.is_empty() || binary_name.contains(';') {
-                Err(InvalidDescriptor)
-            } else {
-                let class_ref = ClassRef::new(binary_name);
-                Ok(Self::Object(class_ref))
-            }
+        let (field_type, end) = Self::parse_at(descriptor, 0)?;
+        if end == descriptor.len() {
+            Ok(field_type)
         } else {
-            Err(InvalidDescriptor)
+            Err(InvalidDescriptor::new(
+                descriptor,
+                end,
+                "the end of the descriptor",
+            ))
+        }
+    }
+}
+
+impl FieldType {
+    /// Parses a field type starting at byte offset `start` of `descriptor`.
+    /// Returns the parsed type and the offset right after it.
+    /// Class names containing `(` or `)` are rejected.
+    pub(crate) fn parse_at(
+        descriptor: &str,
+        start: usize,
+    ) -> Result<(Self, usize), InvalidDescriptor> {
+        let mut pos = start;
+        let mut dimension = 0usize;
+        while descriptor[pos..].starts_with('[') {
+            dimension += 1;
+            pos += '['.len_utf8();
         }
+        let (element_type, end) = match descriptor[pos..].chars().next() {
+            Some('L') => {
+                let name_start = pos + 'L'.len_utf8();
+                // The scan stops at a parenthesis so that a missing `;` in a method descriptor is
+                // reported where it is expected rather than at the end of the descriptor.
+                let name_len = match descriptor[name_start..].find([';', '(', ')']) {
+                    Some(len) if descriptor[name_start + len..].starts_with(';') => len,
+                    end => {
+                        let end = end.map_or(descriptor.len(), |len| name_start + len);
+                        return Err(InvalidDescriptor::new(
+                            descriptor,
+                            end,
+                            "`;` after the class name",
+                        ));
+                    }
+                };
+                if name_len == 0 {
+                    return Err(InvalidDescriptor::new(
+                        descriptor,
+                        name_start,
+                        "a class name",
+                    ));
+                }
+                let binary_name = &descriptor[name_start..name_start + name_len];
+                let class_ref = ClassRef::new(binary_name);
+                (
+                    Self::Object(class_ref),
+                    name_start + name_len + ';'.len_utf8(),
+                )
+            }
+            Some(c) => {
+                let primitive_type = PrimitiveType::try_from(c)
+                    .map_err(|_| InvalidDescriptor::new(descriptor, pos, "a field type"))?;
+                (Self::Base(primitive_type), pos + c.len_utf8())
+            }
+            None => return Err(InvalidDescriptor::new(descriptor, pos, "a field type")),
+        };
+        let field_type = (0..dimension).fold(element_type, |acc, _| acc.into_array_type());
+        Ok((field_type, end))
     }
 }
 
@@ -200,6 +284,19 @@ mod tests {
         assert_eq!(Long.to_string(), "long");
     }
 
+    #[test]
+    fn primitive_type_wrapper_class() {
+        use PrimitiveType::*;
+        assert_eq!(Boolean.wrapper_class(), ClassRef::new("java/lang/Boolean"));
+        assert_eq!(Char.wrapper_class(), ClassRef::new("java/lang/Character"));
+        assert_eq!(Float.wrapper_class(), ClassRef::new("java/lang/Float"));
+        assert_eq!(Double.wrapper_class(), ClassRef::new("java/lang/Double"));
+        assert_eq!(Byte.wrapper_class(), ClassRef::new("java/lang/Byte"));
+        assert_eq!(Short.wrapper_class(), ClassRef::new("java/lang/Short"));
+        assert_eq!(Int.wrapper_class(), ClassRef::new("java/lang/Integer"));
+        assert_eq!(Long.wrapper_class(), ClassRef::new("java/lang/Long"));
+    }
+
     #[test]
     fn primitive_type_from_char() {
         use PrimitiveType::*;
@@ -347,4 +444,22 @@ mod tests {
     fn invalid_array_element() {
         assert!(FieldType::from_str("[A").is_err());
     }
+
+    #[test]
+    fn error_details() {
+        let err = FieldType::from_str("[[Ljava/lang/String").unwrap_err();
+        assert_eq!(err.offset, 19);
+        assert_eq!(err.expected, "`;` after the class name");
+
+        let err = FieldType::from_str("[[X").unwrap_err();
+        assert_eq!(err.offset, 2);
+        assert_eq!(err.expected, "a field type");
+
+        let err = FieldType::from_str("L;").unwrap_err();
+        assert_eq!(err.offset, 1);
+        assert_eq!(err.expected, "a class name");
+
+        let err = FieldType::from_str("IJ").unwrap_err();
+        assert_eq!(err.offset, 1);
+    }
 }
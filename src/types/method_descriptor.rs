@@ -3,17 +3,20 @@
 use itertools::Itertools;
 use std::str::FromStr;
 
-use crate::{jvm::references::ClassRef, macros::see_jvm_spec};
+use crate::macros::see_jvm_spec;
 
-use super::field_type::{FieldType, PrimitiveType};
+use super::field_type::FieldType;
 
 /// The descriptor of a method.
 /// Consists of the parameters types and the return type.
+/// It is displayed as the descriptor string, e.g., `(ILjava/lang/String;)V`, which can be parsed
+/// back with [`FromStr`].
 #[doc = see_jvm_spec!(4, 3, 3)]
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, derive_more::Display)]
 #[display(
-    "({}){return_type}",
-    parameters_types.iter().map(FieldType::descriptor).join("")
+    "({}){}",
+    parameters_types.iter().map(FieldType::descriptor).join(""),
+    return_type.descriptor()
 )]
 pub struct MethodDescriptor {
     /// The type of the parameters.
@@ -39,39 +42,27 @@ impl FromStr for MethodDescriptor {
 
     fn from_str(descriptor: &str) -> Result<Self, Self::Err> {
         if !descriptor.starts_with('(') {
-            return Err(InvalidDescriptor);
+            return Err(InvalidDescriptor::new(descriptor, 0, "`(`"));
         }
-        let mut remaining = &descriptor['('.len_utf8()..];
+        let mut pos = '('.len_utf8();
         let mut parameters_types = Vec::new();
-        loop {
-            if remaining.starts_with(')') {
-                remaining = &remaining[')'.len_utf8()..];
-                break;
+        while !descriptor[pos..].starts_with(')') {
+            if pos == descriptor.len() {
+                return Err(InvalidDescriptor::new(descriptor, pos, "`)`"));
             }
-            let mut array_dim = 0u8;
-            while remaining.starts_with('[') {
-                array_dim += 1;
-                remaining = &remaining['['.len_utf8()..];
-            }
-            let mut next_param = match remaining.chars().next() {
-                Some(ch @ ('Z' | 'C' | 'F' | 'D' | 'B' | 'S' | 'I' | 'J')) => {
-                    remaining = &remaining[ch.len_utf8()..];
-                    PrimitiveType::try_from(ch).map(Into::into)?
-                }
-                Some('L') => {
-                    let semicolon_loc = remaining.find(';').ok_or(InvalidDescriptor)?;
-                    let binary_name = &remaining['L'.len_utf8()..semicolon_loc];
-                    remaining = &remaining[semicolon_loc + ';'.len_utf8()..];
-                    FieldType::Object(ClassRef::new(binary_name))
-                }
-                _ => Err(InvalidDescriptor)?,
-            };
-            for _ in 0..array_dim {
-                next_param = next_param.into_array_type();
-            }
-            parameters_types.push(next_param);
+            let (param, end) = FieldType::parse_at(descriptor, pos)?;
+            parameters_types.push(param);
+            pos = end;
+        }
+        pos += ')'.len_utf8();
+        let (return_type, end) = ReturnType::parse_at(descriptor, pos)?;
+        if end != descriptor.len() {
+            return Err(InvalidDescriptor::new(
+                descriptor,
+                end,
+                "the end of the descriptor",
+            ));
         }
-        let return_type = ReturnType::from_str(remaining)?;
         Ok(Self {
             parameters_types,
             return_type,
@@ -79,23 +70,93 @@ impl FromStr for MethodDescriptor {
     }
 }
 
+impl MethodDescriptor {
+    /// Creates a descriptor for a method with the given parameter types and return type.
+    #[must_use]
+    pub fn new_from_types(parameters_types: Vec<FieldType>, return_type: ReturnType) -> Self {
+        Self {
+            parameters_types,
+            return_type,
+        }
+    }
+
+    /// Returns the method type with qualified names, in the form of `(int,java.lang.String)void`.
+    pub(crate) fn qualified_name(&self) -> String {
+        format!(
+            "({}){}",
+            self.parameters_types
+                .iter()
+                .map(FieldType::qualified_name)
+                .join(","),
+            self.return_type.qualified_name()
+        )
+    }
+}
+
 /// An error indicating that the descriptor string is invalid.
-#[derive(Debug, PartialEq, Eq, thiserror::Error)]
-#[error("Invalid descriptor")]
-pub struct InvalidDescriptor;
+#[derive(Debug, PartialEq, Eq, Clone, thiserror::Error)]
+#[error("Invalid descriptor `{descriptor}`: expected {expected} at offset {offset}")]
+pub struct InvalidDescriptor {
+    /// The descriptor being parsed.
+    pub descriptor: String,
+    /// The byte offset in the descriptor where parsing failed.
+    pub offset: usize,
+    /// A description of what was expected at [`offset`](InvalidDescriptor::offset).
+    pub expected: &'static str,
+}
+
+impl InvalidDescriptor {
+    pub(crate) fn new(
+        descriptor: impl Into<String>,
+        offset: usize,
+        expected: &'static str,
+    ) -> Self {
+        Self {
+            descriptor: descriptor.into(),
+            offset,
+            expected,
+        }
+    }
+}
 
 impl FromStr for ReturnType {
     type Err = InvalidDescriptor;
     fn from_str(descriptor: &str) -> Result<Self, Self::Err> {
-        if descriptor == "V" {
-            Ok(ReturnType::Void)
+        let (return_type, end) = Self::parse_at(descriptor, 0)?;
+        if end == descriptor.len() {
+            Ok(return_type)
         } else {
-            FieldType::from_str(descriptor).map(ReturnType::Some)
+            Err(InvalidDescriptor::new(
+                descriptor,
+                end,
+                "the end of the descriptor",
+            ))
         }
     }
 }
 
 impl ReturnType {
+    /// Returns the qualified name of this type.
+    pub(crate) fn qualified_name(&self) -> String {
+        match self {
+            Self::Some(field_type) => field_type.qualified_name(),
+            Self::Void => "void".to_owned(),
+        }
+    }
+
+    /// Parses a return type starting at byte offset `start` of `descriptor`.
+    /// Returns the parsed type and the offset right after it.
+    pub(crate) fn parse_at(
+        descriptor: &str,
+        start: usize,
+    ) -> Result<(Self, usize), InvalidDescriptor> {
+        if descriptor[start..].starts_with('V') {
+            Ok((ReturnType::Void, start + 'V'.len_utf8()))
+        } else {
+            FieldType::parse_at(descriptor, start).map(|(it, end)| (ReturnType::Some(it), end))
+        }
+    }
+
     /// Returns the descriptor for return type.
     #[must_use]
     pub fn descriptor(&self) -> String {
@@ -110,7 +171,11 @@ mod test {
     use super::*;
     use proptest::prelude::*;
 
-    use crate::{tests::arb_field_type, types::field_type::FieldType};
+    use crate::{
+        jvm::references::ClassRef,
+        tests::arb_field_type,
+        types::field_type::{FieldType, PrimitiveType},
+    };
 
     const MAX_PARAMS: usize = 10;
 
@@ -150,6 +215,29 @@ mod test {
             );
             assert!(MethodDescriptor::from_str(&descriptor).is_err());
         }
+
+        #[test]
+        fn new_from_types_round_trip(
+            params in prop::collection::vec(arb_field_type(), 0..MAX_PARAMS),
+            ret in arb_return_type(),
+        ) {
+            let descriptor = MethodDescriptor::new_from_types(params, ret);
+            let parsed = MethodDescriptor::from_str(&descriptor.to_string())
+                .expect("Failed to parse method descriptor");
+            assert_eq!(descriptor, parsed);
+        }
+    }
+
+    #[test]
+    fn display() {
+        let descriptor = MethodDescriptor::new_from_types(
+            vec![
+                FieldType::Base(PrimitiveType::Int),
+                FieldType::Object(ClassRef::new("java/lang/String")).into_array_type(),
+            ],
+            ReturnType::Void,
+        );
+        assert_eq!("(I[Ljava/lang/String;)V", descriptor.to_string());
     }
 
     #[test]
@@ -186,4 +274,23 @@ mod test {
         let method_descriptor = MethodDescriptor::from_str(descriptor);
         assert!(method_descriptor.is_err());
     }
+
+    #[test]
+    fn error_offset() {
+        let err = MethodDescriptor::from_str("(ILjava/lang/String)V").unwrap_err();
+        assert_eq!(err.offset, 19);
+        assert_eq!(err.expected, "`;` after the class name");
+
+        let err = MethodDescriptor::from_str("(I[[Q)V").unwrap_err();
+        assert_eq!(err.offset, 4);
+        assert_eq!(err.expected, "a field type");
+
+        let err = MethodDescriptor::from_str("(I)VI").unwrap_err();
+        assert_eq!(err.offset, 4);
+        assert_eq!(err.expected, "the end of the descriptor");
+
+        let err = MethodDescriptor::from_str("(I").unwrap_err();
+        assert_eq!(err.offset, 2);
+        assert_eq!(err.expected, "`)`");
+    }
 }
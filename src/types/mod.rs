@@ -1,3 +1,4 @@
 //! Module containing the APIs for the JVM type system.
 pub mod field_type;
 pub mod method_descriptor;
+pub mod names;
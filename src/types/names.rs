@@ -0,0 +1,98 @@
+//! Conversions and validations of class names.
+//!
+//! The class file format refers to classes by their internal names (e.g., `java/lang/String`),
+//! while Java source code and reflection use the dotted binary names (e.g., `java.lang.String`).
+//! Array classes are named by their descriptors in both forms (e.g., `[Ljava/lang/String;` and
+//! `[Ljava.lang.String;`), which matches the behavior of `java.lang.Class::getName`.
+
+use crate::macros::see_jvm_spec;
+
+use super::field_type::FieldType;
+
+/// Converts an internal name (e.g., `java/lang/String`) to a binary name (e.g., `java.lang.String`).
+#[doc = see_jvm_spec!(4, 2, 1)]
+#[must_use]
+pub fn internal_to_binary(internal_name: &str) -> String {
+    internal_name.replace('/', ".")
+}
+
+/// Converts a binary name (e.g., `java.lang.String`) to an internal name (e.g., `java/lang/String`).
+#[doc = see_jvm_spec!(4, 2, 1)]
+#[must_use]
+pub fn binary_to_internal(binary_name: &str) -> String {
+    binary_name.replace('.', "/")
+}
+
+/// Checks if the given string is a legal unqualified name, i.e., a non-empty name without any of
+/// the characters `.`, `;`, `[`, and `/`.
+/// Unlike Java identifiers, such names may start with a digit or contain characters such as `-`,
+/// e.g., `module-info` and the names produced by obfuscators.
+#[doc = see_jvm_spec!(4, 2, 2)]
+#[must_use]
+pub fn is_unqualified_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(['.', ';', '[', '/'])
+}
+
+/// Checks if the given string is a legal internal name of a class, i.e., a `/`-separated
+/// sequence of unqualified names, or an array descriptor whose element type is such a class or a
+/// primitive type.
+#[doc = see_jvm_spec!(4, 2, 1)]
+#[must_use]
+pub fn is_valid_internal_name(internal_name: &str) -> bool {
+    if internal_name.starts_with('[') {
+        let Ok(FieldType::Array(mut element)) = internal_name.parse() else {
+            return false;
+        };
+        while let FieldType::Array(inner) = *element {
+            element = inner;
+        }
+        match *element {
+            FieldType::Base(_) => true,
+            FieldType::Object(class) => is_valid_internal_name(&class.binary_name),
+            FieldType::Array(_) => unreachable!("Array types are unwrapped above"),
+        }
+    } else {
+        internal_name.split('/').all(is_unqualified_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        assert_eq!("java.lang.String", internal_to_binary("java/lang/String"));
+        assert_eq!("java/lang/String", binary_to_internal("java.lang.String"));
+        assert_eq!(
+            "[Ljava.lang.String;",
+            internal_to_binary("[Ljava/lang/String;")
+        );
+        assert_eq!(
+            "[Ljava/lang/String;",
+            binary_to_internal("[Ljava.lang.String;")
+        );
+        assert_eq!("Outer$Inner", internal_to_binary("Outer$Inner"));
+    }
+
+    #[test]
+    fn validation() {
+        assert!(is_valid_internal_name("java/lang/String"));
+        assert!(is_valid_internal_name("Outer$Inner"));
+        assert!(is_valid_internal_name("[[I"));
+        assert!(is_valid_internal_name("[Ljava/lang/String;"));
+        assert!(is_valid_internal_name("module-info"));
+        assert!(is_valid_internal_name("org/mokapot/package-info"));
+        assert!(is_valid_internal_name("1abc"));
+        assert!(is_valid_internal_name("a/b/c$1-\u{00e9}"));
+        assert!(!is_valid_internal_name(""));
+        assert!(!is_valid_internal_name("java//String"));
+        assert!(!is_valid_internal_name("/String"));
+        assert!(!is_valid_internal_name("String/"));
+        assert!(!is_valid_internal_name("java.lang.String"));
+        assert!(!is_valid_internal_name("a;b"));
+        assert!(!is_valid_internal_name("a[b"));
+        assert!(!is_valid_internal_name("[Ljava.lang.String;"));
+        assert!(!is_valid_internal_name("[V"));
+    }
+}
@@ -0,0 +1,11 @@
+package org.mokapot.test;
+
+public class BoundsChecks {
+  public static int sum(int[] values) {
+    int sum = 0;
+    for (int i = 0; i < values.length; i++) {
+      sum += values[i];
+    }
+    return sum;
+  }
+}
@@ -0,0 +1,16 @@
+package org.mokapot.test;
+
+public class OverrideBase {
+
+  public Object get() {
+    return null;
+  }
+
+  protected void run() {}
+
+  void packagePrivate() {}
+
+  private void secret() {}
+
+  public static void util() {}
+}
@@ -0,0 +1,19 @@
+package org.mokapot.test;
+
+public class OverrideDerived extends OverrideBase {
+
+  @Override
+  public String get() {
+    return "";
+  }
+
+  @Override
+  public void run() {}
+
+  @Override
+  void packagePrivate() {}
+
+  private void secret() {}
+
+  public static void util() {}
+}
@@ -0,0 +1,20 @@
+package org.mokapot.test;
+
+public class StaticInheritance {
+
+  static class Parent {
+    static int foo() {
+      return 1;
+    }
+
+    int bar() {
+      return 2;
+    }
+  }
+
+  static class Child extends Parent {}
+
+  static int callFoo() {
+    return Child.foo();
+  }
+}
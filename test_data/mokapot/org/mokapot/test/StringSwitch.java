@@ -0,0 +1,17 @@
+package org.mokapot.test;
+
+public class StringSwitch {
+  public static int classify(String command) {
+    switch (command) {
+      case "start":
+        return 1;
+      case "Aa":
+      case "stop":
+        return 2;
+      case "BB":
+        return 3;
+      default:
+        return 0;
+    }
+  }
+}
@@ -107,6 +107,116 @@ fn jar_class_path_not_found() {
     ));
 }
 
+#[test]
+#[cfg(feature = "rayon")]
+fn jar_classes_par() {
+    let Ok(java_home) = std::env::var("JAVA_HOME") else {
+        return;
+    };
+    let jar_path = PathBuf::from(java_home).join("lib").join("jrt-fs.jar");
+    let jar_cp = JarClassPath::new(jar_path);
+
+    let sequential = jar_cp.classes().unwrap();
+    let parallel = jar_cp.classes_par(Some(2)).unwrap();
+    assert!(!sequential.is_empty());
+    assert_eq!(sequential.len(), parallel.len());
+    for ((name, class), (par_name, par_class)) in sequential.iter().zip(&parallel) {
+        assert_eq!(name, par_name);
+        assert_eq!(
+            class.as_ref().ok().map(|it| &it.binary_name),
+            par_class.as_ref().ok().map(|it| &it.binary_name)
+        );
+    }
+    assert!(parallel
+        .iter()
+        .any(|(name, _)| name == "jdk/internal/jimage/ImageReader"));
+}
+
+#[test]
+fn jar_classes_isolate_entries() {
+    use std::io::Write;
+
+    let jar_path = std::env::temp_dir().join(format!("mokapot-corrupt-{}.jar", std::process::id()));
+    let mut jar_writer = zip::ZipWriter::new(std::fs::File::create(&jar_path).unwrap());
+    let options =
+        zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    jar_writer
+        .start_file("org/mokapot/test/MyClass.class", options)
+        .unwrap();
+    jar_writer
+        .write_all(test_data_class!("mokapot", "org/mokapot/test/MyClass"))
+        .unwrap();
+    jar_writer
+        .start_file("org/mokapot/test/Corrupt.class", options)
+        .unwrap();
+    jar_writer.write_all(b"corrupted entry").unwrap();
+    jar_writer.finish().unwrap();
+    // Corrupt the stored bytes so that the entry fails its CRC check when read.
+    let mut jar_bytes = std::fs::read(&jar_path).unwrap();
+    let offset = jar_bytes
+        .windows(9)
+        .position(|it| it == b"corrupted")
+        .unwrap();
+    jar_bytes[offset] = b'C';
+    std::fs::write(&jar_path, jar_bytes).unwrap();
+
+    let jar_cp = JarClassPath::new(&jar_path);
+    let classes = jar_cp.classes().unwrap();
+    #[cfg(feature = "rayon")]
+    let parallel = jar_cp.classes_par(Some(2)).unwrap();
+    std::fs::remove_file(&jar_path).unwrap();
+
+    let [(my_class, Ok(_)), (corrupt, Err(Error::IO(_)))] = classes.as_slice() else {
+        panic!("Only the corrupted entry should fail: {classes:?}");
+    };
+    assert_eq!("org/mokapot/test/MyClass", my_class);
+    assert_eq!("org/mokapot/test/Corrupt", corrupt);
+    #[cfg(feature = "rayon")]
+    assert!(matches!(
+        parallel.as_slice(),
+        [(_, Ok(_)), (_, Err(Error::IO(_)))]
+    ));
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn jar_export_summary_json() {
+    use std::io::Write;
+
+    let jar_path = std::env::temp_dir().join(format!("mokapot-summary-{}.jar", std::process::id()));
+    let mut jar_writer = zip::ZipWriter::new(std::fs::File::create(&jar_path).unwrap());
+    let options = zip::write::SimpleFileOptions::default();
+    jar_writer
+        .start_file("org/mokapot/test/MyClass.class", options)
+        .unwrap();
+    jar_writer
+        .write_all(test_data_class!("mokapot", "org/mokapot/test/MyClass"))
+        .unwrap();
+    jar_writer
+        .start_file("org/mokapot/test/Broken.class", options)
+        .unwrap();
+    jar_writer.write_all(&[0xCA, 0xFE, 0xBA, 0xBE]).unwrap();
+    jar_writer.finish().unwrap();
+
+    let mut json = Vec::new();
+    let failures = JarClassPath::new(&jar_path)
+        .export_summary_json(&mut json)
+        .unwrap();
+    std::fs::remove_file(&jar_path).unwrap();
+
+    let failed_names: Vec<_> = failures.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(vec!["org/mokapot/test/Broken"], failed_names);
+    let summaries: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    let summaries = summaries.as_array().unwrap();
+    assert_eq!(1, summaries.len());
+    assert_eq!("org/mokapot/test/MyClass", summaries[0]["name"]);
+    assert!(summaries[0]["methods"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|it| it.get("body").is_none()));
+}
+
 #[test]
 fn jar_class_path_not_jar() {
     let jar_path = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"));
@@ -119,6 +229,29 @@ fn jar_class_path_not_jar() {
     ));
 }
 
+#[test]
+fn class_paths_shadowing() {
+    let shadow_dir = std::env::temp_dir().join(format!("mokapot-shadow-{}", std::process::id()));
+    let shadow_path = shadow_dir.join("org/mokapot/test/MyClass.class");
+    std::fs::create_dir_all(shadow_path.parent().unwrap()).unwrap();
+    std::fs::write(
+        &shadow_path,
+        test_data_class!("mokapot", "org/mokapot/test/Anno"),
+    )
+    .unwrap();
+
+    let test_dir = concat!(env!("OUT_DIR"), "/mokapot/java_classes");
+    let class_loader = CachingClassLoader::from_paths([shadow_dir.clone(), test_dir.into()]);
+    let class = class_loader.load_class("org/mokapot/test/MyClass").unwrap();
+    assert_eq!(class.binary_name, "org/mokapot/test/Anno");
+    let class = class_loader
+        .load_class("org/mokapot/test/RecordTest")
+        .unwrap();
+    assert_eq!(class.binary_name, "org/mokapot/test/RecordTest");
+
+    std::fs::remove_dir_all(shadow_dir).unwrap();
+}
+
 fn _class_path_object_safety(_b: Box<dyn ClassPath>) {
     // For compilation checking only.
 }
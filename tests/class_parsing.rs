@@ -1,13 +1,19 @@
 #![cfg(integration_test)]
 
-use std::io::{self};
+use std::{
+    collections::BTreeSet,
+    io::{self},
+};
 
 use mokapot::{
+    analysis::{detect_string_switch, redundant_bounds_checks},
     jvm::{
-        class::{self, AccessFlags, RecordComponent},
-        parsing::Error,
+        annotation::{TargetInfo, TargetKind},
+        class::{self, AccessFlags, MethodHandle, RecordComponent, Version},
+        code::{Instruction, VerificationType},
+        parsing::{Error, ParseOptions},
         references::ClassRef,
-        Class,
+        Class, ConstantValue,
     },
     types::{
         field_type::{FieldType, PrimitiveType},
@@ -97,12 +103,29 @@ fn parse_module_info() {
     let bytes = test_data_class!("mokapot", "module-info");
     let class = Class::from_reader(bytes).expect("Fail to parse module-info");
     assert_eq!("module-info", class.binary_name);
+    assert_eq!(None, class.module_main_class());
     let module = class.module.expect("The class is a module-info");
+    assert_eq!("mokapot_test", module.name);
+    assert_eq!("org/mokapot/test", module.exports[0].package.binary_name);
+    assert_eq!("java.base", module.opens[0].to[0].name);
     assert_eq!(1, module.exports.len());
     assert_eq!(1, module.opens.len());
     assert_eq!(1, module.requires.len());
 }
 
+#[test]
+fn module_info_strict() {
+    let bytes = test_data_class!("mokapot", "module-info");
+    let options = ParseOptions {
+        strict: true,
+        ..Default::default()
+    };
+    let class = Class::from_reader_with_options(bytes, &options).unwrap();
+    assert!(class.module.is_some());
+    assert_eq!(None, class.module_main_class());
+    assert!(class.module_packages().is_empty());
+}
+
 #[test]
 fn parse_record() {
     let bytes = test_data_class!("mokapot", "org/mokapot/test/RecordTest");
@@ -143,3 +166,413 @@ fn not_a_class_file() {
         Err(Error::IO(e)) if e.kind() == io::ErrorKind::InvalidData
     ));
 }
+
+#[test]
+fn clone_class() {
+    let bytes = test_data_class!("mokapot", "org/mokapot/test/MyClass");
+    let my_class = Class::from_reader(bytes).expect("Faied to parse class");
+
+    let mut variant = my_class.clone();
+    variant.binary_name = "org/mokapot/test/MyClassVariant".to_owned();
+    variant.methods.clear();
+
+    assert_eq!("org/mokapot/test/MyClass", my_class.binary_name);
+    assert!(!my_class.methods.is_empty());
+    for (original, cloned) in my_class.methods.iter().zip(my_class.clone().methods) {
+        assert_eq!(original.name, cloned.name);
+        assert_eq!(original.descriptor, cloned.descriptor);
+        assert_eq!(
+            original.body.as_ref().map(|it| it.instructions.len()),
+            cloned.body.as_ref().map(|it| it.instructions.len())
+        );
+    }
+}
+
+#[test]
+fn declaration_order() {
+    let bytes = test_data_class!("mokapot", "org/mokapot/test/OverrideBase");
+    let class = Class::from_reader(bytes).unwrap();
+    let names: Vec<_> = class.methods.iter().map(|it| it.name.as_str()).collect();
+    assert_eq!(
+        vec!["<init>", "get", "run", "packagePrivate", "secret", "util"],
+        names
+    );
+    for (index, method) in class.methods.iter().enumerate() {
+        assert_eq!(index, method.index);
+        assert_eq!(
+            Some(index),
+            class.method_index(&method.name, &method.descriptor)
+        );
+    }
+}
+
+#[test]
+fn strict_parsing() {
+    let bytes = test_data_class!("mokapot", "org/mokapot/test/ComplicatedClass");
+    let options = ParseOptions {
+        strict: true,
+        ..Default::default()
+    };
+    assert!(Class::from_reader_with_options(bytes, &options).is_ok());
+}
+
+#[test]
+fn max_class_version() {
+    let bytes = test_data_class!("mokapot", "org/mokapot/test/ComplicatedClass");
+    let version = Class::from_reader(bytes).unwrap().version;
+    let options = ParseOptions {
+        max_version: Some(version),
+        ..Default::default()
+    };
+    assert!(Class::from_reader_with_options(bytes, &options).is_ok());
+    let options = ParseOptions {
+        max_version: Some(Version::Jdk1_4),
+        ..Default::default()
+    };
+    assert!(matches!(
+        Class::from_reader_with_options(bytes, &options),
+        Err(Error::UnsupportedClassVersion { major, minor, max: Version::Jdk1_4 })
+            if major == version.major() && minor == version.minor()
+    ));
+    // The version is checked before the rest of the file is read.
+    assert!(matches!(
+        Class::from_reader_with_options(&bytes[..8], &options),
+        Err(Error::UnsupportedClassVersion { major, .. }) if major == version.major()
+    ));
+}
+
+#[test]
+fn parse_header() {
+    let bytes = test_data_class!("mokapot", "org/mokapot/test/ComplicatedClass");
+    let class = Class::from_reader(bytes).unwrap();
+    let header = Class::parse_header(bytes).unwrap();
+    assert_eq!(class.version, header.version);
+    assert_eq!(class.access_flags, header.access_flags);
+    assert_eq!(class.binary_name, header.binary_name);
+    assert_eq!(class.super_class, header.super_class);
+    assert_eq!(class.interfaces, header.interfaces);
+    let fields: Vec<_> = header
+        .fields
+        .iter()
+        .map(|it| (it.access_flags, &it.name, &it.field_type))
+        .collect();
+    let expected_fields: Vec<_> = class
+        .fields
+        .iter()
+        .map(|it| (it.access_flags, &it.name, &it.field_type))
+        .collect();
+    assert_eq!(expected_fields, fields);
+    let methods: Vec<_> = header
+        .methods
+        .iter()
+        .map(|it| (it.access_flags, &it.name, &it.descriptor))
+        .collect();
+    let expected_methods: Vec<_> = class
+        .methods
+        .iter()
+        .map(|it| (it.access_flags, &it.name, &it.descriptor))
+        .collect();
+    assert_eq!(expected_methods, methods);
+
+    assert!(matches!(
+        Class::parse_header(&bytes[..bytes.len() - 1]),
+        Err(Error::UnexpectedEof { while_parsing }) if while_parsing.contains("the class")
+    ));
+    assert!(matches!(
+        Class::parse_header(&bytes[..9]),
+        Err(Error::UnexpectedEof { while_parsing }) if while_parsing == "the class file header"
+    ));
+}
+
+#[test]
+fn retain_raw_attributes() {
+    let bytes = test_data_class!("mokapot", "org/mokapot/test/ComplicatedClass");
+    let class = Class::from_reader(bytes).unwrap();
+    assert!(class.raw_attributes.is_empty());
+
+    let options = ParseOptions {
+        retain_raw_attributes: true,
+        ..Default::default()
+    };
+    let class = Class::from_reader_with_options(bytes, &options).unwrap();
+    let source_file = class
+        .raw_attributes
+        .iter()
+        .find(|(name, _)| name == "SourceFile")
+        .map(|(_, bytes)| bytes);
+    assert_eq!(Some(2), source_file.map(Vec::len));
+    let method = class.methods.iter().find(|it| it.body.is_some()).unwrap();
+    assert!(method.raw_attributes.iter().any(|(name, _)| name == "Code"));
+    assert!(method
+        .body
+        .as_ref()
+        .unwrap()
+        .raw_attributes
+        .iter()
+        .any(|(name, _)| name == "LineNumberTable"));
+}
+
+#[test]
+fn retain_raw_code() {
+    let bytes = test_data_class!("mokapot", "org/mokapot/test/ComplicatedClass");
+    let class = Class::from_reader(bytes).unwrap();
+    assert!(class
+        .methods
+        .iter()
+        .filter_map(|it| it.body.as_ref())
+        .all(|it| it.raw_code.is_none()));
+
+    let options = ParseOptions {
+        retain_raw_code: true,
+        ..Default::default()
+    };
+    let class = Class::from_reader_with_options(bytes, &options).unwrap();
+    for body in class.methods.iter().filter_map(|it| it.body.as_ref()) {
+        let raw_code = body.raw_code.as_deref().unwrap();
+        let (last_pc, last_insn) = body.instructions.iter().last().unwrap();
+        let last_offset = usize::from(u16::from(*last_pc));
+        let code_len = last_offset + last_insn.encoded_len(last_offset);
+        assert_eq!(code_len, raw_code.len());
+        for (pc, insn) in &body.instructions {
+            assert_eq!(insn.opcode(), raw_code[usize::from(u16::from(*pc))]);
+        }
+    }
+}
+
+#[test]
+fn local_variable_signature() {
+    let bytes = test_data_class!("mokapot", "org/mokapot/test/Anno");
+    let class = Class::from_reader(bytes).unwrap();
+    let method = class
+        .methods
+        .iter()
+        .find(|it| it.name == "annotatedCode")
+        .unwrap();
+    let local_variable_table = method
+        .body
+        .as_ref()
+        .and_then(|it| it.local_variable_table.as_ref())
+        .unwrap();
+    let (id, entry) = local_variable_table
+        .entries()
+        .find(|(_, entry)| entry.name.as_deref() == Some("mpg"))
+        .unwrap();
+    assert_eq!(
+        Some("Ljava/util/Map<Ljava/lang/String;Ljava/lang/Object;>;"),
+        entry.signature.as_deref()
+    );
+    assert_eq!(
+        Some(FieldType::Object(ClassRef::new("java/util/Map"))),
+        entry.var_type
+    );
+    let entry_at = local_variable_table
+        .entry_at(id.index, id.effective_range.start)
+        .unwrap();
+    assert_eq!(entry.name, entry_at.name);
+}
+
+#[test]
+fn type_annotations_on_method_targets() {
+    let bytes = test_data_class!("mokapot", "org/mokapot/test/Anno");
+    let class = Class::from_reader(bytes).unwrap();
+    let method = class
+        .methods
+        .iter()
+        .find(|it| it.name == "annotatedCode")
+        .unwrap();
+    let foo: FieldType = "Lorg/mokapot/test/Anno$Foo;".parse().unwrap();
+    let bar: FieldType = "Lorg/mokapot/test/Anno$Bar;".parse().unwrap();
+
+    let annotated_parameters: BTreeSet<_> = method
+        .type_annotations_on(TargetKind::FormalParameter)
+        .filter(|it| it.annotation_type == foo)
+        .map(|it| match it.target_info {
+            TargetInfo::FormalParameter { index } => index,
+            ref unexpected => panic!("Unexpected target: {unexpected:?}"),
+        })
+        .collect();
+    assert_eq!((0..11).collect::<BTreeSet<_>>(), annotated_parameters);
+
+    let throws: Vec<_> = method
+        .type_annotations_on(TargetKind::ThrowsClause)
+        .collect();
+    assert_eq!(1, throws.len());
+    assert_eq!(foo, throws[0].annotation_type);
+    assert_eq!(TargetInfo::Throws { index: 0 }, throws[0].target_info);
+
+    let type_parameters: Vec<_> = method
+        .type_annotations_on(TargetKind::MethodTypeParameter)
+        .map(|it| &it.annotation_type)
+        .collect();
+    assert_eq!(vec![&bar], type_parameters);
+
+    assert!(method
+        .type_annotations_on(TargetKind::Cast)
+        .all(|it| matches!(it.target_info, TargetInfo::TypeArgument { .. })));
+    assert_eq!(
+        0,
+        method.type_annotations_on(TargetKind::MethodReturn).count()
+    );
+}
+
+#[test]
+fn constructor_receiver_type() {
+    let bytes = test_data_class!("mokapot", "org/mokapot/test/ComplicatedClass");
+    let class = Class::from_reader(bytes).unwrap();
+    let receiver_type = |name: &str| {
+        class
+            .methods
+            .iter()
+            .find(|it| it.name == name)
+            .unwrap()
+            .receiver_type()
+    };
+    assert_eq!(
+        Some(VerificationType::UninitializedThisVariable),
+        receiver_type("<init>")
+    );
+    assert_eq!(
+        Some(VerificationType::ObjectVariable(class.as_ref())),
+        receiver_type("testMethod")
+    );
+}
+
+#[test]
+fn invoke_dynamic_call_site() {
+    let bytes = test_data_class!("mokapot", "org/mokapot/test/TestAnalysis");
+    let class = Class::from_reader(bytes).unwrap();
+    let (name, descriptor, bootstrap_method_index) = class
+        .methods
+        .iter()
+        .filter_map(|it| it.body.as_ref())
+        .flat_map(|it| it.instructions.iter())
+        .find_map(|(_, insn)| match insn {
+            Instruction::InvokeDynamic {
+                bootstrap_method_index,
+                name,
+                descriptor,
+            } => Some((name, descriptor, *bootstrap_method_index)),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!("applyAsInt", name);
+    assert_eq!(
+        &"(I)Ljava/util/function/IntUnaryOperator;"
+            .parse::<MethodDescriptor>()
+            .unwrap(),
+        descriptor
+    );
+    assert!(usize::from(bootstrap_method_index) < class.bootstrap_methods.len());
+}
+
+#[test]
+fn lambda_metafactory_arguments() {
+    let bytes = test_data_class!("mokapot", "org/mokapot/test/TestAnalysis");
+    let class = Class::from_reader(bytes).unwrap();
+    let bootstrap_method = class
+        .bootstrap_methods
+        .iter()
+        .find(|it| {
+            matches!(
+                &it.method,
+                MethodHandle::RefInvokeStatic(method)
+                    if method.owner.binary_name == "java/lang/invoke/LambdaMetafactory"
+                        && method.name == "metafactory"
+            )
+        })
+        .unwrap();
+    let int_to_int = || ConstantValue::MethodType("(I)I".parse().unwrap());
+    let [sam_type, implementation, instantiated_type] = bootstrap_method.arguments.as_slice()
+    else {
+        panic!("Unexpected arguments: {:?}", bootstrap_method.arguments);
+    };
+    assert_eq!(&int_to_int(), sam_type);
+    assert_eq!(&int_to_int(), instantiated_type);
+    let ConstantValue::Handle(MethodHandle::RefInvokeStatic(implementation)) = implementation
+    else {
+        panic!("Unexpected implementation method: {implementation:?}");
+    };
+    assert_eq!(class.as_ref(), implementation.owner);
+    assert!(implementation.name.starts_with("lambda$"));
+}
+
+#[test]
+fn truncated_class_file() {
+    let bytes = test_data_class!("mokapot", "org/mokapot/test/ComplicatedClass");
+    let while_parsing = |len: usize| match Class::from_reader(&bytes[..len]) {
+        Err(Error::UnexpectedEof { while_parsing }) => while_parsing,
+        other => panic!("Expected UnexpectedEof, got {other:?}"),
+    };
+    assert_eq!("the class file header", while_parsing(6));
+    assert_eq!("the constant pool", while_parsing(12));
+    let last_attribute = while_parsing(bytes.len() - 1);
+    assert!(last_attribute.starts_with("the "));
+    assert!(last_attribute.ends_with(" attribute of the class"));
+    assert_eq!(
+        format!("Unexpected end of file while parsing {last_attribute}"),
+        Class::from_reader(&bytes[..bytes.len() - 1])
+            .unwrap_err()
+            .to_string()
+    );
+}
+
+#[test]
+fn javac_string_switch() {
+    let bytes = test_data_class!("mokapot", "org/mokapot/test/StringSwitch");
+    let class = Class::from_reader(bytes).unwrap();
+    let body = class
+        .methods
+        .iter()
+        .find(|it| it.name == "classify")
+        .and_then(|it| it.body.as_ref())
+        .unwrap();
+    let hash_code_pc = body
+        .instructions
+        .iter()
+        .find(|(_, insn)| matches!(insn, Instruction::InvokeVirtual(method) if method.name == "hashCode"))
+        .map(|(pc, _)| *pc)
+        .unwrap();
+    let start_pc = body.instructions.prev_pc_of(&hash_code_pc).unwrap();
+    let string_switch = detect_string_switch(body, start_pc).unwrap();
+    let returned = |pc| {
+        body.instruction_at(pc)
+            .and_then(Instruction::pushed_constant)
+    };
+    let cases: Vec<_> = string_switch
+        .cases
+        .iter()
+        .map(|(label, target)| (label.as_str(), returned(*target)))
+        .collect();
+    assert!(matches!(
+        cases.as_slice(),
+        [
+            ("start", Some(ConstantValue::Integer(1))),
+            ("Aa", Some(ConstantValue::Integer(2))),
+            ("stop", Some(ConstantValue::Integer(2))),
+            ("BB", Some(ConstantValue::Integer(3))),
+        ]
+    ));
+    assert!(matches!(
+        returned(string_switch.default),
+        Some(ConstantValue::Integer(0))
+    ));
+}
+
+#[test]
+fn redundant_bounds_check_in_counted_loop() {
+    let bytes = test_data_class!("mokapot", "org/mokapot/test/BoundsChecks");
+    let class = Class::from_reader(bytes).unwrap();
+    let body = class
+        .methods
+        .iter()
+        .find(|it| it.name == "sum")
+        .and_then(|it| it.body.as_ref())
+        .unwrap();
+    let array_load = body
+        .instructions
+        .iter()
+        .find(|(_, insn)| matches!(insn, Instruction::IALoad))
+        .map(|(pc, _)| *pc)
+        .unwrap();
+    assert_eq!(vec![array_load], redundant_bounds_checks(body));
+}
@@ -2,7 +2,13 @@
 
 use mokapot::{
     analysis::ResolutionContext,
-    jvm::{class_loader::class_paths::DirectoryClassPath, references::ClassRef},
+    jvm::{
+        class_loader::class_paths::DirectoryClassPath,
+        code::Instruction,
+        references::{ClassRef, MethodRef},
+        Class, Method,
+    },
+    types::method_descriptor::MethodDescriptor,
 };
 
 const TEST_CP: &str = concat!(env!("OUT_DIR"), "/mokapot/java_classes");
@@ -27,3 +33,77 @@ fn interfaces_impl() {
         .iter()
         .any(|it| it == &ClassRef::new("java/io/Closeable")));
 }
+
+fn find_method<'a>(class: &'a Class, name: &str, descriptor: &str) -> &'a Method {
+    let descriptor: MethodDescriptor = descriptor.parse().unwrap();
+    class.get_method(name, descriptor).unwrap()
+}
+
+#[test]
+fn method_overrides() {
+    let app_cp = DirectoryClassPath::new(TEST_CP);
+    let ctx = ResolutionContext::new(&[app_cp], &[]);
+    let base = &ctx.application_classes[&ClassRef::new("org/mokapot/test/OverrideBase")];
+    let derived = &ctx.application_classes[&ClassRef::new("org/mokapot/test/OverrideDerived")];
+    let hierarchy = &ctx.class_hierarchy;
+    let overrides = |name: &str, descriptor: &str| {
+        hierarchy.overrides(
+            find_method(derived, name, descriptor),
+            find_method(base, name, descriptor),
+        )
+    };
+    assert!(overrides("run", "()V"));
+    assert!(overrides("packagePrivate", "()V"));
+    // The bridge method overrides `Object get()`.
+    assert!(overrides("get", "()Ljava/lang/Object;"));
+    assert!(!overrides("secret", "()V"));
+    assert!(!overrides("util", "()V"));
+    assert!(!hierarchy.overrides(
+        find_method(base, "run", "()V"),
+        find_method(derived, "run", "()V")
+    ));
+}
+
+#[test]
+fn inherited_static_method() {
+    let app_cp = DirectoryClassPath::new(TEST_CP);
+    let ctx = ResolutionContext::new(&[app_cp], &[]);
+    let class = &ctx.application_classes[&ClassRef::new("org/mokapot/test/StaticInheritance")];
+    let body = find_method(class, "callFoo", "()I").body.as_ref().unwrap();
+    let reference = body
+        .instructions
+        .iter()
+        .find_map(|(_, insn)| match insn {
+            Instruction::InvokeStatic(method) => Some(method),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(
+        ClassRef::new("org/mokapot/test/StaticInheritance$Child"),
+        reference.owner
+    );
+    let find_class = |class: &ClassRef| ctx.application_classes.get(class);
+    let resolved = ctx
+        .class_hierarchy
+        .resolve_static(reference, find_class)
+        .unwrap();
+    assert_eq!(
+        ClassRef::new("org/mokapot/test/StaticInheritance$Parent"),
+        resolved.owner
+    );
+
+    // An instance method is resolved, but not by `invokestatic`.
+    let bar = MethodRef {
+        owner: ClassRef::new("org/mokapot/test/StaticInheritance$Child"),
+        name: "bar".to_owned(),
+        descriptor: "()I".parse().unwrap(),
+    };
+    assert!(ctx
+        .class_hierarchy
+        .resolve_method(&bar, find_class)
+        .is_some());
+    assert!(ctx
+        .class_hierarchy
+        .resolve_static(&bar, find_class)
+        .is_none());
+}